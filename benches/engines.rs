@@ -0,0 +1,412 @@
+use criterion::{BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
+use omega_cache::{ColumnDefinition, Engine, memory_engine::MemoryEngine, noop_engine::NoopEngine};
+
+/// Sizes in bytes of the values inserted and read back
+const VALUE_SIZES: [usize; 3] = [16, 1024, 64 * 1024];
+
+/// Number of keys inserted by each bulk insert loop
+const BULK_INSERTS: u32 = 100_000;
+
+/// Numbers of keys read by each `try_get_many` call
+const BATCH_SIZES: [usize; 5] = [8, 32, 64, 256, 4096];
+
+/// Inserts and reads done by each thread of the concurrent benchmark
+const CONCURRENT_OPS: u32 = 10_000;
+
+struct BenchColumn {}
+
+impl ColumnDefinition for BenchColumn {
+    fn name(&self) -> String {
+        "bench_column".to_string()
+    }
+
+    fn get_ttl_in_seconds(&self) -> i32 {
+        300
+    }
+}
+
+const COLUMN: BenchColumn = BenchColumn {};
+
+/// Engines to compare, sled and redis only when their features are enabled,
+/// sled both uncompressed and compressed, see also `bench_compression`.
+/// Redis also needs `OMEGA_CACHE_BENCH_REDIS` set to a connection url.
+fn engines() -> Vec<(&'static str, Engine)> {
+    #[cfg_attr(not(any(feature = "sled", feature = "redis")), allow(unused_mut))]
+    let mut engines = vec![
+        ("noop", Engine::new(NoopEngine::passthrough())),
+        ("memory", Engine::new(MemoryEngine::builder().build())),
+    ];
+
+    #[cfg(feature = "sled")]
+    {
+        use omega_cache::CacheStorage;
+
+        engines.push((
+            "sled",
+            Engine::new(omega_cache::sled_engine::SledEngine::build(
+                "./tmp/bench_sled".to_string(),
+                None,
+            )),
+        ));
+        // compressed too, to weigh the default of no compression at each value size
+        engines.push((
+            "sled-zstd",
+            Engine::new(
+                omega_cache::sled_engine::SledEngine::builder("./tmp/bench_sled_zstd")
+                    .compression(5)
+                    .build(),
+            ),
+        ));
+    }
+
+    #[cfg(feature = "redis")]
+    if let Ok(url) = std::env::var("OMEGA_CACHE_BENCH_REDIS") {
+        use omega_cache::CacheStorage;
+
+        engines.push((
+            "redis",
+            Engine::new(omega_cache::redis_engine::RedisEngine::build(url, None)),
+        ));
+    }
+
+    engines
+}
+
+fn bench_insert(c: &mut Criterion) {
+    let mut group = c.benchmark_group("insert");
+
+    for (name, engine) in engines() {
+        for size in VALUE_SIZES {
+            let value = vec![7u8; size];
+
+            group.throughput(Throughput::Bytes(size as u64));
+            group.bench_with_input(BenchmarkId::new(name, size), &value, |b, value| {
+                b.iter(|| engine.try_insert(&COLUMN, &"bench_key", value).unwrap());
+            });
+        }
+    }
+
+    group.finish();
+}
+
+/// Bulk insert loops through the engine and through a column handle
+fn bench_bulk_insert(c: &mut Criterion) {
+    let mut group = c.benchmark_group("bulk_insert");
+    group.sample_size(10);
+    group.throughput(Throughput::Elements(u64::from(BULK_INSERTS)));
+
+    for (name, engine) in engines() {
+        group.bench_function(BenchmarkId::new("engine", name), |b| {
+            b.iter(|| {
+                for i in 0..BULK_INSERTS {
+                    engine.try_insert(&COLUMN, &i.to_be_bytes(), &i).unwrap();
+                }
+            });
+        });
+
+        group.bench_function(BenchmarkId::new("handle", name), |b| {
+            let handle = engine.column_handle(&COLUMN).unwrap();
+
+            b.iter(|| {
+                for i in 0..BULK_INSERTS {
+                    handle.try_insert(&i.to_be_bytes(), &i).unwrap();
+                }
+            });
+        });
+    }
+
+    group.finish();
+}
+
+fn bench_get(c: &mut Criterion) {
+    let mut group = c.benchmark_group("get");
+
+    for (name, engine) in engines() {
+        for size in VALUE_SIZES {
+            let key = format!("bench_key_{size}");
+            engine.try_insert(&COLUMN, &key, &vec![7u8; size]).unwrap();
+
+            group.throughput(Throughput::Bytes(size as u64));
+            group.bench_with_input(BenchmarkId::new(name, size), &key, |b, key| {
+                b.iter(|| engine.try_get::<_, Vec<u8>>(&COLUMN, key).unwrap());
+            });
+        }
+    }
+
+    group.finish();
+}
+
+/// Engines reading batches in turn and in parallel, to pick the parallel read threshold
+#[cfg(feature = "rayon")]
+fn parallel_engines() -> Vec<(&'static str, Engine)> {
+    use omega_cache::sled_engine::SledEngine;
+
+    vec![
+        (
+            "sled-sequential",
+            Engine::new(
+                SledEngine::builder("./tmp/bench_sled_sequential")
+                    .parallel_get_threshold(usize::MAX)
+                    .build(),
+            ),
+        ),
+        (
+            "sled-parallel",
+            Engine::new(
+                SledEngine::builder("./tmp/bench_sled_parallel")
+                    .parallel_get_threshold(0)
+                    .build(),
+            ),
+        ),
+    ]
+}
+
+fn bench_get_many(c: &mut Criterion) {
+    let mut group = c.benchmark_group("get_many");
+
+    #[cfg_attr(not(feature = "rayon"), allow(unused_mut))]
+    let mut engines = engines();
+    #[cfg(feature = "rayon")]
+    engines.extend(parallel_engines());
+
+    for (name, engine) in engines {
+        let engine = engine.with_batch_size(BATCH_SIZES[BATCH_SIZES.len() - 1]);
+
+        for size in BATCH_SIZES {
+            let keys = (0..size)
+                .map(|i| format!("bench_many_key_{i}"))
+                .collect::<Vec<_>>();
+            for key in &keys {
+                engine.try_insert(&COLUMN, key, &vec![7u8; 1024]).unwrap();
+            }
+
+            group.throughput(Throughput::Elements(size as u64));
+            group.bench_with_input(BenchmarkId::new(name, size), &keys, |b, keys| {
+                b.iter(|| engine.try_get_many::<_, Vec<u8>>(&COLUMN, keys).unwrap());
+            });
+        }
+    }
+
+    group.finish();
+}
+
+/// Inserts and reads from a thread per core at once, on keys of their own,
+/// through a single lock and through the default shards of the in-memory engine
+fn bench_concurrent(c: &mut Criterion) {
+    let threads = std::thread::available_parallelism().map_or(1, std::num::NonZeroUsize::get);
+    let mut group = c.benchmark_group("concurrent");
+    group.sample_size(10);
+    group.throughput(Throughput::Elements(
+        threads as u64 * u64::from(CONCURRENT_OPS) * 2,
+    ));
+
+    for (name, storage) in [
+        ("single-lock", MemoryEngine::builder().shards(1).build()),
+        ("sharded", MemoryEngine::builder().build()),
+    ] {
+        let engine = Engine::new(storage);
+
+        group.bench_function(BenchmarkId::new(name, threads), |b| {
+            b.iter(|| {
+                std::thread::scope(|scope| {
+                    for thread in 0..threads {
+                        let engine = &engine;
+                        scope.spawn(move || {
+                            for i in 0..CONCURRENT_OPS {
+                                let key = format!("bench_key_{thread}_{i}");
+                                engine.try_insert(&COLUMN, &key, &i).unwrap();
+                                engine.try_get::<_, u32>(&COLUMN, &key).unwrap();
+                            }
+                        });
+                    }
+                });
+            });
+        });
+    }
+
+    group.finish();
+}
+
+/// Writes and reads of small values by sled in each item format,
+/// printing the bytes each format stores per value
+#[cfg(feature = "sled")]
+fn bench_item_format(c: &mut Criterion) {
+    use omega_cache::sled_engine::{ItemFormat, SledEngine};
+
+    let mut group = c.benchmark_group("item_format");
+
+    for (name, format) in [
+        ("bincode", ItemFormat::Bincode),
+        ("compact", ItemFormat::Compact),
+    ] {
+        let engine = Engine::new(
+            SledEngine::builder(format!("./tmp/bench_sled_item_{name}"))
+                .item_format(format)
+                .build(),
+        );
+
+        for size in [8, VALUE_SIZES[0], 512] {
+            let key = format!("bench_key_{size}");
+            let value = vec![7u8; size];
+            engine.try_insert(&COLUMN, &key, &value).unwrap();
+
+            // the stored value is the item, so this is the item's overhead on the encoded value
+            let stored = engine.try_value_size(&COLUMN, &key).unwrap().unwrap();
+            let encoded = bincode::encode_to_vec(&value, bincode::config::standard())
+                .unwrap()
+                .len();
+            println!(
+                "item_format/{name}/{size}: {} bytes ahead of the value",
+                stored - encoded
+            );
+
+            group.bench_with_input(
+                BenchmarkId::new(format!("{name}/insert"), size),
+                &value,
+                |b, value| b.iter(|| engine.try_insert(&COLUMN, &key, value).unwrap()),
+            );
+            group.bench_with_input(
+                BenchmarkId::new(format!("{name}/get"), size),
+                &key,
+                |b, key| b.iter(|| engine.try_get::<_, Vec<u8>>(&COLUMN, key).unwrap()),
+            );
+        }
+    }
+
+    group.finish();
+}
+
+/// Bytes taken by the files under a directory
+#[cfg(feature = "sled")]
+fn directory_size(path: &std::path::Path) -> u64 {
+    std::fs::read_dir(path)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|entry| match entry.metadata() {
+            Ok(metadata) if metadata.is_dir() => directory_size(&entry.path()),
+            Ok(metadata) => metadata.len(),
+            Err(_) => 0,
+        })
+        .sum()
+}
+
+/// Writes and reads by sled uncompressed and with zstd, of small integers
+/// and of large repetitive text, printing the bytes on disk for each
+#[cfg(feature = "sled")]
+fn bench_compression(c: &mut Criterion) {
+    use omega_cache::sled_engine::SledEngine;
+
+    let mut group = c.benchmark_group("compression");
+    group.sample_size(10);
+
+    let text = "{\"id\":42,\"name\":\"omega\",\"tags\":[\"cache\",\"bench\"]}".repeat(1024);
+    let values: [(&str, Vec<u8>); 2] = [
+        ("small", 42u64.to_be_bytes().to_vec()),
+        ("large", text.into_bytes()),
+    ];
+
+    for (name, level) in [("off", None), ("zstd-1", Some(1)), ("zstd-5", Some(5))] {
+        for (size, value) in &values {
+            let path = format!("./tmp/bench_sled_compression_{name}_{size}");
+            let _ = std::fs::remove_dir_all(&path);
+
+            let builder = SledEngine::builder(&path);
+            let storage = match level {
+                Some(level) => builder.compression(level),
+                None => builder,
+            }
+            .open()
+            .unwrap();
+            let engine = Engine::new(Box::new(storage));
+
+            for i in 0..1000u32 {
+                engine.try_insert(&COLUMN, &i.to_be_bytes(), value).unwrap();
+            }
+            drop(engine);
+            println!(
+                "compression/{name}/{size}: {} bytes on disk for 1000 values of {} bytes",
+                directory_size(std::path::Path::new(&path)),
+                value.len()
+            );
+
+            let builder = SledEngine::builder(&path);
+            let engine = match level {
+                Some(level) => builder.compression(level),
+                None => builder,
+            }
+            .build();
+            let engine = Engine::new(engine);
+
+            group.throughput(Throughput::Bytes(value.len() as u64));
+            group.bench_with_input(
+                BenchmarkId::new(format!("{name}/insert"), size),
+                value,
+                |b, value| b.iter(|| engine.try_insert(&COLUMN, &"bench_key", value).unwrap()),
+            );
+            group.bench_with_input(
+                BenchmarkId::new(format!("{name}/get"), size),
+                &"bench_key",
+                |b, key| b.iter(|| engine.try_get::<_, Vec<u8>>(&COLUMN, key).unwrap()),
+            );
+        }
+    }
+
+    group.finish();
+}
+
+/// Inserts of small values by sled returning once buffered and once flushed to disk
+#[cfg(feature = "sled")]
+fn bench_durability(c: &mut Criterion) {
+    use omega_cache::sled_engine::{Durability, SledEngine};
+
+    let mut group = c.benchmark_group("durability");
+    group.sample_size(10);
+    let value = vec![7u8; VALUE_SIZES[0]];
+
+    for (name, durability) in [
+        ("buffered", Durability::Buffered),
+        ("flushed", Durability::Flushed),
+    ] {
+        let engine = Engine::new(
+            SledEngine::builder(format!("./tmp/bench_sled_durability_{name}"))
+                .durability(durability)
+                .build(),
+        );
+
+        let mut i = 0u32;
+        group.bench_function(name, |b| {
+            b.iter(|| {
+                i = i.wrapping_add(1);
+                engine
+                    .try_insert(&COLUMN, &i.to_be_bytes(), &value)
+                    .unwrap();
+            });
+        });
+    }
+
+    group.finish();
+}
+
+#[cfg(not(feature = "sled"))]
+criterion_group!(
+    benches,
+    bench_insert,
+    bench_bulk_insert,
+    bench_get,
+    bench_get_many,
+    bench_concurrent
+);
+#[cfg(feature = "sled")]
+criterion_group!(
+    benches,
+    bench_insert,
+    bench_bulk_insert,
+    bench_get,
+    bench_get_many,
+    bench_concurrent,
+    bench_item_format,
+    bench_durability,
+    bench_compression
+);
+criterion_main!(benches);
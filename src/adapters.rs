@@ -0,0 +1,139 @@
+//!
+//! Values for types that do not implement bincode's [`Encode`](bincode::Encode)
+//! and [`Decode`](bincode::Decode)
+//!
+//! Many std types implement them already and are cached as they are:
+//! [`Duration`](std::time::Duration), [`SystemTime`](std::time::SystemTime),
+//! [`IpAddr`](std::net::IpAddr), [`Ipv4Addr`](std::net::Ipv4Addr),
+//! [`Ipv6Addr`](std::net::Ipv6Addr), [`SocketAddr`](std::net::SocketAddr),
+//! [`PathBuf`](std::path::PathBuf), strings, numbers and the std collections.
+//!
+//! Types from other crates cannot be given these impls here, so each has an
+//! adapter to cache instead, each behind a feature of its own:
+//!
+//! | Feature | Adapter | Caches |
+//! |---------|---------|--------|
+//! | `uuid`  | [`UuidValue`] | [`uuid::Uuid`] as its 16 bytes |
+//! | `serde` | [`SerdeValue`] | any `serde` serializable type, through bincode's serde support |
+//!
+//! A [`uuid::Uuid`] is used as a key directly, as it is [`AsRef<[u8]>`](AsRef).
+//!
+
+#[cfg(feature = "serde")]
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+/// Any type implementing `serde`'s `Serialize`, and `DeserializeOwned` to read it back,
+/// encoded with bincode's serde support.
+///
+/// ```
+/// use omega_cache::{ColumnDefinition, Engine, adapters::SerdeValue, noop_engine::NoopEngine};
+///
+/// struct Settings;
+///
+/// impl ColumnDefinition for Settings {
+///     fn name(&self) -> String {
+///         "settings".to_string()
+///     }
+///
+///     fn get_ttl_in_seconds(&self) -> i32 {
+///         60
+///     }
+/// }
+///
+/// let engine = Engine::new(NoopEngine::passthrough());
+/// engine.try_insert(&Settings, &"dark_mode", &SerdeValue(true)).unwrap();
+///
+/// let value = engine.try_get::<_, SerdeValue<bool>>(&Settings, &"dark_mode").unwrap();
+/// assert_eq!(value.map(|v| v.0), Some(true));
+/// ```
+pub use bincode::serde::Compat as SerdeValue;
+
+#[cfg(feature = "uuid")]
+#[cfg_attr(docsrs, doc(cfg(feature = "uuid")))]
+pub use uuid_value::UuidValue;
+
+#[cfg(feature = "uuid")]
+mod uuid_value {
+    use bincode::{
+        Decode, Encode,
+        de::Decoder,
+        enc::Encoder,
+        error::{DecodeError, EncodeError},
+    };
+
+    ///
+    /// A [`uuid::Uuid`] cached as its 16 bytes
+    ///
+    /// ```
+    /// use omega_cache::{ColumnDefinition, Engine, adapters::UuidValue, noop_engine::NoopEngine};
+    ///
+    /// struct Owners;
+    ///
+    /// impl ColumnDefinition for Owners {
+    ///     fn name(&self) -> String {
+    ///         "owners".to_string()
+    ///     }
+    ///
+    ///     fn get_ttl_in_seconds(&self) -> i32 {
+    ///         60
+    ///     }
+    /// }
+    ///
+    /// let engine = Engine::new(NoopEngine::passthrough());
+    /// let owner = uuid::Uuid::from_u128(42);
+    ///
+    /// engine.try_insert(&Owners, &"report", &UuidValue(owner)).unwrap();
+    ///
+    /// let value = engine.try_get::<_, UuidValue>(&Owners, &"report").unwrap();
+    /// assert_eq!(value.map(uuid::Uuid::from), Some(owner));
+    /// ```
+    ///
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+    pub struct UuidValue(pub uuid::Uuid);
+
+    impl From<uuid::Uuid> for UuidValue {
+        fn from(uuid: uuid::Uuid) -> Self {
+            UuidValue(uuid)
+        }
+    }
+
+    impl From<UuidValue> for uuid::Uuid {
+        fn from(value: UuidValue) -> Self {
+            value.0
+        }
+    }
+
+    impl Encode for UuidValue {
+        fn encode<E: Encoder>(&self, encoder: &mut E) -> Result<(), EncodeError> {
+            self.0.as_bytes().encode(encoder)
+        }
+    }
+
+    impl<Context> Decode<Context> for UuidValue {
+        fn decode<D: Decoder<Context = Context>>(decoder: &mut D) -> Result<Self, DecodeError> {
+            Ok(UuidValue(uuid::Uuid::from_bytes(<[u8; 16]>::decode(
+                decoder,
+            )?)))
+        }
+    }
+
+    bincode::impl_borrow_decode!(UuidValue);
+
+    #[cfg(test)]
+    mod test {
+        use super::UuidValue;
+
+        #[test]
+        fn test_uuid_round_trip() {
+            let value = UuidValue(uuid::Uuid::from_u128(0x1234_5678_9abc_def0));
+            let bytes = bincode::encode_to_vec(value, bincode::config::standard()).unwrap();
+
+            assert_eq!(bytes.len(), 16);
+            assert_eq!(
+                bincode::decode_from_slice::<UuidValue, _>(&bytes, bincode::config::standard())
+                    .unwrap()
+                    .0,
+                value
+            );
+        }
+    }
+}
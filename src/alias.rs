@@ -0,0 +1,461 @@
+use std::{collections::HashSet, sync::Arc, time::Duration};
+
+use crate::{
+    BackendConfig, CacheError, CacheStorage, ColumnDefinition, ColumnReport, ColumnWriter,
+    EvictionListener, InsertOutcome, RawEntry, SledLayout, SlowOperationListener, StoredValue,
+    TimedEntry, TransactionCondition, TransactionWrite, TtlEnforcement, noop_engine::NoopEngine,
+};
+
+/// A column read under one of its [`ColumnDefinition::aliases`]
+struct AliasColumn<'a> {
+    column: &'a dyn ColumnDefinition,
+    name: &'a str,
+}
+
+impl ColumnDefinition for AliasColumn<'_> {
+    fn name(&self) -> String {
+        self.name.to_string()
+    }
+
+    fn get_ttl_in_seconds(&self) -> i32 {
+        self.column.get_ttl_in_seconds()
+    }
+
+    fn max_entries(&self) -> Option<usize> {
+        self.column.max_entries()
+    }
+
+    fn ttl_jitter(&self) -> Option<f32> {
+        self.column.ttl_jitter()
+    }
+
+    fn idle_ttl_in_seconds(&self) -> Option<u32> {
+        self.column.idle_ttl_in_seconds()
+    }
+
+    fn expire_on_ttl_change(&self) -> bool {
+        self.column.expire_on_ttl_change()
+    }
+
+    fn read_only(&self) -> bool {
+        self.column.read_only()
+    }
+
+    fn storage_layout(&self) -> SledLayout {
+        self.column.storage_layout()
+    }
+}
+
+/// The column under each of its aliases, in order
+fn aliases(c: &dyn ColumnDefinition) -> impl Iterator<Item = AliasColumn<'_>> {
+    c.aliases()
+        .iter()
+        .map(move |name| AliasColumn { column: c, name })
+}
+
+/// Result of `read` on the column, then on each alias until one finds a value
+fn read_aliased<T>(
+    c: &dyn ColumnDefinition,
+    read: impl Fn(&dyn ColumnDefinition) -> Result<Option<T>, CacheError>,
+) -> Result<Option<T>, CacheError> {
+    if let Some(value) = read(c)? {
+        return Ok(Some(value));
+    }
+
+    for alias in aliases(c) {
+        if let Some(value) = read(&alias)? {
+            return Ok(Some(value));
+        }
+    }
+
+    Ok(None)
+}
+
+/// Storage wrapper falling back to a column's [`ColumnDefinition::aliases`]
+/// on reads that miss, and dropping and counting them along with the column.
+/// Writes only go to the column's name.
+pub(crate) struct Aliased {
+    pub(crate) inner: Box<dyn CacheStorage + Send + Sync>,
+}
+
+impl CacheStorage for Aliased {
+    /// Only built by wrapping the storage of an engine, this builds an unwrapped [`NoopEngine`]
+    fn build(path: String, capacity: Option<u64>) -> Box<dyn CacheStorage + Send + Sync>
+    where
+        Self: Sized,
+    {
+        NoopEngine::build(path, capacity)
+    }
+
+    fn discards_writes(&self) -> bool {
+        self.inner.discards_writes()
+    }
+
+    fn ttl_enforcement(&self) -> TtlEnforcement {
+        self.inner.ttl_enforcement()
+    }
+
+    fn describe_config(&self) -> BackendConfig {
+        self.inner.describe_config()
+    }
+
+    fn try_insert(
+        &self,
+        c: &dyn ColumnDefinition,
+        key: &[u8],
+        value: &[u8],
+    ) -> Result<(), CacheError> {
+        self.inner.try_insert(c, key, value)
+    }
+
+    fn try_column_writer<'a>(
+        &'a self,
+        c: &'a dyn ColumnDefinition,
+    ) -> Result<Box<dyn ColumnWriter + 'a>, CacheError> {
+        self.inner.try_column_writer(c)
+    }
+
+    fn try_insert_with_ttl(
+        &self,
+        c: &dyn ColumnDefinition,
+        key: &[u8],
+        value: &[u8],
+        ttl_in_seconds: i32,
+    ) -> Result<(), CacheError> {
+        self.inner
+            .try_insert_with_ttl(c, key, value, ttl_in_seconds)
+    }
+
+    fn try_insert_reporting(
+        &self,
+        c: &dyn ColumnDefinition,
+        key: &[u8],
+        value: &[u8],
+    ) -> Result<InsertOutcome, CacheError> {
+        self.inner.try_insert_reporting(c, key, value)
+    }
+
+    fn try_insert_batch(
+        &self,
+        c: &dyn ColumnDefinition,
+        items: &[(Vec<u8>, Vec<u8>)],
+    ) -> Result<(), CacheError> {
+        self.inner.try_insert_batch(c, items)
+    }
+
+    fn try_swap(
+        &self,
+        c: &dyn ColumnDefinition,
+        key: &[u8],
+        value: &[u8],
+    ) -> Result<Option<Vec<u8>>, CacheError> {
+        self.inner.try_swap(c, key, value)
+    }
+
+    /// Renames within the first of the column and its aliases holding `from`,
+    /// so a value only held under an alias stays there
+    fn try_rename(
+        &self,
+        c: &dyn ColumnDefinition,
+        from: &[u8],
+        to: &[u8],
+    ) -> Result<bool, CacheError> {
+        if self.inner.try_rename(c, from, to)? {
+            return Ok(true);
+        }
+
+        for alias in aliases(c) {
+            if self.inner.try_rename(&alias, from, to)? {
+                return Ok(true);
+            }
+        }
+
+        Ok(false)
+    }
+
+    /// Takes from the first of the column and its aliases holding key
+    fn try_take(
+        &self,
+        c: &dyn ColumnDefinition,
+        key: &[u8],
+    ) -> Result<Option<Vec<u8>>, CacheError> {
+        if let Some(value) = self.inner.try_take(c, key)? {
+            return Ok(Some(value));
+        }
+
+        for alias in aliases(c) {
+            if let Some(value) = self.inner.try_take(&alias, key)? {
+                return Ok(Some(value));
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Only the column's own keys are checked and written, not those of its aliases
+    fn try_commit(
+        &self,
+        c: &dyn ColumnDefinition,
+        conditions: &[TransactionCondition],
+        writes: &[TransactionWrite],
+    ) -> Result<bool, CacheError> {
+        self.inner.try_commit(c, conditions, writes)
+    }
+
+    fn try_touch_many(
+        &self,
+        c: &dyn ColumnDefinition,
+        keys: &[&[u8]],
+        ttl_in_seconds: i32,
+    ) -> Result<usize, CacheError> {
+        let mut touched = self.inner.try_touch_many(c, keys, ttl_in_seconds)?;
+
+        for alias in aliases(c) {
+            touched += self.inner.try_touch_many(&alias, keys, ttl_in_seconds)?;
+        }
+
+        Ok(touched)
+    }
+
+    fn try_get(&self, c: &dyn ColumnDefinition, key: &[u8]) -> Result<Option<Vec<u8>>, CacheError> {
+        read_aliased(c, |c| self.inner.try_get(c, key))
+    }
+
+    fn try_get_many(
+        &self,
+        c: &dyn ColumnDefinition,
+        keys: &[&[u8]],
+    ) -> Result<Vec<Option<Vec<u8>>>, CacheError> {
+        let mut values = self.inner.try_get_many(c, keys)?;
+
+        for alias in aliases(c) {
+            let missed = values
+                .iter()
+                .zip(keys)
+                .filter_map(|(value, key)| value.is_none().then_some(*key))
+                .collect::<Vec<_>>();
+            if missed.is_empty() {
+                break;
+            }
+
+            let mut found = self.inner.try_get_many(&alias, &missed)?.into_iter();
+            for value in values.iter_mut().filter(|value| value.is_none()) {
+                *value = found.next().flatten();
+            }
+        }
+
+        Ok(values)
+    }
+
+    fn try_value_size(
+        &self,
+        c: &dyn ColumnDefinition,
+        key: &[u8],
+    ) -> Result<Option<usize>, CacheError> {
+        read_aliased(c, |c| self.inner.try_value_size(c, key))
+    }
+
+    fn try_get_stored(
+        &self,
+        c: &dyn ColumnDefinition,
+        key: &[u8],
+    ) -> Result<Option<StoredValue>, CacheError> {
+        read_aliased(c, |c| self.inner.try_get_stored(c, key))
+    }
+
+    fn try_get_allowing_stale(
+        &self,
+        c: &dyn ColumnDefinition,
+        key: &[u8],
+    ) -> Result<Option<(Vec<u8>, bool)>, CacheError> {
+        read_aliased(c, |c| self.inner.try_get_allowing_stale(c, key))
+    }
+
+    fn try_drop_column(&self, c: &dyn ColumnDefinition) -> Result<(), CacheError> {
+        self.inner.try_drop_column(c)?;
+
+        for alias in aliases(c) {
+            self.inner.try_drop_column(&alias)?;
+        }
+
+        Ok(())
+    }
+
+    /// Entries of the column, then those of each alias under keys not seen yet
+    fn try_entries(&self, c: &dyn ColumnDefinition) -> Result<Vec<RawEntry>, CacheError> {
+        let mut entries = self.inner.try_entries(c)?;
+        if c.aliases().is_empty() {
+            return Ok(entries);
+        }
+
+        let mut seen = entries
+            .iter()
+            .map(|(key, _)| key.clone())
+            .collect::<HashSet<_>>();
+        for alias in aliases(c) {
+            for (key, value) in self.inner.try_entries(&alias)? {
+                if seen.insert(key.clone()) {
+                    entries.push((key, value));
+                }
+            }
+        }
+
+        Ok(entries)
+    }
+
+    fn try_timed_entries(&self, c: &dyn ColumnDefinition) -> Result<Vec<TimedEntry>, CacheError> {
+        let mut entries = self.inner.try_timed_entries(c)?;
+        if c.aliases().is_empty() {
+            return Ok(entries);
+        }
+
+        let mut seen = entries
+            .iter()
+            .map(|(key, _, _)| key.clone())
+            .collect::<HashSet<_>>();
+        for alias in aliases(c) {
+            for (key, value, ttl) in self.inner.try_timed_entries(&alias)? {
+                if seen.insert(key.clone()) {
+                    entries.push((key, value, ttl));
+                }
+            }
+        }
+
+        Ok(entries)
+    }
+
+    fn try_count_column(&self, c: &dyn ColumnDefinition) -> Result<usize, CacheError> {
+        let mut keys = self.inner.try_count_column(c)?;
+
+        for alias in aliases(c) {
+            keys += self.inner.try_count_column(&alias)?;
+        }
+
+        Ok(keys)
+    }
+
+    fn try_entry_ages(&self, c: &dyn ColumnDefinition) -> Result<Vec<Duration>, CacheError> {
+        let mut ages = self.inner.try_entry_ages(c)?;
+
+        for alias in aliases(c) {
+            ages.extend(self.inner.try_entry_ages(&alias)?);
+        }
+
+        Ok(ages)
+    }
+
+    fn validate_column(&self, c: &dyn ColumnDefinition) -> Result<(), CacheError> {
+        self.inner.validate_column(c)?;
+
+        for alias in aliases(c) {
+            self.inner.validate_column(&alias)?;
+        }
+
+        Ok(())
+    }
+
+    fn try_column_layout(&self, c: &dyn ColumnDefinition) -> Result<SledLayout, CacheError> {
+        self.inner.try_column_layout(c)
+    }
+
+    fn try_list_columns(&self) -> Result<Vec<String>, CacheError> {
+        self.inner.try_list_columns()
+    }
+
+    fn try_report(&self) -> Result<Vec<ColumnReport>, CacheError> {
+        self.inner.try_report()
+    }
+
+    fn try_maintenance(&self) -> Result<(), CacheError> {
+        self.inner.try_maintenance()
+    }
+
+    fn try_increment(
+        &self,
+        c: &dyn ColumnDefinition,
+        key: &[u8],
+        delta: i64,
+    ) -> Result<i64, CacheError> {
+        self.inner.try_increment(c, key, delta)
+    }
+
+    fn set_eviction_listener(
+        &mut self,
+        listener: Arc<dyn EvictionListener>,
+    ) -> Result<(), CacheError> {
+        self.inner.set_eviction_listener(listener)
+    }
+
+    fn set_slow_operation_listener(
+        &mut self,
+        threshold: Duration,
+        listener: SlowOperationListener,
+    ) -> Result<(), CacheError> {
+        self.inner.set_slow_operation_listener(threshold, listener)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{CacheStorage, ColumnDefinition, noop_engine::NoopEngine};
+
+    use super::Aliased;
+
+    struct RenamedColumn {}
+
+    impl ColumnDefinition for RenamedColumn {
+        fn name(&self) -> String {
+            "users".to_string()
+        }
+
+        fn get_ttl_in_seconds(&self) -> i32 {
+            60
+        }
+
+        fn aliases(&self) -> &[&str] {
+            &["people", "accounts"]
+        }
+    }
+
+    struct OldColumn {
+        name: &'static str,
+    }
+
+    impl ColumnDefinition for OldColumn {
+        fn name(&self) -> String {
+            self.name.to_string()
+        }
+
+        fn get_ttl_in_seconds(&self) -> i32 {
+            60
+        }
+    }
+
+    #[test]
+    fn test_reads_fall_back_to_aliases() {
+        let storage = Aliased {
+            inner: NoopEngine::passthrough(),
+        };
+        let (people, accounts) = (OldColumn { name: "people" }, OldColumn { name: "accounts" });
+
+        assert!(storage.inner.try_insert(&people, b"a", b"old").is_ok());
+        assert!(storage.inner.try_insert(&accounts, b"b", b"older").is_ok());
+        assert!(storage.try_insert(&RenamedColumn {}, b"a", b"new").is_ok());
+
+        assert_eq!(
+            storage.try_get(&RenamedColumn {}, b"a").unwrap(),
+            Some(b"new".to_vec())
+        );
+        assert_eq!(
+            storage
+                .try_get_many(&RenamedColumn {}, &[b"a", b"b", b"c"])
+                .unwrap(),
+            vec![Some(b"new".to_vec()), Some(b"older".to_vec()), None]
+        );
+        assert_eq!(storage.try_count_column(&RenamedColumn {}).unwrap(), 3);
+
+        assert!(storage.try_drop_column(&RenamedColumn {}).is_ok());
+        assert!(storage.inner.try_get(&people, b"a").unwrap().is_none());
+        assert!(storage.inner.try_get(&accounts, b"b").unwrap().is_none());
+    }
+}
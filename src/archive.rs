@@ -0,0 +1,326 @@
+use std::{
+    io::{ErrorKind, Read, Write},
+    time::Duration,
+};
+
+use crate::{CacheError, ColumnDefinition, SledLayout};
+
+/// Key, value and seconds left to live of an exported value, zero if it never expires
+pub(crate) type Record = (Vec<u8>, Vec<u8>, u32);
+
+/// First bytes of every snapshot, ahead of its format version
+const SNAPSHOT_MAGIC: &[u8; 8] = b"OMEGASNP";
+
+/// Format version written by [`write_snapshot_header`].
+///
+/// Version 1 columns only carry the name and TTL, version 2 adds the idle TTL and sled layout
+const SNAPSHOT_VERSION: u8 = 2;
+
+/// Column rebuilt from a snapshot, holding the settings that decide where
+/// and for how long its values are stored
+pub(crate) struct SnapshotColumn {
+    pub(crate) name: String,
+    pub(crate) ttl_in_seconds: i32,
+    pub(crate) idle_ttl_in_seconds: Option<u32>,
+    pub(crate) storage_layout: SledLayout,
+}
+
+impl ColumnDefinition for SnapshotColumn {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn get_ttl_in_seconds(&self) -> i32 {
+        self.ttl_in_seconds
+    }
+
+    fn idle_ttl_in_seconds(&self) -> Option<u32> {
+        self.idle_ttl_in_seconds
+    }
+
+    fn storage_layout(&self) -> SledLayout {
+        self.storage_layout
+    }
+}
+
+/// Whole seconds left to live of an exported value, rounded up,
+/// zero if it never expires
+pub(crate) fn remaining_seconds(ttl: Option<Duration>) -> u32 {
+    // a live value has at least a second left, zero is kept for no expiry
+    ttl.map_or(0, |ttl| {
+        let seconds = ttl.as_secs() + u64::from(ttl.subsec_nanos() > 0);
+        u32::try_from(seconds.max(1)).unwrap_or(u32::MAX)
+    })
+}
+
+/// Write the magic bytes and format version that start a snapshot
+pub(crate) fn write_snapshot_header(writer: &mut dyn Write) -> Result<(), CacheError> {
+    writer
+        .write_all(&[SNAPSHOT_MAGIC.as_slice(), &[SNAPSHOT_VERSION]].concat())
+        .map_err(|e| CacheError::Engine(format!("Failed to write snapshot: {e}")))
+}
+
+/// Check the start of a snapshot written by [`write_snapshot_header`], returning its format version
+pub(crate) fn read_snapshot_header(reader: &mut dyn Read) -> Result<u8, CacheError> {
+    let mut header = [0u8; SNAPSHOT_MAGIC.len() + 1];
+    read_exact(reader, &mut header)?;
+
+    let (magic, version) = header.split_at(SNAPSHOT_MAGIC.len());
+    if magic != SNAPSHOT_MAGIC {
+        return Err(CacheError::Engine("not a cache snapshot".to_string()));
+    }
+
+    match version[0] {
+        version @ 1..=SNAPSHOT_VERSION => Ok(version),
+        version => Err(CacheError::Engine(format!(
+            "snapshot format version {version} is not supported, only up to {SNAPSHOT_VERSION} are"
+        ))),
+    }
+}
+
+/// Write the header of a column in a snapshot, followed by its `entries` records.
+///
+/// The header is the name prefixed by its length as a big endian `u32`, the column TTL
+/// as a big endian `i32`, the idle TTL as a big endian `u32`, zero if there is none,
+/// a byte for the sled layout, zero for a dedicated tree and one for the shared tree,
+/// and the number of records as a big endian `u64`
+pub(crate) fn write_column_header(
+    writer: &mut dyn Write,
+    c: &SnapshotColumn,
+    entries: u64,
+) -> Result<(), CacheError> {
+    let len = u32::try_from(c.name.len())
+        .map_err(|_| CacheError::Engine("column name is too long to snapshot".to_string()))?;
+    let layout = match c.storage_layout {
+        SledLayout::DedicatedTree => 0u8,
+        SledLayout::SharedPrefixed => 1,
+    };
+
+    let header = [
+        len.to_be_bytes().as_slice(),
+        c.name.as_bytes(),
+        &c.ttl_in_seconds.to_be_bytes(),
+        &c.idle_ttl_in_seconds.unwrap_or(0).to_be_bytes(),
+        &[layout],
+        &entries.to_be_bytes(),
+    ]
+    .concat();
+
+    writer
+        .write_all(&header)
+        .map_err(|e| CacheError::Engine(format!("Failed to write snapshot: {e}")))
+}
+
+/// Read the next column header written by [`write_column_header`], or by format `version` 1
+/// without the idle TTL and layout, as the column and its number of records,
+/// [`None`] at the end of the snapshot
+pub(crate) fn read_column_header(
+    reader: &mut dyn Read,
+    version: u8,
+) -> Result<Option<(SnapshotColumn, u64)>, CacheError> {
+    let mut len = [0u8; 4];
+
+    // the snapshot may only end between columns
+    match reader.read(&mut len[..1]) {
+        Ok(0) => return Ok(None),
+        Ok(_) => read_exact(reader, &mut len[1..])?,
+        Err(e) => return Err(CacheError::Engine(format!("Failed to read snapshot: {e}"))),
+    }
+
+    let name = String::from_utf8(read_bytes(reader, u32::from_be_bytes(len))?)
+        .map_err(|_| CacheError::Engine("column name in snapshot is not UTF-8".to_string()))?;
+    let mut ttl_in_seconds = [0u8; 4];
+    read_exact(reader, &mut ttl_in_seconds)?;
+
+    let mut idle_ttl_in_seconds = [0u8; 4];
+    let mut layout = [0u8];
+    if version >= 2 {
+        read_exact(reader, &mut idle_ttl_in_seconds)?;
+        read_exact(reader, &mut layout)?;
+    }
+
+    let mut entries = [0u8; 8];
+    read_exact(reader, &mut entries)?;
+
+    Ok(Some((
+        SnapshotColumn {
+            name,
+            ttl_in_seconds: i32::from_be_bytes(ttl_in_seconds),
+            idle_ttl_in_seconds: Some(u32::from_be_bytes(idle_ttl_in_seconds))
+                .filter(|idle| *idle > 0),
+            storage_layout: match layout[0] {
+                0 => SledLayout::DedicatedTree,
+                1 => SledLayout::SharedPrefixed,
+                layout => {
+                    return Err(CacheError::Engine(format!(
+                        "unknown sled layout {layout} in snapshot"
+                    )));
+                }
+            },
+        },
+        u64::from_be_bytes(entries),
+    )))
+}
+
+/// Write one `(key, value, remaining_ttl)` record of a column export.
+///
+/// Records are the key and the value, each prefixed by its length as a big endian `u32`,
+/// followed by the seconds left to live as a big endian `u32`, zero if it never expires
+pub(crate) fn write_record(
+    writer: &mut dyn Write,
+    key: &[u8],
+    value: &[u8],
+    ttl_in_seconds: u32,
+) -> Result<(), CacheError> {
+    let len = |bytes: &[u8]| {
+        u32::try_from(bytes.len())
+            .map_err(|_| CacheError::Engine("entry is too large to export".to_string()))
+    };
+
+    let record = [
+        len(key)?.to_be_bytes().as_slice(),
+        key,
+        &len(value)?.to_be_bytes(),
+        value,
+        &ttl_in_seconds.to_be_bytes(),
+    ]
+    .concat();
+
+    writer
+        .write_all(&record)
+        .map_err(|e| CacheError::Engine(format!("Failed to write export: {e}")))
+}
+
+/// Read the next record written by [`write_record`], [`None`] at the end of the export
+pub(crate) fn read_record(reader: &mut dyn Read) -> Result<Option<Record>, CacheError> {
+    let mut len = [0u8; 4];
+
+    // the export may only end between records
+    match reader.read(&mut len[..1]) {
+        Ok(0) => return Ok(None),
+        Ok(_) => read_exact(reader, &mut len[1..])?,
+        Err(e) => return Err(CacheError::Engine(format!("Failed to read export: {e}"))),
+    }
+
+    let key = read_bytes(reader, u32::from_be_bytes(len))?;
+    read_exact(reader, &mut len)?;
+    let value = read_bytes(reader, u32::from_be_bytes(len))?;
+    read_exact(reader, &mut len)?;
+
+    Ok(Some((key, value, u32::from_be_bytes(len))))
+}
+
+/// Fill `buf`, failing on an export cut short
+fn read_exact(reader: &mut dyn Read, buf: &mut [u8]) -> Result<(), CacheError> {
+    reader.read_exact(buf).map_err(|e| match e.kind() {
+        ErrorKind::UnexpectedEof => CacheError::Engine("export ends mid record".to_string()),
+        _ => CacheError::Engine(format!("Failed to read export: {e}")),
+    })
+}
+
+/// Read `len` bytes, growing the buffer as they arrive rather than trusting `len` up front
+fn read_bytes(reader: &mut dyn Read, len: u32) -> Result<Vec<u8>, CacheError> {
+    let mut bytes = Vec::new();
+
+    let read = Read::take(&mut *reader, u64::from(len))
+        .read_to_end(&mut bytes)
+        .map_err(|e| CacheError::Engine(format!("Failed to read export: {e}")))?;
+
+    if u64::try_from(read).ok() != Some(u64::from(len)) {
+        return Err(CacheError::Engine("export ends mid record".to_string()));
+    }
+
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{
+        SnapshotColumn, read_column_header, read_record, read_snapshot_header, write_column_header,
+        write_record, write_snapshot_header,
+    };
+    use crate::{ColumnDefinition, SledLayout};
+
+    #[test]
+    fn test_records_round_trip() {
+        let mut export = Vec::new();
+        write_record(&mut export, b"key", b"value", 60).unwrap();
+        write_record(&mut export, b"", b"forever", 0).unwrap();
+
+        let mut reader = export.as_slice();
+        assert_eq!(
+            read_record(&mut reader).unwrap(),
+            Some((b"key".to_vec(), b"value".to_vec(), 60))
+        );
+        assert_eq!(
+            read_record(&mut reader).unwrap(),
+            Some((Vec::new(), b"forever".to_vec(), 0))
+        );
+        assert_eq!(read_record(&mut reader).unwrap(), None);
+    }
+
+    #[test]
+    fn test_truncated_record() {
+        let mut export = Vec::new();
+        write_record(&mut export, b"key", b"value", 60).unwrap();
+        export.pop();
+
+        assert!(read_record(&mut export.as_slice()).is_err());
+    }
+
+    #[test]
+    fn test_snapshot_headers_round_trip() {
+        let mut snapshot = Vec::new();
+        write_snapshot_header(&mut snapshot).unwrap();
+        let column = SnapshotColumn {
+            name: "prices".to_string(),
+            ttl_in_seconds: 60,
+            idle_ttl_in_seconds: Some(30),
+            storage_layout: SledLayout::SharedPrefixed,
+        };
+        write_column_header(&mut snapshot, &column, 2).unwrap();
+
+        let mut reader = snapshot.as_slice();
+        let version = read_snapshot_header(&mut reader).unwrap();
+        let (column, entries) = read_column_header(&mut reader, version).unwrap().unwrap();
+        assert_eq!(
+            (column.name(), column.get_ttl_in_seconds()),
+            ("prices".to_string(), 60)
+        );
+        assert_eq!(column.idle_ttl_in_seconds(), Some(30));
+        assert_eq!(column.storage_layout(), SledLayout::SharedPrefixed);
+        assert_eq!(entries, 2);
+        assert!(read_column_header(&mut reader, version).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_version_1_column_header() {
+        let header = [
+            [0, 0, 0, 6].as_slice(),
+            b"prices",
+            &60i32.to_be_bytes(),
+            &2u64.to_be_bytes(),
+        ]
+        .concat();
+
+        let (column, entries) = read_column_header(&mut header.as_slice(), 1)
+            .unwrap()
+            .unwrap();
+        assert_eq!(column.name(), "prices");
+        assert_eq!(column.idle_ttl_in_seconds(), None);
+        assert_eq!(column.storage_layout(), SledLayout::DedicatedTree);
+        assert_eq!(entries, 2);
+    }
+
+    #[test]
+    fn test_unsupported_snapshot() {
+        let mut snapshot = Vec::new();
+        write_snapshot_header(&mut snapshot).unwrap();
+
+        let last = snapshot.len() - 1;
+        snapshot[last] = 3;
+        assert!(read_snapshot_header(&mut snapshot.as_slice()).is_err());
+        assert!(read_snapshot_header(&mut b"OMEGAEXP\x01".as_slice()).is_err());
+        assert!(read_snapshot_header(&mut b"".as_slice()).is_err());
+    }
+}
@@ -0,0 +1,51 @@
+/// Length in bytes of the checksum prefixed to each value
+const CHECKSUM_LEN: usize = 4;
+
+/// Prefix the encoded value with its CRC32 checksum
+pub(crate) fn seal(bytes: &[u8]) -> Vec<u8> {
+    let mut sealed = Vec::with_capacity(CHECKSUM_LEN + bytes.len());
+    sealed.extend_from_slice(&crc32fast::hash(bytes).to_be_bytes());
+    sealed.extend_from_slice(bytes);
+
+    sealed
+}
+
+/// Verify and strip the CRC32 checksum prefixed by [`seal`]
+///
+/// Returns [`None`] if the value is too short to hold a checksum
+/// or the checksum does not match the payload
+pub(crate) fn unseal(bytes: &[u8]) -> Option<&[u8]> {
+    if bytes.len() < CHECKSUM_LEN {
+        return None;
+    }
+
+    let (checksum, payload) = bytes.split_at(CHECKSUM_LEN);
+
+    if checksum != crc32fast::hash(payload).to_be_bytes() {
+        return None;
+    }
+
+    Some(payload)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{seal, unseal};
+
+    #[test]
+    fn test_seal_and_unseal() {
+        let sealed = seal(b"some value");
+
+        assert_eq!(unseal(&sealed).unwrap(), b"some value");
+    }
+
+    #[test]
+    fn test_unseal_corrupted() {
+        let mut sealed = seal(b"some value");
+        let last = sealed.len() - 1;
+        sealed[last] ^= 0x01;
+
+        assert!(unseal(&sealed).is_none());
+        assert!(unseal(&[0u8; 2]).is_none());
+    }
+}
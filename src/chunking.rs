@@ -0,0 +1,127 @@
+/// Marker for a value stored whole at its key
+const INLINE: u8 = 0;
+/// Marker for a manifest of a value split across chunk keys
+const MANIFEST: u8 = 1;
+/// Separator between a key and the index of one of its chunks
+const CHUNK_SEPARATOR: u8 = b'#';
+
+/// What is stored at a key when chunking is enabled
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum Stored<'a> {
+    /// The value itself
+    Inline(&'a [u8]),
+    /// The value is split into `chunks` chunks totalling `len` bytes, with the CRC32
+    /// `checksum` of the whole value so chunks left over from an earlier value of
+    /// the same length are not joined into it. Manifests written before the checksum
+    /// was added have none.
+    Manifest {
+        chunks: u32,
+        len: u64,
+        checksum: Option<u32>,
+    },
+}
+
+/// Mark a value small enough to be stored whole
+pub(crate) fn inline(bytes: &[u8]) -> Vec<u8> {
+    let mut stored = Vec::with_capacity(1 + bytes.len());
+    stored.push(INLINE);
+    stored.extend_from_slice(bytes);
+
+    stored
+}
+
+/// Describe a value split into chunks
+pub(crate) fn manifest(chunks: u32, value: &[u8]) -> Vec<u8> {
+    let mut stored = Vec::with_capacity(17);
+    stored.push(MANIFEST);
+    stored.extend_from_slice(&chunks.to_be_bytes());
+    stored.extend_from_slice(&(value.len() as u64).to_be_bytes());
+    stored.extend_from_slice(&crc32fast::hash(value).to_be_bytes());
+
+    stored
+}
+
+/// Whether the chunks joined for a manifest add up to the value it describes
+pub(crate) fn matches(value: &[u8], len: u64, checksum: Option<u32>) -> bool {
+    value.len() as u64 == len && checksum.is_none_or(|checksum| checksum == crc32fast::hash(value))
+}
+
+/// Read what is stored at a key, [`None`] if it was not written with chunking enabled
+pub(crate) fn parse(bytes: &[u8]) -> Option<Stored<'_>> {
+    match bytes.split_first()? {
+        (&INLINE, value) => Some(Stored::Inline(value)),
+        (&MANIFEST, manifest) if manifest.len() == 12 || manifest.len() == 16 => {
+            let (chunks, rest) = manifest.split_at(4);
+            let (len, checksum) = rest.split_at(8);
+
+            Some(Stored::Manifest {
+                chunks: u32::from_be_bytes(chunks.try_into().ok()?),
+                len: u64::from_be_bytes(len.try_into().ok()?),
+                checksum: match checksum {
+                    [] => None,
+                    checksum => Some(u32::from_be_bytes(checksum.try_into().ok()?)),
+                },
+            })
+        }
+        _ => None,
+    }
+}
+
+/// Key holding chunk `index` of the value at key, `{key}#{index}`
+pub(crate) fn chunk_key(key: &[u8], index: u32) -> Vec<u8> {
+    let mut chunk_key = key.to_vec();
+    chunk_key.push(CHUNK_SEPARATOR);
+    chunk_key.extend_from_slice(index.to_string().as_bytes());
+
+    chunk_key
+}
+
+#[cfg(test)]
+mod test {
+    use super::{MANIFEST, Stored, chunk_key, inline, manifest, matches, parse};
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            parse(&inline(b"some value")),
+            Some(Stored::Inline(b"some value"))
+        );
+        assert_eq!(
+            parse(&manifest(3, &[7; 1024])),
+            Some(Stored::Manifest {
+                chunks: 3,
+                len: 1024,
+                checksum: Some(crc32fast::hash(&[7; 1024])),
+            })
+        );
+
+        let mut legacy = vec![MANIFEST];
+        legacy.extend_from_slice(&3u32.to_be_bytes());
+        legacy.extend_from_slice(&1024u64.to_be_bytes());
+        assert_eq!(
+            parse(&legacy),
+            Some(Stored::Manifest {
+                chunks: 3,
+                len: 1024,
+                checksum: None,
+            })
+        );
+        assert_eq!(parse(b""), None);
+        assert_eq!(parse(&[1, 2, 3]), None);
+    }
+
+    #[test]
+    fn test_matches() {
+        let checksum = Some(crc32fast::hash(b"new value"));
+
+        assert!(matches(b"new value", 9, checksum));
+        assert!(!matches(b"old value", 9, checksum));
+        assert!(!matches(b"new value!", 9, checksum));
+        assert!(matches(b"old value", 9, None));
+    }
+
+    #[test]
+    fn test_chunk_key() {
+        assert_eq!(chunk_key(b"key", 12), b"key#12");
+    }
+}
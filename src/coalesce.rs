@@ -0,0 +1,704 @@
+//!
+//! Coalesce repeated writes of the same key, for keys updated many times a second.
+//!
+//! ```
+//! use std::time::Duration;
+//!
+//! use omega_cache::{Engine, coalesce::CoalescingEngine, noop_engine::NoopEngine};
+//!
+//! // each key is written at most once every 100ms, with its latest value
+//! let engine = Engine::new(Box::new(CoalescingEngine::new(
+//!     NoopEngine::passthrough(),
+//!     Duration::from_millis(100),
+//! )));
+//! ```
+//!
+//! Only the value pending when the writes are flushed reaches storage, every value
+//! inserted before it is intentionally lost. Reads through the same engine see pending
+//! values, while other processes sharing the storage see the value from the last flush,
+//! so are up to `flush_interval` behind, and longer if no operation follows to trigger
+//! the flush. Pending writes are also flushed when the engine is dropped and by
+//! [`CoalescingEngine::try_flush`], but are lost if the process exits without either.
+//!
+//! A flush on drop has no caller to fail, so its error is only passed to
+//! [`CoalescingEngine::on_drop_error`] if set. Call [`CoalescingEngine::try_flush`]
+//! before shutting down to handle failed writes.
+//!
+
+use std::{
+    collections::HashMap,
+    fmt::Debug,
+    sync::{Arc, Mutex, MutexGuard, PoisonError},
+    time::{Duration, Instant},
+};
+
+use crate::noop_engine::NoopEngine;
+use crate::{
+    BackendConfig, CacheError, CacheStorage, ColumnDefinition, ColumnReport, EvictionListener,
+    InsertOutcome, RawEntry, SledLayout, SlowOperationListener, StoredValue, TimedEntry,
+    TransactionCondition, TransactionWrite, TtlEnforcement,
+};
+
+/// Copy of the column settings storage reads on insert, kept with a pending write
+struct PendingColumn {
+    name: String,
+    ttl_in_seconds: i32,
+    max_entries: Option<usize>,
+    ttl_jitter: Option<f32>,
+    idle_ttl_in_seconds: Option<u32>,
+    expire_on_ttl_change: bool,
+    storage_layout: SledLayout,
+}
+
+impl PendingColumn {
+    fn new(c: &dyn ColumnDefinition) -> PendingColumn {
+        PendingColumn {
+            name: c.name(),
+            ttl_in_seconds: c.get_ttl_in_seconds(),
+            max_entries: c.max_entries(),
+            ttl_jitter: c.ttl_jitter(),
+            idle_ttl_in_seconds: c.idle_ttl_in_seconds(),
+            expire_on_ttl_change: c.expire_on_ttl_change(),
+            storage_layout: c.storage_layout(),
+        }
+    }
+}
+
+impl ColumnDefinition for PendingColumn {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn get_ttl_in_seconds(&self) -> i32 {
+        self.ttl_in_seconds
+    }
+
+    fn max_entries(&self) -> Option<usize> {
+        self.max_entries
+    }
+
+    fn ttl_jitter(&self) -> Option<f32> {
+        self.ttl_jitter
+    }
+
+    fn idle_ttl_in_seconds(&self) -> Option<u32> {
+        self.idle_ttl_in_seconds
+    }
+
+    fn expire_on_ttl_change(&self) -> bool {
+        self.expire_on_ttl_change
+    }
+
+    fn storage_layout(&self) -> SledLayout {
+        self.storage_layout
+    }
+}
+
+/// Latest value inserted at a key and not yet written
+struct Pending {
+    column: PendingColumn,
+    value: Vec<u8>,
+    /// TTL override of the insert, if any
+    ttl_in_seconds: Option<i32>,
+}
+
+/// Writes waiting for the next flush, by column name and key
+#[derive(Default)]
+struct Buffer {
+    pending: HashMap<(String, Vec<u8>), Pending>,
+    /// When the oldest pending write was made
+    since: Option<Instant>,
+}
+
+///
+/// Storage holding inserts for up to `flush_interval` and writing only the latest
+/// value of each key, see the [module docs](self) for what is lost.
+///
+/// [`CacheStorage::try_insert`], [`CacheStorage::try_insert_with_ttl`] and
+/// [`CacheStorage::try_insert_batch`] are coalesced. Every other write, as well as
+/// listing, counting and reporting, flushes the pending writes first so it sees them.
+/// Dropping a column discards its pending writes. Pending writes are flushed all
+/// together by the first operation once the oldest is `flush_interval` old, so a key
+/// updated continuously is still written once per interval.
+///
+pub struct CoalescingEngine {
+    inner: Box<dyn CacheStorage + Send + Sync>,
+    flush_interval: Duration,
+    buffer: Mutex<Buffer>,
+    on_drop_error: Option<DropErrorListener>,
+}
+
+/// Called with the error of the flush on drop, see [`CoalescingEngine::on_drop_error`]
+pub type DropErrorListener = Box<dyn Fn(&CacheError) + Send + Sync>;
+
+impl Debug for CoalescingEngine {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CoalescingEngine")
+            .field("flush_interval", &self.flush_interval)
+            .field("pending", &self.buffer().pending.len())
+            .field("on_drop_error", &self.on_drop_error.is_some())
+            .finish_non_exhaustive()
+    }
+}
+
+impl CoalescingEngine {
+    /// Coalesce writes to `inner`, holding them for up to `flush_interval`
+    #[must_use]
+    pub fn new(
+        inner: Box<dyn CacheStorage + Send + Sync>,
+        flush_interval: Duration,
+    ) -> CoalescingEngine {
+        CoalescingEngine {
+            inner,
+            flush_interval,
+            buffer: Mutex::new(Buffer::default()),
+            on_drop_error: None,
+        }
+    }
+
+    /// Call `f` with the error of the flush made when the engine is dropped,
+    /// which is otherwise silently lost
+    #[must_use]
+    pub fn on_drop_error(
+        mut self,
+        f: impl Fn(&CacheError) + Send + Sync + 'static,
+    ) -> CoalescingEngine {
+        self.on_drop_error = Some(Box::new(f));
+        self
+    }
+
+    /// Write every pending value now
+    ///
+    /// # Errors
+    /// Returns the first error from the inner storage,
+    /// the values that failed to write are dropped
+    pub fn try_flush(&self) -> Result<(), CacheError> {
+        Self::flush(&*self.inner, &mut self.buffer())
+    }
+
+    fn buffer(&self) -> MutexGuard<'_, Buffer> {
+        self.buffer.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Write and clear the pending values
+    fn flush(inner: &dyn CacheStorage, buffer: &mut Buffer) -> Result<(), CacheError> {
+        buffer.since = None;
+        let mut result = Ok(());
+
+        for ((_, key), pending) in buffer.pending.drain() {
+            let written = match pending.ttl_in_seconds {
+                Some(ttl) => inner.try_insert_with_ttl(&pending.column, &key, &pending.value, ttl),
+                None => inner.try_insert(&pending.column, &key, &pending.value),
+            };
+
+            if result.is_ok() {
+                result = written;
+            }
+        }
+
+        result
+    }
+
+    /// Hold a value until the next flush, flushing if the oldest pending value is due
+    fn buffer_insert(
+        &self,
+        c: &dyn ColumnDefinition,
+        key: &[u8],
+        value: &[u8],
+        ttl_in_seconds: Option<i32>,
+    ) -> Result<(), CacheError> {
+        let mut buffer = self.buffer();
+
+        buffer.since.get_or_insert_with(Instant::now);
+        buffer.pending.insert(
+            (c.name(), key.to_vec()),
+            Pending {
+                column: PendingColumn::new(c),
+                value: value.to_vec(),
+                ttl_in_seconds,
+            },
+        );
+
+        self.flush_due(&mut buffer)
+    }
+
+    /// Flush if the oldest pending value has waited `flush_interval`
+    fn flush_due(&self, buffer: &mut Buffer) -> Result<(), CacheError> {
+        match buffer.since {
+            Some(since) if since.elapsed() >= self.flush_interval => {
+                Self::flush(&*self.inner, buffer)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Flush everything pending, then run an operation on the inner storage
+    fn flushed<T>(
+        &self,
+        operation: impl FnOnce(&dyn CacheStorage) -> Result<T, CacheError>,
+    ) -> Result<T, CacheError> {
+        let mut buffer = self.buffer();
+        Self::flush(&*self.inner, &mut buffer)?;
+
+        operation(&*self.inner)
+    }
+
+    /// Pending value at key, or the result of reading it from the inner storage
+    fn read<T>(
+        &self,
+        c: &dyn ColumnDefinition,
+        key: &[u8],
+        pending: impl FnOnce(&[u8]) -> T,
+        read: impl FnOnce(&dyn CacheStorage) -> Result<Option<T>, CacheError>,
+    ) -> Result<Option<T>, CacheError> {
+        let mut buffer = self.buffer();
+        self.flush_due(&mut buffer)?;
+
+        if let Some(value) = buffer.pending.get(&(c.name(), key.to_vec())) {
+            return Ok(Some(pending(&value.value)));
+        }
+
+        read(&*self.inner)
+    }
+}
+
+impl Drop for CoalescingEngine {
+    fn drop(&mut self) {
+        if let Err(e) = self.try_flush()
+            && let Some(f) = &self.on_drop_error
+        {
+            f(&e);
+        }
+    }
+}
+
+impl CacheStorage for CoalescingEngine {
+    /// Only built around another storage, this coalesces writes to a passthrough [`NoopEngine`]
+    fn build(_path: String, _capacity: Option<u64>) -> Box<dyn CacheStorage + Send + Sync>
+    where
+        Self: Sized,
+    {
+        Box::new(CoalescingEngine::new(
+            NoopEngine::passthrough(),
+            Duration::from_secs(1),
+        ))
+    }
+
+    fn discards_writes(&self) -> bool {
+        self.inner.discards_writes()
+    }
+
+    fn ttl_enforcement(&self) -> TtlEnforcement {
+        self.inner.ttl_enforcement()
+    }
+
+    fn describe_config(&self) -> BackendConfig {
+        self.inner.describe_config().with(
+            "coalesce_flush_interval",
+            format!("{:?}", self.flush_interval),
+        )
+    }
+
+    fn try_insert(
+        &self,
+        c: &dyn ColumnDefinition,
+        key: &[u8],
+        value: &[u8],
+    ) -> Result<(), CacheError> {
+        self.buffer_insert(c, key, value, None)
+    }
+
+    fn try_insert_with_ttl(
+        &self,
+        c: &dyn ColumnDefinition,
+        key: &[u8],
+        value: &[u8],
+        ttl_in_seconds: i32,
+    ) -> Result<(), CacheError> {
+        self.buffer_insert(c, key, value, Some(ttl_in_seconds))
+    }
+
+    fn try_insert_reporting(
+        &self,
+        c: &dyn ColumnDefinition,
+        key: &[u8],
+        value: &[u8],
+    ) -> Result<InsertOutcome, CacheError> {
+        self.flushed(|inner| inner.try_insert_reporting(c, key, value))
+    }
+
+    fn try_insert_batch(
+        &self,
+        c: &dyn ColumnDefinition,
+        items: &[(Vec<u8>, Vec<u8>)],
+    ) -> Result<(), CacheError> {
+        for (key, value) in items {
+            self.buffer_insert(c, key, value, None)?;
+        }
+
+        Ok(())
+    }
+
+    fn try_swap(
+        &self,
+        c: &dyn ColumnDefinition,
+        key: &[u8],
+        value: &[u8],
+    ) -> Result<Option<Vec<u8>>, CacheError> {
+        self.flushed(|inner| inner.try_swap(c, key, value))
+    }
+
+    fn try_rename(
+        &self,
+        c: &dyn ColumnDefinition,
+        from: &[u8],
+        to: &[u8],
+    ) -> Result<bool, CacheError> {
+        self.flushed(|inner| inner.try_rename(c, from, to))
+    }
+
+    fn try_take(
+        &self,
+        c: &dyn ColumnDefinition,
+        key: &[u8],
+    ) -> Result<Option<Vec<u8>>, CacheError> {
+        self.flushed(|inner| inner.try_take(c, key))
+    }
+
+    fn try_commit(
+        &self,
+        c: &dyn ColumnDefinition,
+        conditions: &[TransactionCondition],
+        writes: &[TransactionWrite],
+    ) -> Result<bool, CacheError> {
+        self.flushed(|inner| inner.try_commit(c, conditions, writes))
+    }
+
+    fn try_touch_many(
+        &self,
+        c: &dyn ColumnDefinition,
+        keys: &[&[u8]],
+        ttl_in_seconds: i32,
+    ) -> Result<usize, CacheError> {
+        self.flushed(|inner| inner.try_touch_many(c, keys, ttl_in_seconds))
+    }
+
+    fn try_get(&self, c: &dyn ColumnDefinition, key: &[u8]) -> Result<Option<Vec<u8>>, CacheError> {
+        self.read(c, key, <[u8]>::to_vec, |inner| inner.try_get(c, key))
+    }
+
+    fn try_get_many(
+        &self,
+        c: &dyn ColumnDefinition,
+        keys: &[&[u8]],
+    ) -> Result<Vec<Option<Vec<u8>>>, CacheError> {
+        let mut buffer = self.buffer();
+        self.flush_due(&mut buffer)?;
+
+        let name = c.name();
+        let mut values = keys
+            .iter()
+            .map(|key| {
+                buffer
+                    .pending
+                    .get(&(name.clone(), key.to_vec()))
+                    .map(|pending| pending.value.clone())
+            })
+            .collect::<Vec<_>>();
+
+        let missing = keys
+            .iter()
+            .zip(&values)
+            .filter(|(_, value)| value.is_none())
+            .map(|(key, _)| *key)
+            .collect::<Vec<_>>();
+
+        if missing.is_empty() {
+            return Ok(values);
+        }
+
+        let mut read = self.inner.try_get_many(c, &missing)?.into_iter();
+        for value in values.iter_mut().filter(|value| value.is_none()) {
+            *value = read.next().flatten();
+        }
+
+        Ok(values)
+    }
+
+    fn try_value_size(
+        &self,
+        c: &dyn ColumnDefinition,
+        key: &[u8],
+    ) -> Result<Option<usize>, CacheError> {
+        self.read(c, key, <[u8]>::len, |inner| inner.try_value_size(c, key))
+    }
+
+    fn try_get_stored(
+        &self,
+        c: &dyn ColumnDefinition,
+        key: &[u8],
+    ) -> Result<Option<StoredValue>, CacheError> {
+        self.read(
+            c,
+            key,
+            |value| StoredValue::from(value.to_vec()),
+            |inner| inner.try_get_stored(c, key),
+        )
+    }
+
+    fn try_get_allowing_stale(
+        &self,
+        c: &dyn ColumnDefinition,
+        key: &[u8],
+    ) -> Result<Option<(Vec<u8>, bool)>, CacheError> {
+        self.read(
+            c,
+            key,
+            |value| (value.to_vec(), false),
+            |inner| inner.try_get_allowing_stale(c, key),
+        )
+    }
+
+    /// Discards the column's pending writes without writing them
+    fn try_drop_column(&self, c: &dyn ColumnDefinition) -> Result<(), CacheError> {
+        let mut buffer = self.buffer();
+
+        let name = c.name();
+        buffer.pending.retain(|(column, _), _| *column != name);
+        if buffer.pending.is_empty() {
+            buffer.since = None;
+        }
+
+        self.inner.try_drop_column(c)
+    }
+
+    fn try_entries(&self, c: &dyn ColumnDefinition) -> Result<Vec<RawEntry>, CacheError> {
+        self.flushed(|inner| inner.try_entries(c))
+    }
+
+    fn try_timed_entries(&self, c: &dyn ColumnDefinition) -> Result<Vec<TimedEntry>, CacheError> {
+        self.flushed(|inner| inner.try_timed_entries(c))
+    }
+
+    fn try_count_column(&self, c: &dyn ColumnDefinition) -> Result<usize, CacheError> {
+        self.flushed(|inner| inner.try_count_column(c))
+    }
+
+    fn try_entry_ages(&self, c: &dyn ColumnDefinition) -> Result<Vec<Duration>, CacheError> {
+        self.flushed(|inner| inner.try_entry_ages(c))
+    }
+
+    fn validate_column(&self, c: &dyn ColumnDefinition) -> Result<(), CacheError> {
+        self.inner.validate_column(c)
+    }
+
+    fn try_column_layout(&self, c: &dyn ColumnDefinition) -> Result<SledLayout, CacheError> {
+        self.flushed(|inner| inner.try_column_layout(c))
+    }
+
+    fn try_list_columns(&self) -> Result<Vec<String>, CacheError> {
+        self.flushed(|inner| inner.try_list_columns())
+    }
+
+    fn try_report(&self) -> Result<Vec<ColumnReport>, CacheError> {
+        self.flushed(|inner| inner.try_report())
+    }
+
+    fn try_maintenance(&self) -> Result<(), CacheError> {
+        self.flushed(|inner| inner.try_maintenance())
+    }
+
+    fn try_increment(
+        &self,
+        c: &dyn ColumnDefinition,
+        key: &[u8],
+        delta: i64,
+    ) -> Result<i64, CacheError> {
+        self.flushed(|inner| inner.try_increment(c, key, delta))
+    }
+
+    fn set_eviction_listener(
+        &mut self,
+        listener: Arc<dyn EvictionListener>,
+    ) -> Result<(), CacheError> {
+        self.inner.set_eviction_listener(listener)
+    }
+
+    fn set_slow_operation_listener(
+        &mut self,
+        threshold: Duration,
+        listener: SlowOperationListener,
+    ) -> Result<(), CacheError> {
+        self.inner.set_slow_operation_listener(threshold, listener)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{
+        sync::{
+            Arc,
+            atomic::{AtomicUsize, Ordering},
+        },
+        time::Duration,
+    };
+
+    use crate::{CacheError, CacheStorage, ColumnDefinition, noop_engine::NoopEngine};
+
+    use super::CoalescingEngine;
+
+    struct TestColumn {}
+
+    impl ColumnDefinition for TestColumn {
+        fn name(&self) -> String {
+            "coalesced_column".to_string()
+        }
+
+        fn get_ttl_in_seconds(&self) -> i32 {
+            60
+        }
+    }
+
+    /// Passthrough storage counting the inserts that reach it
+    struct CountingEngine {
+        inner: Box<dyn CacheStorage + Send + Sync>,
+        inserts: Arc<AtomicUsize>,
+        failing: bool,
+    }
+
+    impl CacheStorage for CountingEngine {
+        fn build(_path: String, _capacity: Option<u64>) -> Box<dyn CacheStorage + Send + Sync> {
+            Box::new(CountingEngine {
+                inner: NoopEngine::passthrough(),
+                inserts: Arc::default(),
+                failing: false,
+            })
+        }
+
+        fn try_insert(
+            &self,
+            c: &dyn ColumnDefinition,
+            key: &[u8],
+            value: &[u8],
+        ) -> Result<(), CacheError> {
+            self.inserts.fetch_add(1, Ordering::SeqCst);
+            if self.failing {
+                return Err(CacheError::Put("failing".to_string()));
+            }
+
+            self.inner.try_insert(c, key, value)
+        }
+
+        fn try_get(
+            &self,
+            c: &dyn ColumnDefinition,
+            key: &[u8],
+        ) -> Result<Option<Vec<u8>>, CacheError> {
+            self.inner.try_get(c, key)
+        }
+
+        fn try_drop_column(&self, c: &dyn ColumnDefinition) -> Result<(), CacheError> {
+            self.inner.try_drop_column(c)
+        }
+    }
+
+    fn counting(flush_interval: Duration) -> (CoalescingEngine, Arc<AtomicUsize>) {
+        let inserts = Arc::new(AtomicUsize::new(0));
+        let inner = CountingEngine {
+            inner: NoopEngine::passthrough(),
+            inserts: inserts.clone(),
+            failing: false,
+        };
+
+        (
+            CoalescingEngine::new(Box::new(inner), flush_interval),
+            inserts,
+        )
+    }
+
+    #[test]
+    fn test_only_latest_value_is_written() {
+        let (storage, inserts) = counting(Duration::from_mins(1));
+
+        for i in 0..100u8 {
+            assert!(storage.try_insert(&TestColumn {}, b"key", &[i]).is_ok());
+        }
+
+        // pending values are read back before they are written
+        assert_eq!(inserts.load(Ordering::SeqCst), 0);
+        assert_eq!(
+            storage.try_get(&TestColumn {}, b"key").unwrap(),
+            Some(vec![99])
+        );
+        assert_eq!(
+            storage
+                .try_get_many(&TestColumn {}, &[b"missing", b"key"])
+                .unwrap(),
+            vec![None, Some(vec![99])]
+        );
+
+        assert!(storage.try_flush().is_ok());
+        assert_eq!(inserts.load(Ordering::SeqCst), 1);
+        assert_eq!(
+            storage.inner.try_get(&TestColumn {}, b"key").unwrap(),
+            Some(vec![99])
+        );
+    }
+
+    #[test]
+    fn test_due_writes_are_flushed() {
+        let (storage, inserts) = counting(Duration::ZERO);
+
+        assert!(storage.try_insert(&TestColumn {}, b"key", b"value").is_ok());
+        assert_eq!(inserts.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_flush_on_drop() {
+        let (storage, inserts) = counting(Duration::from_mins(1));
+
+        assert!(storage.try_insert(&TestColumn {}, b"key", b"value").is_ok());
+        assert!(
+            storage
+                .try_insert(&TestColumn {}, b"other", b"value")
+                .is_ok()
+        );
+        drop(storage);
+
+        assert_eq!(inserts.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn test_flush_on_drop_error() {
+        let errors = Arc::new(AtomicUsize::new(0));
+        let counted = errors.clone();
+        let inner = CountingEngine {
+            inner: NoopEngine::passthrough(),
+            inserts: Arc::default(),
+            failing: true,
+        };
+        let storage = CoalescingEngine::new(Box::new(inner), Duration::from_mins(1)).on_drop_error(
+            move |e| {
+                assert!(matches!(e, CacheError::Put(_)));
+                counted.fetch_add(1, Ordering::SeqCst);
+            },
+        );
+
+        assert!(storage.try_insert(&TestColumn {}, b"key", b"value").is_ok());
+        drop(storage);
+
+        assert_eq!(errors.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_drop_column_discards_pending() {
+        let (storage, inserts) = counting(Duration::from_mins(1));
+
+        assert!(storage.try_insert(&TestColumn {}, b"key", b"value").is_ok());
+        assert!(storage.try_drop_column(&TestColumn {}).is_ok());
+        assert!(storage.try_get(&TestColumn {}, b"key").unwrap().is_none());
+        drop(storage);
+
+        assert_eq!(inserts.load(Ordering::SeqCst), 0);
+    }
+}
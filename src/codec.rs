@@ -0,0 +1,235 @@
+use bincode::{
+    BorrowDecode, Decode, Encode,
+    config::Config,
+    de::{
+        Decoder, DecoderImpl,
+        read::{BorrowReader, Reader},
+    },
+    error::DecodeError,
+    error::EncodeError,
+};
+
+///
+/// Encoding used for values handed to storage
+///
+/// Set the codec used for writes with [`crate::Engine::with_codec`]
+/// and keep reading values written by a previous codec with
+/// [`crate::Engine::with_legacy_codec`] while migrating between them.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Codec {
+    /// bincode's standard configuration, variable width integers
+    #[default]
+    Standard,
+    /// bincode's legacy configuration, fixed width integers as written by bincode 1.x
+    Legacy,
+}
+
+/// What a read does with a value whose format marker this engine does not read,
+/// see [`crate::Engine::with_format_marker`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FormatMismatch {
+    /// Read the value as missing, so it is refilled and overwritten
+    Miss,
+    /// Fail the read with [`crate::CacheError::Decode`] naming the marker found
+    Error,
+}
+
+impl Codec {
+    /// Byte marking values encoded with this codec, see [`crate::Engine::with_format_marker`]
+    pub(crate) fn marker(self) -> u8 {
+        match self {
+            Codec::Standard => 0xC2,
+            Codec::Legacy => 0xC1,
+        }
+    }
+
+    /// Codec of a format marker written by [`Codec::marker`]
+    pub(crate) fn from_marker(marker: u8) -> Option<Codec> {
+        [Codec::Standard, Codec::Legacy]
+            .into_iter()
+            .find(|codec| codec.marker() == marker)
+    }
+
+    /// Encode a value with this codec
+    pub(crate) fn encode<V: Encode>(self, value: &V) -> Result<Vec<u8>, EncodeError> {
+        match self {
+            Codec::Standard => bincode::encode_to_vec(value, bincode::config::standard()),
+            Codec::Legacy => bincode::encode_to_vec(value, bincode::config::legacy()),
+        }
+    }
+
+    /// Decode a value with this codec, allocating at most `limit` bytes for its collections,
+    /// returning it and the number of bytes read
+    pub(crate) fn decode<V: Decode<()>>(
+        self,
+        bytes: &[u8],
+        limit: usize,
+    ) -> Result<(V, usize), DecodeError> {
+        self.decode_with_context(bytes, (), limit)
+    }
+
+    /// Decode a value with this codec and a bincode decode context, allocating at most
+    /// `limit` bytes for its collections, returning it and the number of bytes read
+    pub(crate) fn decode_with_context<Ctx, V: Decode<Ctx>>(
+        self,
+        bytes: &[u8],
+        context: Ctx,
+        limit: usize,
+    ) -> Result<(V, usize), DecodeError> {
+        match self {
+            Codec::Standard => limited(
+                bytes,
+                bincode::config::standard().with_limit::<{ usize::MAX }>(),
+                context,
+                limit,
+                |d| V::decode(d),
+            ),
+            Codec::Legacy => limited(
+                bytes,
+                bincode::config::legacy().with_limit::<{ usize::MAX }>(),
+                context,
+                limit,
+                |d| V::decode(d),
+            ),
+        }
+    }
+
+    /// Decode a value borrowing from `bytes` with this codec, allocating at most `limit`
+    /// bytes for its collections, returning it and the number of bytes read
+    pub(crate) fn borrow_decode<'de, V: BorrowDecode<'de, ()>>(
+        self,
+        bytes: &'de [u8],
+        limit: usize,
+    ) -> Result<(V, usize), DecodeError> {
+        match self {
+            Codec::Standard => limited(
+                bytes,
+                bincode::config::standard().with_limit::<{ usize::MAX }>(),
+                (),
+                limit,
+                |d| V::borrow_decode(d),
+            ),
+            Codec::Legacy => limited(
+                bytes,
+                bincode::config::legacy().with_limit::<{ usize::MAX }>(),
+                (),
+                limit,
+                |d| V::borrow_decode(d),
+            ),
+        }
+    }
+}
+
+/// Reader over the bytes of a value, left holding the bytes not read
+struct Remaining<'de>(&'de [u8]);
+
+impl Reader for Remaining<'_> {
+    fn read(&mut self, bytes: &mut [u8]) -> Result<(), DecodeError> {
+        let read = self.take_bytes(bytes.len())?;
+        bytes.copy_from_slice(read);
+
+        Ok(())
+    }
+}
+
+impl<'de> BorrowReader<'de> for Remaining<'de> {
+    fn take_bytes(&mut self, length: usize) -> Result<&'de [u8], DecodeError> {
+        if length > self.0.len() {
+            return Err(DecodeError::UnexpectedEnd {
+                additional: length - self.0.len(),
+            });
+        }
+
+        let (read, rest) = self.0.split_at(length);
+        self.0 = rest;
+
+        Ok(read)
+    }
+}
+
+/// Run `decode` over `bytes` with `config`, failing with [`DecodeError::LimitExceeded`]
+/// rather than allocating more than `limit` bytes for collections, as claimed by
+/// their length prefixes before any of their items are read.
+///
+/// bincode only takes a limit as a const generic, so `config` must carry the largest
+/// limit there is and all but `limit` of it is claimed up front
+fn limited<'de, C: Config, Ctx, V>(
+    bytes: &'de [u8],
+    config: C,
+    context: Ctx,
+    limit: usize,
+    decode: impl FnOnce(&mut DecoderImpl<Remaining<'de>, C, Ctx>) -> Result<V, DecodeError>,
+) -> Result<(V, usize), DecodeError> {
+    let mut decoder = DecoderImpl::new(Remaining(bytes), config, context);
+    decoder.claim_bytes_read(usize::MAX - limit)?;
+
+    let value = decode(&mut decoder)?;
+
+    Ok((value, bytes.len() - decoder.reader().0.len()))
+}
+
+#[cfg(test)]
+mod test {
+    use bincode::error::DecodeError;
+
+    use super::Codec;
+
+    #[test]
+    fn test_codecs_round_trip() {
+        for codec in [Codec::Standard, Codec::Legacy] {
+            let bytes = codec.encode(&300u32).unwrap();
+
+            assert_eq!(
+                codec.decode::<u32>(&bytes, usize::MAX).unwrap(),
+                (300, bytes.len())
+            );
+        }
+    }
+
+    #[test]
+    fn test_markers_round_trip() {
+        for codec in [Codec::Standard, Codec::Legacy] {
+            assert_eq!(Codec::from_marker(codec.marker()), Some(codec));
+        }
+
+        assert_eq!(Codec::from_marker(0), None);
+    }
+
+    #[test]
+    fn test_codecs_differ() {
+        assert_ne!(
+            Codec::Standard.encode(&300u32).unwrap(),
+            Codec::Legacy.encode(&300u32).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_decode_limit() {
+        // a varint length prefix claiming u64::MAX / 2 items and no items
+        let mut crafted = vec![0xFD];
+        crafted.extend_from_slice(&(u64::MAX / 2).to_le_bytes());
+
+        assert!(matches!(
+            Codec::Standard.decode::<Vec<u64>>(&crafted, 1024 * 1024),
+            Err(DecodeError::LimitExceeded)
+        ));
+        assert!(matches!(
+            Codec::Standard.decode::<Vec<u8>>(&crafted, 1024 * 1024),
+            Err(DecodeError::LimitExceeded)
+        ));
+
+        let bytes = Codec::Legacy.encode(&vec![1u32; 100]).unwrap();
+        assert!(Codec::Legacy.decode::<Vec<u32>>(&bytes, 200).is_err());
+        assert_eq!(
+            Codec::Legacy.decode::<Vec<u32>>(&bytes, 1024).unwrap(),
+            (vec![1u32; 100], bytes.len())
+        );
+
+        let bytes = Codec::Standard.encode(&"borrowed").unwrap();
+        assert_eq!(
+            Codec::Standard.borrow_decode::<&str>(&bytes, 64).unwrap(),
+            ("borrowed", bytes.len())
+        );
+    }
+}
@@ -0,0 +1,112 @@
+use std::{
+    borrow::Cow,
+    io::{Read, Write},
+};
+
+use flate2::{Compression as Level, read::GzDecoder, write::GzEncoder};
+
+/// First two bytes of every gzip stream
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+/// Header of values stored as they are, which no gzip stream starts with
+const UNCOMPRESSED: u8 = 0x00;
+
+///
+/// Compression of values at rest, set with [`crate::Engine::with_compression`]
+///
+/// Each value is compressed after encoding and starts with a header naming
+/// how it was stored, so a column can mix algorithms and uncompressed values:
+/// reads go by the header of each value rather than by the engine's setting.
+/// Values that do not shrink are stored uncompressed behind a `0x00` header.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[non_exhaustive]
+pub enum Compression {
+    /// A gzip stream, whose own `1f 8b` magic bytes are the header,
+    /// so the stored bytes can be served as they are with `Content-Encoding: gzip`
+    #[default]
+    Gzip,
+}
+
+impl Compression {
+    /// Compress the encoded value, or prefix it with the uncompressed header if it does not shrink
+    pub(crate) fn compress(self, bytes: &[u8]) -> std::io::Result<Vec<u8>> {
+        let compressed = match self {
+            Compression::Gzip => {
+                let mut encoder = GzEncoder::new(Vec::new(), Level::default());
+                encoder.write_all(bytes)?;
+                encoder.finish()?
+            }
+        };
+
+        if compressed.len() < bytes.len() {
+            return Ok(compressed);
+        }
+
+        let mut stored = Vec::with_capacity(1 + bytes.len());
+        stored.push(UNCOMPRESSED);
+        stored.extend_from_slice(bytes);
+
+        Ok(stored)
+    }
+}
+
+/// Undo [`Compression::compress`] with whichever algorithm the header names
+///
+/// Returns [`None`] if the header is unknown or the value does not decompress
+pub(crate) fn decompress(bytes: Cow<'_, [u8]>) -> Option<Cow<'_, [u8]>> {
+    match bytes.first()? {
+        &UNCOMPRESSED => Some(match bytes {
+            Cow::Borrowed(bytes) => Cow::Borrowed(&bytes[1..]),
+            Cow::Owned(mut bytes) => {
+                bytes.remove(0);
+                Cow::Owned(bytes)
+            }
+        }),
+        _ if bytes.starts_with(&GZIP_MAGIC) => {
+            let mut value = Vec::new();
+            GzDecoder::new(&*bytes).read_to_end(&mut value).ok()?;
+
+            Some(Cow::Owned(value))
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{borrow::Cow, io::Read};
+
+    use super::{Compression, decompress};
+
+    #[test]
+    fn test_gzip_round_trip() {
+        let value = b"some value ".repeat(32);
+        let compressed = Compression::Gzip.compress(&value).unwrap();
+
+        assert!(compressed.len() < value.len());
+        assert_eq!(decompress(Cow::Borrowed(&compressed)).unwrap(), value);
+
+        // readable by any gzip decoder
+        let mut read = Vec::new();
+        flate2::read::GzDecoder::new(compressed.as_slice())
+            .read_to_end(&mut read)
+            .unwrap();
+        assert_eq!(read, value);
+    }
+
+    #[test]
+    fn test_small_values_stored_uncompressed() {
+        let compressed = Compression::Gzip.compress(b"tiny").unwrap();
+
+        assert_eq!(compressed, b"\x00tiny");
+        assert_eq!(&*decompress(Cow::Owned(compressed)).unwrap(), b"tiny");
+    }
+
+    #[test]
+    fn test_unknown_header() {
+        assert!(decompress(Cow::Borrowed(b"\x07value")).is_none());
+        assert!(decompress(Cow::Borrowed(b"")).is_none());
+        assert!(decompress(Cow::Borrowed(&[0x1f, 0x8b, 0x00])).is_none());
+    }
+}
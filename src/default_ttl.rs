@@ -0,0 +1,307 @@
+use std::{sync::Arc, time::Duration};
+
+use crate::{
+    BackendConfig, CacheError, CacheStorage, ColumnDefinition, ColumnIndex, ColumnReport,
+    ColumnWriter, EvictionListener, InsertOutcome, RawEntry, SledLayout, SlowOperationListener,
+    StoredValue, TimedEntry, TransactionCondition, TransactionWrite, TtlEnforcement,
+    handle::StorageWriter, noop_engine::NoopEngine,
+};
+
+/// A column with no TTL of its own, given the engine default
+struct DefaultTtlColumn<'a> {
+    column: &'a dyn ColumnDefinition,
+    ttl_in_seconds: i32,
+}
+
+impl ColumnDefinition for DefaultTtlColumn<'_> {
+    fn name(&self) -> String {
+        self.column.name()
+    }
+
+    fn get_ttl_in_seconds(&self) -> i32 {
+        self.ttl_in_seconds
+    }
+
+    fn max_entries(&self) -> Option<usize> {
+        self.column.max_entries()
+    }
+
+    fn ttl_jitter(&self) -> Option<f32> {
+        self.column.ttl_jitter()
+    }
+
+    fn idle_ttl_in_seconds(&self) -> Option<u32> {
+        self.column.idle_ttl_in_seconds()
+    }
+
+    fn aliases(&self) -> &[&str] {
+        self.column.aliases()
+    }
+
+    fn description(&self) -> Option<&str> {
+        self.column.description()
+    }
+
+    fn index(&self) -> Option<&dyn ColumnIndex> {
+        self.column.index()
+    }
+
+    fn expire_on_ttl_change(&self) -> bool {
+        self.column.expire_on_ttl_change()
+    }
+
+    fn read_only(&self) -> bool {
+        self.column.read_only()
+    }
+
+    fn storage_layout(&self) -> SledLayout {
+        self.column.storage_layout()
+    }
+
+    fn default_value_bytes(&self) -> Option<Vec<u8>> {
+        self.column.default_value_bytes()
+    }
+}
+
+/// Storage wrapper giving columns that never expire a TTL, see [`crate::Engine::with_default_ttl`]
+pub(crate) struct DefaultTtl {
+    pub(crate) inner: Box<dyn CacheStorage + Send + Sync>,
+    pub(crate) ttl_in_seconds: i32,
+}
+
+impl DefaultTtl {
+    /// Run `f` on the column as the inner storage sees it,
+    /// with the default TTL if its own is zero or less
+    fn column<T>(&self, c: &dyn ColumnDefinition, f: impl FnOnce(&dyn ColumnDefinition) -> T) -> T {
+        if c.get_ttl_in_seconds() > 0 {
+            return f(c);
+        }
+
+        f(&DefaultTtlColumn {
+            column: c,
+            ttl_in_seconds: self.ttl_in_seconds,
+        })
+    }
+}
+
+impl CacheStorage for DefaultTtl {
+    /// Only built by wrapping the storage of an engine, this builds an unwrapped [`NoopEngine`]
+    fn build(path: String, capacity: Option<u64>) -> Box<dyn CacheStorage + Send + Sync>
+    where
+        Self: Sized,
+    {
+        NoopEngine::build(path, capacity)
+    }
+
+    fn discards_writes(&self) -> bool {
+        self.inner.discards_writes()
+    }
+
+    fn ttl_enforcement(&self) -> TtlEnforcement {
+        self.inner.ttl_enforcement()
+    }
+
+    fn describe_config(&self) -> BackendConfig {
+        self.inner
+            .describe_config()
+            .with("default_ttl", self.ttl_in_seconds)
+    }
+
+    fn try_insert(
+        &self,
+        c: &dyn ColumnDefinition,
+        key: &[u8],
+        value: &[u8],
+    ) -> Result<(), CacheError> {
+        self.column(c, |c| self.inner.try_insert(c, key, value))
+    }
+
+    /// Columns given the default TTL insert through this storage rather than a writer of the inner one
+    fn try_column_writer<'a>(
+        &'a self,
+        c: &'a dyn ColumnDefinition,
+    ) -> Result<Box<dyn ColumnWriter + 'a>, CacheError> {
+        if c.get_ttl_in_seconds() > 0 {
+            return self.inner.try_column_writer(c);
+        }
+
+        Ok(Box::new(StorageWriter {
+            storage: self,
+            column: c,
+        }))
+    }
+
+    fn try_insert_with_ttl(
+        &self,
+        c: &dyn ColumnDefinition,
+        key: &[u8],
+        value: &[u8],
+        ttl_in_seconds: i32,
+    ) -> Result<(), CacheError> {
+        self.column(c, |c| {
+            self.inner
+                .try_insert_with_ttl(c, key, value, ttl_in_seconds)
+        })
+    }
+
+    fn try_insert_reporting(
+        &self,
+        c: &dyn ColumnDefinition,
+        key: &[u8],
+        value: &[u8],
+    ) -> Result<InsertOutcome, CacheError> {
+        self.column(c, |c| self.inner.try_insert_reporting(c, key, value))
+    }
+
+    fn try_insert_batch(
+        &self,
+        c: &dyn ColumnDefinition,
+        items: &[(Vec<u8>, Vec<u8>)],
+    ) -> Result<(), CacheError> {
+        self.column(c, |c| self.inner.try_insert_batch(c, items))
+    }
+
+    fn try_swap(
+        &self,
+        c: &dyn ColumnDefinition,
+        key: &[u8],
+        value: &[u8],
+    ) -> Result<Option<Vec<u8>>, CacheError> {
+        self.column(c, |c| self.inner.try_swap(c, key, value))
+    }
+
+    fn try_rename(
+        &self,
+        c: &dyn ColumnDefinition,
+        from: &[u8],
+        to: &[u8],
+    ) -> Result<bool, CacheError> {
+        self.column(c, |c| self.inner.try_rename(c, from, to))
+    }
+
+    fn try_take(
+        &self,
+        c: &dyn ColumnDefinition,
+        key: &[u8],
+    ) -> Result<Option<Vec<u8>>, CacheError> {
+        self.column(c, |c| self.inner.try_take(c, key))
+    }
+
+    fn try_commit(
+        &self,
+        c: &dyn ColumnDefinition,
+        conditions: &[TransactionCondition],
+        writes: &[TransactionWrite],
+    ) -> Result<bool, CacheError> {
+        self.column(c, |c| self.inner.try_commit(c, conditions, writes))
+    }
+
+    fn try_touch_many(
+        &self,
+        c: &dyn ColumnDefinition,
+        keys: &[&[u8]],
+        ttl_in_seconds: i32,
+    ) -> Result<usize, CacheError> {
+        self.column(c, |c| self.inner.try_touch_many(c, keys, ttl_in_seconds))
+    }
+
+    fn try_get(&self, c: &dyn ColumnDefinition, key: &[u8]) -> Result<Option<Vec<u8>>, CacheError> {
+        self.column(c, |c| self.inner.try_get(c, key))
+    }
+
+    fn try_get_many(
+        &self,
+        c: &dyn ColumnDefinition,
+        keys: &[&[u8]],
+    ) -> Result<Vec<Option<Vec<u8>>>, CacheError> {
+        self.column(c, |c| self.inner.try_get_many(c, keys))
+    }
+
+    fn try_value_size(
+        &self,
+        c: &dyn ColumnDefinition,
+        key: &[u8],
+    ) -> Result<Option<usize>, CacheError> {
+        self.column(c, |c| self.inner.try_value_size(c, key))
+    }
+
+    fn try_get_stored(
+        &self,
+        c: &dyn ColumnDefinition,
+        key: &[u8],
+    ) -> Result<Option<StoredValue>, CacheError> {
+        self.column(c, |c| self.inner.try_get_stored(c, key))
+    }
+
+    fn try_get_allowing_stale(
+        &self,
+        c: &dyn ColumnDefinition,
+        key: &[u8],
+    ) -> Result<Option<(Vec<u8>, bool)>, CacheError> {
+        self.column(c, |c| self.inner.try_get_allowing_stale(c, key))
+    }
+
+    fn try_drop_column(&self, c: &dyn ColumnDefinition) -> Result<(), CacheError> {
+        self.column(c, |c| self.inner.try_drop_column(c))
+    }
+
+    fn try_entries(&self, c: &dyn ColumnDefinition) -> Result<Vec<RawEntry>, CacheError> {
+        self.column(c, |c| self.inner.try_entries(c))
+    }
+
+    fn try_timed_entries(&self, c: &dyn ColumnDefinition) -> Result<Vec<TimedEntry>, CacheError> {
+        self.column(c, |c| self.inner.try_timed_entries(c))
+    }
+
+    fn try_count_column(&self, c: &dyn ColumnDefinition) -> Result<usize, CacheError> {
+        self.column(c, |c| self.inner.try_count_column(c))
+    }
+
+    fn try_entry_ages(&self, c: &dyn ColumnDefinition) -> Result<Vec<Duration>, CacheError> {
+        self.column(c, |c| self.inner.try_entry_ages(c))
+    }
+
+    fn validate_column(&self, c: &dyn ColumnDefinition) -> Result<(), CacheError> {
+        self.column(c, |c| self.inner.validate_column(c))
+    }
+
+    fn try_column_layout(&self, c: &dyn ColumnDefinition) -> Result<SledLayout, CacheError> {
+        self.column(c, |c| self.inner.try_column_layout(c))
+    }
+
+    fn try_list_columns(&self) -> Result<Vec<String>, CacheError> {
+        self.inner.try_list_columns()
+    }
+
+    fn try_report(&self) -> Result<Vec<ColumnReport>, CacheError> {
+        self.inner.try_report()
+    }
+
+    fn try_maintenance(&self) -> Result<(), CacheError> {
+        self.inner.try_maintenance()
+    }
+
+    fn try_increment(
+        &self,
+        c: &dyn ColumnDefinition,
+        key: &[u8],
+        delta: i64,
+    ) -> Result<i64, CacheError> {
+        self.column(c, |c| self.inner.try_increment(c, key, delta))
+    }
+
+    fn set_eviction_listener(
+        &mut self,
+        listener: Arc<dyn EvictionListener>,
+    ) -> Result<(), CacheError> {
+        self.inner.set_eviction_listener(listener)
+    }
+
+    fn set_slow_operation_listener(
+        &mut self,
+        threshold: Duration,
+        listener: SlowOperationListener,
+    ) -> Result<(), CacheError> {
+        self.inner.set_slow_operation_listener(threshold, listener)
+    }
+}
@@ -0,0 +1,513 @@
+//!
+//! Write to two storages while reading from one, for migrating between backends.
+//!
+//! ```
+//! use omega_cache::{Engine, dual_write::DualWriteEngine, noop_engine::NoopEngine};
+//!
+//! // reads still come from the old storage while the new one fills up
+//! let engine = Engine::new(Box::new(
+//!     DualWriteEngine::new(NoopEngine::passthrough(), NoopEngine::passthrough())
+//!         .fall_back_on_miss(),
+//! ));
+//! ```
+//!
+//! Once the new storage holds everything worth keeping, swap the two around,
+//! then drop the dual write.
+//!
+
+use std::{fmt::Debug, sync::Arc, time::Duration};
+
+use crate::noop_engine::NoopEngine;
+use crate::{
+    BackendConfig, CacheError, CacheStorage, ColumnDefinition, ColumnReport, EvictionListener,
+    InsertOutcome, RawEntry, SledLayout, SlowOperationListener, StoredValue, TimedEntry,
+    TransactionCondition, TransactionWrite, TtlEnforcement,
+};
+
+///
+/// Storage sending every write to two storages and reading from the first
+///
+/// Writes, increments and drops run on `read_from` first, and only reach
+/// `also_write_to` if they succeed there. A failed write to `also_write_to`
+/// is passed to [`DualWriteEngine::on_secondary_error`] and otherwise ignored,
+/// unless [`DualWriteEngine::fail_on_secondary_error`] is set.
+/// Counters are incremented on both, so they only agree if both started out equal.
+/// Listing, reporting and counting only look at `read_from`.
+///
+pub struct DualWriteEngine {
+    read_from: Box<dyn CacheStorage + Send + Sync>,
+    also_write_to: Box<dyn CacheStorage + Send + Sync>,
+    fall_back_on_miss: bool,
+    fail_on_secondary_error: bool,
+    on_secondary_error: Option<SecondaryErrorListener>,
+}
+
+/// Called with each ignored failure of `also_write_to`, see [`DualWriteEngine::on_secondary_error`]
+pub type SecondaryErrorListener = Box<dyn Fn(&CacheError) + Send + Sync>;
+
+impl Debug for DualWriteEngine {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DualWriteEngine")
+            .field("fall_back_on_miss", &self.fall_back_on_miss)
+            .field("fail_on_secondary_error", &self.fail_on_secondary_error)
+            .field("on_secondary_error", &self.on_secondary_error.is_some())
+            .finish_non_exhaustive()
+    }
+}
+
+impl DualWriteEngine {
+    /// Read from `read_from` and write to both storages
+    #[must_use]
+    pub fn new(
+        read_from: Box<dyn CacheStorage + Send + Sync>,
+        also_write_to: Box<dyn CacheStorage + Send + Sync>,
+    ) -> DualWriteEngine {
+        DualWriteEngine {
+            read_from,
+            also_write_to,
+            fall_back_on_miss: false,
+            fail_on_secondary_error: false,
+            on_secondary_error: None,
+        }
+    }
+
+    /// Read from `also_write_to` when `read_from` misses,
+    /// for values only written since the migration started on another instance
+    #[must_use]
+    pub fn fall_back_on_miss(mut self) -> DualWriteEngine {
+        self.fall_back_on_miss = true;
+        self
+    }
+
+    /// Fail operations when the write to `also_write_to` fails,
+    /// after the write to `read_from` has already been made
+    #[must_use]
+    pub fn fail_on_secondary_error(mut self) -> DualWriteEngine {
+        self.fail_on_secondary_error = true;
+        self
+    }
+
+    /// Call `f` with each failure of `also_write_to` that is ignored,
+    /// for logging how far the storages drift apart. Failures are silent by default
+    #[must_use]
+    pub fn on_secondary_error(
+        mut self,
+        f: impl Fn(&CacheError) + Send + Sync + 'static,
+    ) -> DualWriteEngine {
+        self.on_secondary_error = Some(Box::new(f));
+        self
+    }
+
+    /// Handle the result of an operation on `also_write_to`
+    fn secondary<T>(&self, result: Result<T, CacheError>) -> Result<(), CacheError> {
+        match result {
+            Ok(_) => Ok(()),
+            Err(e) if self.fail_on_secondary_error => Err(e),
+            Err(e) => {
+                if let Some(f) = &self.on_secondary_error {
+                    f(&e);
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl CacheStorage for DualWriteEngine {
+    /// Only built from two storages, this dual writes to two passthrough [`NoopEngine`]s
+    fn build(_path: String, _capacity: Option<u64>) -> Box<dyn CacheStorage + Send + Sync>
+    where
+        Self: Sized,
+    {
+        Box::new(DualWriteEngine::new(
+            NoopEngine::passthrough(),
+            NoopEngine::passthrough(),
+        ))
+    }
+
+    fn discards_writes(&self) -> bool {
+        self.read_from.discards_writes() && self.also_write_to.discards_writes()
+    }
+
+    fn ttl_enforcement(&self) -> TtlEnforcement {
+        self.read_from.ttl_enforcement()
+    }
+
+    /// Settings of the storage read from, with the other storage as `also_write_to`
+    fn describe_config(&self) -> BackendConfig {
+        self.read_from
+            .describe_config()
+            .with("also_write_to", self.also_write_to.describe_config())
+    }
+
+    fn try_insert(
+        &self,
+        c: &dyn ColumnDefinition,
+        key: &[u8],
+        value: &[u8],
+    ) -> Result<(), CacheError> {
+        self.read_from.try_insert(c, key, value)?;
+
+        self.secondary(self.also_write_to.try_insert(c, key, value))
+    }
+
+    fn try_insert_with_ttl(
+        &self,
+        c: &dyn ColumnDefinition,
+        key: &[u8],
+        value: &[u8],
+        ttl_in_seconds: i32,
+    ) -> Result<(), CacheError> {
+        self.read_from
+            .try_insert_with_ttl(c, key, value, ttl_in_seconds)?;
+
+        self.secondary(
+            self.also_write_to
+                .try_insert_with_ttl(c, key, value, ttl_in_seconds),
+        )
+    }
+
+    fn try_insert_reporting(
+        &self,
+        c: &dyn ColumnDefinition,
+        key: &[u8],
+        value: &[u8],
+    ) -> Result<InsertOutcome, CacheError> {
+        let outcome = self.read_from.try_insert_reporting(c, key, value)?;
+        self.secondary(self.also_write_to.try_insert(c, key, value))?;
+
+        Ok(outcome)
+    }
+
+    fn try_insert_batch(
+        &self,
+        c: &dyn ColumnDefinition,
+        items: &[(Vec<u8>, Vec<u8>)],
+    ) -> Result<(), CacheError> {
+        self.read_from.try_insert_batch(c, items)?;
+
+        self.secondary(self.also_write_to.try_insert_batch(c, items))
+    }
+
+    fn try_swap(
+        &self,
+        c: &dyn ColumnDefinition,
+        key: &[u8],
+        value: &[u8],
+    ) -> Result<Option<Vec<u8>>, CacheError> {
+        let old = self.read_from.try_swap(c, key, value)?;
+        self.secondary(self.also_write_to.try_insert(c, key, value))?;
+
+        Ok(old)
+    }
+
+    fn try_rename(
+        &self,
+        c: &dyn ColumnDefinition,
+        from: &[u8],
+        to: &[u8],
+    ) -> Result<bool, CacheError> {
+        let renamed = self.read_from.try_rename(c, from, to)?;
+        self.secondary(self.also_write_to.try_rename(c, from, to))?;
+
+        Ok(renamed)
+    }
+
+    fn try_take(
+        &self,
+        c: &dyn ColumnDefinition,
+        key: &[u8],
+    ) -> Result<Option<Vec<u8>>, CacheError> {
+        let taken = self.read_from.try_take(c, key)?;
+        self.secondary(self.also_write_to.try_take(c, key))?;
+
+        Ok(taken)
+    }
+
+    /// Commits on the storage read from, then applies the writes to the other unconditionally
+    fn try_commit(
+        &self,
+        c: &dyn ColumnDefinition,
+        conditions: &[TransactionCondition],
+        writes: &[TransactionWrite],
+    ) -> Result<bool, CacheError> {
+        if !self.read_from.try_commit(c, conditions, writes)? {
+            return Ok(false);
+        }
+
+        self.secondary(self.also_write_to.try_commit(c, &[], writes))?;
+
+        Ok(true)
+    }
+
+    fn try_touch_many(
+        &self,
+        c: &dyn ColumnDefinition,
+        keys: &[&[u8]],
+        ttl_in_seconds: i32,
+    ) -> Result<usize, CacheError> {
+        let touched = self.read_from.try_touch_many(c, keys, ttl_in_seconds)?;
+        self.secondary(self.also_write_to.try_touch_many(c, keys, ttl_in_seconds))?;
+
+        Ok(touched)
+    }
+
+    fn try_get(&self, c: &dyn ColumnDefinition, key: &[u8]) -> Result<Option<Vec<u8>>, CacheError> {
+        match self.read_from.try_get(c, key)? {
+            None if self.fall_back_on_miss => self.also_write_to.try_get(c, key),
+            value => Ok(value),
+        }
+    }
+
+    fn try_get_many(
+        &self,
+        c: &dyn ColumnDefinition,
+        keys: &[&[u8]],
+    ) -> Result<Vec<Option<Vec<u8>>>, CacheError> {
+        let mut values = self.read_from.try_get_many(c, keys)?;
+        if !self.fall_back_on_miss {
+            return Ok(values);
+        }
+
+        let missed = values
+            .iter()
+            .zip(keys)
+            .filter_map(|(value, key)| value.is_none().then_some(*key))
+            .collect::<Vec<_>>();
+        if missed.is_empty() {
+            return Ok(values);
+        }
+
+        let mut fallback = self.also_write_to.try_get_many(c, &missed)?.into_iter();
+        for value in values.iter_mut().filter(|value| value.is_none()) {
+            *value = fallback.next().flatten();
+        }
+
+        Ok(values)
+    }
+
+    fn try_value_size(
+        &self,
+        c: &dyn ColumnDefinition,
+        key: &[u8],
+    ) -> Result<Option<usize>, CacheError> {
+        match self.read_from.try_value_size(c, key)? {
+            None if self.fall_back_on_miss => self.also_write_to.try_value_size(c, key),
+            size => Ok(size),
+        }
+    }
+
+    fn try_get_stored(
+        &self,
+        c: &dyn ColumnDefinition,
+        key: &[u8],
+    ) -> Result<Option<StoredValue>, CacheError> {
+        match self.read_from.try_get_stored(c, key)? {
+            None if self.fall_back_on_miss => self.also_write_to.try_get_stored(c, key),
+            value => Ok(value),
+        }
+    }
+
+    fn try_get_allowing_stale(
+        &self,
+        c: &dyn ColumnDefinition,
+        key: &[u8],
+    ) -> Result<Option<(Vec<u8>, bool)>, CacheError> {
+        match self.read_from.try_get_allowing_stale(c, key)? {
+            None if self.fall_back_on_miss => self.also_write_to.try_get_allowing_stale(c, key),
+            value => Ok(value),
+        }
+    }
+
+    fn try_drop_column(&self, c: &dyn ColumnDefinition) -> Result<(), CacheError> {
+        self.read_from.try_drop_column(c)?;
+
+        self.secondary(self.also_write_to.try_drop_column(c))
+    }
+
+    fn try_entries(&self, c: &dyn ColumnDefinition) -> Result<Vec<RawEntry>, CacheError> {
+        self.read_from.try_entries(c)
+    }
+
+    fn try_timed_entries(&self, c: &dyn ColumnDefinition) -> Result<Vec<TimedEntry>, CacheError> {
+        self.read_from.try_timed_entries(c)
+    }
+
+    fn try_count_column(&self, c: &dyn ColumnDefinition) -> Result<usize, CacheError> {
+        self.read_from.try_count_column(c)
+    }
+
+    fn try_entry_ages(&self, c: &dyn ColumnDefinition) -> Result<Vec<Duration>, CacheError> {
+        self.read_from.try_entry_ages(c)
+    }
+
+    /// The column must be valid on both storages
+    fn validate_column(&self, c: &dyn ColumnDefinition) -> Result<(), CacheError> {
+        self.read_from.validate_column(c)?;
+        self.also_write_to.validate_column(c)
+    }
+
+    fn try_column_layout(&self, c: &dyn ColumnDefinition) -> Result<SledLayout, CacheError> {
+        self.read_from.try_column_layout(c)
+    }
+
+    fn try_list_columns(&self) -> Result<Vec<String>, CacheError> {
+        self.read_from.try_list_columns()
+    }
+
+    fn try_report(&self) -> Result<Vec<ColumnReport>, CacheError> {
+        self.read_from.try_report()
+    }
+
+    fn try_maintenance(&self) -> Result<(), CacheError> {
+        self.read_from.try_maintenance()?;
+        self.secondary(self.also_write_to.try_maintenance())
+    }
+
+    fn try_increment(
+        &self,
+        c: &dyn ColumnDefinition,
+        key: &[u8],
+        delta: i64,
+    ) -> Result<i64, CacheError> {
+        let value = self.read_from.try_increment(c, key, delta)?;
+        self.secondary(self.also_write_to.try_increment(c, key, delta))?;
+
+        Ok(value)
+    }
+
+    /// Only evictions from `read_from` are reported
+    fn set_eviction_listener(
+        &mut self,
+        listener: Arc<dyn EvictionListener>,
+    ) -> Result<(), CacheError> {
+        self.read_from.set_eviction_listener(listener)
+    }
+
+    /// Operations on both storages are reported, under the column names each of them sees.
+    /// A storage that does not time its operations is handled like a failed write
+    fn set_slow_operation_listener(
+        &mut self,
+        threshold: Duration,
+        listener: SlowOperationListener,
+    ) -> Result<(), CacheError> {
+        self.read_from
+            .set_slow_operation_listener(threshold, listener.clone())?;
+        let result = self
+            .also_write_to
+            .set_slow_operation_listener(threshold, listener);
+        self.secondary(result)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    };
+
+    use crate::{CacheError, CacheStorage, ColumnDefinition, noop_engine::NoopEngine};
+
+    use super::DualWriteEngine;
+
+    struct TestColumn {}
+
+    impl ColumnDefinition for TestColumn {
+        fn name(&self) -> String {
+            "dual_column".to_string()
+        }
+
+        fn get_ttl_in_seconds(&self) -> i32 {
+            60
+        }
+    }
+
+    /// Storage failing every operation
+    struct FailingEngine;
+
+    impl CacheStorage for FailingEngine {
+        fn build(_path: String, _capacity: Option<u64>) -> Box<dyn CacheStorage + Send + Sync> {
+            Box::new(FailingEngine)
+        }
+
+        fn try_insert(
+            &self,
+            _c: &dyn ColumnDefinition,
+            _key: &[u8],
+            _value: &[u8],
+        ) -> Result<(), CacheError> {
+            Err(CacheError::Put("failing".to_string()))
+        }
+
+        fn try_get(
+            &self,
+            _c: &dyn ColumnDefinition,
+            _key: &[u8],
+        ) -> Result<Option<Vec<u8>>, CacheError> {
+            Err(CacheError::Get("failing".to_string()))
+        }
+
+        fn try_drop_column(&self, _c: &dyn ColumnDefinition) -> Result<(), CacheError> {
+            Err(CacheError::Engine("failing".to_string()))
+        }
+    }
+
+    #[test]
+    fn test_writes_reach_both() {
+        let storage = DualWriteEngine::new(NoopEngine::passthrough(), NoopEngine::passthrough());
+
+        assert!(storage.try_insert(&TestColumn {}, b"key", b"value").is_ok());
+        assert!(
+            storage
+                .also_write_to
+                .try_get(&TestColumn {}, b"key")
+                .unwrap()
+                .is_some()
+        );
+
+        // without fallback, values only held by the secondary are misses
+        assert!(
+            storage
+                .also_write_to
+                .try_insert(&TestColumn {}, b"new", b"value")
+                .is_ok()
+        );
+        assert!(storage.try_get(&TestColumn {}, b"new").unwrap().is_none());
+
+        let storage = storage.fall_back_on_miss();
+        assert!(storage.try_get(&TestColumn {}, b"new").unwrap().is_some());
+        assert_eq!(
+            storage
+                .try_get_many(&TestColumn {}, &[b"missing", b"key", b"new"])
+                .unwrap(),
+            vec![None, Some(b"value".to_vec()), Some(b"value".to_vec())]
+        );
+
+        assert!(storage.try_drop_column(&TestColumn {}).is_ok());
+        assert!(storage.try_get(&TestColumn {}, b"new").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_secondary_errors() {
+        let failures = Arc::new(AtomicUsize::new(0));
+        let counted = failures.clone();
+        let storage = DualWriteEngine::new(NoopEngine::passthrough(), Box::new(FailingEngine))
+            .on_secondary_error(move |e| {
+                assert!(matches!(e, CacheError::Put(_)));
+                counted.fetch_add(1, Ordering::Relaxed);
+            });
+
+        assert!(storage.try_insert(&TestColumn {}, b"key", b"value").is_ok());
+        assert!(storage.try_get(&TestColumn {}, b"key").unwrap().is_some());
+        assert_eq!(failures.load(Ordering::Relaxed), 1);
+
+        let storage = storage.fail_on_secondary_error();
+        assert!(matches!(
+            storage.try_insert(&TestColumn {}, b"key", b"value"),
+            Err(CacheError::Put(_))
+        ));
+        // failures returned are not reported again
+        assert_eq!(failures.load(Ordering::Relaxed), 1);
+    }
+}
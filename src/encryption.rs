@@ -0,0 +1,145 @@
+use aes_gcm::{
+    Aes256Gcm, KeyInit, Nonce,
+    aead::{Aead, AeadCore, OsRng, Payload},
+};
+
+/// Length in bytes of the nonce prefixed to each value
+const NONCE_LEN: usize = 12;
+
+///
+/// AES-256-GCM encryption of values at rest
+///
+/// Set with [`crate::Engine::with_encryption`], values are encrypted after encoding
+/// with a fresh random nonce prefixed to each value, and authenticated together with
+/// their column name and key so a value copied under another key fails to decrypt.
+/// Keeping the key safe, and the same across restarts, is up to the caller.
+///
+#[derive(Clone)]
+pub struct Encryptor {
+    cipher: Aes256Gcm,
+}
+
+impl Encryptor {
+    /// Encrypt with a 256 bit key
+    #[must_use]
+    pub fn new(key: &[u8; 32]) -> Encryptor {
+        Encryptor {
+            cipher: Aes256Gcm::new(key.into()),
+        }
+    }
+
+    /// Encrypt the encoded value stored at key in the column, prefixing the nonce used
+    pub(crate) fn seal(
+        &self,
+        column: &str,
+        key: &[u8],
+        bytes: &[u8],
+    ) -> Result<Vec<u8>, aes_gcm::Error> {
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let ciphertext = self.cipher.encrypt(
+            &nonce,
+            Payload {
+                msg: bytes,
+                aad: &associated_data(column, key),
+            },
+        )?;
+
+        let mut sealed = Vec::with_capacity(NONCE_LEN + ciphertext.len());
+        sealed.extend_from_slice(&nonce);
+        sealed.extend_from_slice(&ciphertext);
+
+        Ok(sealed)
+    }
+
+    /// Decrypt a value sealed by [`Encryptor::seal`]
+    ///
+    /// Returns [`None`] if the value is too short to hold a nonce, was encrypted
+    /// with another key, sealed for another column or key, or has been tampered with
+    pub(crate) fn open(&self, column: &str, key: &[u8], bytes: &[u8]) -> Option<Vec<u8>> {
+        if bytes.len() < NONCE_LEN {
+            return None;
+        }
+
+        let (nonce, ciphertext) = bytes.split_at(NONCE_LEN);
+
+        self.cipher
+            .decrypt(
+                Nonce::from_slice(nonce),
+                Payload {
+                    msg: ciphertext,
+                    aad: &associated_data(column, key),
+                },
+            )
+            .ok()
+    }
+}
+
+/// Column name prefixed by its length, then the key, authenticated along with each value
+fn associated_data(column: &str, key: &[u8]) -> Vec<u8> {
+    let mut data = Vec::with_capacity(4 + column.len() + key.len());
+    data.extend_from_slice(
+        &u32::try_from(column.len())
+            .unwrap_or(u32::MAX)
+            .to_be_bytes(),
+    );
+    data.extend_from_slice(column.as_bytes());
+    data.extend_from_slice(key);
+
+    data
+}
+
+impl std::fmt::Debug for Encryptor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Encryptor").finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Encryptor;
+
+    #[test]
+    fn test_seal_and_open() {
+        let encryptor = Encryptor::new(&[7; 32]);
+        let sealed = encryptor.seal("column", b"key", b"some value").unwrap();
+
+        assert_ne!(&sealed[12..], b"some value");
+        assert_eq!(
+            encryptor.open("column", b"key", &sealed).unwrap(),
+            b"some value"
+        );
+        assert_ne!(
+            encryptor.seal("column", b"key", b"some value").unwrap(),
+            sealed
+        );
+    }
+
+    #[test]
+    fn test_open_with_wrong_key() {
+        let sealed = Encryptor::new(&[7; 32])
+            .seal("column", b"key", b"some value")
+            .unwrap();
+
+        assert!(
+            Encryptor::new(&[8; 32])
+                .open("column", b"key", &sealed)
+                .is_none()
+        );
+        assert!(
+            Encryptor::new(&[7; 32])
+                .open("column", b"key", &sealed[..8])
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_open_under_another_key() {
+        let encryptor = Encryptor::new(&[7; 32]);
+        let sealed = encryptor.seal("column", b"key", b"some value").unwrap();
+
+        assert!(encryptor.open("column", b"other", &sealed).is_none());
+        assert!(encryptor.open("other", b"key", &sealed).is_none());
+        // the column name length keeps its bytes from moving into the key
+        assert!(encryptor.open("colum", b"nkey", &sealed).is_none());
+    }
+}
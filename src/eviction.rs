@@ -0,0 +1,103 @@
+use std::sync::Arc;
+
+use crate::CacheError;
+
+///
+/// Why a value left the cache, passed to [`EvictionListener::on_evict`]
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum EvictReason {
+    /// The value outlived its TTL and was removed when next touched
+    Expired,
+    /// The value was removed on request
+    Removed,
+    /// A write replaced the live value at its key
+    Replaced,
+    /// The value was evicted to keep the column or the storage within its bounds,
+    /// see [`crate::ColumnDefinition::max_entries`]
+    CapacityEvicted,
+}
+
+///
+/// Observer of values leaving the cache, set with [`crate::Engine::set_eviction_listener`]
+///
+/// Listeners run on the thread doing the operation that evicted the value, with the
+/// storage's locks possibly held, so they should be quick and must not call back
+/// into the same engine. sled is the exception, calling them once its locks are
+/// released, possibly on the thread of another operation. Keys are passed as the
+/// engine's caller sees them.
+///
+/// ```
+/// use omega_cache::{EvictReason, EvictionListener};
+///
+/// struct LogEvictions;
+///
+/// impl EvictionListener for LogEvictions {
+///     fn on_evict(&self, column: &str, key: &[u8], reason: EvictReason) {
+///         println!("{column}/{} left the cache: {reason:?}", String::from_utf8_lossy(key));
+///     }
+/// }
+/// ```
+///
+pub trait EvictionListener: Send + Sync {
+    /// Called once for each value leaving the cache
+    fn on_evict(&self, column: &str, key: &[u8], reason: EvictReason);
+
+    /// Called when the storage may fail to report values leaving the cache, such as redis
+    /// not publishing expired keys or its subscription to them dropping, defaults to ignoring it
+    fn on_error(&self, _error: &CacheError) {}
+}
+
+/// The listener a storage notifies of evictions, if any
+#[derive(Clone, Default)]
+pub(crate) struct Evictions {
+    listener: Option<Arc<dyn EvictionListener>>,
+}
+
+impl std::fmt::Debug for Evictions {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Evictions")
+            .field("listening", &self.listener.is_some())
+            .finish()
+    }
+}
+
+impl Evictions {
+    /// No listener, usable in statics
+    pub(crate) const fn none() -> Evictions {
+        Evictions { listener: None }
+    }
+
+    pub(crate) fn set(&mut self, listener: Arc<dyn EvictionListener>) {
+        self.listener = Some(listener);
+    }
+
+    /// Notify the listener of key leaving the column
+    pub(crate) fn evicted(&self, column: &str, key: &[u8], reason: EvictReason) {
+        if let Some(listener) = &self.listener {
+            listener.on_evict(column, key, reason);
+        }
+    }
+
+    /// The call notifying the listener of key leaving the column,
+    /// for storages to make once their locks are released
+    #[cfg(feature = "sled")]
+    pub(crate) fn later(
+        &self,
+        column: &str,
+        key: &[u8],
+        reason: EvictReason,
+    ) -> Option<Box<dyn FnOnce() + Send>> {
+        let listener = self.listener.clone()?;
+        let (column, key) = (column.to_string(), key.to_vec());
+
+        Some(Box::new(move || listener.on_evict(&column, &key, reason)))
+    }
+
+    /// Whether anyone is listening, to skip work only needed to notify
+    #[cfg(feature = "sled")]
+    pub(crate) fn is_listening(&self) -> bool {
+        self.listener.is_some()
+    }
+}
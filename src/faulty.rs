@@ -0,0 +1,487 @@
+//!
+//! Storage failing on demand, for testing how an app copes with cache errors.
+//!
+//! ```
+//! use omega_cache::{
+//!     CacheError, ColumnDefinition, Engine,
+//!     faulty::{Fault, FaultyEngine, Operation},
+//!     noop_engine::NoopEngine,
+//! };
+//!
+//! struct Users;
+//!
+//! impl ColumnDefinition for Users {
+//!     fn name(&self) -> String {
+//!         "users".to_string()
+//!     }
+//!
+//!     fn get_ttl_in_seconds(&self) -> i32 {
+//!         60
+//!     }
+//! }
+//!
+//! // reads work twice, then the cache goes away
+//! let engine = Engine::new(Box::new(
+//!     FaultyEngine::new(NoopEngine::passthrough())
+//!         .with_fault(Fault::on(Operation::Get).after(2))
+//!         .with_fault(
+//!             Fault::on(Operation::Insert)
+//!                 .in_column("users")
+//!                 .error(CacheError::Put("disk full".to_string())),
+//!         ),
+//! ));
+//!
+//! assert!(engine.try_get::<_, u8>(&Users, &"ada").is_ok());
+//! assert!(engine.try_get::<_, u8>(&Users, &"ada").is_ok());
+//! assert!(matches!(
+//!     engine.try_get::<_, u8>(&Users, &"ada"),
+//!     Err(CacheError::Unavailable(_))
+//! ));
+//! assert!(matches!(
+//!     engine.try_insert(&Users, &"ada", &1u8),
+//!     Err(CacheError::Put(_))
+//! ));
+//! ```
+//!
+
+use std::{
+    fmt::Debug,
+    sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    },
+    time::Duration,
+};
+
+use crate::noop_engine::NoopEngine;
+use crate::{
+    BackendConfig, CacheError, CacheStorage, ColumnDefinition, ColumnReport, EvictionListener,
+    InsertOutcome, RawEntry, SledLayout, SlowOperationListener, StoredValue, TimedEntry,
+    TransactionCondition, TransactionWrite, TtlEnforcement,
+};
+
+/// Kind of storage operation a [`Fault`] applies to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operation {
+    /// Inserts, batch inserts, swaps and TTL refreshes
+    Insert,
+    /// Reads of one or many keys, including takes
+    Get,
+    /// Column drops
+    Drop,
+    /// Counter increments
+    Increment,
+    /// Key renames
+    Rename,
+    /// Walks over a column or the whole storage: listing, counting, reporting and entries
+    Scan,
+}
+
+/// A failure injected by [`FaultyEngine`]
+///
+/// Matches every operation in every column until narrowed, and fails
+/// with [`CacheError::Unavailable`] unless given another error.
+#[derive(Debug)]
+pub struct Fault {
+    operation: Option<Operation>,
+    column: Option<String>,
+    after: usize,
+    error: CacheError,
+    /// Operations matched so far
+    seen: AtomicUsize,
+}
+
+impl Fault {
+    /// Fail every operation
+    #[must_use]
+    pub fn always() -> Fault {
+        Fault {
+            operation: None,
+            column: None,
+            after: 0,
+            error: CacheError::Unavailable("injected fault".to_string()),
+            seen: AtomicUsize::new(0),
+        }
+    }
+
+    /// Fail operations of one kind
+    #[must_use]
+    pub fn on(operation: Operation) -> Fault {
+        Fault {
+            operation: Some(operation),
+            ..Fault::always()
+        }
+    }
+
+    /// Only fail operations on the column of this name,
+    /// operations that are not on a column, such as listing columns, no longer fail
+    #[must_use]
+    pub fn in_column(mut self, column: impl Into<String>) -> Fault {
+        self.column = Some(column.into());
+        self
+    }
+
+    /// Let the first `operations` matching operations succeed, then fail every one after
+    #[must_use]
+    pub fn after(mut self, operations: usize) -> Fault {
+        self.after = operations;
+        self
+    }
+
+    /// Error returned by failed operations
+    #[must_use]
+    pub fn error(mut self, error: CacheError) -> Fault {
+        self.error = error;
+        self
+    }
+
+    /// The error to fail an operation with, counting it if it matches
+    fn check(&self, operation: Operation, column: Option<&str>) -> Option<CacheError> {
+        if self.operation.is_some_and(|o| o != operation) {
+            return None;
+        }
+
+        if let Some(name) = &self.column
+            && column != Some(name.as_str())
+        {
+            return None;
+        }
+
+        (self.seen.fetch_add(1, Ordering::Relaxed) >= self.after).then(|| self.error.clone())
+    }
+}
+
+///
+/// Storage wrapper failing operations as scripted by its [`Fault`]s
+/// and passing the rest to the storage it wraps
+///
+/// Every fault matching an operation counts it, and the first one due to fail fails it.
+/// Column checks always pass through.
+///
+pub struct FaultyEngine {
+    inner: Box<dyn CacheStorage + Send + Sync>,
+    faults: Vec<Fault>,
+}
+
+impl Debug for FaultyEngine {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FaultyEngine")
+            .field("faults", &self.faults)
+            .finish_non_exhaustive()
+    }
+}
+
+impl FaultyEngine {
+    /// Pass every operation to `inner` until faults are added
+    #[must_use]
+    pub fn new(inner: Box<dyn CacheStorage + Send + Sync>) -> FaultyEngine {
+        FaultyEngine {
+            inner,
+            faults: Vec::new(),
+        }
+    }
+
+    /// Add a fault, checked after those added before it
+    #[must_use]
+    pub fn with_fault(mut self, fault: Fault) -> FaultyEngine {
+        self.faults.push(fault);
+        self
+    }
+
+    /// Fail the operation if any fault says so
+    fn check(
+        &self,
+        operation: Operation,
+        column: Option<&dyn ColumnDefinition>,
+    ) -> Result<(), CacheError> {
+        let name = column.map(ColumnDefinition::name);
+
+        // Every matching fault counts the operation, so check them all
+        let mut failure = None;
+        for fault in &self.faults {
+            if let Some(error) = fault.check(operation, name.as_deref()) {
+                failure.get_or_insert(error);
+            }
+        }
+
+        failure.map_or(Ok(()), Err)
+    }
+}
+
+impl CacheStorage for FaultyEngine {
+    /// Only built from a storage, this wraps a passthrough [`NoopEngine`] with no faults
+    fn build(_path: String, _capacity: Option<u64>) -> Box<dyn CacheStorage + Send + Sync>
+    where
+        Self: Sized,
+    {
+        Box::new(FaultyEngine::new(NoopEngine::passthrough()))
+    }
+
+    fn discards_writes(&self) -> bool {
+        self.inner.discards_writes()
+    }
+
+    fn ttl_enforcement(&self) -> TtlEnforcement {
+        self.inner.ttl_enforcement()
+    }
+
+    fn describe_config(&self) -> BackendConfig {
+        self.inner.describe_config()
+    }
+
+    fn try_insert(
+        &self,
+        c: &dyn ColumnDefinition,
+        key: &[u8],
+        value: &[u8],
+    ) -> Result<(), CacheError> {
+        self.check(Operation::Insert, Some(c))?;
+        self.inner.try_insert(c, key, value)
+    }
+
+    fn try_insert_with_ttl(
+        &self,
+        c: &dyn ColumnDefinition,
+        key: &[u8],
+        value: &[u8],
+        ttl_in_seconds: i32,
+    ) -> Result<(), CacheError> {
+        self.check(Operation::Insert, Some(c))?;
+        self.inner
+            .try_insert_with_ttl(c, key, value, ttl_in_seconds)
+    }
+
+    fn try_insert_reporting(
+        &self,
+        c: &dyn ColumnDefinition,
+        key: &[u8],
+        value: &[u8],
+    ) -> Result<InsertOutcome, CacheError> {
+        self.check(Operation::Insert, Some(c))?;
+        self.inner.try_insert_reporting(c, key, value)
+    }
+
+    fn try_insert_batch(
+        &self,
+        c: &dyn ColumnDefinition,
+        items: &[(Vec<u8>, Vec<u8>)],
+    ) -> Result<(), CacheError> {
+        self.check(Operation::Insert, Some(c))?;
+        self.inner.try_insert_batch(c, items)
+    }
+
+    fn try_swap(
+        &self,
+        c: &dyn ColumnDefinition,
+        key: &[u8],
+        value: &[u8],
+    ) -> Result<Option<Vec<u8>>, CacheError> {
+        self.check(Operation::Insert, Some(c))?;
+        self.inner.try_swap(c, key, value)
+    }
+
+    fn try_rename(
+        &self,
+        c: &dyn ColumnDefinition,
+        from: &[u8],
+        to: &[u8],
+    ) -> Result<bool, CacheError> {
+        self.check(Operation::Rename, Some(c))?;
+        self.inner.try_rename(c, from, to)
+    }
+
+    fn try_take(
+        &self,
+        c: &dyn ColumnDefinition,
+        key: &[u8],
+    ) -> Result<Option<Vec<u8>>, CacheError> {
+        self.check(Operation::Get, Some(c))?;
+        self.inner.try_take(c, key)
+    }
+
+    fn try_commit(
+        &self,
+        c: &dyn ColumnDefinition,
+        conditions: &[TransactionCondition],
+        writes: &[TransactionWrite],
+    ) -> Result<bool, CacheError> {
+        self.check(Operation::Insert, Some(c))?;
+        self.inner.try_commit(c, conditions, writes)
+    }
+
+    fn try_touch_many(
+        &self,
+        c: &dyn ColumnDefinition,
+        keys: &[&[u8]],
+        ttl_in_seconds: i32,
+    ) -> Result<usize, CacheError> {
+        self.check(Operation::Insert, Some(c))?;
+        self.inner.try_touch_many(c, keys, ttl_in_seconds)
+    }
+
+    fn try_get(&self, c: &dyn ColumnDefinition, key: &[u8]) -> Result<Option<Vec<u8>>, CacheError> {
+        self.check(Operation::Get, Some(c))?;
+        self.inner.try_get(c, key)
+    }
+
+    fn try_get_many(
+        &self,
+        c: &dyn ColumnDefinition,
+        keys: &[&[u8]],
+    ) -> Result<Vec<Option<Vec<u8>>>, CacheError> {
+        self.check(Operation::Get, Some(c))?;
+        self.inner.try_get_many(c, keys)
+    }
+
+    fn try_value_size(
+        &self,
+        c: &dyn ColumnDefinition,
+        key: &[u8],
+    ) -> Result<Option<usize>, CacheError> {
+        self.check(Operation::Get, Some(c))?;
+        self.inner.try_value_size(c, key)
+    }
+
+    fn try_get_stored(
+        &self,
+        c: &dyn ColumnDefinition,
+        key: &[u8],
+    ) -> Result<Option<StoredValue>, CacheError> {
+        self.check(Operation::Get, Some(c))?;
+        self.inner.try_get_stored(c, key)
+    }
+
+    fn try_get_allowing_stale(
+        &self,
+        c: &dyn ColumnDefinition,
+        key: &[u8],
+    ) -> Result<Option<(Vec<u8>, bool)>, CacheError> {
+        self.check(Operation::Get, Some(c))?;
+        self.inner.try_get_allowing_stale(c, key)
+    }
+
+    fn try_drop_column(&self, c: &dyn ColumnDefinition) -> Result<(), CacheError> {
+        self.check(Operation::Drop, Some(c))?;
+        self.inner.try_drop_column(c)
+    }
+
+    fn try_entries(&self, c: &dyn ColumnDefinition) -> Result<Vec<RawEntry>, CacheError> {
+        self.check(Operation::Scan, Some(c))?;
+        self.inner.try_entries(c)
+    }
+
+    fn try_timed_entries(&self, c: &dyn ColumnDefinition) -> Result<Vec<TimedEntry>, CacheError> {
+        self.check(Operation::Scan, Some(c))?;
+        self.inner.try_timed_entries(c)
+    }
+
+    fn try_count_column(&self, c: &dyn ColumnDefinition) -> Result<usize, CacheError> {
+        self.check(Operation::Scan, Some(c))?;
+        self.inner.try_count_column(c)
+    }
+
+    fn try_entry_ages(&self, c: &dyn ColumnDefinition) -> Result<Vec<Duration>, CacheError> {
+        self.check(Operation::Scan, Some(c))?;
+        self.inner.try_entry_ages(c)
+    }
+
+    fn validate_column(&self, c: &dyn ColumnDefinition) -> Result<(), CacheError> {
+        self.inner.validate_column(c)
+    }
+
+    fn try_column_layout(&self, c: &dyn ColumnDefinition) -> Result<SledLayout, CacheError> {
+        self.check(Operation::Scan, Some(c))?;
+        self.inner.try_column_layout(c)
+    }
+
+    fn try_list_columns(&self) -> Result<Vec<String>, CacheError> {
+        self.check(Operation::Scan, None)?;
+        self.inner.try_list_columns()
+    }
+
+    fn try_report(&self) -> Result<Vec<ColumnReport>, CacheError> {
+        self.check(Operation::Scan, None)?;
+        self.inner.try_report()
+    }
+
+    fn try_maintenance(&self) -> Result<(), CacheError> {
+        self.check(Operation::Scan, None)?;
+        self.inner.try_maintenance()
+    }
+
+    fn try_increment(
+        &self,
+        c: &dyn ColumnDefinition,
+        key: &[u8],
+        delta: i64,
+    ) -> Result<i64, CacheError> {
+        self.check(Operation::Increment, Some(c))?;
+        self.inner.try_increment(c, key, delta)
+    }
+
+    fn set_eviction_listener(
+        &mut self,
+        listener: Arc<dyn EvictionListener>,
+    ) -> Result<(), CacheError> {
+        self.inner.set_eviction_listener(listener)
+    }
+
+    fn set_slow_operation_listener(
+        &mut self,
+        threshold: Duration,
+        listener: SlowOperationListener,
+    ) -> Result<(), CacheError> {
+        self.inner.set_slow_operation_listener(threshold, listener)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{CacheError, CacheStorage, ColumnDefinition, noop_engine::NoopEngine};
+
+    use super::{Fault, FaultyEngine, Operation};
+
+    struct TestColumn {
+        name: &'static str,
+    }
+
+    impl ColumnDefinition for TestColumn {
+        fn name(&self) -> String {
+            self.name.to_string()
+        }
+
+        fn get_ttl_in_seconds(&self) -> i32 {
+            60
+        }
+    }
+
+    #[test]
+    fn test_faults() {
+        let (a, b) = (TestColumn { name: "a" }, TestColumn { name: "b" });
+        let storage = FaultyEngine::new(NoopEngine::passthrough())
+            .with_fault(Fault::on(Operation::Insert).in_column("a"))
+            .with_fault(
+                Fault::on(Operation::Get)
+                    .after(1)
+                    .error(CacheError::Get("gone".to_string())),
+            );
+
+        assert!(matches!(
+            storage.try_insert(&a, b"key", b"value"),
+            Err(CacheError::Unavailable(_))
+        ));
+        assert!(storage.try_insert(&b, b"key", b"value").is_ok());
+        assert!(storage.try_list_columns().is_ok());
+
+        assert!(storage.try_get(&b, b"key").unwrap().is_some());
+        assert!(matches!(
+            storage.try_get(&b, b"key"),
+            Err(CacheError::Get(_))
+        ));
+        assert!(storage.try_get_many(&a, &[b"key"]).is_err());
+
+        let storage = FaultyEngine::new(NoopEngine::passthrough()).with_fault(Fault::always());
+        assert!(storage.try_list_columns().is_err());
+        assert!(storage.validate_column(&a).is_ok());
+    }
+}
@@ -0,0 +1,98 @@
+use bincode::Encode;
+
+use crate::{CacheError, CacheStorage, ColumnDefinition, Engine};
+
+///
+/// Storage side of a [`ColumnHandle`], inserting into one column
+///
+/// Returned by [`CacheStorage::try_column_writer`] with whatever the storage needs
+/// to reach the column already set up, such as the opened sled tree or the
+/// escaped Redis key prefix, so each insert only writes.
+///
+pub trait ColumnWriter {
+    /// Insert a value into the column with its TTL
+    /// # Errors
+    /// Returns [`CacheError::Put`] if insert fails
+    fn try_insert(&self, key: &[u8], value: &[u8]) -> Result<(), CacheError>;
+}
+
+/// Writer of storages with no per column setup, inserting through the storage itself
+pub(crate) struct StorageWriter<'a, S: ?Sized> {
+    pub(crate) storage: &'a S,
+    pub(crate) column: &'a dyn ColumnDefinition,
+}
+
+impl<S: CacheStorage + ?Sized> ColumnWriter for StorageWriter<'_, S> {
+    fn try_insert(&self, key: &[u8], value: &[u8]) -> Result<(), CacheError> {
+        self.storage.try_insert(self.column, key, value)
+    }
+}
+
+///
+/// Column of an [`Engine`] set up once for many inserts, from [`Engine::column_handle`]
+///
+/// The column checks and the storage's per column setup are done when the handle is
+/// made rather than on every insert, for bulk loads and other tight loops.
+/// Inserts otherwise behave as [`Engine::try_insert`].
+///
+/// ```
+/// use omega_cache::{ColumnDefinition, Engine, noop_engine::NoopEngine};
+///
+/// struct Prices;
+///
+/// impl ColumnDefinition for Prices {
+///     fn name(&self) -> String {
+///         "prices".to_string()
+///     }
+///
+///     fn get_ttl_in_seconds(&self) -> i32 {
+///         60
+///     }
+/// }
+///
+/// let engine = Engine::new(NoopEngine::passthrough());
+/// let prices = engine.column_handle(&Prices).unwrap();
+///
+/// for i in 0..1000u32 {
+///     prices.try_insert(&i.to_be_bytes(), &(i * 2)).unwrap();
+/// }
+///
+/// assert_eq!(engine.try_get(&Prices, &7u32.to_be_bytes()).unwrap(), Some(14u32));
+/// ```
+///
+pub struct ColumnHandle<'a> {
+    pub(crate) engine: &'a Engine,
+    pub(crate) column: &'a dyn ColumnDefinition,
+    pub(crate) writer: Box<dyn ColumnWriter + 'a>,
+}
+
+impl std::fmt::Debug for ColumnHandle<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ColumnHandle")
+            .field("column", &self.column.name())
+            .finish_non_exhaustive()
+    }
+}
+
+impl ColumnHandle<'_> {
+    /// Insert a value into the column with its TTL
+    ///
+    /// # Errors
+    /// Returns [`CacheError::Put`] if insert fails
+    /// Returns [`CacheError::Encode`] if type V cannot be encoded to [`Vec<u8>`]
+    pub fn try_insert<K: AsRef<[u8]>, V: Encode>(
+        &self,
+        key: &K,
+        value: &V,
+    ) -> Result<(), CacheError> {
+        if self.engine.storage().discards_writes() {
+            return Ok(());
+        }
+
+        let value_bytes = self
+            .engine
+            .store_value(self.column, key.as_ref(), value, None)?;
+
+        self.writer.try_insert(key.as_ref(), &value_bytes)
+    }
+}
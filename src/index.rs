@@ -0,0 +1,78 @@
+use crate::{ColumnDefinition, SledLayout};
+
+///
+/// Secondary index of a column, mapping each value to an index key
+///
+/// Returned by [`ColumnDefinition::index`], the engine then keeps a reverse
+/// mapping from index keys to the keys holding them, read with
+/// [`crate::Engine::try_get_by_index`].
+///
+/// ```
+/// use omega_cache::{ColumnDefinition, ColumnIndex};
+///
+/// /// Indexes `session_id -> user_id` by user
+/// struct ByUser;
+///
+/// impl ColumnIndex for ByUser {
+///     fn index_key(&self, _key: &[u8], value: &[u8]) -> Option<Vec<u8>> {
+///         let (user_id, _) =
+///             bincode::decode_from_slice::<u64, _>(value, bincode::config::standard()).ok()?;
+///
+///         Some(user_id.to_be_bytes().to_vec())
+///     }
+/// }
+///
+/// struct Sessions;
+///
+/// impl ColumnDefinition for Sessions {
+///     fn name(&self) -> String {
+///         "sessions".to_string()
+///     }
+///
+///     fn get_ttl_in_seconds(&self) -> i32 {
+///         3600
+///     }
+///
+///     fn index(&self) -> Option<&dyn ColumnIndex> {
+///         Some(&ByUser)
+///     }
+/// }
+/// ```
+///
+pub trait ColumnIndex {
+    /// Index key of the value at key, [`None`] to leave the value out of the index.
+    ///
+    /// `value` is encoded with the engine's [`crate::Codec`],
+    /// before any checksum or encryption is applied
+    fn index_key(&self, key: &[u8], value: &[u8]) -> Option<Vec<u8>>;
+}
+
+/// Column holding the reverse mapping of an indexed column,
+/// index entries take the TTL of the column and are refreshed on each insert
+pub(crate) struct IndexColumn<'a> {
+    pub(crate) column: &'a dyn ColumnDefinition,
+}
+
+impl ColumnDefinition for IndexColumn<'_> {
+    fn name(&self) -> String {
+        format!("{}#index", self.column.name())
+    }
+
+    fn get_ttl_in_seconds(&self) -> i32 {
+        self.column.get_ttl_in_seconds()
+    }
+
+    fn storage_layout(&self) -> SledLayout {
+        self.column.storage_layout()
+    }
+}
+
+/// Encode the keys held by an index entry
+pub(crate) fn encode_keys(keys: &[Vec<u8>]) -> Result<Vec<u8>, bincode::error::EncodeError> {
+    bincode::encode_to_vec(keys, bincode::config::standard())
+}
+
+/// Decode the keys held by an index entry
+pub(crate) fn decode_keys(bytes: &[u8]) -> Result<Vec<Vec<u8>>, bincode::error::DecodeError> {
+    bincode::decode_from_slice(bytes, bincode::config::standard()).map(|keys| keys.0)
+}
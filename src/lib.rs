@@ -5,7 +5,8 @@
 //! The lib is pretty simple, you just need to create a column definition
 //! and an instance of [`Engine`] with whatever [`CacheStorage`] you want.
 //!
-//! There are currently implementations for [`sled`] and [`redis`]
+//! There are currently implementations for [`sled`] and [`redis`],
+//! and an in-memory [`memory_engine::MemoryEngine`]
 //!
 //! ```
 //! use omega_cache::{Engine, CacheStorage, noop_engine::NoopEngine, ColumnDefinition, CacheError};
@@ -40,26 +41,192 @@
 //! ```
 //!
 
+pub mod adapters;
+mod alias;
+mod archive;
+mod checksum;
+mod chunking;
+pub mod coalesce;
+mod codec;
+#[cfg(feature = "gzip")]
+#[cfg_attr(docsrs, doc(cfg(feature = "gzip")))]
+mod compression;
+mod default_ttl;
+pub mod dual_write;
+#[cfg(feature = "encryption")]
+#[cfg_attr(docsrs, doc(cfg(feature = "encryption")))]
+mod encryption;
+mod eviction;
+#[cfg(feature = "test-util")]
+#[cfg_attr(docsrs, doc(cfg(feature = "test-util")))]
+pub mod faulty;
+mod handle;
+mod index;
+pub mod memory_engine;
+#[cfg(feature = "metrics-registry")]
+#[cfg_attr(docsrs, doc(cfg(feature = "metrics-registry")))]
+pub mod metrics;
+mod middleware;
 pub mod noop_engine;
+mod prefix;
+#[cfg(feature = "record-replay")]
+#[cfg_attr(docsrs, doc(cfg(feature = "record-replay")))]
+pub mod recording;
 #[cfg(feature = "redis")]
 #[cfg_attr(docsrs, doc(cfg(feature = "redis")))]
 pub mod redis_engine;
 #[cfg(feature = "sled")]
 #[cfg_attr(docsrs, doc(cfg(feature = "sled")))]
 pub mod sled_engine;
+mod timing;
+mod transaction;
+mod ttl;
 
-use std::{any::Any, fmt::Debug};
+use std::{
+    any::Any,
+    borrow::Cow,
+    collections::HashMap,
+    fmt::{Debug, Write},
+    io::Read,
+    sync::Arc,
+    time::Duration,
+};
 
-use bincode::{Decode, Encode};
+use alias::Aliased;
+use bincode::{BorrowDecode, Decode, Encode};
+pub use codec::{Codec, FormatMismatch};
+#[cfg(feature = "gzip")]
+pub use compression::Compression;
+use default_ttl::DefaultTtl;
+#[cfg(feature = "encryption")]
+pub use encryption::Encryptor;
+pub use eviction::{EvictReason, EvictionListener};
+pub use handle::{ColumnHandle, ColumnWriter};
+pub use index::ColumnIndex;
+use index::IndexColumn;
+pub use middleware::{Checksum, ValueMiddleware};
 use noop_engine::NoopEngine;
+use prefix::KeyPrefix;
+pub use timing::{OperationKind, SlowOperation, SlowOperationListener, set_timing_enabled};
+pub use transaction::{ConditionCheck, Transaction, TransactionCondition, TransactionWrite};
 
+/// Errors returned by [`Engine`] and [`CacheStorage`] operations
+///
+/// New variants may be added, so matches need a wildcard arm.
 #[derive(Debug, Clone)]
+#[non_exhaustive]
 pub enum CacheError {
     Put(String),
     Get(String),
-    Encode(String),
-    Decode(String),
+    /// A value could not be encoded
+    Encode {
+        /// Name of the type being encoded, from [`std::any::type_name`]
+        type_name: String,
+        /// Underlying encode failure
+        source: String,
+    },
+    /// A stored value could not be decoded
+    Decode {
+        /// Name of the column holding the value
+        column: String,
+        /// Printable, possibly truncated, form of the key
+        key_hint: String,
+        /// Underlying decode failure
+        source: String,
+    },
     Engine(String),
+    /// No live value is stored at the key
+    ///
+    /// Only returned by operations that must act on an existing value, currently
+    /// [`Engine::try_get_required`]. Plain reads such as [`Engine::try_get`] report a miss
+    /// as `Ok(None)` and [`Engine::try_rename`] as `Ok(false)`.
+    NotFound {
+        /// Name of the column searched
+        column: String,
+        /// Printable, possibly truncated, form of the key
+        key_hint: String,
+    },
+    /// The storage could not be reached, e.g. no pooled connection became free in time,
+    /// the connection to redis dropped or timed out, or sled could not access its files
+    Unavailable(String),
+}
+
+impl CacheError {
+    /// Build a [`CacheError::Encode`] for a value of type V
+    pub(crate) fn encode<V: ?Sized>(source: String) -> CacheError {
+        CacheError::Encode {
+            type_name: std::any::type_name::<V>().to_string(),
+            source,
+        }
+    }
+
+    /// Build a [`CacheError::NotFound`] for key in the column
+    pub(crate) fn not_found(c: &dyn ColumnDefinition, key: &[u8]) -> CacheError {
+        CacheError::NotFound {
+            column: c.name(),
+            key_hint: key_hint(key),
+        }
+    }
+
+    /// Whether this is a [`CacheError::NotFound`], i.e. the operation needed a value
+    /// that is missing or expired
+    #[must_use]
+    pub fn is_not_found(&self) -> bool {
+        matches!(self, CacheError::NotFound { .. })
+    }
+
+    /// Build a [`CacheError::Decode`] for a value stored at key in the column
+    pub(crate) fn decode(c: &dyn ColumnDefinition, key: &[u8], source: String) -> CacheError {
+        CacheError::Decode {
+            column: c.name(),
+            key_hint: key_hint(key),
+            source,
+        }
+    }
+}
+
+/// Fail writes to a [`ColumnDefinition::read_only`] column
+fn check_writable(c: &dyn ColumnDefinition) -> Result<(), CacheError> {
+    if c.read_only() {
+        return Err(CacheError::Put("column is read-only".to_string()));
+    }
+
+    Ok(())
+}
+
+/// Storage key of an [`Encode`] key, see [`Engine::try_insert_keyed`]
+fn encode_key<K: Encode>(key: &K) -> Result<Vec<u8>, CacheError> {
+    bincode::encode_to_vec(key, bincode::config::standard())
+        .map_err(|e| CacheError::encode::<K>(e.to_string()))
+}
+
+/// Maximum number of key bytes included in error messages
+const KEY_HINT_LEN: usize = 32;
+
+/// Render a key for error messages.
+///
+/// Printable UTF-8 keys are shown as is, anything else as hex.
+/// Keys longer than [`KEY_HINT_LEN`] bytes are truncated.
+fn key_hint(key: &[u8]) -> String {
+    let (head, truncated) = if key.len() > KEY_HINT_LEN {
+        (&key[..KEY_HINT_LEN], true)
+    } else {
+        (key, false)
+    };
+
+    let mut hint = match std::str::from_utf8(head) {
+        Ok(text) if !text.chars().any(char::is_control) => text.to_string(),
+        _ => head.iter().fold(String::from("0x"), |mut hex, byte| {
+            let _ = write!(hex, "{byte:02x}");
+            hex
+        }),
+    };
+
+    if truncated {
+        hint.push_str("...");
+    }
+
+    hint
 }
 
 impl std::fmt::Display for CacheError {
@@ -67,24 +234,355 @@ impl std::fmt::Display for CacheError {
         match self {
             CacheError::Put(key) => write!(f, "Failed to insert value into cache: {key}"),
             CacheError::Get(key) => write!(f, "Failed to get value from cache: {key}"),
-            CacheError::Encode(key) => write!(f, "Failed to encode value for cache: {key}"),
-            CacheError::Decode(key) => write!(f, "Failed to decode value for cache: {key}"),
+            CacheError::Encode { type_name, source } => {
+                write!(f, "Failed to encode {type_name} for cache: {source}")
+            }
+            CacheError::Decode {
+                column,
+                key_hint,
+                source,
+            } => write!(
+                f,
+                "Failed to decode value for cache at {column}/{key_hint}: {source}"
+            ),
             CacheError::Engine(message) => write!(f, "Engine failed: {message}"),
+            CacheError::NotFound { column, key_hint } => {
+                write!(f, "No value in cache at {column}/{key_hint}")
+            }
+            CacheError::Unavailable(message) => write!(f, "Cache unavailable: {message}"),
         }
     }
 }
 
 impl std::error::Error for CacheError {}
 
+/// A stored value that could not be decoded, returned by [`Engine::try_get_raw_or_decoded`]
+#[derive(Debug)]
+pub struct DecodeFailure {
+    /// Why decoding failed
+    pub error: CacheError,
+    /// The value as held by storage, after joining chunks
+    /// but before checksum verification and decryption
+    pub bytes: Vec<u8>,
+}
+
+/// A value decoded as one of two types, returned by [`Engine::try_get_or`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Either<L, R> {
+    /// The value decoded as the primary type
+    Left(L),
+    /// The value only decoded as the fallback type
+    Right(R),
+}
+
+/// Whether an insert created a key or replaced a live value
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InsertOutcome {
+    /// The key was missing or expired
+    Created,
+    /// The key held a live value which was replaced
+    Overwrote,
+}
+
+/// Size of a column held by storage, returned by [`Engine::try_report`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ColumnReport {
+    /// Column name
+    pub name: String,
+    /// Number of stored keys, including expired keys not yet evicted
+    pub entries: usize,
+    /// Bytes taken by the column where the storage can tell
+    pub size_on_disk: Option<u64>,
+    /// [`ColumnDefinition::description`] of the column, filled in by [`Engine::try_report`]
+    /// for columns registered with [`Engine::register_column`]
+    pub description: Option<String>,
+}
+
+/// Effective settings of a storage, returned by [`Engine::describe_config`]
+///
+/// Displays as the backend then each setting, such as
+/// `sled (path=./cache, cache_capacity=1073741824, compression=off)`,
+/// to paste into a support ticket as it is.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BackendConfig {
+    /// Kind of storage, such as `sled` or `redis`
+    pub backend: String,
+    /// Names and values of the settings the storage runs with, in the order it lists them
+    pub settings: Vec<(String, String)>,
+}
+
+impl BackendConfig {
+    /// Settings of a storage of kind `backend`, with none listed yet
+    #[must_use]
+    pub fn new(backend: impl Into<String>) -> BackendConfig {
+        BackendConfig {
+            backend: backend.into(),
+            settings: Vec::new(),
+        }
+    }
+
+    /// List a setting and its value
+    #[must_use]
+    pub fn with(mut self, name: impl Into<String>, value: impl std::fmt::Display) -> BackendConfig {
+        self.settings.push((name.into(), value.to_string()));
+        self
+    }
+
+    /// Value of the setting `name`, if listed
+    #[must_use]
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.settings
+            .iter()
+            .find(|(setting, _)| setting == name)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+impl std::fmt::Display for BackendConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.backend)?;
+
+        for (i, (name, value)) in self.settings.iter().enumerate() {
+            let separator = if i == 0 { " (" } else { ", " };
+            write!(f, "{separator}{name}={value}")?;
+        }
+        if !self.settings.is_empty() {
+            f.write_str(")")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Where the sled engine keeps a column's items, see [`ColumnDefinition::storage_layout`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum SledLayout {
+    /// A tree of its own, named after the column.
+    ///
+    /// Dropping the column drops the tree, which is cheap however many items it holds.
+    #[default]
+    DedicatedTree,
+    /// A tree shared with the other columns using this layout, each key prefixed
+    /// by the column name, so many small or short lived columns do not each cost a tree.
+    ///
+    /// Dropping the column deletes its range of the shared tree key by key, which takes
+    /// time in proportion to its items. Counting the column and listing or reporting
+    /// columns walk the range, or the whole shared tree, rather than reading tree metadata.
+    SharedPrefixed,
+}
+
+/// How a storage removes values once their TTL is up, see [`Engine::ttl_enforcement`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TtlEnforcement {
+    /// The server removes values when they expire, whether or not they are read again
+    ServerSide,
+    /// Expired values are never returned, but are only removed when next read,
+    /// so they keep taking up space until then
+    LazyOnRead,
+    /// Values never expire
+    None,
+}
+
 /// Definition of a Cache column
 pub trait ColumnDefinition {
     /// Column name
     fn name(&self) -> String;
 
     /// Column items TTL
+    ///
+    /// A TTL of zero or less means items never expire
     fn get_ttl_in_seconds(&self) -> i32;
+
+    /// Maximum number of items held by the column.
+    ///
+    /// When an insert takes the column over this limit the oldest items
+    /// are evicted. Enforced by the sled, in-memory and passthrough Noop engines,
+    /// defaults to unbounded.
+    ///
+    /// sled keeps the write order of the column's keys in memory, loaded by walking
+    /// the column on its first write since the engine was opened, so the items
+    /// written last are the ones kept, even within the same second.
+    fn max_entries(&self) -> Option<usize> {
+        None
+    }
+
+    /// Fractional spread applied to the TTL of each inserted item,
+    /// `0.1` gives each key a TTL randomly within ±10% of the column TTL.
+    ///
+    /// The jitter is picked per key at insert time so keys inserted together
+    /// do not all expire together and stampede the source on refill.
+    /// Values are clamped to `1.0`, defaults to no jitter.
+    fn ttl_jitter(&self) -> Option<f32> {
+        None
+    }
+
+    /// Sliding TTL expiring items that go unread for this long.
+    ///
+    /// Each read through [`Engine::try_get`] and the other value reads restarts the idle timer,
+    /// while [`ColumnDefinition::get_ttl_in_seconds`] stays an absolute cap counted from the
+    /// insert, so a session can live at most a day but end after half an hour without use.
+    /// Enforced by the sled, in-memory and Redis engines, where Redis keeps the cap in a
+    /// companion `{column}#deadline` key per value. Counter increments do not count as reads.
+    /// Defaults to no idle expiry.
+    fn idle_ttl_in_seconds(&self) -> Option<u32> {
+        None
+    }
+
+    /// Former names of the column, for renaming a column without orphaning its data.
+    ///
+    /// Reads through [`Engine`] that miss under [`ColumnDefinition::name`] try each alias
+    /// in turn, while writes, swaps and counter increments only use the name, so values
+    /// move to it as they are rewritten. Drops and [`Engine::try_drop_column_dry_run`]
+    /// cover the aliases too, so stop listing an alias once its data has aged out.
+    /// Secondary indexes are not carried over, defaults to none.
+    fn aliases(&self) -> &[&str] {
+        &[]
+    }
+
+    /// Human readable description of what the column holds,
+    /// for dashboards and diagnostics where [`ColumnDefinition::name`] is too terse.
+    ///
+    /// Shown by [`Engine::try_report`] for registered columns, by
+    /// [`Engine::metrics_snapshot`], by printed operation timings and
+    /// passed to [`Engine::on_slow_operation`] listeners.
+    fn description(&self) -> Option<&str> {
+        None
+    }
+
+    /// Secondary index maintained for the column, see [`Engine::try_get_by_index`],
+    /// defaults to none
+    fn index(&self) -> Option<&dyn ColumnIndex> {
+        None
+    }
+
+    /// Whether values written before the column's TTL last changed are treated as expired.
+    ///
+    /// Each value keeps the TTL it was written with, so by default lowering the TTL
+    /// leaves existing values to live out the old one and raising it does not extend them.
+    /// When set, sled records the column TTL alongside the database and, on seeing it change,
+    /// expires everything written before the change. Other storages ignore it,
+    /// defaults to keeping existing values.
+    fn expire_on_ttl_change(&self) -> bool {
+        false
+    }
+
+    /// Whether this service may only read the column.
+    ///
+    /// Writes, counter increments, even by a delta of zero, and drops through [`Engine`] fail with
+    /// [`CacheError::Put`] when set, for columns owned by another service.
+    fn read_only(&self) -> bool {
+        false
+    }
+
+    /// Whether sled keeps the column in a tree of its own or in a tree shared with other
+    /// columns, see [`SledLayout`] for the cost of each. Other storages ignore it,
+    /// defaults to [`SledLayout::DedicatedTree`].
+    ///
+    /// Items are only found under the layout they were written with,
+    /// so changing it leaves a column's existing items behind until they are dropped.
+    fn storage_layout(&self) -> SledLayout {
+        SledLayout::DedicatedTree
+    }
+
+    /// Value read by [`Engine::try_get_with_column_default`] when the key is missing,
+    /// encoded with the engine's codec, defaults to none.
+    ///
+    /// Only the encoding of [`Engine::with_codec`] is expected: no checksum, compression,
+    /// encryption or middleware. Other reads ignore it and still report a miss as `None`.
+    fn default_value_bytes(&self) -> Option<Vec<u8>> {
+        None
+    }
+}
+
+/// Bytes of a value held by storage, kept alive so values can be decoded borrowing from them.
+///
+/// Returned by [`Engine::try_get_borrowed`]. The bytes are owned by this value,
+/// so anything decoded with [`StoredValue::decode`] borrows from it and cannot outlive it.
+/// On sled this holds the tree's reference counted buffer so no copy of the value is made.
+pub struct StoredValue {
+    bytes: Box<dyn AsRef<[u8]> + Send + Sync>,
+    start: usize,
+    end: usize,
+    codec: Codec,
+    /// Bytes decoding may allocate, see [`Engine::with_decode_limit`]
+    decode_limit: usize,
+    column: String,
+    key: Vec<u8>,
 }
 
+impl StoredValue {
+    /// Wrap bytes returned by storage
+    pub fn new(bytes: impl AsRef<[u8]> + Send + Sync + 'static) -> StoredValue {
+        let end = bytes.as_ref().len();
+
+        StoredValue {
+            bytes: Box::new(bytes),
+            start: 0,
+            end,
+            codec: Codec::default(),
+            decode_limit: Engine::DEFAULT_DECODE_LIMIT,
+            column: String::new(),
+            key: Vec::new(),
+        }
+    }
+
+    /// Narrow the value to `start..end` of the held bytes
+    #[cfg(feature = "sled")]
+    pub(crate) fn slice(mut self, start: usize, end: usize) -> StoredValue {
+        self.start = start;
+        self.end = end;
+        self
+    }
+
+    /// The encoded value
+    #[must_use]
+    pub fn as_bytes(&self) -> &[u8] {
+        &(*self.bytes).as_ref()[self.start..self.end]
+    }
+
+    /// Decode the value, borrowing from the held bytes where `V` allows,
+    /// e.g. `&[u8]`, `&str` or structs of them.
+    ///
+    /// Only the codec set with [`Engine::with_codec`] is tried, legacy codecs are ignored.
+    ///
+    /// # Errors
+    /// Returns [`CacheError::Decode`] if the bytes cannot be decoded to type V
+    /// or would allocate more than the [`Engine::with_decode_limit`]
+    pub fn decode<'de, V: BorrowDecode<'de, ()>>(&'de self) -> Result<V, CacheError> {
+        self.codec
+            .borrow_decode(self.as_bytes(), self.decode_limit)
+            .map(|v| v.0)
+            .map_err(|e| CacheError::Decode {
+                column: self.column.clone(),
+                key_hint: key_hint(&self.key),
+                source: e.to_string(),
+            })
+    }
+}
+
+impl From<Vec<u8>> for StoredValue {
+    fn from(bytes: Vec<u8>) -> Self {
+        StoredValue::new(bytes)
+    }
+}
+
+impl Debug for StoredValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("StoredValue")
+            .field("len", &(self.end - self.start))
+            .field("codec", &self.codec)
+            .field("column", &self.column)
+            .field("key", &key_hint(&self.key))
+            .finish_non_exhaustive()
+    }
+}
+
+/// Key and value bytes of a stored entry, see [`CacheStorage::try_entries`]
+pub type RawEntry = (Vec<u8>, Vec<u8>);
+
+/// Key, value and time left to live of a stored item, [`None`] if it never expires
+pub type TimedEntry = (Vec<u8>, Vec<u8>, Option<Duration>);
+
 /// Trait for Cache storage engine
 pub trait CacheStorage {
     /// Build new storage
@@ -103,113 +601,5197 @@ pub trait CacheStorage {
         value: &[u8],
     ) -> Result<(), CacheError>;
 
+    /// Whether every write is thrown away, letting [`Engine`] skip encoding values
+    fn discards_writes(&self) -> bool {
+        false
+    }
+
+    /// How values are removed once their TTL is up, [`TtlEnforcement::None`] unless
+    /// the storage says otherwise, as the weakest guarantee
+    fn ttl_enforcement(&self) -> TtlEnforcement {
+        TtlEnforcement::None
+    }
+
+    /// Effective settings of the storage for diagnostics, see [`Engine::describe_config`].
+    /// Unless the storage lists its settings, its type name with none
+    fn describe_config(&self) -> BackendConfig {
+        BackendConfig::new(std::any::type_name::<Self>())
+    }
+
+    /// Insert a value with a TTL other than the column's,
+    /// a TTL of zero or less means the value never expires
     /// # Errors
-    /// Returns [`CacheError::Get`] if get fails
-    fn try_get(&self, c: &dyn ColumnDefinition, key: &[u8]) -> Result<Option<Vec<u8>>, CacheError>;
+    /// Returns [`CacheError::Put`] if insert fails
+    fn try_insert_with_ttl(
+        &self,
+        _c: &dyn ColumnDefinition,
+        _key: &[u8],
+        _value: &[u8],
+        _ttl_in_seconds: i32,
+    ) -> Result<(), CacheError> {
+        Err(CacheError::Engine(
+            "per insert TTL is not supported by this storage".to_string(),
+        ))
+    }
 
+    /// Insert a value and report whether a live value was replaced.
+    ///
+    /// The default checks for the key before inserting so is not atomic.
     /// # Errors
-    /// Returns [`CacheError::Engine`] if drop fails
-    fn try_drop_column(&self, c: &dyn ColumnDefinition) -> Result<(), CacheError>;
-}
+    /// Returns [`CacheError::Put`] if insert fails
+    fn try_insert_reporting(
+        &self,
+        c: &dyn ColumnDefinition,
+        key: &[u8],
+        value: &[u8],
+    ) -> Result<InsertOutcome, CacheError> {
+        let outcome = match self.try_get(c, key)? {
+            Some(_) => InsertOutcome::Overwrote,
+            None => InsertOutcome::Created,
+        };
 
-pub struct Engine {
-    storage: Box<dyn CacheStorage + Sync + Send>,
-}
+        self.try_insert(c, key, value)?;
 
-impl Default for Engine {
-    fn default() -> Self {
-        Self {
-            storage: Box::new(NoopEngine::default()),
-        }
+        Ok(outcome)
     }
-}
 
-impl Debug for Engine {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.debug_struct("Engine")
-            .field("storage", &(*self.storage).type_id())
-            .finish()
+    /// Insert many values into the column with its TTL,
+    /// the default inserts them one at a time
+    /// # Errors
+    /// Returns [`CacheError::Put`] if insert fails, some of the values may have been inserted
+    fn try_insert_batch(
+        &self,
+        c: &dyn ColumnDefinition,
+        items: &[(Vec<u8>, Vec<u8>)],
+    ) -> Result<(), CacheError> {
+        for (key, value) in items {
+            self.try_insert(c, key, value)?;
+        }
+
+        Ok(())
     }
-}
 
-impl Engine {
-    ///
-    /// ```
-    /// use omega_cache::{Engine, noop_engine::NoopEngine, CacheStorage};
+    /// Writer inserting into the column, with the storage's per column setup done up front.
     ///
-    /// let engine = Engine::new(NoopEngine::build(String::new(), None));
-    /// ```
-    ///
-    #[must_use]
-    pub fn new(storage: Box<dyn CacheStorage + Sync + Send>) -> Engine {
-        Engine { storage }
+    /// Used by [`ColumnHandle`], the default inserts through [`CacheStorage::try_insert`].
+    /// # Errors
+    /// Returns [`CacheError::Put`] if the column cannot be opened
+    fn try_column_writer<'a>(
+        &'a self,
+        c: &'a dyn ColumnDefinition,
+    ) -> Result<Box<dyn ColumnWriter + 'a>, CacheError> {
+        Ok(Box::new(handle::StorageWriter {
+            storage: self,
+            column: c,
+        }))
     }
 
+    /// Insert a value with the column TTL and return the live value it replaced.
+    ///
+    /// The default checks for the key before inserting so is not atomic.
     /// # Errors
     /// Returns [`CacheError::Put`] if insert fails
-    /// Returns [`CacheError::Encode`] if type V cannot be encoded to [`Vec<u8>`]
-    pub fn try_insert<'a, K: AsRef<[u8]> + 'a, V: Encode + 'a>(
-        &'a self,
+    fn try_swap(
+        &self,
         c: &dyn ColumnDefinition,
-        key: &'a K,
-        value: &'a V,
-    ) -> Result<(), CacheError> {
-        let key_bytes = key.as_ref();
-        let value_bytes = bincode::encode_to_vec(value, bincode::config::standard())
-            .map_err(|e| CacheError::Encode(e.to_string()))?;
+        key: &[u8],
+        value: &[u8],
+    ) -> Result<Option<Vec<u8>>, CacheError> {
+        let old = self.try_get(c, key)?;
+        self.try_insert(c, key, value)?;
 
-        self.storage.try_insert(c, key_bytes, &value_bytes)
+        Ok(old)
     }
 
+    /// Atomically move the live value at `from` to `to`, replacing any value there,
+    /// and return whether there was one to move
     /// # Errors
-    /// Returns [`CacheError::Get`] if get fails.
-    /// Returns [`CacheError::Decode`] if get result cannot be decoded to type V from a [`Vec<u8>`]
-    pub fn try_get<'a, K: AsRef<[u8]> + 'a, V: Decode<()> + Encode + 'a>(
-        &'a self,
+    /// Returns [`CacheError::Put`] if the move fails
+    fn try_rename(
+        &self,
+        _c: &dyn ColumnDefinition,
+        _from: &[u8],
+        _to: &[u8],
+    ) -> Result<bool, CacheError> {
+        Err(CacheError::Engine(
+            "renaming keys is not supported by this storage".to_string(),
+        ))
+    }
+
+    /// Atomically remove the live value at key and return it
+    /// # Errors
+    /// Returns [`CacheError::Engine`] if the storage cannot take values atomically
+    fn try_take(
+        &self,
+        _c: &dyn ColumnDefinition,
+        _key: &[u8],
+    ) -> Result<Option<Vec<u8>>, CacheError> {
+        Err(CacheError::Engine(
+            "taking values is not supported by this storage".to_string(),
+        ))
+    }
+
+    /// Check every condition against the live values as stored and, only if they
+    /// all hold, apply the writes in order, as one atomic step, returning whether
+    /// the conditions held. Inserts take the column TTL.
+    /// # Errors
+    /// Returns [`CacheError::Put`] if the commit fails,
+    /// or the first error of a condition's check
+    fn try_commit(
+        &self,
+        _c: &dyn ColumnDefinition,
+        _conditions: &[TransactionCondition],
+        _writes: &[TransactionWrite],
+    ) -> Result<bool, CacheError> {
+        Err(CacheError::Engine(
+            "transactions are not supported by this storage".to_string(),
+        ))
+    }
+
+    /// Restart the TTL of the live values at keys with `ttl_in_seconds`,
+    /// never expiring them if it is zero or less, and return how many there were
+    /// # Errors
+    /// Returns [`CacheError::Put`] if refreshing fails
+    fn try_touch_many(
+        &self,
+        _c: &dyn ColumnDefinition,
+        _keys: &[&[u8]],
+        _ttl_in_seconds: i32,
+    ) -> Result<usize, CacheError> {
+        Err(CacheError::Engine(
+            "refreshing TTLs is not supported by this storage".to_string(),
+        ))
+    }
+
+    /// # Errors
+    /// Returns [`CacheError::Get`] if get fails
+    fn try_get(&self, c: &dyn ColumnDefinition, key: &[u8]) -> Result<Option<Vec<u8>>, CacheError>;
+
+    /// Get the values at many keys, in the order of `keys`,
+    /// the default gets them one at a time
+    /// # Errors
+    /// Returns [`CacheError::Get`] if get fails
+    fn try_get_many(
+        &self,
         c: &dyn ColumnDefinition,
-        key: &'a K,
-    ) -> Result<Option<V>, CacheError> {
-        let key_bytes = key.as_ref();
+        keys: &[&[u8]],
+    ) -> Result<Vec<Option<Vec<u8>>>, CacheError> {
+        keys.iter().map(|key| self.try_get(c, key)).collect()
+    }
 
-        match self.storage.try_get(c, key_bytes)? {
-            Some(bytes) => bincode::decode_from_slice(&bytes, bincode::config::standard())
-                .map_err(|e| CacheError::Decode(e.to_string()))
-                .map(|v| Some(v.0)),
-            None => Ok(None),
-        }
+    /// Number of bytes stored at key, including any storage overhead,
+    /// the default reads the value with [`CacheStorage::try_get`]
+    /// # Errors
+    /// Returns [`CacheError::Get`] if the size cannot be read
+    fn try_value_size(
+        &self,
+        c: &dyn ColumnDefinition,
+        key: &[u8],
+    ) -> Result<Option<usize>, CacheError> {
+        Ok(self.try_get(c, key)?.map(|bytes| bytes.len()))
     }
 
+    /// Get the bytes at key without copying them where the storage allows,
+    /// the default wraps the result of [`CacheStorage::try_get`]
     /// # Errors
-    /// Returns [`CacheError::Engine`] if drop fails
-    pub fn try_drop_column(&self, c: &dyn ColumnDefinition) -> Result<(), CacheError> {
-        self.storage.try_drop_column(c)
+    /// Returns [`CacheError::Get`] if get fails
+    fn try_get_stored(
+        &self,
+        c: &dyn ColumnDefinition,
+        key: &[u8],
+    ) -> Result<Option<StoredValue>, CacheError> {
+        Ok(self.try_get(c, key)?.map(StoredValue::from))
     }
-}
 
-#[cfg(test)]
-mod test {
-    use crate::{ColumnDefinition, Engine};
+    /// Get the bytes at key even if they expired, along with whether they did.
+    ///
+    /// Expired values are left in place. The default reads live values with
+    /// [`CacheStorage::try_get`], which is all a storage that deletes values when they expire,
+    /// such as Redis, can return.
+    /// # Errors
+    /// Returns [`CacheError::Get`] if get fails
+    fn try_get_allowing_stale(
+        &self,
+        c: &dyn ColumnDefinition,
+        key: &[u8],
+    ) -> Result<Option<(Vec<u8>, bool)>, CacheError> {
+        Ok(self.try_get(c, key)?.map(|bytes| (bytes, false)))
+    }
 
-    struct TestColumn {}
+    /// # Errors
+    /// Returns [`CacheError::Engine`] if drop fails
+    fn try_drop_column(&self, c: &dyn ColumnDefinition) -> Result<(), CacheError>;
 
-    impl ColumnDefinition for TestColumn {
-        fn name(&self) -> String {
-            "test_column".to_string()
-        }
+    /// Every live key and value held by the column, in no particular order
+    /// # Errors
+    /// Returns [`CacheError::Get`] if the column cannot be read
+    fn try_entries(&self, _c: &dyn ColumnDefinition) -> Result<Vec<RawEntry>, CacheError> {
+        Err(CacheError::Engine(
+            "listing entries is not supported by this storage".to_string(),
+        ))
+    }
 
-        fn get_ttl_in_seconds(&self) -> i32 {
-            1
-        }
+    /// Every live key and value held by the column with the time each has left to live,
+    /// in no particular order
+    /// # Errors
+    /// Returns [`CacheError::Get`] if the column cannot be read
+    fn try_timed_entries(&self, _c: &dyn ColumnDefinition) -> Result<Vec<TimedEntry>, CacheError> {
+        Err(CacheError::Engine(
+            "listing entries with their TTL is not supported by this storage".to_string(),
+        ))
     }
 
-    const COLUMN: TestColumn = TestColumn {};
+    /// Time since each live stored key of the column was written, in no particular order
+    /// # Errors
+    /// Returns [`CacheError::Get`] if the column cannot be read
+    fn try_entry_ages(&self, _c: &dyn ColumnDefinition) -> Result<Vec<Duration>, CacheError> {
+        Err(CacheError::Engine(
+            "entry ages are not supported by this storage".to_string(),
+        ))
+    }
 
-    #[test]
-    fn test_default_engine() {
-        let engine = Engine::default();
+    /// Number of stored keys [`CacheStorage::try_drop_column`] would remove,
+    /// without removing them
+    /// # Errors
+    /// Returns [`CacheError::Engine`] if the keys cannot be counted
+    fn try_count_column(&self, _c: &dyn ColumnDefinition) -> Result<usize, CacheError> {
+        Err(CacheError::Engine(
+            "counting keys is not supported by this storage".to_string(),
+        ))
+    }
 
-        assert!(engine.try_insert(&COLUMN, &String::new(), &100i32).is_ok());
-        assert!(engine.try_get::<&str, i32>(&COLUMN, &"").is_ok());
-        assert!(engine.try_drop_column(&COLUMN).is_ok());
+    /// Storage specific checks on a column definition, see [`Engine::validate_column`]
+    /// # Errors
+    /// Returns [`CacheError::Engine`] if the column cannot be held by the storage
+    fn validate_column(&self, _c: &dyn ColumnDefinition) -> Result<(), CacheError> {
+        Ok(())
+    }
+
+    /// Names of the columns currently held by the storage
+    /// # Errors
+    /// Returns [`CacheError::Engine`] if the columns cannot be listed
+    fn try_list_columns(&self) -> Result<Vec<String>, CacheError> {
+        Err(CacheError::Engine(
+            "listing columns is not supported by this storage".to_string(),
+        ))
+    }
+
+    /// Layout the storage holds the column's values in, which sled finds from its trees
+    /// for columns known only by name, such as those listed by
+    /// [`CacheStorage::try_list_columns`]. Defaults to the column's
+    /// [`ColumnDefinition::storage_layout`], as other storages ignore it
+    /// # Errors
+    /// Returns [`CacheError::Engine`] if the column's values are held in both layouts
+    fn try_column_layout(&self, c: &dyn ColumnDefinition) -> Result<SledLayout, CacheError> {
+        Ok(c.storage_layout())
+    }
+
+    /// Size of every column currently held by the storage, sorted by name
+    /// # Errors
+    /// Returns [`CacheError::Engine`] if the columns cannot be measured
+    fn try_report(&self) -> Result<Vec<ColumnReport>, CacheError> {
+        Err(CacheError::Engine(
+            "reporting is not supported by this storage".to_string(),
+        ))
+    }
+
+    /// Reclaim space held by expired or dropped items, nothing by default
+    /// # Errors
+    /// Returns [`CacheError::Put`] if the storage cannot be maintained
+    fn try_maintenance(&self) -> Result<(), CacheError> {
+        Ok(())
+    }
+
+    /// Atomically add `delta` to the counter stored at key,
+    /// creating it at zero if it is missing or expired
+    /// # Errors
+    /// Returns [`CacheError::Put`] if the increment fails or would overflow
+    fn try_increment(
+        &self,
+        _c: &dyn ColumnDefinition,
+        _key: &[u8],
+        _delta: i64,
+    ) -> Result<i64, CacheError> {
+        Err(CacheError::Engine(
+            "increment is not supported by this storage".to_string(),
+        ))
+    }
+
+    /// Notify `listener` of values leaving the storage, replacing any earlier listener
+    /// # Errors
+    /// Returns [`CacheError::Engine`] if the storage cannot observe evictions
+    fn set_eviction_listener(
+        &mut self,
+        _listener: Arc<dyn EvictionListener>,
+    ) -> Result<(), CacheError> {
+        Err(CacheError::Engine(
+            "eviction listeners are not supported by this storage".to_string(),
+        ))
+    }
+
+    /// Report operations taking at least `threshold` to `listener`, replacing any earlier listener
+    /// # Errors
+    /// Returns [`CacheError::Engine`] if the storage does not time its operations
+    fn set_slow_operation_listener(
+        &mut self,
+        _threshold: Duration,
+        _listener: SlowOperationListener,
+    ) -> Result<(), CacheError> {
+        Err(CacheError::Engine(
+            "slow operation listeners are not supported by this storage".to_string(),
+        ))
+    }
+}
+
+/// Storage used by a disabled [`Engine`]
+static DISABLED: NoopEngine = NoopEngine::inert();
+
+pub struct Engine {
+    /// [`None`] when the engine is disabled
+    storage: Option<Box<dyn CacheStorage + Sync + Send>>,
+    checksums: bool,
+    /// Whether values carry a format marker, and what reads do on a mismatch
+    format_marker: Option<FormatMismatch>,
+    codec: Codec,
+    legacy_codecs: Vec<Codec>,
+    rewrite_legacy: bool,
+    /// Bytes decoding a value may allocate, see [`Engine::with_decode_limit`]
+    decode_limit: usize,
+    batch_size: usize,
+    chunk_size: Option<usize>,
+    /// Longest key accepted, see [`Engine::with_max_key_bytes`]
+    max_key_bytes: Option<usize>,
+    /// TTL in seconds of columns whose own TTL is zero or less, see [`Engine::with_default_ttl`]
+    default_ttl: Option<i32>,
+    /// Settings of each registered column by name
+    columns: HashMap<String, RegisteredColumn>,
+    /// Whether columns must be registered before use
+    strict_columns: bool,
+    #[cfg(feature = "encryption")]
+    encryptor: Option<Encryptor>,
+    #[cfg(feature = "gzip")]
+    compression: Option<Compression>,
+    /// Transformations of encoded values in the order writes apply them, see [`Engine::with_middleware`]
+    middleware: Vec<Box<dyn ValueMiddleware>>,
+    /// Counters shared with the metered storage, see [`Engine::with_metrics`]
+    #[cfg(feature = "metrics-registry")]
+    metrics: Option<std::sync::Arc<metrics::MetricsRegistry>>,
+}
+
+/// What the engine keeps of a column given to [`Engine::register_column`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct RegisteredColumn {
+    ttl_in_seconds: i32,
+    idle_ttl_in_seconds: Option<u32>,
+    storage_layout: SledLayout,
+    description: Option<String>,
+}
+
+impl Default for Engine {
+    fn default() -> Self {
+        Engine::new(Box::new(NoopEngine::default()))
+    }
+}
+
+impl Debug for Engine {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut debug = f.debug_struct("Engine");
+        debug
+            .field("storage", &self.storage().type_id())
+            .field("checksums", &self.checksums)
+            .field("format_marker", &self.format_marker)
+            .field("codec", &self.codec)
+            .field("legacy_codecs", &self.legacy_codecs)
+            .field("rewrite_legacy", &self.rewrite_legacy)
+            .field("decode_limit", &self.decode_limit)
+            .field("batch_size", &self.batch_size)
+            .field("chunk_size", &self.chunk_size)
+            .field("max_key_bytes", &self.max_key_bytes)
+            .field("default_ttl", &self.default_ttl)
+            .field("columns", &self.columns)
+            .field("strict_columns", &self.strict_columns);
+
+        #[cfg(feature = "encryption")]
+        debug.field("encrypted", &self.encryptor.is_some());
+
+        #[cfg(feature = "gzip")]
+        debug.field("compression", &self.compression);
+
+        debug.field("middleware", &self.middleware.len());
+
+        #[cfg(feature = "metrics-registry")]
+        debug.field("metrics", &self.metrics.is_some());
+
+        debug.finish()
+    }
+}
+
+impl Engine {
+    /// Default number of items per storage call in batch operations,
+    /// see [`Engine::with_batch_size`]
+    pub const DEFAULT_BATCH_SIZE: usize = 256;
+
+    /// Default bytes decoding a value may allocate, see [`Engine::with_decode_limit`]
+    pub const DEFAULT_DECODE_LIMIT: usize = 64 * 1024 * 1024;
+
+    ///
+    /// ```
+    /// use omega_cache::{Engine, noop_engine::NoopEngine, CacheStorage};
+    ///
+    /// let engine = Engine::new(NoopEngine::build(String::new(), None));
+    /// ```
+    ///
+    #[must_use]
+    pub fn new(storage: Box<dyn CacheStorage + Sync + Send>) -> Engine {
+        Engine::with_storage(Some(storage))
+    }
+
+    fn with_storage(storage: Option<Box<dyn CacheStorage + Sync + Send>>) -> Engine {
+        Engine {
+            storage: storage.map(|inner| -> Box<dyn CacheStorage + Sync + Send> {
+                Box::new(Aliased { inner })
+            }),
+            checksums: false,
+            format_marker: None,
+            codec: Codec::default(),
+            legacy_codecs: Vec::new(),
+            rewrite_legacy: false,
+            decode_limit: Self::DEFAULT_DECODE_LIMIT,
+            batch_size: Self::DEFAULT_BATCH_SIZE,
+            chunk_size: None,
+            max_key_bytes: None,
+            default_ttl: None,
+            columns: HashMap::new(),
+            strict_columns: false,
+            #[cfg(feature = "encryption")]
+            encryptor: None,
+            #[cfg(feature = "gzip")]
+            compression: None,
+            middleware: Vec::new(),
+            #[cfg(feature = "metrics-registry")]
+            metrics: None,
+        }
+    }
+
+    /// An engine with caching switched off, for example behind a feature flag.
+    ///
+    /// Writes succeed without encoding anything and reads miss, without building any storage.
+    /// Engines over a storage that discards writes, such as the default [`NoopEngine`],
+    /// skip encoding in the same way but still report as enabled.
+    /// Check [`Engine::is_enabled`] to skip computing values only worth caching.
+    ///
+    /// ```
+    /// use omega_cache::Engine;
+    ///
+    /// let engine = Engine::disabled();
+    ///
+    /// assert!(!engine.is_enabled());
+    /// ```
+    #[must_use]
+    pub fn disabled() -> Engine {
+        Engine::with_storage(None)
+    }
+
+    /// Whether the engine caches anything, `false` for [`Engine::disabled`]
+    #[must_use]
+    pub fn is_enabled(&self) -> bool {
+        self.storage.is_some()
+    }
+
+    /// Build an engine from a url, picking the storage from its scheme.
+    ///
+    /// - `redis://` and `rediss://` connect to redis with the whole url
+    /// - `sled://<path>` opens a sled database at `<path>`
+    /// - `memory://` builds a [`memory_engine::MemoryEngine`] with the default shards
+    /// - `noop://` builds a [`NoopEngine`] that stores nothing
+    ///
+    /// Handy for selecting the storage from a single `CACHE_URL` setting.
+    ///
+    /// ```
+    /// use omega_cache::Engine;
+    ///
+    /// let engine = Engine::from_url("noop://").unwrap();
+    ///
+    /// assert!(Engine::from_url("memcached://localhost").is_err());
+    /// ```
+    ///
+    /// # Errors
+    /// Returns [`CacheError::Engine`] if the scheme is unknown,
+    /// the feature it needs is not compiled in or the storage cannot be opened
+    pub fn from_url(url: &str) -> Result<Engine, CacheError> {
+        #[cfg_attr(not(feature = "sled"), allow(unused_variables))]
+        let Some((scheme, path)) = url.split_once("://") else {
+            return Err(CacheError::Engine(format!(
+                "Cache url `{url}` has no scheme"
+            )));
+        };
+
+        match scheme {
+            "noop" => Ok(Engine::default()),
+            "memory" => Ok(Engine::new(memory_engine::MemoryEngine::builder().build())),
+            #[cfg(feature = "sled")]
+            "sled" => sled_engine::SledEngine::builder(path)
+                .try_build()
+                .map(Engine::new),
+            #[cfg(feature = "redis")]
+            "redis" | "rediss" => redis_engine::RedisEngine::builder(url)
+                .try_build()
+                .map(Engine::new),
+            #[cfg(not(feature = "sled"))]
+            "sled" => Err(CacheError::Engine(
+                "sled:// urls need the `sled` feature of omega-cache".to_string(),
+            )),
+            #[cfg(not(feature = "redis"))]
+            "redis" | "rediss" => Err(CacheError::Engine(
+                "redis:// urls need the `redis` feature of omega-cache".to_string(),
+            )),
+            _ => Err(CacheError::Engine(format!(
+                "Unknown cache url scheme `{scheme}`"
+            ))),
+        }
+    }
+
+    /// Store a CRC32 checksum alongside every value and verify it on read.
+    ///
+    /// Catches silent corruption (bit rot, a buggy writer) that bincode
+    /// might otherwise decode without complaint. Values written without
+    /// checksums cannot be read back once this is enabled.
+    ///
+    /// ```
+    /// use omega_cache::{Engine, noop_engine::NoopEngine, CacheStorage};
+    ///
+    /// let engine = Engine::new(NoopEngine::build(String::new(), None)).with_checksums();
+    /// ```
+    ///
+    #[must_use]
+    pub fn with_checksums(mut self) -> Engine {
+        self.checksums = true;
+        self
+    }
+
+    /// Prefix every stored value with a byte marking its encoding, so values written
+    /// by an encoding this engine does not read are caught before decoding.
+    ///
+    /// The marker is the first byte handed to storage, ahead of any checksum or
+    /// encryption: `0xC2` for [`Codec::Standard`] and `0xC1` for [`Codec::Legacy`].
+    /// Reads accept the markers of the engine's codec and its legacy codecs, and
+    /// decode each value with the codec it was marked with rather than trying them
+    /// in turn. Any other first byte, such as a marker from a later version or a
+    /// value written without markers, is a mismatch, read as a miss or failed with
+    /// a [`CacheError::Decode`] naming the marker, as set by `on_mismatch`.
+    /// Values written before this is enabled are all mismatches, so with
+    /// [`FormatMismatch::Miss`] the cache refills rather than erroring.
+    ///
+    /// ```
+    /// use omega_cache::{Codec, ColumnDefinition, Engine, FormatMismatch, noop_engine::NoopEngine};
+    ///
+    /// struct Users;
+    ///
+    /// impl ColumnDefinition for Users {
+    ///     fn name(&self) -> String {
+    ///         "users".to_string()
+    ///     }
+    ///
+    ///     fn get_ttl_in_seconds(&self) -> i32 {
+    ///         60
+    ///     }
+    /// }
+    ///
+    /// let engine = Engine::new(NoopEngine::passthrough())
+    ///     .with_codec(Codec::Legacy)
+    ///     .with_format_marker(FormatMismatch::Miss);
+    /// engine.try_insert(&Users, &"ada", &36u32).unwrap();
+    ///
+    /// // after switching codecs, old values are misses rather than misreads
+    /// let engine = engine.with_codec(Codec::Standard);
+    /// assert_eq!(engine.try_get::<_, u32>(&Users, &"ada").unwrap(), None);
+    /// ```
+    ///
+    #[must_use]
+    pub fn with_format_marker(mut self, on_mismatch: FormatMismatch) -> Engine {
+        self.format_marker = Some(on_mismatch);
+        self
+    }
+
+    /// Encrypt values before handing them to storage and decrypt them on read,
+    /// so a leaked database file or redis dump does not expose them.
+    ///
+    /// Keys are stored in the clear. Each value is authenticated with its column name
+    /// and key, so a stored value copied under another key or column fails to decode
+    /// rather than being read as that key's, as do values written without encryption,
+    /// or with another encryption key, once this is enabled. For the same reason
+    /// [`Engine::try_rename`] and [`Engine::try_copy_column`] into a column of another
+    /// name fail on an engine with encryption.
+    ///
+    /// ```
+    /// use omega_cache::{Engine, Encryptor, noop_engine::NoopEngine};
+    ///
+    /// let key = [7u8; 32]; // load this from your secret store
+    /// let engine = Engine::new(NoopEngine::passthrough()).with_encryption(Encryptor::new(&key));
+    /// ```
+    ///
+    #[cfg(feature = "encryption")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "encryption")))]
+    #[must_use]
+    pub fn with_encryption(mut self, encryptor: Encryptor) -> Engine {
+        self.encryptor = Some(encryptor);
+        self
+    }
+
+    /// Compress values after encoding and decompress them on read.
+    ///
+    /// Each value starts with a header naming its algorithm, so reads never depend
+    /// on this setting and a column can mix algorithms, or values that were not
+    /// worth compressing, freely. Values written before this is enabled have no
+    /// header and fail to decode. Compression runs before encryption, checksums and
+    /// format markers, so with none of those set and no chunking a
+    /// [`Compression::Gzip`] value is stored as a plain gzip stream, ready for
+    /// external readers to serve with `Content-Encoding: gzip`.
+    ///
+    /// ```
+    /// use omega_cache::{ColumnDefinition, Compression, Engine, noop_engine::NoopEngine};
+    ///
+    /// struct Pages;
+    ///
+    /// impl ColumnDefinition for Pages {
+    ///     fn name(&self) -> String {
+    ///         "pages".to_string()
+    ///     }
+    ///
+    ///     fn get_ttl_in_seconds(&self) -> i32 {
+    ///         60
+    ///     }
+    /// }
+    ///
+    /// let engine = Engine::new(NoopEngine::passthrough()).with_compression(Compression::Gzip);
+    /// let page = "<p>hello</p>".repeat(100);
+    /// engine.try_insert(&Pages, &"index", &page).unwrap();
+    ///
+    /// assert_eq!(engine.try_get::<_, String>(&Pages, &"index").unwrap(), Some(page));
+    /// ```
+    ///
+    #[cfg(feature = "gzip")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "gzip")))]
+    #[must_use]
+    pub fn with_compression(mut self, compression: Compression) -> Engine {
+        self.compression = Some(compression);
+        self
+    }
+
+    /// Add a [`ValueMiddleware`] to the stack transforming values after encoding and before decoding.
+    ///
+    /// Writes apply the stack in the order it was added and reads undo it in reverse,
+    /// so `.with_middleware(a).with_middleware(b)` stores `b(a(value))`. The stack runs
+    /// closest to the encoded value: the engine's own compression, encryption, checksums
+    /// and format marker, if set, are applied to what the stack writes. Values written
+    /// with another stack, or before this is called, generally fail to decode.
+    ///
+    /// ```
+    /// use omega_cache::{Checksum, ColumnDefinition, Engine, ValueMiddleware, noop_engine::NoopEngine};
+    ///
+    /// struct Users;
+    ///
+    /// impl ColumnDefinition for Users {
+    ///     fn name(&self) -> String {
+    ///         "users".to_string()
+    ///     }
+    ///
+    ///     fn get_ttl_in_seconds(&self) -> i32 {
+    ///         60
+    ///     }
+    /// }
+    ///
+    /// // e.g. .with_middleware(Compression::Gzip).with_middleware(Encryptor::new(&key))
+    /// // compresses, then encrypts, the only order in which compression pays off
+    /// let engine = Engine::new(NoopEngine::passthrough()).with_middleware(Checksum);
+    /// engine.try_insert(&Users, &"ada", &36u32).unwrap();
+    ///
+    /// assert_eq!(engine.try_get::<_, u32>(&Users, &"ada").unwrap(), Some(36));
+    /// ```
+    ///
+    #[must_use]
+    pub fn with_middleware(mut self, middleware: impl ValueMiddleware + 'static) -> Engine {
+        self.middleware.push(Box::new(middleware));
+        self
+    }
+
+    /// Number of items per storage call in batch operations such as
+    /// [`Engine::try_get_many`] and [`Engine::try_preload`], defaults to [`Engine::DEFAULT_BATCH_SIZE`].
+    ///
+    /// On redis each batch is a single `MGET` or pipeline, so larger batches save
+    /// round trips but block the server for longer. sled is not affected.
+    /// A size of zero is treated as one.
+    #[must_use]
+    pub fn with_batch_size(mut self, batch_size: usize) -> Engine {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    /// Split values larger than `max_value_bytes` across several storage entries,
+    /// for values beyond what the storage handles well, such as redis' 512MB limit.
+    ///
+    /// A large value is written as chunks at `{key}#0`, `{key}#1`, ... followed by
+    /// a small manifest at the key, and reassembled on read. Every stored value
+    /// gains a one byte marker and a chunked value costs one extra read per
+    /// [`Engine::with_batch_size`] chunks, plus the manifest.
+    ///
+    /// Chunks take the same TTL as the value but are separate entries, so
+    /// jitter, eviction or a failed write can lose some of them, and a value with
+    /// a missing or mismatched chunk reads as a miss. The manifest holds a checksum
+    /// of the whole value, so chunks of another value of the same length are not
+    /// joined in its place. Chunks left behind by a
+    /// replaced value stay until they expire, and keys ending in `#<number>`
+    /// collide with chunk keys. Values written without chunking fail to decode
+    /// once this is enabled.
+    ///
+    /// A size of zero is treated as one.
+    #[must_use]
+    pub fn with_chunking(mut self, max_value_bytes: usize) -> Engine {
+        self.chunk_size = Some(max_value_bytes.max(1));
+        self
+    }
+
+    /// Reject keys longer than `max_key_bytes` before they reach storage,
+    /// to catch a whole document or other unbounded input used as a key.
+    ///
+    /// Inserts fail with [`CacheError::Put`] and reads with [`CacheError::Get`],
+    /// both saying `key exceeds max length`. The limit applies to the key as passed in,
+    /// before any [`Engine::with_key_prefix`] prefix or chunk suffix is added,
+    /// so leave room for those under a hard storage limit.
+    ///
+    /// ```
+    /// use omega_cache::{ColumnDefinition, Engine, noop_engine::NoopEngine};
+    ///
+    /// struct Pages;
+    ///
+    /// impl ColumnDefinition for Pages {
+    ///     fn name(&self) -> String {
+    ///         "pages".to_string()
+    ///     }
+    ///
+    ///     fn get_ttl_in_seconds(&self) -> i32 {
+    ///         60
+    ///     }
+    /// }
+    ///
+    /// let engine = Engine::new(NoopEngine::passthrough()).with_max_key_bytes(8);
+    ///
+    /// assert!(engine.try_insert(&Pages, &"/about", &"About us").is_ok());
+    /// assert!(engine.try_insert(&Pages, &"/about/team", &"Our team").is_err());
+    /// assert!(engine.try_get::<_, String>(&Pages, &"/about/team").is_err());
+    /// ```
+    #[must_use]
+    pub fn with_max_key_bytes(mut self, max_key_bytes: usize) -> Engine {
+        self.max_key_bytes = Some(max_key_bytes);
+        self
+    }
+
+    /// Expire values of columns whose [`ColumnDefinition::get_ttl_in_seconds`] is zero or less
+    /// after `ttl` rather than never, so one config value can set a baseline TTL
+    /// for many columns.
+    ///
+    /// The TTL of an insert is taken from, in order of precedence, the override passed to
+    /// [`Engine::try_insert_with_ttl`], the column's own TTL when above zero, then this default.
+    /// It is rounded up to whole seconds, and a zero `ttl` leaves such columns never expiring.
+    /// [`ColumnDefinition::ttl_jitter`] and [`ColumnDefinition::idle_ttl_in_seconds`] apply to the default
+    /// as to a column TTL. Calling this again replaces the default.
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use omega_cache::{ColumnDefinition, Engine};
+    ///
+    /// struct Generic;
+    ///
+    /// impl ColumnDefinition for Generic {
+    ///     fn name(&self) -> String {
+    ///         "generic".to_string()
+    ///     }
+    ///
+    ///     fn get_ttl_in_seconds(&self) -> i32 {
+    ///         0
+    ///     }
+    /// }
+    ///
+    /// let engine = Engine::default().with_default_ttl(Duration::from_secs(300));
+    /// assert_eq!(engine.effective_ttl(&Generic), Some(Duration::from_secs(300)));
+    /// ```
+    #[must_use]
+    pub fn with_default_ttl(mut self, ttl: Duration) -> Engine {
+        let seconds = ttl
+            .as_secs()
+            .saturating_add(u64::from(ttl.subsec_nanos() > 0));
+        let ttl_in_seconds = i32::try_from(seconds).unwrap_or(i32::MAX);
+
+        if ttl_in_seconds == 0 {
+            return self;
+        }
+
+        self.storage = self
+            .storage
+            .map(|inner| -> Box<dyn CacheStorage + Sync + Send> {
+                Box::new(DefaultTtl {
+                    inner,
+                    ttl_in_seconds,
+                })
+            });
+        self.default_ttl = Some(ttl_in_seconds);
+        self
+    }
+
+    /// Prepend `prefix` to every key before it reaches storage,
+    /// such as the application version so a deploy starts with a cold cache without flushing.
+    ///
+    /// ```
+    /// use omega_cache::{Engine, noop_engine::NoopEngine};
+    ///
+    /// let engine = Engine::new(NoopEngine::passthrough())
+    ///     .with_key_prefix(concat!(env!("CARGO_PKG_VERSION"), ":"));
+    /// ```
+    ///
+    /// Values written under another prefix are never read, but are left in storage
+    /// until their TTL expires them, or for good in columns that never expire.
+    /// Chunk and index keys are prefixed too. Dropping a column drops the keys of every prefix,
+    /// and [`Engine::try_list_columns`] and [`Engine::try_report`] also see every prefix.
+    /// Calling this again adds to the end of the earlier prefix.
+    #[must_use]
+    pub fn with_key_prefix(mut self, prefix: impl Into<Vec<u8>>) -> Engine {
+        let prefix = prefix.into();
+
+        self.storage = self
+            .storage
+            .map(|inner| -> Box<dyn CacheStorage + Sync + Send> {
+                Box::new(KeyPrefix { inner, prefix })
+            });
+        self
+    }
+
+    /// Codec used to encode values on write and tried first on read,
+    /// defaults to [`Codec::Standard`]
+    #[must_use]
+    pub fn with_codec(mut self, codec: Codec) -> Engine {
+        self.codec = codec;
+        self
+    }
+
+    /// Bytes decoding a value may allocate for its collections, strings and boxes,
+    /// defaults to [`Engine::DEFAULT_DECODE_LIMIT`].
+    ///
+    /// Collections are allocated up front from the length prefixed to them, so a
+    /// corrupt or malicious value, e.g. one planted in a shared redis, could otherwise
+    /// claim a length of billions and abort the process on allocation. Reads of values
+    /// that would go over the limit fail with [`CacheError::Decode`] instead, before
+    /// allocating. Each collection counts its length times the size of its items in
+    /// memory, e.g. 24 bytes per `String` of a `Vec<String>` plus the bytes of each
+    /// string, so the limit is a bound on memory rather than on stored bytes.
+    /// `usize::MAX` lifts the limit.
+    ///
+    /// ```
+    /// use omega_cache::{CacheError, ColumnDefinition, Engine, noop_engine::NoopEngine};
+    ///
+    /// struct Blobs;
+    ///
+    /// impl ColumnDefinition for Blobs {
+    ///     fn name(&self) -> String {
+    ///         "blobs".to_string()
+    ///     }
+    ///
+    ///     fn get_ttl_in_seconds(&self) -> i32 {
+    ///         60
+    ///     }
+    /// }
+    ///
+    /// let engine = Engine::new(NoopEngine::passthrough()).with_decode_limit(1024);
+    /// engine.try_insert(&Blobs, &"large", &vec![0u8; 4096]).unwrap();
+    ///
+    /// assert!(matches!(
+    ///     engine.try_get::<_, Vec<u8>>(&Blobs, &"large"),
+    ///     Err(CacheError::Decode { .. })
+    /// ));
+    /// ```
+    ///
+    #[must_use]
+    pub fn with_decode_limit(mut self, max_bytes: usize) -> Engine {
+        self.decode_limit = max_bytes;
+        self
+    }
+
+    /// Also try decoding values with `codec` when the primary codec fails,
+    /// so a codec can be switched without flushing the cache.
+    ///
+    /// Legacy codecs are tried in the order they were added. Once any are set
+    /// a value only counts as decoded when the codec consumed every stored byte,
+    /// which stops a value being misread by the wrong codec.
+    ///
+    /// ```
+    /// use omega_cache::{Codec, Engine, noop_engine::NoopEngine};
+    ///
+    /// let engine = Engine::new(NoopEngine::passthrough())
+    ///     .with_codec(Codec::Standard)
+    ///     .with_legacy_codec(Codec::Legacy)
+    ///     .with_legacy_rewrite();
+    /// ```
+    ///
+    #[must_use]
+    pub fn with_legacy_codec(mut self, codec: Codec) -> Engine {
+        self.legacy_codecs.push(codec);
+        self
+    }
+
+    /// Rewrite values read with a legacy codec using the primary codec,
+    /// migrating them on read. The rewrite resets the value's TTL to the column TTL.
+    #[must_use]
+    pub fn with_legacy_rewrite(mut self) -> Engine {
+        self.rewrite_legacy = true;
+        self
+    }
+
+    /// Fail reads and writes of columns not registered with [`Engine::register_column`]
+    /// with [`CacheError::Engine`] rather than using them, catching typos in column names.
+    ///
+    /// Without it any column can be used, and on sled reading a column that was never
+    /// written creates an empty tree for it. Drops and dry runs still accept any column
+    /// so stray columns can be cleaned up.
+    ///
+    /// ```
+    /// use omega_cache::{ColumnDefinition, Engine, noop_engine::NoopEngine};
+    ///
+    /// struct Sessions;
+    ///
+    /// impl ColumnDefinition for Sessions {
+    ///     fn name(&self) -> String {
+    ///         "sessions".to_string()
+    ///     }
+    ///
+    ///     fn get_ttl_in_seconds(&self) -> i32 {
+    ///         60
+    ///     }
+    /// }
+    ///
+    /// let mut engine = Engine::new(NoopEngine::passthrough()).with_strict_columns();
+    /// assert!(engine.try_get::<_, u8>(&Sessions, &"key").is_err());
+    ///
+    /// engine.register_column(&Sessions).unwrap();
+    /// assert!(engine.try_get::<_, u8>(&Sessions, &"key").is_ok());
+    /// ```
+    #[must_use]
+    pub fn with_strict_columns(mut self) -> Engine {
+        self.strict_columns = true;
+        self
+    }
+
+    /// Count hits, misses, errors and latencies of every column in the engine,
+    /// read with [`Engine::metrics_snapshot`]. See [`metrics`] for an example.
+    ///
+    /// Operations are counted as they reach storage, so wrap the engine with this last
+    /// to count keys as the caller passed them. Calling this again starts new counts.
+    #[cfg(feature = "metrics-registry")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "metrics-registry")))]
+    #[must_use]
+    pub fn with_metrics(mut self) -> Engine {
+        let registry = std::sync::Arc::new(metrics::MetricsRegistry::default());
+
+        self.storage = self
+            .storage
+            .map(|inner| -> Box<dyn CacheStorage + Sync + Send> {
+                Box::new(metrics::Metered {
+                    inner,
+                    registry: registry.clone(),
+                })
+            });
+        self.metrics = Some(registry);
+        self
+    }
+
+    /// Counts of every column used since [`Engine::with_metrics`],
+    /// empty without it or when the engine is [`Engine::disabled`]
+    #[cfg(feature = "metrics-registry")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "metrics-registry")))]
+    #[must_use]
+    pub fn metrics_snapshot(&self) -> metrics::CacheMetrics {
+        self.metrics
+            .as_ref()
+            .map(|registry| registry.snapshot())
+            .unwrap_or_default()
+    }
+
+    /// TTL a fresh insert into the column is stored with, [`None`] when it never expires.
+    ///
+    /// TTLs of zero or less never expire, unless the engine has a [`Engine::with_default_ttl`]. With a [`ColumnDefinition::idle_ttl_in_seconds`]
+    /// this is the time the value lives unread, the smaller of the two TTLs. With a [`ColumnDefinition::ttl_jitter`] every insert
+    /// draws its own TTL, so this is one such draw and changes from call to call, within
+    /// the jitter of the column TTL and never under a second.
+    /// Storages that do not expire values, such as the passthrough [`NoopEngine`],
+    /// keep values for good whatever this returns.
+    ///
+    /// ```
+    /// use omega_cache::{ColumnDefinition, Engine};
+    ///
+    /// struct Forever;
+    ///
+    /// impl ColumnDefinition for Forever {
+    ///     fn name(&self) -> String {
+    ///         "forever".to_string()
+    ///     }
+    ///
+    ///     fn get_ttl_in_seconds(&self) -> i32 {
+    ///         0
+    ///     }
+    /// }
+    ///
+    /// assert_eq!(Engine::default().effective_ttl(&Forever), None);
+    /// ```
+    #[must_use]
+    pub fn effective_ttl(&self, c: &dyn ColumnDefinition) -> Option<Duration> {
+        let ttl = ttl::expiry(c, self.column_ttl(c));
+        let idle = c.idle_ttl_in_seconds().map(u64::from);
+
+        match (ttl, idle) {
+            (Some(ttl), Some(idle)) => Some(ttl.min(idle)),
+            (ttl, idle) => ttl.or(idle),
+        }
+        .map(Duration::from_secs)
+    }
+
+    /// How the storage removes values once their TTL is up, for code relying on values
+    /// actually disappearing to check at startup that the storage does that.
+    ///
+    /// - Redis: [`TtlEnforcement::ServerSide`], the server expires keys itself.
+    /// - sled: [`TtlEnforcement::LazyOnRead`], expired values read as missing and are
+    ///   removed when next read, otherwise they stay on disk.
+    /// - [`NoopEngine`], passthrough or not, and [`Engine::disabled`]:
+    ///   [`TtlEnforcement::None`].
+    ///
+    /// Storages wrapping another report the storage they wrap,
+    /// and [`dual_write::DualWriteEngine`] reports the storage it reads from.
+    ///
+    /// ```
+    /// use omega_cache::{Engine, TtlEnforcement, noop_engine::NoopEngine};
+    ///
+    /// let engine = Engine::new(NoopEngine::passthrough());
+    ///
+    /// assert_eq!(engine.ttl_enforcement(), TtlEnforcement::None);
+    /// ```
+    #[must_use]
+    pub fn ttl_enforcement(&self) -> TtlEnforcement {
+        self.storage().ttl_enforcement()
+    }
+
+    /// Settings the storage runs with, such as sled's path, page cache and compression
+    /// or redis' address, database, pool size and key prefix, for support tools to dump
+    /// when a cache behaves oddly. Passwords are never listed.
+    ///
+    /// The engine's own key prefix and default TTL are listed after the storage's
+    /// settings, its other options are in its [`Debug`] output.
+    ///
+    /// ```
+    /// use omega_cache::{Engine, memory_engine::MemoryEngine};
+    ///
+    /// let engine = Engine::new(MemoryEngine::builder().shards(8).build());
+    ///
+    /// assert_eq!(engine.describe_config().to_string(), "memory (shards=8)");
+    /// ```
+    #[must_use]
+    pub fn describe_config(&self) -> BackendConfig {
+        self.storage().describe_config()
+    }
+
+    /// Check a column definition before it is used, surfacing misconfiguration
+    /// that would otherwise show up as collisions or command errors at runtime.
+    ///
+    /// The name must not be empty nor end in `#index` or `#deadline`, which are reserved for
+    /// [`ColumnDefinition::index`] columns and the deadlines of
+    /// [`ColumnDefinition::idle_ttl_in_seconds`], and any [`ColumnDefinition::ttl_jitter`]
+    /// must be a non-negative number. Every TTL is in range since zero or less never expires.
+    /// The storage adds its own checks, sled reserves the names of its bookkeeping trees.
+    /// Columns are validated when registered with [`Engine::register_column`].
+    ///
+    /// ```
+    /// use omega_cache::{ColumnDefinition, Engine};
+    ///
+    /// struct Unnamed;
+    ///
+    /// impl ColumnDefinition for Unnamed {
+    ///     fn name(&self) -> String {
+    ///         String::new()
+    ///     }
+    ///
+    ///     fn get_ttl_in_seconds(&self) -> i32 {
+    ///         60
+    ///     }
+    /// }
+    ///
+    /// assert!(Engine::default().validate_column(&Unnamed).is_err());
+    /// ```
+    ///
+    /// # Errors
+    /// Returns [`CacheError::Engine`] describing the first problem found
+    pub fn validate_column(&self, c: &dyn ColumnDefinition) -> Result<(), CacheError> {
+        let name = c.name();
+
+        if name.is_empty() {
+            return Err(CacheError::Engine(
+                "column name must not be empty".to_string(),
+            ));
+        }
+
+        if name.ends_with("#index") {
+            return Err(CacheError::Engine(format!(
+                "column `{name}` ends in `#index`, which is reserved for secondary indexes"
+            )));
+        }
+
+        if name.ends_with("#deadline") {
+            return Err(CacheError::Engine(format!(
+                "column `{name}` ends in `#deadline`, which is reserved for idle TTL deadlines"
+            )));
+        }
+
+        if let Some(jitter) = c.ttl_jitter()
+            && (jitter.is_nan() || jitter < 0.0)
+        {
+            return Err(CacheError::Engine(format!(
+                "column `{name}` has a TTL jitter of {jitter}, it must be a non-negative number"
+            )));
+        }
+
+        if c.idle_ttl_in_seconds() == Some(0) {
+            return Err(CacheError::Engine(format!(
+                "column `{name}` has an idle TTL of zero, leave it unset to disable idle expiry"
+            )));
+        }
+
+        self.storage().validate_column(c)
+    }
+
+    /// Notify `listener` of values leaving the cache, for logging or keeping other
+    /// caches coherent, replacing any earlier listener.
+    ///
+    /// sled reports values reaped on read after they expire, values replaced by a write,
+    /// and values evicted by [`ColumnDefinition::max_entries`] or
+    /// `SledEngineBuilder::max_total_bytes`. The passthrough [`NoopEngine`] reports
+    /// replaced values and [`ColumnDefinition::max_entries`] evictions. Neither reports
+    /// the values of a dropped column, the value a batch insert replaces, nor expired
+    /// values never read again. Redis expires values server side, so Redis storage fails
+    /// this unless the `redis-notifications` feature is enabled, which subscribes to
+    /// the server's keyspace notifications to report expired values only.
+    /// Storages combining two others, such as [`dual_write::DualWriteEngine`],
+    /// only report the storage they read from.
+    ///
+    /// ```
+    /// use std::sync::{Arc, Mutex};
+    ///
+    /// use omega_cache::{
+    ///     ColumnDefinition, Engine, EvictReason, EvictionListener, noop_engine::NoopEngine,
+    /// };
+    ///
+    /// struct Recent;
+    ///
+    /// impl ColumnDefinition for Recent {
+    ///     fn name(&self) -> String {
+    ///         "recent".to_string()
+    ///     }
+    ///
+    ///     fn get_ttl_in_seconds(&self) -> i32 {
+    ///         60
+    ///     }
+    ///
+    ///     fn max_entries(&self) -> Option<usize> {
+    ///         Some(1)
+    ///     }
+    /// }
+    ///
+    /// #[derive(Default)]
+    /// struct Evicted(Mutex<Vec<(Vec<u8>, EvictReason)>>);
+    ///
+    /// impl EvictionListener for Evicted {
+    ///     fn on_evict(&self, _column: &str, key: &[u8], reason: EvictReason) {
+    ///         self.0.lock().unwrap().push((key.to_vec(), reason));
+    ///     }
+    /// }
+    ///
+    /// let evicted = Arc::new(Evicted::default());
+    /// let mut engine = Engine::new(NoopEngine::passthrough());
+    /// engine.set_eviction_listener(evicted.clone()).unwrap();
+    ///
+    /// engine.try_insert(&Recent, &"first", &1u8).unwrap();
+    /// engine.try_insert(&Recent, &"second", &2u8).unwrap();
+    ///
+    /// assert_eq!(
+    ///     *evicted.0.lock().unwrap(),
+    ///     vec![(b"first".to_vec(), EvictReason::CapacityEvicted)]
+    /// );
+    /// ```
+    ///
+    /// # Errors
+    /// Returns [`CacheError::Engine`] if the storage cannot observe evictions
+    pub fn set_eviction_listener(
+        &mut self,
+        listener: Arc<dyn EvictionListener>,
+    ) -> Result<(), CacheError> {
+        match &mut self.storage {
+            Some(storage) => storage.set_eviction_listener(listener),
+            None => Ok(()),
+        }
+    }
+
+    /// Call `f` with the column, kind and duration of every storage operation
+    /// taking at least `threshold`, to catch tail latency such as a slow redis or a
+    /// contended sled tree without printing the time of every operation.
+    ///
+    /// This reuses the timing printed by debug builds with the `timing` feature, so
+    /// it covers the same operations: single and batch inserts and reads, whole column
+    /// reads, TTL refreshes and transaction commits. The time is taken around the
+    /// storage's own work, after any wait for a pooled redis connection, and the
+    /// clock is only read once a listener is set or timings are printed.
+    ///
+    /// `f` runs synchronously on the thread of the operation once it completes, before
+    /// its result is returned, so it adds to the latency it reports on and should be
+    /// quick, e.g. bumping a metric or sending to a channel. It must not call back into
+    /// the same engine. Only sled and redis time their operations.
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use omega_cache::{CacheError, Engine};
+    ///
+    /// fn log_slow_operations(engine: &mut Engine) -> Result<(), CacheError> {
+    ///     engine.on_slow_operation(Duration::from_millis(50), |op| {
+    ///         eprintln!("slow {} on {} took {:?}", op.kind, op.column, op.elapsed);
+    ///     })
+    /// }
+    /// ```
+    ///
+    /// # Errors
+    /// Returns [`CacheError::Engine`] if the storage does not time its operations
+    pub fn on_slow_operation(
+        &mut self,
+        threshold: Duration,
+        f: impl Fn(&SlowOperation) + Send + Sync + 'static,
+    ) -> Result<(), CacheError> {
+        match &mut self.storage {
+            Some(storage) => storage.set_slow_operation_listener(threshold, Arc::new(f)),
+            None => Ok(()),
+        }
+    }
+
+    /// Record a column the application uses, catching two column definitions
+    /// that share a name, and so share data, but disagree on the TTL.
+    ///
+    /// Registration is optional, unregistered columns can still be used.
+    ///
+    /// ```
+    /// use omega_cache::{Engine, ColumnDefinition};
+    ///
+    /// struct Sessions;
+    ///
+    /// impl ColumnDefinition for Sessions {
+    ///     fn name(&self) -> String {
+    ///         "sessions".to_string()
+    ///     }
+    ///
+    ///     fn get_ttl_in_seconds(&self) -> i32 {
+    ///         3600
+    ///     }
+    /// }
+    ///
+    /// struct Tokens;
+    ///
+    /// impl ColumnDefinition for Tokens {
+    ///     fn name(&self) -> String {
+    ///         "sessions".to_string()
+    ///     }
+    ///
+    ///     fn get_ttl_in_seconds(&self) -> i32 {
+    ///         60
+    ///     }
+    /// }
+    ///
+    /// let mut engine = Engine::default();
+    ///
+    /// assert!(engine.register_column(&Sessions).is_ok());
+    /// assert!(engine.register_column(&Tokens).is_err());
+    /// ```
+    ///
+    /// # Errors
+    /// Returns [`CacheError::Engine`] if the column fails [`Engine::validate_column`]
+    /// or a column with the same name but a different TTL is already registered
+    pub fn register_column(&mut self, c: &dyn ColumnDefinition) -> Result<(), CacheError> {
+        self.validate_column(c)?;
+
+        let name = c.name();
+        let ttl_in_seconds = c.get_ttl_in_seconds();
+
+        match self.columns.get(&name) {
+            Some(registered) if registered.ttl_in_seconds != ttl_in_seconds => {
+                Err(CacheError::Engine(format!(
+                    "column `{name}` is already registered with a TTL of {}s, not {ttl_in_seconds}s",
+                    registered.ttl_in_seconds
+                )))
+            }
+            Some(_) => Ok(()),
+            None => {
+                self.columns.insert(
+                    name,
+                    RegisteredColumn {
+                        ttl_in_seconds,
+                        idle_ttl_in_seconds: c.idle_ttl_in_seconds(),
+                        storage_layout: c.storage_layout(),
+                        description: c.description().map(str::to_string),
+                    },
+                );
+                Ok(())
+            }
+        }
+    }
+
+    /// # Errors
+    /// Returns [`CacheError::Put`] if insert fails or the column is read only
+    /// Returns [`CacheError::Encode`] if type V cannot be encoded to [`Vec<u8>`]
+    pub fn try_insert<'a, K: AsRef<[u8]> + 'a, V: Encode + 'a>(
+        &'a self,
+        c: &dyn ColumnDefinition,
+        key: &'a K,
+        value: &'a V,
+    ) -> Result<(), CacheError> {
+        self.try_insert_bytes_key(c, key.as_ref(), value)
+    }
+
+    /// Insert a value at a key already in bytes, as [`Engine::try_insert`] does
+    /// without its generic key parameter.
+    ///
+    /// The low ceremony option for hot code holding a `&[u8]`, which leaves nothing
+    /// for the key type to infer. Both methods store the same bytes, so either one
+    /// reads what the other writes.
+    ///
+    /// ```
+    /// use omega_cache::{ColumnDefinition, Engine, noop_engine::NoopEngine};
+    ///
+    /// struct Blobs;
+    ///
+    /// impl ColumnDefinition for Blobs {
+    ///     fn name(&self) -> String {
+    ///         "blobs".to_string()
+    ///     }
+    ///
+    ///     fn get_ttl_in_seconds(&self) -> i32 {
+    ///         60
+    ///     }
+    /// }
+    ///
+    /// let engine = Engine::new(NoopEngine::passthrough());
+    /// let key: &[u8] = b"blob";
+    ///
+    /// engine.try_insert_bytes_key(&Blobs, key, &7u32).unwrap();
+    ///
+    /// assert_eq!(engine.try_get_bytes_key::<u32>(&Blobs, key).unwrap(), Some(7));
+    /// assert_eq!(engine.try_get::<_, u32>(&Blobs, &"blob").unwrap(), Some(7));
+    /// ```
+    ///
+    /// # Errors
+    /// Returns [`CacheError::Put`] if insert fails or the column is read only
+    /// Returns [`CacheError::Encode`] if type V cannot be encoded to [`Vec<u8>`]
+    pub fn try_insert_bytes_key<V: Encode>(
+        &self,
+        c: &dyn ColumnDefinition,
+        key: &[u8],
+        value: &V,
+    ) -> Result<(), CacheError> {
+        check_writable(c)?;
+        self.check_column(c)?;
+        if self.storage().discards_writes() {
+            return Ok(());
+        }
+
+        let value_bytes = self.store_value(c, key, value, None)?;
+
+        self.storage().try_insert(c, key, &value_bytes)
+    }
+
+    /// Handle inserting into the column without redoing the column checks
+    /// and storage setup on every insert, see [`ColumnHandle`].
+    ///
+    /// On sled the handle holds the column's opened tree, on Redis its escaped key prefix.
+    /// Wrapping storages, such as [`crate::dual_write::DualWriteEngine`], insert as usual.
+    ///
+    /// # Errors
+    /// Returns [`CacheError::Put`] if the column is read only or cannot be opened
+    /// Returns [`CacheError::Engine`] if the column is not registered with strict columns
+    pub fn column_handle<'a>(
+        &'a self,
+        c: &'a dyn ColumnDefinition,
+    ) -> Result<ColumnHandle<'a>, CacheError> {
+        check_writable(c)?;
+        self.check_column(c)?;
+
+        Ok(ColumnHandle {
+            engine: self,
+            column: c,
+            writer: self.storage().try_column_writer(c)?,
+        })
+    }
+
+    /// Start a transaction of conditional writes to the column, see [`Transaction`]
+    ///
+    /// sled commits in a transaction on the column's tree. Redis `WATCH`es the keys
+    /// checked, reads them, and applies the writes with `MULTI`/`EXEC`, starting over
+    /// if a watched key changed meanwhile. The passthrough [`NoopEngine`] commits
+    /// under its lock, and the default one checks every condition against a missing
+    /// value. Storages combining two others, such as [`dual_write::DualWriteEngine`],
+    /// commit on the storage they read from and then write to the other.
+    /// Values under the column's old [`ColumnDefinition::aliases`] are not checked.
+    ///
+    /// A [`ColumnIndex`] on the column gains entries for values inserted
+    /// whether or not the transaction commits, which the next
+    /// [`Engine::try_get_by_index`] prunes if they are stale.
+    #[must_use]
+    pub fn transaction<'a>(&'a self, c: &'a dyn ColumnDefinition) -> Transaction<'a> {
+        Transaction::new(self, c)
+    }
+
+    /// Insert a value and report whether it replaced a live value,
+    /// without reading or decoding the old value.
+    ///
+    /// # Errors
+    /// Returns [`CacheError::Put`] if insert fails or the column is read only
+    /// Returns [`CacheError::Encode`] if type V cannot be encoded to [`Vec<u8>`]
+    pub fn try_insert_reporting<'a, K: AsRef<[u8]> + 'a, V: Encode + 'a>(
+        &'a self,
+        c: &dyn ColumnDefinition,
+        key: &'a K,
+        value: &'a V,
+    ) -> Result<InsertOutcome, CacheError> {
+        check_writable(c)?;
+        self.check_column(c)?;
+        if self.storage().discards_writes() {
+            return Ok(InsertOutcome::Created);
+        }
+
+        let value_bytes = self.store_value(c, key.as_ref(), value, None)?;
+
+        self.storage()
+            .try_insert_reporting(c, key.as_ref(), &value_bytes)
+    }
+
+    /// Insert a value unless the live value at key already equals it,
+    /// returning whether it was written.
+    ///
+    /// Cuts the writes, and on redis the replication traffic, of values refreshed on
+    /// a schedule that rarely change. The check costs a read and decode of the current
+    /// value before every insert, so it only pays off when most writes are skipped.
+    /// A write restarts the column TTL as [`Engine::try_insert`] does, while a skipped
+    /// write leaves the TTL of the stored value running, so a value that never changes
+    /// still expires. A current value that fails to decode as V counts as changed.
+    ///
+    /// The read and the write are separate operations, so a write by another process
+    /// landing between them may be overwritten, or kept when it should have been replaced.
+    /// Use [`Engine::transaction`] where that matters. Storages discarding writes
+    /// never write and return `false`.
+    ///
+    /// # Errors
+    /// Returns [`CacheError::Get`] if reading the current value fails
+    /// Returns [`CacheError::Put`] if insert fails or the column is read only
+    /// Returns [`CacheError::Encode`] if type V cannot be encoded to [`Vec<u8>`]
+    pub fn try_insert_if_changed<K: AsRef<[u8]>, V: Encode + PartialEq + Decode<()>>(
+        &self,
+        c: &dyn ColumnDefinition,
+        key: &K,
+        value: &V,
+    ) -> Result<bool, CacheError> {
+        check_writable(c)?;
+        self.check_column(c)?;
+        if self.storage().discards_writes() {
+            return Ok(false);
+        }
+
+        let key_bytes = key.as_ref();
+        match self.try_get_bytes_key::<V>(c, key_bytes) {
+            Ok(Some(current)) if current == *value => return Ok(false),
+            Ok(_) | Err(CacheError::Decode { .. }) => {}
+            Err(e) => return Err(e),
+        }
+
+        self.try_insert_bytes_key(c, key_bytes, value)?;
+
+        Ok(true)
+    }
+
+    /// Insert a value, restarting the column TTL, and return the live value it replaced,
+    /// for leases and similar hand overs.
+    ///
+    /// On redis this is a single `SET ... GET` and on sled a single tree insert,
+    /// so no other write to the key can land between reading the old value
+    /// and writing the new one. Storages without an atomic swap, such as
+    /// custom [`CacheStorage`] implementations, fall back to a get then insert.
+    /// With [`Engine::with_chunking`] a replaced value that was split into chunks
+    /// is returned as [`None`], as its chunks are overwritten by the new value.
+    ///
+    /// # Errors
+    /// Returns [`CacheError::Put`] if insert fails or the column is read only.
+    /// Returns [`CacheError::Encode`] if type V cannot be encoded to [`Vec<u8>`].
+    /// Returns [`CacheError::Decode`] if the old value cannot be decoded to type V,
+    /// the new value is inserted regardless
+    pub fn try_swap<K: AsRef<[u8]>, V: Decode<()> + Encode>(
+        &self,
+        c: &dyn ColumnDefinition,
+        key: &K,
+        value: &V,
+    ) -> Result<Option<V>, CacheError> {
+        check_writable(c)?;
+        self.check_column(c)?;
+        if self.storage().discards_writes() {
+            return Ok(None);
+        }
+
+        let key_bytes = key.as_ref();
+        let value_bytes = self.store_value(c, key_bytes, value, None)?;
+
+        let Some(old) = self.storage().try_swap(c, key_bytes, &value_bytes)? else {
+            return Ok(None);
+        };
+
+        if self.chunk_size.is_some()
+            && matches!(
+                chunking::parse(&old),
+                Some(chunking::Stored::Manifest { .. })
+            )
+        {
+            return Ok(None);
+        }
+
+        match self.load_value(c, key_bytes, &old)? {
+            Some(bytes) => self
+                .decode_value(c, key_bytes, &bytes, false)
+                .map(|v| Some(v.0)),
+            None => Ok(None),
+        }
+    }
+
+    /// Remove the live value at key and return it, so that of several consumers
+    /// taking the same key, such as a one shot token, only one gets the value.
+    ///
+    /// On redis this is a single `GETDEL`, needing redis 6.2 or later, and on sled
+    /// a single tree remove, so no other read or write sees the value once it is taken.
+    /// [`NoopEngine::passthrough`] takes under its lock while other [`NoopEngine`]s
+    /// hold nothing to take and return [`None`]. Custom [`CacheStorage`] implementations
+    /// without a take return an error rather than fall back to a racy get then remove.
+    /// With [`Engine::with_chunking`] the chunks of a taken value are left to expire.
+    ///
+    /// # Errors
+    /// Returns [`CacheError::Put`] if the remove fails or the column is read only
+    /// Returns [`CacheError::Decode`] if the value cannot be decoded to type V,
+    /// the value is removed regardless
+    /// Returns [`CacheError::Engine`] if the storage cannot take values
+    pub fn try_take<K: AsRef<[u8]>, V: Decode<()>>(
+        &self,
+        c: &dyn ColumnDefinition,
+        key: &K,
+    ) -> Result<Option<V>, CacheError> {
+        check_writable(c)?;
+        self.check_column(c)?;
+
+        let key_bytes = key.as_ref();
+        let Some(taken) = self.storage().try_take(c, key_bytes)? else {
+            return Ok(None);
+        };
+
+        match self.load_value(c, key_bytes, &taken)? {
+            Some(bytes) => self
+                .decode_value(c, key_bytes, &bytes, false)
+                .map(|v| Some(v.0)),
+            None => Ok(None),
+        }
+    }
+
+    /// Insert a value with an optional TTL override,
+    /// such as a shorter TTL for a cached negative result.
+    ///
+    /// [`None`] uses the column TTL, `Some(ttl)` replaces it for this value only.
+    /// As with column TTLs an override of zero or less means the value
+    /// never expires. Any [`ColumnDefinition::ttl_jitter`] still applies.
+    ///
+    /// # Errors
+    /// Returns [`CacheError::Put`] if insert fails or the column is read only
+    /// Returns [`CacheError::Encode`] if type V cannot be encoded to [`Vec<u8>`]
+    pub fn try_insert_with_ttl<'a, K: AsRef<[u8]> + 'a, V: Encode + 'a>(
+        &'a self,
+        c: &dyn ColumnDefinition,
+        key: &'a K,
+        value: &'a V,
+        ttl_in_seconds: Option<i32>,
+    ) -> Result<(), CacheError> {
+        check_writable(c)?;
+        self.check_column(c)?;
+        if self.storage().discards_writes() {
+            return Ok(());
+        }
+
+        let ttl_in_seconds = ttl_in_seconds.unwrap_or_else(|| self.column_ttl(c));
+        let value_bytes = self.store_value(c, key.as_ref(), value, Some(ttl_in_seconds))?;
+
+        self.storage()
+            .try_insert_with_ttl(c, key.as_ref(), &value_bytes, ttl_in_seconds)
+    }
+
+    /// Move the value at `from` to `to` in one step, replacing any value at `to`,
+    /// and return whether `from` held a live value. Promotes a value staged under a
+    /// temporary key without readers ever seeing it half written.
+    ///
+    /// The value keeps its TTL: on sled its original write time and TTL move with it,
+    /// and redis `RENAME` carries the remaining expiry over. The passthrough
+    /// [`NoopEngine`] never expires values, and the default one always returns `false`.
+    ///
+    /// Values split by [`Engine::with_chunking`] have their chunks moved first, so only
+    /// the final step is atomic and reads of either key in between miss.
+    /// A [`ColumnIndex`] on the column gains an entry for `to`, and the stale entry for `from`
+    /// is pruned by the next [`Engine::try_get_by_index`].
+    ///
+    /// ```
+    /// use omega_cache::{CacheError, ColumnDefinition, Engine, noop_engine::NoopEngine};
+    ///
+    /// struct Reports;
+    ///
+    /// impl ColumnDefinition for Reports {
+    ///     fn name(&self) -> String {
+    ///         "reports".to_string()
+    ///     }
+    ///
+    ///     fn get_ttl_in_seconds(&self) -> i32 {
+    ///         60
+    ///     }
+    /// }
+    ///
+    /// fn main() -> Result<(), CacheError> {
+    ///     let cache = Engine::new(NoopEngine::passthrough());
+    ///
+    ///     cache.try_insert(&Reports, &"daily.tmp", &"contents")?;
+    ///     assert!(cache.try_rename(&Reports, &"daily.tmp", &"daily")?);
+    ///
+    ///     assert_eq!(cache.try_get::<_, String>(&Reports, &"daily")?.as_deref(), Some("contents"));
+    ///     assert!(!cache.try_rename(&Reports, &"daily.tmp", &"daily")?);
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    ///
+    /// # Errors
+    /// Returns [`CacheError::Put`] if the move fails or the column is read only
+    /// Returns [`CacheError::Engine`] if the storage cannot rename keys, or the engine
+    /// encrypts values as they are bound to their key, see [`Engine::with_encryption`]
+    pub fn try_rename<K: AsRef<[u8]>>(
+        &self,
+        c: &dyn ColumnDefinition,
+        from: &K,
+        to: &K,
+    ) -> Result<bool, CacheError> {
+        check_writable(c)?;
+        self.check_column(c)?;
+
+        #[cfg(feature = "encryption")]
+        if self.encryptor.is_some() {
+            return Err(CacheError::Engine(
+                "renaming encrypted values is not supported".to_string(),
+            ));
+        }
+        let (from, to) = (from.as_ref(), to.as_ref());
+
+        if self.chunk_size.is_some() {
+            let stored = self.storage().try_get(c, from)?;
+
+            if let Some(chunking::Stored::Manifest { chunks, .. }) =
+                stored.as_deref().and_then(chunking::parse)
+            {
+                for index in 0..chunks {
+                    self.storage().try_rename(
+                        c,
+                        &chunking::chunk_key(from, index),
+                        &chunking::chunk_key(to, index),
+                    )?;
+                }
+            }
+        }
+
+        if !self.storage().try_rename(c, from, to)? {
+            return Ok(false);
+        }
+
+        if let Some(index) = c.index()
+            && let Some(payload) = self.try_get_payload(c, to)?
+        {
+            self.add_to_index(c, index, to, payload.as_bytes())?;
+        }
+
+        Ok(true)
+    }
+
+    /// Restart the TTL of the live values at keys, as if they were just inserted,
+    /// and return how many there were, such as to slide the expiry of every active
+    /// session at once.
+    ///
+    /// [`None`] uses the column TTL, `Some(ttl)` replaces it, and as with
+    /// [`Engine::try_insert_with_ttl`] a TTL of zero or less means the values never
+    /// expire. Any [`ColumnDefinition::ttl_jitter`] still applies, per key.
+    ///
+    /// The batch is not atomic as a whole, each key is refreshed on its own so
+    /// a value written or expiring meanwhile may be refreshed or missed:
+    /// sled rewrites the write times in one transaction, Redis pipelines `EXPIRE`
+    /// in one round trip without `MULTI`, and the passthrough [`NoopEngine`] never
+    /// expires values so only counts them. The chunks of values split by
+    /// [`Engine::with_chunking`] are refreshed along with them.
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use omega_cache::{CacheError, ColumnDefinition, Engine, noop_engine::NoopEngine};
+    ///
+    /// struct Sessions;
+    ///
+    /// impl ColumnDefinition for Sessions {
+    ///     fn name(&self) -> String {
+    ///         "sessions".to_string()
+    ///     }
+    ///
+    ///     fn get_ttl_in_seconds(&self) -> i32 {
+    ///         900
+    ///     }
+    /// }
+    ///
+    /// fn main() -> Result<(), CacheError> {
+    ///     let cache = Engine::new(NoopEngine::passthrough());
+    ///     cache.try_insert(&Sessions, &"ada", &1u64)?;
+    ///     cache.try_insert(&Sessions, &"grace", &2u64)?;
+    ///
+    ///     let active = ["ada", "grace", "linus"];
+    ///     assert_eq!(cache.try_touch_many(&Sessions, &active, None)?, 2);
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    ///
+    /// # Errors
+    /// Returns [`CacheError::Put`] if refreshing fails or the column is read only
+    /// Returns [`CacheError::Engine`] if the storage cannot refresh TTLs
+    pub fn try_touch_many<K: AsRef<[u8]>>(
+        &self,
+        c: &dyn ColumnDefinition,
+        keys: &[K],
+        new_ttl: Option<i32>,
+    ) -> Result<usize, CacheError> {
+        check_writable(c)?;
+        self.check_column(c)?;
+        if self.storage().discards_writes() {
+            return Ok(0);
+        }
+
+        let keys = keys.iter().map(AsRef::as_ref).collect::<Vec<_>>();
+        for key in &keys {
+            self.check_key(key, CacheError::Put)?;
+        }
+        let ttl_in_seconds = new_ttl.unwrap_or_else(|| self.column_ttl(c));
+
+        if self.chunk_size.is_some() {
+            let mut chunk_keys = Vec::new();
+
+            for (key, stored) in keys.iter().zip(self.storage().try_get_many(c, &keys)?) {
+                if let Some(chunking::Stored::Manifest { chunks, .. }) =
+                    stored.as_deref().and_then(chunking::parse)
+                {
+                    chunk_keys.extend((0..chunks).map(|index| chunking::chunk_key(key, index)));
+                }
+            }
+
+            if !chunk_keys.is_empty() {
+                self.storage().try_touch_many(
+                    c,
+                    &chunk_keys.iter().map(Vec::as_slice).collect::<Vec<_>>(),
+                    ttl_in_seconds,
+                )?;
+            }
+        }
+
+        self.storage().try_touch_many(c, &keys, ttl_in_seconds)
+    }
+
+    /// Insert a value with a type tag stored ahead of it,
+    /// for columns holding values of several types.
+    ///
+    /// Read it back with [`Engine::try_get_tagged`], which returns the tag so the
+    /// caller can pick the type to decode. The value is stored as the tuple `(tag, value)`,
+    /// so a [`ColumnIndex`] on the column sees the tag at the start of the encoded bytes.
+    ///
+    /// # Errors
+    /// Returns [`CacheError::Put`] if insert fails or the column is read only
+    /// Returns [`CacheError::Encode`] if type V cannot be encoded to [`Vec<u8>`]
+    pub fn try_insert_tagged<K: AsRef<[u8]>, V: Encode>(
+        &self,
+        c: &dyn ColumnDefinition,
+        key: &K,
+        tag: u16,
+        value: &V,
+    ) -> Result<(), CacheError> {
+        self.try_insert(c, key, &(tag, value))
+    }
+
+    /// Insert a value under a key of any [`Encode`] type, such as `(u64, String)`,
+    /// read it back with [`Engine::try_get_keyed`].
+    ///
+    /// The key is encoded with bincode's standard configuration whatever the
+    /// [`Engine::with_codec`] setting, so changing codecs keeps keys addressable,
+    /// and those bytes are the key storage holds. Keys read back from storage,
+    /// such as those of [`CacheStorage::try_entries`], are these encoded bytes:
+    /// decode them with [`bincode::decode_from_slice`] and the standard configuration.
+    ///
+    /// # Errors
+    /// Returns [`CacheError::Put`] if insert fails or the column is read only
+    /// Returns [`CacheError::Encode`] if the key or value cannot be encoded to [`Vec<u8>`]
+    pub fn try_insert_keyed<K: Encode, V: Encode>(
+        &self,
+        c: &dyn ColumnDefinition,
+        key: &K,
+        value: &V,
+    ) -> Result<(), CacheError> {
+        self.try_insert(c, &encode_key(key)?, value)
+    }
+
+    /// Get the value at key, decoded with bincode's unit `()` decode context.
+    ///
+    /// `#[derive(Decode)]` and bincode's own impls work with any context so satisfy
+    /// the `Decode<()>` bound, use [`Engine::try_get_with_context`] for types
+    /// that only decode with a specific context.
+    ///
+    /// # Errors
+    /// Returns [`CacheError::Get`] if get fails.
+    /// Returns [`CacheError::Decode`] if get result cannot be decoded to type V from a [`Vec<u8>`]
+    /// or checksums are enabled and the stored checksum does not match
+    pub fn try_get<'a, K: AsRef<[u8]> + 'a, V: Decode<()> + Encode + 'a>(
+        &'a self,
+        c: &dyn ColumnDefinition,
+        key: &'a K,
+    ) -> Result<Option<V>, CacheError> {
+        self.try_get_bytes_key(c, key.as_ref())
+    }
+
+    /// Get the value at a key already in bytes, as [`Engine::try_get`] does
+    /// without its generic key parameter, see [`Engine::try_insert_bytes_key`]
+    ///
+    /// # Errors
+    /// Returns [`CacheError::Get`] if get fails.
+    /// Returns [`CacheError::Decode`] if get result cannot be decoded to type V from a [`Vec<u8>`]
+    /// or checksums are enabled and the stored checksum does not match
+    pub fn try_get_bytes_key<V: Decode<()> + Encode>(
+        &self,
+        c: &dyn ColumnDefinition,
+        key: &[u8],
+    ) -> Result<Option<V>, CacheError> {
+        self.check_column(c)?;
+        self.check_key(key, CacheError::Get)?;
+        let bytes = self.storage().try_get(c, key)?;
+
+        self.read_stored(c, key, bytes)
+    }
+
+    /// Get the value inserted with [`Engine::try_insert_keyed`] under an [`Encode`] key
+    ///
+    /// ```
+    /// use omega_cache::{CacheError, ColumnDefinition, Engine, noop_engine::NoopEngine};
+    ///
+    /// struct Orders;
+    ///
+    /// impl ColumnDefinition for Orders {
+    ///     fn name(&self) -> String {
+    ///         "orders".to_string()
+    ///     }
+    ///
+    ///     fn get_ttl_in_seconds(&self) -> i32 {
+    ///         60
+    ///     }
+    /// }
+    ///
+    /// fn main() -> Result<(), CacheError> {
+    ///     let cache = Engine::new(NoopEngine::passthrough());
+    ///     cache.try_insert_keyed(&Orders, &(42u64, "eu".to_string()), &3u32)?;
+    ///
+    ///     let count = cache.try_get_keyed::<_, u32>(&Orders, &(42u64, "eu".to_string()))?;
+    ///     assert_eq!(count, Some(3));
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    ///
+    /// # Errors
+    /// Returns [`CacheError::Encode`] if the key cannot be encoded to [`Vec<u8>`]
+    /// Returns [`CacheError::Get`] if get fails.
+    /// Returns [`CacheError::Decode`] if get result cannot be decoded to type V from a [`Vec<u8>`]
+    /// or checksums are enabled and the stored checksum does not match
+    pub fn try_get_keyed<K: Encode, V: Decode<()> + Encode>(
+        &self,
+        c: &dyn ColumnDefinition,
+        key: &K,
+    ) -> Result<Option<V>, CacheError> {
+        self.try_get(c, &encode_key(key)?)
+    }
+
+    /// Get the value at key as `V1`, or as `V2` if it does not decode as `V1`,
+    /// for reading a column holding values of both an old and a new shape
+    /// during a data model migration, without flushing it.
+    ///
+    /// A value only counts as decoded when the type takes up every stored byte,
+    /// so a new shape that starts like the old one is not misread. Values decoded
+    /// as `V2` are left as they are, rewrite them as `V1` to finish the migration.
+    /// This is about the value's type, see [`Engine::with_legacy_codec`] for changing its encoding.
+    ///
+    /// ```
+    /// use bincode::{Decode, Encode};
+    /// use omega_cache::{CacheError, ColumnDefinition, Either, Engine, noop_engine::NoopEngine};
+    ///
+    /// struct Users;
+    ///
+    /// impl ColumnDefinition for Users {
+    ///     fn name(&self) -> String {
+    ///         "users".to_string()
+    ///     }
+    ///
+    ///     fn get_ttl_in_seconds(&self) -> i32 {
+    ///         60
+    ///     }
+    /// }
+    ///
+    /// #[derive(Encode, Decode)]
+    /// struct UserV1 {
+    ///     name: String,
+    /// }
+    ///
+    /// #[derive(Encode, Decode)]
+    /// struct UserV2 {
+    ///     name: String,
+    ///     email: Option<String>,
+    /// }
+    ///
+    /// fn main() -> Result<(), CacheError> {
+    ///     let cache = Engine::new(NoopEngine::passthrough());
+    ///     cache.try_insert(&Users, &"ada", &UserV1 { name: "Ada".to_string() })?;
+    ///
+    ///     let user = match cache.try_get_or::<_, UserV2, UserV1>(&Users, &"ada")? {
+    ///         Some(Either::Left(user)) => Some(user),
+    ///         Some(Either::Right(old)) => Some(UserV2 { name: old.name, email: None }),
+    ///         None => None,
+    ///     };
+    ///     assert!(user.is_some_and(|user| user.email.is_none()));
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    ///
+    /// # Errors
+    /// Returns [`CacheError::Get`] if get fails.
+    /// Returns [`CacheError::Decode`] with the `V1` failure if the value decodes as neither type,
+    /// or checksums are enabled and the stored checksum does not match
+    pub fn try_get_or<K: AsRef<[u8]>, V1: Decode<()>, V2: Decode<()>>(
+        &self,
+        c: &dyn ColumnDefinition,
+        key: &K,
+    ) -> Result<Option<Either<V1, V2>>, CacheError> {
+        self.check_column(c)?;
+        let key_bytes = key.as_ref();
+        self.check_key(key_bytes, CacheError::Get)?;
+
+        let Some(bytes) = self.storage().try_get(c, key_bytes)? else {
+            return Ok(None);
+        };
+        let Some(value) = self.load_value(c, key_bytes, &bytes)? else {
+            return Ok(None);
+        };
+
+        match self.decode_value::<V1>(c, key_bytes, &value, true) {
+            Ok((value, _)) => Ok(Some(Either::Left(value))),
+            Err(e) => match self.decode_value::<V2>(c, key_bytes, &value, true) {
+                Ok((value, _)) => Ok(Some(Either::Right(value))),
+                Err(_) => Err(e),
+            },
+        }
+    }
+
+    /// Get the value at key in the first of `columns` holding it, for layered lookups
+    /// such as a per user override column in front of a defaults column.
+    ///
+    /// Columns are read in the order given, one storage read each, and reading stops
+    /// at the first hit, so later columns are only read when earlier ones miss.
+    /// An error, including a value in an earlier column that fails to decode,
+    /// is returned rather than falling through to the next column.
+    ///
+    /// ```
+    /// use omega_cache::{CacheError, ColumnDefinition, Engine, noop_engine::NoopEngine};
+    ///
+    /// struct Column(&'static str);
+    ///
+    /// impl ColumnDefinition for Column {
+    ///     fn name(&self) -> String {
+    ///         self.0.to_string()
+    ///     }
+    ///
+    ///     fn get_ttl_in_seconds(&self) -> i32 {
+    ///         60
+    ///     }
+    /// }
+    ///
+    /// const OVERRIDES: Column = Column("overrides");
+    /// const DEFAULTS: Column = Column("defaults");
+    ///
+    /// fn main() -> Result<(), CacheError> {
+    ///     let cache = Engine::new(NoopEngine::passthrough());
+    ///     cache.try_insert(&DEFAULTS, &"theme", &"light".to_string())?;
+    ///     cache.try_insert(&DEFAULTS, &"language", &"en".to_string())?;
+    ///     cache.try_insert(&OVERRIDES, &"theme", &"dark".to_string())?;
+    ///
+    ///     let layers: [&dyn ColumnDefinition; 2] = [&OVERRIDES, &DEFAULTS];
+    ///     assert_eq!(cache.try_get_first::<_, String>(&layers, &"theme")?, Some("dark".to_string()));
+    ///     assert_eq!(cache.try_get_first::<_, String>(&layers, &"language")?, Some("en".to_string()));
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    ///
+    /// # Errors
+    /// Returns [`CacheError::Get`] if a get fails.
+    /// Returns [`CacheError::Decode`] if the value found cannot be decoded to type V
+    pub fn try_get_first<K: AsRef<[u8]>, V: Decode<()> + Encode>(
+        &self,
+        columns: &[&dyn ColumnDefinition],
+        key: &K,
+    ) -> Result<Option<V>, CacheError> {
+        for c in columns {
+            if let Some(value) = self.try_get(*c, key)? {
+                return Ok(Some(value));
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Get the value at key, decoding it with a bincode decode context,
+    /// for types that implement [`Decode`] only for a specific context
+    /// such as an interner or allocator.
+    ///
+    /// ```
+    /// use bincode::{Decode, Encode, de::Decoder, error::DecodeError};
+    /// use omega_cache::{CacheError, ColumnDefinition, Engine, noop_engine::NoopEngine};
+    ///
+    /// struct Prices;
+    ///
+    /// impl ColumnDefinition for Prices {
+    ///     fn name(&self) -> String {
+    ///         "prices".to_string()
+    ///     }
+    ///
+    ///     fn get_ttl_in_seconds(&self) -> i32 {
+    ///         60
+    ///     }
+    /// }
+    ///
+    /// /// Currency the cached amounts are in, known to the reader rather than stored
+    /// #[derive(Debug, Clone, Copy, PartialEq)]
+    /// enum Currency {
+    ///     Eur,
+    ///     Usd,
+    /// }
+    ///
+    /// #[derive(Debug, PartialEq)]
+    /// struct Price {
+    ///     cents: u64,
+    ///     currency: Currency,
+    /// }
+    ///
+    /// impl Decode<Currency> for Price {
+    ///     fn decode<D: Decoder<Context = Currency>>(decoder: &mut D) -> Result<Self, DecodeError> {
+    ///         Ok(Price {
+    ///             cents: u64::decode(decoder)?,
+    ///             currency: *decoder.context(),
+    ///         })
+    ///     }
+    /// }
+    ///
+    /// fn main() -> Result<(), CacheError> {
+    ///     let cache = Engine::new(NoopEngine::passthrough());
+    ///     cache.try_insert(&Prices, &"coffee", &250u64)?;
+    ///
+    ///     let price = cache.try_get_with_context::<_, _, Price>(Currency::Eur, &Prices, &"coffee")?;
+    ///
+    ///     assert_eq!(price, Some(Price { cents: 250, currency: Currency::Eur }));
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    ///
+    /// Only the codec set with [`Engine::with_codec`] is tried, legacy codecs and rewriting are not applied.
+    ///
+    /// # Errors
+    /// Returns [`CacheError::Get`] if get fails.
+    /// Returns [`CacheError::Decode`] if the value cannot be decoded to type V with the context
+    /// or checksums are enabled and the stored checksum does not match
+    pub fn try_get_with_context<K: AsRef<[u8]>, Ctx, V: Decode<Ctx>>(
+        &self,
+        context: Ctx,
+        c: &dyn ColumnDefinition,
+        key: &K,
+    ) -> Result<Option<V>, CacheError> {
+        self.check_column(c)?;
+        let key_bytes = key.as_ref();
+        self.check_key(key_bytes, CacheError::Get)?;
+
+        let Some(bytes) = self.storage().try_get(c, key_bytes)? else {
+            return Ok(None);
+        };
+        let Some(value) = self.load_value(c, key_bytes, &bytes)? else {
+            return Ok(None);
+        };
+
+        self.marked_codec(&value)
+            .unwrap_or(self.codec)
+            .decode_with_context(
+                &self.payload(c, key_bytes, &value)?,
+                context,
+                self.decode_limit,
+            )
+            .map(|v| Some(v.0))
+            .map_err(|e| CacheError::decode(c, key_bytes, e.to_string()))
+    }
+
+    /// Like [`Engine::try_get`], but a value that fails to decode is returned with
+    /// the bytes storage holds for it, for inspecting schema drift in production.
+    ///
+    /// ```
+    /// use omega_cache::{CacheError, ColumnDefinition, Engine, noop_engine::NoopEngine};
+    ///
+    /// struct Profiles;
+    ///
+    /// impl ColumnDefinition for Profiles {
+    ///     fn name(&self) -> String {
+    ///         "profiles".to_string()
+    ///     }
+    ///
+    ///     fn get_ttl_in_seconds(&self) -> i32 {
+    ///         60
+    ///     }
+    /// }
+    ///
+    /// fn main() -> Result<(), CacheError> {
+    ///     let cache = Engine::new(NoopEngine::passthrough());
+    ///     cache.try_insert(&Profiles, &"ada", &2u8)?;
+    ///
+    ///     match cache.try_get_raw_or_decoded::<_, bool>(&Profiles, &"ada")? {
+    ///         Some(Ok(active)) => println!("active: {active}"),
+    ///         Some(Err(failure)) => eprintln!("{} from {:?}", failure.error, failure.bytes),
+    ///         None => println!("miss"),
+    ///     }
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    ///
+    /// # Errors
+    /// Returns [`CacheError::Get`] if get fails, decode failures are returned
+    /// within the `Ok` value instead
+    pub fn try_get_raw_or_decoded<K: AsRef<[u8]>, V: Decode<()> + Encode>(
+        &self,
+        c: &dyn ColumnDefinition,
+        key: &K,
+    ) -> Result<Option<Result<V, DecodeFailure>>, CacheError> {
+        self.check_column(c)?;
+        let key_bytes = key.as_ref();
+        self.check_key(key_bytes, CacheError::Get)?;
+
+        let Some(bytes) = self.storage().try_get(c, key_bytes)? else {
+            return Ok(None);
+        };
+
+        let value = match self.load_value(c, key_bytes, &bytes) {
+            Ok(Some(value)) => value,
+            Ok(None) => return Ok(None),
+            Err(error) => return Ok(Some(Err(DecodeFailure { error, bytes }))),
+        };
+
+        Ok(Some(self.read_value(c, key_bytes, &value).map_err(
+            |error| DecodeFailure {
+                error,
+                bytes: value.into_owned(),
+            },
+        )))
+    }
+
+    /// [`Engine::try_get`] for a [`String`] value
+    ///
+    /// # Errors
+    /// Returns any error from [`Engine::try_get`]
+    pub fn try_get_string<K: AsRef<[u8]>>(
+        &self,
+        c: &dyn ColumnDefinition,
+        key: &K,
+    ) -> Result<Option<String>, CacheError> {
+        self.try_get(c, key)
+    }
+
+    /// [`Engine::try_get`] for an [`i64`] value,
+    /// counters from [`Engine::try_increment`] are read with a `delta` of zero instead
+    ///
+    /// # Errors
+    /// Returns any error from [`Engine::try_get`]
+    pub fn try_get_i64<K: AsRef<[u8]>>(
+        &self,
+        c: &dyn ColumnDefinition,
+        key: &K,
+    ) -> Result<Option<i64>, CacheError> {
+        self.try_get(c, key)
+    }
+
+    /// [`Engine::try_get`] for a [`Vec<u8>`] value
+    ///
+    /// # Errors
+    /// Returns any error from [`Engine::try_get`]
+    pub fn try_get_bytes<K: AsRef<[u8]>>(
+        &self,
+        c: &dyn ColumnDefinition,
+        key: &K,
+    ) -> Result<Option<Vec<u8>>, CacheError> {
+        self.try_get(c, key)
+    }
+
+    /// Get the values at many keys, in the order of `keys`,
+    /// with [`None`] for keys that are missing or expired.
+    ///
+    /// Keys are fetched [`Engine::with_batch_size`] at a time, as a single `MGET` on redis.
+    ///
+    /// # Errors
+    /// Returns [`CacheError::Get`] if get fails.
+    /// Returns [`CacheError::Decode`] if any value cannot be decoded to type V
+    pub fn try_get_many<K: AsRef<[u8]>, V: Decode<()> + Encode>(
+        &self,
+        c: &dyn ColumnDefinition,
+        keys: &[K],
+    ) -> Result<Vec<Option<V>>, CacheError> {
+        self.check_column(c)?;
+        let mut values = Vec::with_capacity(keys.len());
+
+        for chunk in keys.chunks(self.batch_size) {
+            let chunk = chunk.iter().map(AsRef::as_ref).collect::<Vec<&[u8]>>();
+            for key in &chunk {
+                self.check_key(key, CacheError::Get)?;
+            }
+
+            for (key, bytes) in chunk.iter().zip(self.storage().try_get_many(c, &chunk)?) {
+                values.push(self.read_stored(c, key, bytes)?);
+            }
+        }
+
+        Ok(values)
+    }
+
+    /// Insert the items from a lazy iterator,
+    /// such as rows streamed from a database, returning how many were inserted.
+    ///
+    /// Items are encoded and written [`Engine::with_batch_size`] at a time,
+    /// so only one batch is held in memory. sled writes each batch atomically
+    /// and redis pipelines it in a single round trip.
+    ///
+    /// # Errors
+    /// Returns [`CacheError::Encode`] if an item cannot be encoded.
+    /// Returns [`CacheError::Put`] if a batch cannot be inserted,
+    /// batches before it stay inserted
+    pub fn try_preload<K, V, I>(
+        &self,
+        c: &dyn ColumnDefinition,
+        items: I,
+    ) -> Result<usize, CacheError>
+    where
+        K: AsRef<[u8]>,
+        V: Encode,
+        I: IntoIterator<Item = (K, V)>,
+    {
+        check_writable(c)?;
+        self.check_column(c)?;
+        if self.storage().discards_writes() {
+            return Ok(0);
+        }
+
+        let mut batch = Vec::with_capacity(self.batch_size);
+        let mut inserted = 0;
+
+        for (key, value) in items {
+            let value_bytes = self.store_value(c, key.as_ref(), &value, None)?;
+            batch.push((key.as_ref().to_vec(), value_bytes));
+
+            if batch.len() == self.batch_size {
+                self.storage().try_insert_batch(c, &batch)?;
+                inserted += batch.len();
+                batch.clear();
+            }
+        }
+
+        if !batch.is_empty() {
+            self.storage().try_insert_batch(c, &batch)?;
+            inserted += batch.len();
+        }
+
+        Ok(inserted)
+    }
+
+    /// Get the stored value at key so it can be decoded into a type borrowing from it.
+    ///
+    /// ```
+    /// use omega_cache::{Engine, noop_engine::NoopEngine, ColumnDefinition, CacheError};
+    ///
+    /// struct Blobs;
+    ///
+    /// impl ColumnDefinition for Blobs {
+    ///     fn name(&self) -> String {
+    ///         "blobs".to_string()
+    ///     }
+    ///
+    ///     fn get_ttl_in_seconds(&self) -> i32 {
+    ///         60
+    ///     }
+    /// }
+    ///
+    /// fn main() -> Result<(), CacheError> {
+    ///     let cache = Engine::new(NoopEngine::passthrough());
+    ///     cache.try_insert(&Blobs, &"key", &vec![1u8; 4096])?;
+    ///
+    ///     let stored = cache.try_get_borrowed(&Blobs, &"key")?.unwrap();
+    ///     // `blob` borrows from `stored`, so must be dropped before it
+    ///     let blob: &[u8] = stored.decode()?;
+    ///
+    ///     assert_eq!(blob.len(), 4096);
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    ///
+    /// Decoding borrows from the returned [`StoredValue`] rather than the engine,
+    /// so the value can be kept after further calls but not past the [`StoredValue`] itself.
+    /// Only sled avoids copying the value out of storage, other storages copy it once
+    /// but decoding still avoids a second copy. Legacy codecs and rewriting are not applied.
+    ///
+    /// # Errors
+    /// Returns [`CacheError::Get`] if get fails.
+    /// Returns [`CacheError::Decode`] if checksums are enabled and the stored checksum does not match,
+    /// or encryption is enabled and the value cannot be decrypted
+    pub fn try_get_borrowed<'a, K: AsRef<[u8]> + 'a>(
+        &'a self,
+        c: &dyn ColumnDefinition,
+        key: &'a K,
+    ) -> Result<Option<StoredValue>, CacheError> {
+        let key_bytes = key.as_ref();
+
+        let Some(mut stored) = self.try_get_payload(c, key_bytes)? else {
+            return Ok(None);
+        };
+
+        stored.codec = self.codec;
+        stored.decode_limit = self.decode_limit;
+        stored.column = c.name();
+        stored.key = key_bytes.to_vec();
+
+        Ok(Some(stored))
+    }
+
+    /// Read the encoded value at key into `buf`, replacing its contents,
+    /// and return whether a live value was found.
+    ///
+    /// For hot paths reading small values, `buf` is reused between calls so no
+    /// [`Vec`] is allocated per get once it has grown to fit. The bytes are the raw
+    /// encoded value, not a decoded one: checksums and encryption are removed and
+    /// chunks joined, leaving the value as encoded by the engine's [`Codec`],
+    /// which the caller decodes themselves. On a miss `buf` is left empty.
+    /// Legacy codecs and rewriting are not applied.
+    ///
+    /// ```
+    /// use omega_cache::{CacheError, ColumnDefinition, Engine, noop_engine::NoopEngine};
+    ///
+    /// struct Scores;
+    ///
+    /// impl ColumnDefinition for Scores {
+    ///     fn name(&self) -> String {
+    ///         "scores".to_string()
+    ///     }
+    ///
+    ///     fn get_ttl_in_seconds(&self) -> i32 {
+    ///         60
+    ///     }
+    /// }
+    ///
+    /// fn main() -> Result<(), CacheError> {
+    ///     let cache = Engine::new(NoopEngine::passthrough());
+    ///     cache.try_insert(&Scores, &"alice", &42u32)?;
+    ///
+    ///     let mut buf = Vec::with_capacity(16);
+    ///     assert!(cache.try_get_into(&Scores, &"alice", &mut buf)?);
+    ///
+    ///     let (score, _): (u32, usize) =
+    ///         bincode::decode_from_slice(&buf, bincode::config::standard()).unwrap();
+    ///     assert_eq!(score, 42);
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    ///
+    /// # Errors
+    /// Returns [`CacheError::Get`] if get fails.
+    /// Returns [`CacheError::Decode`] if checksums are enabled and the stored checksum does not match,
+    /// or encryption is enabled and the value cannot be decrypted
+    pub fn try_get_into<K: AsRef<[u8]>>(
+        &self,
+        c: &dyn ColumnDefinition,
+        key: &K,
+        buf: &mut Vec<u8>,
+    ) -> Result<bool, CacheError> {
+        buf.clear();
+
+        let Some(stored) = self.try_get_payload(c, key.as_ref())? else {
+            return Ok(false);
+        };
+
+        buf.extend_from_slice(stored.as_bytes());
+
+        Ok(true)
+    }
+
+    /// Stored value at key with chunks joined and checksums, encryption, compression
+    /// and middleware removed
+    fn try_get_payload(
+        &self,
+        c: &dyn ColumnDefinition,
+        key_bytes: &[u8],
+    ) -> Result<Option<StoredValue>, CacheError> {
+        self.check_column(c)?;
+        self.check_key(key_bytes, CacheError::Get)?;
+        let Some(mut stored) = self.storage().try_get_stored(c, key_bytes)? else {
+            return Ok(None);
+        };
+
+        let loaded = self
+            .load_value(c, key_bytes, stored.as_bytes())?
+            .map(|value| match value {
+                Cow::Owned(joined) => Ok(joined),
+                Cow::Borrowed(value) => Err(value.len()),
+            });
+
+        match loaded {
+            None => return Ok(None),
+            Some(Ok(joined)) => stored = StoredValue::from(joined),
+            Some(Err(len)) => stored.start = stored.end - len,
+        }
+
+        if self.format_marker.is_some() {
+            stored.start += 1;
+        }
+
+        if self.checksums {
+            let payload_len = checksum::unseal(stored.as_bytes())
+                .ok_or_else(|| CacheError::decode(c, key_bytes, "checksum mismatch".to_string()))?
+                .len();
+
+            stored.start = stored.end - payload_len;
+        }
+
+        if let Cow::Owned(decrypted) = self.decrypt(c, key_bytes, stored.as_bytes())? {
+            stored = StoredValue::from(decrypted);
+        }
+
+        let decompressed = self.decompress(c, key_bytes, Cow::Borrowed(stored.as_bytes()))?;
+        let decompressed = match self.undo_middleware(c, key_bytes, decompressed)? {
+            Cow::Owned(decompressed) => Ok(decompressed),
+            Cow::Borrowed(value) => Err(value.len()),
+        };
+
+        match decompressed {
+            Ok(decompressed) => stored = StoredValue::from(decompressed),
+            Err(len) => stored.start = stored.end - len,
+        }
+
+        Ok(Some(stored))
+    }
+
+    /// Get a value inserted with [`Engine::try_insert_tagged`] along with its tag,
+    /// leaving the caller to decode the value as the type the tag stands for.
+    ///
+    /// ```
+    /// use omega_cache::{CacheError, ColumnDefinition, Engine, noop_engine::NoopEngine};
+    ///
+    /// struct Shapes;
+    ///
+    /// impl ColumnDefinition for Shapes {
+    ///     fn name(&self) -> String {
+    ///         "shapes".to_string()
+    ///     }
+    ///
+    ///     fn get_ttl_in_seconds(&self) -> i32 {
+    ///         60
+    ///     }
+    /// }
+    ///
+    /// const CIRCLE: u16 = 1;
+    /// const POLYGON: u16 = 2;
+    ///
+    /// fn main() -> Result<(), CacheError> {
+    ///     let cache = Engine::new(NoopEngine::passthrough());
+    ///     cache.try_insert_tagged(&Shapes, &"wheel", CIRCLE, &2.5f64)?;
+    ///     cache.try_insert_tagged(&Shapes, &"square", POLYGON, &vec![(0u8, 0u8), (0, 1), (1, 1), (1, 0)])?;
+    ///
+    ///     let (tag, stored) = cache.try_get_tagged(&Shapes, &"square")?.unwrap();
+    ///     match tag {
+    ///         CIRCLE => println!("radius {}", stored.decode::<f64>()?),
+    ///         POLYGON => println!("{} sides", stored.decode::<Vec<(u8, u8)>>()?.len()),
+    ///         _ => unreachable!(),
+    ///     }
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    ///
+    /// As with [`Engine::try_get_borrowed`] legacy codecs and rewriting are not applied.
+    ///
+    /// # Errors
+    /// Returns [`CacheError::Get`] if get fails.
+    /// Returns [`CacheError::Decode`] if the value has no tag,
+    /// or checksums or encryption are enabled and the value cannot be verified
+    pub fn try_get_tagged<K: AsRef<[u8]>>(
+        &self,
+        c: &dyn ColumnDefinition,
+        key: &K,
+    ) -> Result<Option<(u16, StoredValue)>, CacheError> {
+        let Some(mut stored) = self.try_get_borrowed(c, key)? else {
+            return Ok(None);
+        };
+
+        let (tag, read) = self
+            .codec
+            .decode::<u16>(stored.as_bytes(), self.decode_limit)
+            .map_err(|e| CacheError::decode(c, key.as_ref(), e.to_string()))?;
+        stored.start += read;
+
+        Ok(Some((tag, stored)))
+    }
+
+    /// Number of bytes the storage holds for the value at key, without decoding it,
+    /// for spotting unexpectedly large values.
+    ///
+    /// Includes checksums and storage overhead such as sled's timestamp and TTL.
+    /// sled may count an expired value it has not yet removed, and for a value
+    /// split by [`Engine::with_chunking`] only the manifest at key is counted.
+    ///
+    /// # Errors
+    /// Returns [`CacheError::Get`] if the size cannot be read
+    pub fn try_value_size<K: AsRef<[u8]>>(
+        &self,
+        c: &dyn ColumnDefinition,
+        key: &K,
+    ) -> Result<Option<usize>, CacheError> {
+        self.check_column(c)?;
+        self.storage().try_value_size(c, key.as_ref())
+    }
+
+    /// Get the value at key, treating a miss as an error.
+    ///
+    /// Unlike [`Engine::try_get`], which returns `Ok(None)` for a missing or expired key,
+    /// this returns [`CacheError::NotFound`], for values that should always be present.
+    ///
+    /// # Errors
+    /// Returns [`CacheError::NotFound`] if there is no live value at key.
+    /// Returns any error from [`Engine::try_get`]
+    pub fn try_get_required<'a, K: AsRef<[u8]> + 'a, V: Decode<()> + Encode + 'a>(
+        &'a self,
+        c: &dyn ColumnDefinition,
+        key: &'a K,
+    ) -> Result<V, CacheError> {
+        self.try_get(c, key)?
+            .ok_or_else(|| CacheError::not_found(c, key.as_ref()))
+    }
+
+    /// Get the value at key, or the column's [`ColumnDefinition::default_value_bytes`]
+    /// decoded if there is no live value, so defaults live with the column rather than
+    /// in an `unwrap_or` at every call site.
+    ///
+    /// Returns `None` only on a miss in a column without a default. The default is not
+    /// inserted, so every miss decodes it again and [`Engine::try_get`] still misses.
+    /// Anything read from storage wins over the default: a cached negative result, such as
+    /// an `Option::None` stored for a key known to be absent, is returned as stored,
+    /// and a value that fails to decode is a [`CacheError::Decode`] rather than a
+    /// default, so corruption is not hidden. Values read as a miss, such as a
+    /// [`FormatMismatch::Miss`] or missing chunks, do read as the default.
+    ///
+    /// ```
+    /// use omega_cache::{ColumnDefinition, Engine, noop_engine::NoopEngine};
+    ///
+    /// struct Limits;
+    ///
+    /// impl ColumnDefinition for Limits {
+    ///     fn name(&self) -> String {
+    ///         "limits".to_string()
+    ///     }
+    ///
+    ///     fn get_ttl_in_seconds(&self) -> i32 {
+    ///         300
+    ///     }
+    ///
+    ///     fn default_value_bytes(&self) -> Option<Vec<u8>> {
+    ///         bincode::encode_to_vec(100u32, bincode::config::standard()).ok()
+    ///     }
+    /// }
+    ///
+    /// let engine = Engine::new(NoopEngine::passthrough());
+    /// engine.try_insert(&Limits, &"ada", &500u32).unwrap();
+    ///
+    /// assert_eq!(engine.try_get_with_column_default(&Limits, &"ada").unwrap(), Some(500u32));
+    /// assert_eq!(engine.try_get_with_column_default(&Limits, &"grace").unwrap(), Some(100u32));
+    /// ```
+    ///
+    /// # Errors
+    /// Returns any error from [`Engine::try_get`].
+    /// Returns [`CacheError::Decode`] if the default cannot be decoded to type V
+    pub fn try_get_with_column_default<'a, K: AsRef<[u8]> + 'a, V: Decode<()> + Encode + 'a>(
+        &'a self,
+        c: &dyn ColumnDefinition,
+        key: &'a K,
+    ) -> Result<Option<V>, CacheError> {
+        if let Some(value) = self.try_get(c, key)? {
+            return Ok(Some(value));
+        }
+
+        c.default_value_bytes()
+            .map(|bytes| {
+                self.codec
+                    .decode(&bytes, self.decode_limit)
+                    .map(|(value, _)| value)
+                    .map_err(|e| {
+                        CacheError::decode(c, key.as_ref(), format!("column default: {e}"))
+                    })
+            })
+            .transpose()
+    }
+
+    /// Get the value at key even if it expired, along with whether it did,
+    /// to serve the last known good value when refreshing it fails.
+    ///
+    /// ```
+    /// use omega_cache::{CacheError, ColumnDefinition, Engine, noop_engine::NoopEngine};
+    ///
+    /// struct Rates;
+    ///
+    /// impl ColumnDefinition for Rates {
+    ///     fn name(&self) -> String {
+    ///         "rates".to_string()
+    ///     }
+    ///
+    ///     fn get_ttl_in_seconds(&self) -> i32 {
+    ///         60
+    ///     }
+    /// }
+    ///
+    /// fn fetch_rate() -> Result<f64, String> {
+    ///     Err("upstream is down".to_string())
+    /// }
+    ///
+    /// fn main() -> Result<(), CacheError> {
+    ///     let cache = Engine::new(NoopEngine::passthrough());
+    ///     cache.try_insert(&Rates, &"EUR", &1.08f64)?;
+    ///
+    ///     let rate = match cache.try_get_allowing_stale::<_, f64>(&Rates, &"EUR")? {
+    ///         Some((rate, false)) => Some(rate),
+    ///         stale => fetch_rate().ok().or(stale.map(|(rate, _)| rate)),
+    ///     };
+    ///     assert_eq!(rate, Some(1.08));
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    ///
+    /// Expired values are only returned by storages that keep them until they are read,
+    /// such as sled, where this reads as `SledEngine::try_peek` and leaves
+    /// them in place. Redis deletes values server side when they expire, and the crate
+    /// stores no timestamp of its own there, so on Redis this only returns live values.
+    /// Chunked values whose chunks have already been removed read as a miss.
+    ///
+    /// # Errors
+    /// Returns [`CacheError::Get`] if get fails
+    /// Returns [`CacheError::Decode`] if the value cannot be decoded to type V
+    pub fn try_get_allowing_stale<K: AsRef<[u8]>, V: Decode<()> + Encode>(
+        &self,
+        c: &dyn ColumnDefinition,
+        key: &K,
+    ) -> Result<Option<(V, bool)>, CacheError> {
+        self.check_column(c)?;
+        let key_bytes = key.as_ref();
+        self.check_key(key_bytes, CacheError::Get)?;
+
+        let Some((bytes, expired)) = self.storage().try_get_allowing_stale(c, key_bytes)? else {
+            return Ok(None);
+        };
+
+        Ok(self
+            .read_stored(c, key_bytes, Some(bytes))?
+            .map(|value| (value, expired)))
+    }
+
+    /// Get the value at key, or compute, insert and return it on a miss.
+    ///
+    /// `f` only runs when the key is missing or expired.
+    ///
+    /// # Errors
+    /// Returns any error from [`Engine::try_get`] or [`Engine::try_insert`]
+    pub fn try_get_or_insert_with<'a, K, V, F>(
+        &'a self,
+        c: &dyn ColumnDefinition,
+        key: &'a K,
+        f: F,
+    ) -> Result<V, CacheError>
+    where
+        K: AsRef<[u8]> + 'a,
+        V: Decode<()> + Encode + 'a,
+        F: FnOnce() -> V,
+    {
+        if let Some(value) = self.try_get(c, key)? {
+            return Ok(value);
+        }
+
+        let value = f();
+        self.try_insert(c, key, &value)?;
+
+        Ok(value)
+    }
+
+    /// Like [`Engine::try_get_or_insert_with`] but a value computed on a miss
+    /// is stored with the TTL override, see [`Engine::try_insert_with_ttl`],
+    /// useful for letting cached failures expire sooner than successes.
+    ///
+    /// # Errors
+    /// Returns any error from [`Engine::try_get`] or [`Engine::try_insert_with_ttl`]
+    pub fn try_get_or_insert_with_ttl<'a, K, V, F>(
+        &'a self,
+        c: &dyn ColumnDefinition,
+        key: &'a K,
+        ttl_in_seconds: Option<i32>,
+        f: F,
+    ) -> Result<V, CacheError>
+    where
+        K: AsRef<[u8]> + 'a,
+        V: Decode<()> + Encode + 'a,
+        F: FnOnce() -> V,
+    {
+        if let Some(value) = self.try_get(c, key)? {
+            return Ok(value);
+        }
+
+        let value = f();
+        self.try_insert_with_ttl(c, key, &value, ttl_in_seconds)?;
+
+        Ok(value)
+    }
+
+    /// # Errors
+    /// Returns [`CacheError::Engine`] if drop fails.
+    /// Returns [`CacheError::Put`] if the column is read only
+    pub fn try_drop_column(&self, c: &dyn ColumnDefinition) -> Result<(), CacheError> {
+        check_writable(c)?;
+        if c.index().is_some() {
+            self.storage().try_drop_column(&IndexColumn { column: c })?;
+        }
+
+        self.storage().try_drop_column(c)
+    }
+
+    /// Copy every live value of `from` into `to` with `to`'s TTL, returning the number of
+    /// stored keys copied, for example to warm `prices_v2` from `prices_v1` before switching.
+    ///
+    /// Values are copied as stored, without decoding, so both columns must be read by engines
+    /// with the same codec, checksum and encryption settings. Chunked values are copied chunk
+    /// by chunk, each counting once. Values already in `to` are overwritten, and `to`'s
+    /// [`ColumnDefinition::index`] is not updated. The whole column is read into memory,
+    /// on sled by walking its tree and on redis by a SCAN of its keys, then written
+    /// [`Engine::with_batch_size`] values at a time.
+    ///
+    /// # Errors
+    /// Returns [`CacheError::Get`] if `from` cannot be read.
+    /// Returns [`CacheError::Put`] if a write fails, some of the values may have been copied,
+    /// or if `to` is read only
+    /// Returns [`CacheError::Engine`] if the engine encrypts values and `to` is named
+    /// otherwise than `from`, as values are bound to their column, see [`Engine::with_encryption`]
+    pub fn try_copy_column(
+        &self,
+        from: &dyn ColumnDefinition,
+        to: &dyn ColumnDefinition,
+    ) -> Result<usize, CacheError> {
+        check_writable(to)?;
+        self.check_column(from)?;
+        self.check_column(to)?;
+
+        #[cfg(feature = "encryption")]
+        if self.encryptor.is_some() && from.name() != to.name() {
+            return Err(CacheError::Engine(
+                "copying encrypted values to another column is not supported".to_string(),
+            ));
+        }
+
+        let entries = self.storage().try_entries(from)?;
+        for batch in entries.chunks(self.batch_size) {
+            self.storage().try_insert_batch(to, batch)?;
+        }
+
+        Ok(entries.len())
+    }
+
+    /// Write every live value of the column with the time it has left to live to `writer`,
+    /// returning the number of stored keys written, for backups or moving a column to
+    /// another environment or storage with [`Engine::try_import_column`].
+    ///
+    /// The export is a sequence of `(key, value, remaining_ttl)` records: the key and the
+    /// value, each prefixed by its length as a big endian `u32`, then the whole seconds
+    /// left to live as a big endian `u32`, rounded up, with zero for values that never
+    /// expire. Values are exported as stored, without decoding, so they must be imported
+    /// by an engine with the same codec, checksum, encryption and chunking settings.
+    /// Chunked values are exported chunk by chunk, each counting once, and the column's
+    /// [`ColumnDefinition::index`] is not exported. The whole column is read into memory.
+    ///
+    /// ```
+    /// use omega_cache::{ColumnDefinition, Engine, noop_engine::NoopEngine};
+    ///
+    /// struct Prices;
+    ///
+    /// impl ColumnDefinition for Prices {
+    ///     fn name(&self) -> String {
+    ///         "prices".to_string()
+    ///     }
+    ///
+    ///     fn get_ttl_in_seconds(&self) -> i32 {
+    ///         60
+    ///     }
+    /// }
+    ///
+    /// let staging = Engine::new(NoopEngine::passthrough());
+    /// staging.try_insert(&Prices, &"apple", &3u32).unwrap();
+    ///
+    /// let mut export = Vec::new();
+    /// assert_eq!(staging.try_export_column(&Prices, &mut export).unwrap(), 1);
+    ///
+    /// let production = Engine::new(NoopEngine::passthrough());
+    /// assert_eq!(production.try_import_column(&Prices, &mut export.as_slice()).unwrap(), 1);
+    /// assert_eq!(production.try_get(&Prices, &"apple").unwrap(), Some(3u32));
+    /// ```
+    ///
+    /// # Errors
+    /// Returns [`CacheError::Get`] if the column cannot be read.
+    /// Returns [`CacheError::Engine`] if the storage cannot tell the time left to live
+    /// of its values, or `writer` fails, in which case part of the column may be written
+    pub fn try_export_column(
+        &self,
+        c: &dyn ColumnDefinition,
+        writer: &mut dyn std::io::Write,
+    ) -> Result<usize, CacheError> {
+        self.check_column(c)?;
+
+        let entries = self.storage().try_timed_entries(c)?;
+        for (key, value, ttl) in &entries {
+            archive::write_record(writer, key, value, archive::remaining_seconds(*ttl))?;
+        }
+
+        Ok(entries.len())
+    }
+
+    /// Insert every value of an export written by [`Engine::try_export_column`]
+    /// into the column, returning the number of stored keys inserted.
+    ///
+    /// Each value is inserted with the time it had left to live when exported, so the
+    /// time spent between export and import is not taken off: TTLs restart from the
+    /// import, and any [`ColumnDefinition::ttl_jitter`] of the column is applied again.
+    /// Values already in the column are overwritten. The export may come from any storage,
+    /// but must have been written by an engine with the same codec, checksum, encryption
+    /// and chunking settings, and the column's [`ColumnDefinition::index`] is not updated.
+    ///
+    /// # Errors
+    /// Returns [`CacheError::Put`] if an insert fails, earlier values stay inserted,
+    /// or if the column is read only.
+    /// Returns [`CacheError::Engine`] if `reader` fails or the export is cut short
+    pub fn try_import_column(
+        &self,
+        c: &dyn ColumnDefinition,
+        reader: &mut dyn Read,
+    ) -> Result<usize, CacheError> {
+        check_writable(c)?;
+        self.check_column(c)?;
+
+        let mut imported = 0;
+        while let Some((key, value, ttl_in_seconds)) = archive::read_record(reader)? {
+            let ttl_in_seconds = i32::try_from(ttl_in_seconds).unwrap_or(i32::MAX);
+
+            self.storage()
+                .try_insert_with_ttl(c, &key, &value, ttl_in_seconds)?;
+            imported += 1;
+        }
+
+        Ok(imported)
+    }
+
+    /// Write every live value of every column held by the storage to `writer` as a single
+    /// snapshot, returning the number of stored keys written, for disaster recovery or
+    /// cloning a whole cache to another environment with [`Engine::try_restore`].
+    ///
+    /// The snapshot starts with the magic bytes `OMEGASNP` and a format version byte,
+    /// currently `2`. Each column of [`Engine::try_list_columns`] follows as its name
+    /// prefixed by its length as a big endian `u32`, its TTL as a big endian `i32`, its
+    /// idle TTL as a big endian `u32` or zero, a byte for its [`SledLayout`], zero for a
+    /// dedicated tree and one for the shared tree, and its number of records as a big endian
+    /// `u64`, then its records in the format of [`Engine::try_export_column`].
+    /// Version `1` snapshots lack the idle TTL and layout and are still restored.
+    /// Later versions of the format will be told apart by the version byte, and
+    /// snapshots of a version this engine does not know are refused rather than misread.
+    ///
+    /// The TTL and idle TTL are those the column was registered with through
+    /// [`Engine::register_column`], and for other columns none. The layout is the one the
+    /// storage holds the column's values in, see [`CacheStorage::try_column_layout`],
+    /// so on sled columns are read from the tree holding them whether or not they are
+    /// registered, and a column with values both in a tree of its own and in the shared
+    /// tree fails the snapshot.
+    ///
+    /// Values are written as stored, so restoring needs the same codec, checksum,
+    /// encryption, compression, middleware and chunking settings, and secondary indexes
+    /// are written as the columns holding them. Each column is read into memory in turn,
+    /// and a column written to during the snapshot may be caught part way through.
+    /// On redis columns are found by scanning the whole keyspace, see [`Engine::try_list_columns`].
+    ///
+    /// ```
+    /// use omega_cache::{ColumnDefinition, Engine, noop_engine::NoopEngine};
+    ///
+    /// struct Prices;
+    ///
+    /// impl ColumnDefinition for Prices {
+    ///     fn name(&self) -> String {
+    ///         "prices".to_string()
+    ///     }
+    ///
+    ///     fn get_ttl_in_seconds(&self) -> i32 {
+    ///         60
+    ///     }
+    /// }
+    ///
+    /// let staging = Engine::new(NoopEngine::passthrough());
+    /// staging.try_insert(&Prices, &"apple", &3u32).unwrap();
+    ///
+    /// let mut snapshot = Vec::new();
+    /// assert_eq!(staging.try_snapshot(&mut snapshot).unwrap(), 1);
+    ///
+    /// let production = Engine::new(NoopEngine::passthrough());
+    /// assert_eq!(production.try_restore(&mut snapshot.as_slice()).unwrap(), 1);
+    /// assert_eq!(production.try_get(&Prices, &"apple").unwrap(), Some(3u32));
+    /// ```
+    ///
+    /// # Errors
+    /// Returns [`CacheError::Get`] if a column cannot be read.
+    /// Returns [`CacheError::Engine`] if the columns cannot be listed, a column's layout cannot
+    /// be told, the storage cannot tell the time left to live of its values, or `writer` fails, in which case part of the
+    /// snapshot may be written
+    pub fn try_snapshot(&self, writer: &mut dyn std::io::Write) -> Result<usize, CacheError> {
+        archive::write_snapshot_header(writer)?;
+
+        let mut written = 0;
+        for name in self.storage().try_list_columns()? {
+            let mut c = self.registered_column(name);
+            c.storage_layout = self.storage().try_column_layout(&c)?;
+
+            let entries = self.storage().try_timed_entries(&c)?;
+            archive::write_column_header(writer, &c, entries.len() as u64)?;
+
+            for (key, value, ttl) in &entries {
+                archive::write_record(writer, key, value, archive::remaining_seconds(*ttl))?;
+            }
+
+            written += entries.len();
+        }
+
+        Ok(written)
+    }
+
+    /// Insert every value of a snapshot written by [`Engine::try_snapshot`] into the column
+    /// it was taken from, returning the number of stored keys inserted.
+    ///
+    /// As with [`Engine::try_import_column`] each value is inserted with the time it had
+    /// left to live when the snapshot was taken, and values already stored are overwritten
+    /// while other values are left alone, so restoring into a cache that is not empty merges
+    /// the two. Columns are restored whether or not they are registered with this engine.
+    ///
+    /// A snapshot may be restored into any storage, not only the kind it was taken from,
+    /// as values are stored bytes whatever the storage. The remaining TTLs restart from
+    /// the restore on every storage that expires values, and a snapshot of a storage that
+    /// keeps no TTLs, such as the passthrough [`NoopEngine`], restores every value as
+    /// never expiring. Column TTLs are carried for the storage to see but never override
+    /// the remaining TTL of a value. Columns registered with this engine are restored with
+    /// their registered idle TTL and [`SledLayout`], other columns with those of the snapshot.
+    ///
+    /// # Errors
+    /// Returns [`CacheError::Put`] if an insert fails, earlier values stay inserted.
+    /// Returns [`CacheError::Engine`] if `reader` fails, the snapshot is cut short, or it is
+    /// not a snapshot or of a format version this engine does not read
+    pub fn try_restore(&self, reader: &mut dyn Read) -> Result<usize, CacheError> {
+        let version = archive::read_snapshot_header(reader)?;
+
+        let mut restored = 0;
+        while let Some((c, entries)) = archive::read_column_header(reader, version)? {
+            let c = if self.columns.contains_key(&c.name) {
+                self.registered_column(c.name)
+            } else {
+                c
+            };
+
+            for _ in 0..entries {
+                let Some((key, value, ttl_in_seconds)) = archive::read_record(reader)? else {
+                    return Err(CacheError::Engine(format!(
+                        "snapshot ends within column `{}`",
+                        c.name
+                    )));
+                };
+                let ttl_in_seconds = i32::try_from(ttl_in_seconds).unwrap_or(i32::MAX);
+
+                self.storage()
+                    .try_insert_with_ttl(&c, &key, &value, ttl_in_seconds)?;
+                restored += 1;
+            }
+        }
+
+        Ok(restored)
+    }
+
+    /// Histogram of the ages of the column's live values, to tell whether values are
+    /// read well before they expire or expire cold.
+    ///
+    /// `buckets` are ascending upper bounds, and the result holds one more count than there
+    /// are buckets: the values younger than the first bound, then those between each bound
+    /// and the next, then those at least as old as the last bound.
+    /// Ages count stored keys, so a chunked value counts once per chunk.
+    ///
+    /// On sled the age comes from the write time stored with each value. Redis only keeps
+    /// the time left to live, so the age is estimated as the column TTL minus that: values
+    /// written with [`Engine::try_insert_with_ttl`], a TTL jitter or by another writer
+    /// skew the estimate, and values that never expire are left out. The passthrough
+    /// [`NoopEngine`] does not record write times.
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// # fn ages(engine: &omega_cache::Engine, column: &dyn omega_cache::ColumnDefinition) -> Result<(), omega_cache::CacheError> {
+    /// // under a minute, under an hour, an hour or older
+    /// let histogram = engine.try_age_distribution(
+    ///     column,
+    ///     &[Duration::from_mins(1), Duration::from_hours(1)],
+    /// )?;
+    /// assert_eq!(histogram.len(), 3);
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    /// Returns [`CacheError::Engine`] if the buckets are not in ascending order
+    /// or the storage does not record ages.
+    /// Returns [`CacheError::Get`] if the column cannot be read
+    pub fn try_age_distribution(
+        &self,
+        c: &dyn ColumnDefinition,
+        buckets: &[Duration],
+    ) -> Result<Vec<usize>, CacheError> {
+        self.check_column(c)?;
+        if !buckets.is_sorted() {
+            return Err(CacheError::Engine(
+                "age buckets must be in ascending order".to_string(),
+            ));
+        }
+
+        let mut histogram = vec![0; buckets.len() + 1];
+        for age in self.storage().try_entry_ages(c)? {
+            histogram[buckets.partition_point(|bound| *bound <= age)] += 1;
+        }
+
+        Ok(histogram)
+    }
+
+    /// Number of keys [`Engine::try_drop_column`] would remove, without removing them,
+    /// to check the blast radius of a drop before running it.
+    ///
+    /// Includes the keys of the column's [`ColumnDefinition::index`] and expired keys not yet
+    /// cleaned up. On sled this is the size of the column's tree. On redis it is the number of
+    /// keys matched by the same SCAN pattern the drop uses, so keys written outside
+    /// [`Engine`] that happen to match are counted too, and like any SCAN it is slow
+    /// on large keyspaces.
+    ///
+    /// # Errors
+    /// Returns [`CacheError::Engine`] if the keys cannot be counted
+    pub fn try_drop_column_dry_run(&self, c: &dyn ColumnDefinition) -> Result<usize, CacheError> {
+        let mut keys = self.storage().try_count_column(c)?;
+        if c.index().is_some() {
+            keys += self
+                .storage()
+                .try_count_column(&IndexColumn { column: c })?;
+        }
+
+        Ok(keys)
+    }
+
+    /// Keys whose live values have the index key, under the column's [`ColumnDefinition::index`],
+    /// for example every session of a user so they can be invalidated together.
+    ///
+    /// The index is stored in a companion `<column>#index` column and updated on every insert
+    /// through [`Engine`]. Keys whose values expired or now map to another index key are
+    /// filtered out, by reading each value, and pruned from the index. Updates to an index entry are committed only if
+    /// it is unchanged since read, see [`CacheStorage::try_commit`], so concurrent inserts under
+    /// the same index key keep every key. On storages without transactions they can drop keys.
+    /// Values written before the index was declared are not in it.
+    ///
+    /// # Errors
+    /// Returns [`CacheError::Engine`] if the column has no index.
+    /// Returns [`CacheError::Get`] if the index or values cannot be read
+    pub fn try_get_by_index<K: AsRef<[u8]>>(
+        &self,
+        c: &dyn ColumnDefinition,
+        index_key: &K,
+    ) -> Result<Vec<Vec<u8>>, CacheError> {
+        self.check_column(c)?;
+        let Some(index) = c.index() else {
+            return Err(CacheError::Engine(format!(
+                "column `{}` has no index",
+                c.name()
+            )));
+        };
+
+        let index_key = index_key.as_ref();
+        let indexed = self.index_keys(c, index_key)?;
+        let mut keys = Vec::with_capacity(indexed.len());
+
+        for batch in indexed.chunks(self.batch_size) {
+            let batch = batch.iter().map(Vec::as_slice).collect::<Vec<&[u8]>>();
+
+            for (key, bytes) in batch.iter().zip(self.storage().try_get_many(c, &batch)?) {
+                let Some(bytes) = bytes else {
+                    continue;
+                };
+                let Some(value) = self.load_value(c, key, &bytes)? else {
+                    continue;
+                };
+
+                let payload = self.payload(c, key, &value)?;
+                if index.index_key(key, &payload).as_deref() == Some(index_key) {
+                    keys.push(key.to_vec());
+                }
+            }
+        }
+
+        if keys.len() < indexed.len() && !c.read_only() {
+            // keys indexed since the entry was read are kept
+            let stale = indexed
+                .iter()
+                .filter(|key| !keys.contains(key))
+                .collect::<std::collections::HashSet<_>>();
+            self.update_index(c, index_key, |current| {
+                current.retain(|key| !stale.contains(key));
+            })?;
+        }
+
+        Ok(keys)
+    }
+
+    /// Names of the columns currently held by the storage, sorted.
+    ///
+    /// Useful for inspection tools that do not know column names up front.
+    /// On redis this scans the whole keyspace so is expensive, and only
+    /// approximate as any key containing `:` is taken to belong to a column.
+    ///
+    /// # Errors
+    /// Returns [`CacheError::Engine`] if the columns cannot be listed
+    pub fn try_list_columns(&self) -> Result<Vec<String>, CacheError> {
+        self.storage().try_list_columns()
+    }
+
+    /// Name, entry count and size of every column currently held by the storage, sorted by name.
+    ///
+    /// Gathered in a single pass over the storage for an operations overview.
+    /// Entries are stored keys, so a chunked value counts once per chunk and
+    /// secondary indexes show up as their own `#index` columns.
+    /// On sled every tree is walked and `size_on_disk` is the bytes of its keys and values
+    /// before compression. On redis the whole keyspace is scanned, which is slow
+    /// and adds load on large keyspaces, and `size_on_disk` is not known.
+    /// Columns registered with [`Engine::register_column`] carry their description.
+    ///
+    /// # Errors
+    /// Returns [`CacheError::Engine`] if the columns cannot be measured
+    pub fn try_report(&self) -> Result<Vec<ColumnReport>, CacheError> {
+        let mut report = self.storage().try_report()?;
+
+        for column in &mut report {
+            if let Some(registered) = self.columns.get(&column.name) {
+                column.description.clone_from(&registered.description);
+            }
+        }
+
+        Ok(report)
+    }
+
+    /// Have the storage reclaim space held by expired or dropped items.
+    ///
+    /// On sled this is `SledEngine::try_compact`, which walks every
+    /// tree, so it is expensive on large databases and best run off the hot path,
+    /// for example on a timer or after a large drop. Redis and the other storages
+    /// reclaim space as they go, so it does nothing there.
+    ///
+    /// # Errors
+    /// Returns [`CacheError::Put`] if the storage cannot be maintained
+    pub fn try_maintenance(&self) -> Result<(), CacheError> {
+        self.storage().try_maintenance()
+    }
+
+    /// Atomically add `delta` to an [`i64`] counter and return the new value.
+    ///
+    /// A missing or expired counter starts at zero and takes the column TTL.
+    /// Counters are written by the storage directly so they bypass checksums
+    /// and always use [`Codec::Standard`], and on redis they are stored as plain integers rather than bincode,
+    /// so read them back with a `delta` of zero rather than [`Engine::try_get`].
+    ///
+    /// # Errors
+    /// Returns [`CacheError::Put`] with `counter overflow` if the result would not fit in an [`i64`],
+    /// the stored counter is left unchanged in that case.
+    /// Returns [`CacheError::Engine`] if the storage does not support counters.
+    /// Returns [`CacheError::Put`] if the column is read only, whatever the `delta`
+    pub fn try_increment<'a, K: AsRef<[u8]> + 'a>(
+        &'a self,
+        c: &dyn ColumnDefinition,
+        key: &'a K,
+        delta: i64,
+    ) -> Result<i64, CacheError> {
+        // even a delta of zero writes, as storage creates a missing counter
+        check_writable(c)?;
+
+        self.check_column(c)?;
+        self.storage().try_increment(c, key.as_ref(), delta)
+    }
+
+    /// Decode a value read from storage, if there was one and none of its chunks are missing
+    fn read_stored<V: Decode<()> + Encode>(
+        &self,
+        c: &dyn ColumnDefinition,
+        key: &[u8],
+        bytes: Option<Vec<u8>>,
+    ) -> Result<Option<V>, CacheError> {
+        let Some(bytes) = bytes else {
+            return Ok(None);
+        };
+
+        match self.load_value(c, key, &bytes)? {
+            Some(value) => self.read_value(c, key, &value).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Encode a value, writing its chunks to storage if it is too large to store whole,
+    /// and return the bytes to store at key.
+    ///
+    /// Chunks are written with the TTL override if there is one, otherwise the column TTL
+    fn store_value<V: Encode>(
+        &self,
+        c: &dyn ColumnDefinition,
+        key: &[u8],
+        value: &V,
+        ttl_in_seconds: Option<i32>,
+    ) -> Result<Vec<u8>, CacheError> {
+        self.check_key(key, CacheError::Put)?;
+        let value_bytes = self.encode_value(c, key, value)?;
+
+        let Some(chunk_size) = self.chunk_size else {
+            return Ok(value_bytes);
+        };
+
+        if value_bytes.len() <= chunk_size {
+            return Ok(chunking::inline(&value_bytes));
+        }
+
+        let mut chunks = 0u32;
+        for chunk in value_bytes.chunks(chunk_size) {
+            let chunk_key = chunking::chunk_key(key, chunks);
+
+            match ttl_in_seconds {
+                Some(ttl) => self
+                    .storage()
+                    .try_insert_with_ttl(c, &chunk_key, chunk, ttl)?,
+                None => self.storage().try_insert(c, &chunk_key, chunk)?,
+            }
+
+            chunks = chunks
+                .checked_add(1)
+                .ok_or_else(|| CacheError::encode::<V>("value has too many chunks".to_string()))?;
+        }
+
+        Ok(chunking::manifest(chunks, &value_bytes))
+    }
+
+    /// Undo [`Engine::store_value`] on the bytes at key, reading and joining chunks if needed
+    /// and checking the format marker, which is left on the value.
+    ///
+    /// Returns [`None`] if any chunk is missing or they do not add up to the value,
+    /// or on a format mismatch read as a miss
+    fn load_value<'b>(
+        &self,
+        c: &dyn ColumnDefinition,
+        key: &[u8],
+        bytes: &'b [u8],
+    ) -> Result<Option<Cow<'b, [u8]>>, CacheError> {
+        let Some(value) = self.join_chunks(c, key, bytes)? else {
+            return Ok(None);
+        };
+
+        let Some(on_mismatch) = self.format_marker else {
+            return Ok(Some(value));
+        };
+
+        match value.first() {
+            Some(marker) if self.marked_codec(&value).is_none() => match on_mismatch {
+                FormatMismatch::Miss => Ok(None),
+                FormatMismatch::Error => Err(CacheError::decode(
+                    c,
+                    key,
+                    format!(
+                        "value has format marker {marker:#04x}, which this engine does not read"
+                    ),
+                )),
+            },
+            Some(_) => Ok(Some(value)),
+            None => match on_mismatch {
+                FormatMismatch::Miss => Ok(None),
+                FormatMismatch::Error => Err(CacheError::decode(
+                    c,
+                    key,
+                    "value has no format marker".to_string(),
+                )),
+            },
+        }
+    }
+
+    /// Codec named by the format marker of a loaded value,
+    /// [`None`] without markers or if the engine does not read the codec
+    fn marked_codec(&self, bytes: &[u8]) -> Option<Codec> {
+        self.format_marker?;
+
+        Codec::from_marker(*bytes.first()?)
+            .filter(|codec| *codec == self.codec || self.legacy_codecs.contains(codec))
+    }
+
+    /// Bytes at key, reading and joining chunks if needed.
+    ///
+    /// Returns [`None`] if any chunk is missing or they do not add up to the value
+    fn join_chunks<'b>(
+        &self,
+        c: &dyn ColumnDefinition,
+        key: &[u8],
+        bytes: &'b [u8],
+    ) -> Result<Option<Cow<'b, [u8]>>, CacheError> {
+        if self.chunk_size.is_none() {
+            return Ok(Some(Cow::Borrowed(bytes)));
+        }
+
+        let (chunks, len, checksum) = match chunking::parse(bytes) {
+            Some(chunking::Stored::Inline(value)) => return Ok(Some(Cow::Borrowed(value))),
+            Some(chunking::Stored::Manifest {
+                chunks,
+                len,
+                checksum,
+            }) => (chunks, len, checksum),
+            None => {
+                return Err(CacheError::decode(
+                    c,
+                    key,
+                    "value was not written with chunking enabled".to_string(),
+                ));
+            }
+        };
+
+        let chunk_keys = (0..chunks)
+            .map(|index| chunking::chunk_key(key, index))
+            .collect::<Vec<_>>();
+        let mut value = Vec::with_capacity(usize::try_from(len).unwrap_or_default());
+
+        for batch in chunk_keys.chunks(self.batch_size) {
+            let batch = batch.iter().map(Vec::as_slice).collect::<Vec<&[u8]>>();
+
+            for chunk in self.storage().try_get_many(c, &batch)? {
+                let Some(chunk) = chunk else {
+                    return Ok(None);
+                };
+
+                value.extend_from_slice(&chunk);
+            }
+        }
+
+        if !chunking::matches(&value, len, checksum) {
+            return Ok(None);
+        }
+
+        Ok(Some(Cow::Owned(value)))
+    }
+
+    /// Decode a value read from storage,
+    /// rewriting it with the current codec if a legacy codec decoded it
+    fn read_value<V: Decode<()> + Encode>(
+        &self,
+        c: &dyn ColumnDefinition,
+        key: &[u8],
+        bytes: &[u8],
+    ) -> Result<V, CacheError> {
+        let (value, codec) = self.decode_value(c, key, bytes, false)?;
+
+        if self.rewrite_legacy && codec != self.codec && !c.read_only() {
+            self.try_insert(c, &key, &value)?;
+        }
+
+        Ok(value)
+    }
+
+    /// Column named `name` with the settings it was registered with,
+    /// or no TTL and a dedicated tree if it is not registered
+    fn registered_column(&self, name: String) -> archive::SnapshotColumn {
+        match self.columns.get(&name) {
+            Some(registered) => archive::SnapshotColumn {
+                ttl_in_seconds: registered.ttl_in_seconds,
+                idle_ttl_in_seconds: registered.idle_ttl_in_seconds,
+                storage_layout: registered.storage_layout,
+                name,
+            },
+            None => archive::SnapshotColumn {
+                name,
+                ttl_in_seconds: 0,
+                idle_ttl_in_seconds: None,
+                storage_layout: SledLayout::DedicatedTree,
+            },
+        }
+    }
+
+    /// Fail use of an unregistered column when columns are strict
+    fn check_column(&self, c: &dyn ColumnDefinition) -> Result<(), CacheError> {
+        if self.strict_columns && !self.columns.contains_key(&c.name()) {
+            return Err(CacheError::Engine("unknown column".to_string()));
+        }
+
+        Ok(())
+    }
+
+    /// TTL in seconds of inserts into the column, its own or the [`Engine::with_default_ttl`]
+    fn column_ttl(&self, c: &dyn ColumnDefinition) -> i32 {
+        match (c.get_ttl_in_seconds(), self.default_ttl) {
+            (ttl, Some(default)) if ttl <= 0 => default,
+            (ttl, _) => ttl,
+        }
+    }
+
+    /// Fail keys longer than [`Engine::with_max_key_bytes`] with the error of the operation,
+    /// [`CacheError::Put`] or [`CacheError::Get`]
+    fn check_key(&self, key: &[u8], error: fn(String) -> CacheError) -> Result<(), CacheError> {
+        match self.max_key_bytes {
+            Some(max) if key.len() > max => Err(error("key exceeds max length".to_string())),
+            _ => Ok(()),
+        }
+    }
+
+    /// The storage, or one that discards everything when the engine is disabled
+    fn storage(&self) -> &(dyn CacheStorage + Sync + Send + 'static) {
+        self.storage.as_deref().unwrap_or(&DISABLED)
+    }
+
+    /// Encode a value to the bytes handed to storage, adding key to the column index if it has one
+    fn encode_value<V: Encode>(
+        &self,
+        c: &dyn ColumnDefinition,
+        key: &[u8],
+        value: &V,
+    ) -> Result<Vec<u8>, CacheError> {
+        let value_bytes = self
+            .codec
+            .encode(value)
+            .map_err(|e| CacheError::encode::<V>(e.to_string()))?;
+
+        if let Some(index) = c.index() {
+            self.add_to_index(c, index, key, &value_bytes)?;
+        }
+
+        let value_bytes = self.apply_middleware::<V>(value_bytes)?;
+        let value_bytes = self.compress::<V>(value_bytes)?;
+        let value_bytes = self.encrypt::<V>(c, key, value_bytes)?;
+
+        let value_bytes = if self.checksums {
+            checksum::seal(&value_bytes)
+        } else {
+            value_bytes
+        };
+
+        if self.format_marker.is_some() {
+            return Ok([&[self.codec.marker()], value_bytes.as_slice()].concat());
+        }
+
+        Ok(value_bytes)
+    }
+
+    /// Pass encoded value bytes through the middleware stack in order
+    fn apply_middleware<V>(&self, bytes: Vec<u8>) -> Result<Vec<u8>, CacheError> {
+        self.middleware
+            .iter()
+            .try_fold(bytes, |bytes, middleware| middleware.on_write(bytes))
+            .map_err(|e| match e {
+                CacheError::Encode { source, .. } => CacheError::encode::<V>(source),
+                e => e,
+            })
+    }
+
+    /// Undo the middleware stack on decompressed value bytes for key, in reverse order
+    fn undo_middleware<'b>(
+        &self,
+        c: &dyn ColumnDefinition,
+        key: &[u8],
+        bytes: Cow<'b, [u8]>,
+    ) -> Result<Cow<'b, [u8]>, CacheError> {
+        if self.middleware.is_empty() {
+            return Ok(bytes);
+        }
+
+        self.middleware
+            .iter()
+            .rev()
+            .try_fold(bytes.into_owned(), |bytes, middleware| {
+                middleware.on_read(bytes)
+            })
+            .map(Cow::Owned)
+            .map_err(|e| match e {
+                CacheError::Decode { source, .. } => CacheError::decode(c, key, source),
+                e => e,
+            })
+    }
+
+    /// Compress encoded value bytes if compression is enabled
+    #[cfg_attr(
+        not(feature = "gzip"),
+        allow(
+            clippy::unnecessary_wraps,
+            clippy::unused_self,
+            clippy::extra_unused_type_parameters
+        )
+    )]
+    fn compress<V>(&self, bytes: Vec<u8>) -> Result<Vec<u8>, CacheError> {
+        #[cfg(feature = "gzip")]
+        if let Some(compression) = self.compression {
+            return compression
+                .compress(&bytes)
+                .map_err(|e| CacheError::encode::<V>(e.to_string()));
+        }
+
+        Ok(bytes)
+    }
+
+    /// Decompress decrypted value bytes for key if compression is enabled
+    #[cfg_attr(
+        not(feature = "gzip"),
+        allow(clippy::unnecessary_wraps, clippy::unused_self, unused_variables)
+    )]
+    fn decompress<'b>(
+        &self,
+        c: &dyn ColumnDefinition,
+        key: &[u8],
+        bytes: Cow<'b, [u8]>,
+    ) -> Result<Cow<'b, [u8]>, CacheError> {
+        #[cfg(feature = "gzip")]
+        if self.compression.is_some() {
+            return compression::decompress(bytes)
+                .ok_or_else(|| CacheError::decode(c, key, "decompression failed".to_string()));
+        }
+
+        Ok(bytes)
+    }
+
+    /// Encrypt encoded value bytes for key if encryption is enabled
+    #[cfg_attr(
+        not(feature = "encryption"),
+        allow(
+            clippy::unnecessary_wraps,
+            clippy::unused_self,
+            clippy::extra_unused_type_parameters,
+            unused_variables
+        )
+    )]
+    fn encrypt<V>(
+        &self,
+        c: &dyn ColumnDefinition,
+        key: &[u8],
+        bytes: Vec<u8>,
+    ) -> Result<Vec<u8>, CacheError> {
+        #[cfg(feature = "encryption")]
+        if let Some(encryptor) = &self.encryptor {
+            return encryptor
+                .seal(&c.name(), key, &bytes)
+                .map_err(|e| CacheError::encode::<V>(e.to_string()));
+        }
+
+        Ok(bytes)
+    }
+
+    /// Decrypt value bytes returned by storage for key if encryption is enabled
+    #[cfg_attr(
+        not(feature = "encryption"),
+        allow(clippy::unnecessary_wraps, clippy::unused_self, unused_variables)
+    )]
+    fn decrypt<'b>(
+        &self,
+        c: &dyn ColumnDefinition,
+        key: &[u8],
+        bytes: &'b [u8],
+    ) -> Result<Cow<'b, [u8]>, CacheError> {
+        #[cfg(feature = "encryption")]
+        if let Some(encryptor) = &self.encryptor {
+            return encryptor
+                .open(&c.name(), key, bytes)
+                .map(Cow::Owned)
+                .ok_or_else(|| CacheError::decode(c, key, "decryption failed".to_string()));
+        }
+
+        Ok(Cow::Borrowed(bytes))
+    }
+
+    /// Verify, decrypt and decompress the bytes returned by storage for key and undo
+    /// the middleware stack, leaving the value as encoded by the codec
+    fn payload<'b>(
+        &self,
+        c: &dyn ColumnDefinition,
+        key: &[u8],
+        bytes: &'b [u8],
+    ) -> Result<Cow<'b, [u8]>, CacheError> {
+        // the marker was checked when the value was loaded
+        let bytes = if self.format_marker.is_some() {
+            bytes.get(1..).unwrap_or_default()
+        } else {
+            bytes
+        };
+
+        let payload = if self.checksums {
+            checksum::unseal(bytes)
+                .ok_or_else(|| CacheError::decode(c, key, "checksum mismatch".to_string()))?
+        } else {
+            bytes
+        };
+
+        let payload = self.decrypt(c, key, payload)?;
+        let payload = self.decompress(c, key, payload)?;
+
+        self.undo_middleware(c, key, payload)
+    }
+
+    /// Keys currently held by the index entry for index key
+    fn index_keys(
+        &self,
+        c: &dyn ColumnDefinition,
+        index_key: &[u8],
+    ) -> Result<Vec<Vec<u8>>, CacheError> {
+        match self
+            .storage()
+            .try_get(&IndexColumn { column: c }, index_key)?
+        {
+            Some(bytes) => index::decode_keys(&bytes).map_err(|e| {
+                CacheError::decode(&IndexColumn { column: c }, index_key, e.to_string())
+            }),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    /// Add key to the index entry for the encoded value
+    fn add_to_index(
+        &self,
+        c: &dyn ColumnDefinition,
+        index: &dyn ColumnIndex,
+        key: &[u8],
+        value: &[u8],
+    ) -> Result<(), CacheError> {
+        let Some(index_key) = index.index_key(key, value) else {
+            return Ok(());
+        };
+
+        self.update_index(c, &index_key, |keys| {
+            if !keys.iter().any(|k| k == key) {
+                keys.push(key.to_vec());
+            }
+        })
+    }
+
+    /// Rewrite the keys held by the index entry for index key with `update`.
+    ///
+    /// The entry is only written if it is unchanged since it was read, retrying otherwise,
+    /// so concurrent updates are not lost. Storages without transactions have it
+    /// written over, where concurrent updates can lose keys.
+    fn update_index(
+        &self,
+        c: &dyn ColumnDefinition,
+        index_key: &[u8],
+        update: impl Fn(&mut Vec<Vec<u8>>),
+    ) -> Result<(), CacheError> {
+        let column = IndexColumn { column: c };
+
+        loop {
+            let stored = self.storage().try_get(&column, index_key)?;
+            let mut keys = match &stored {
+                Some(bytes) => index::decode_keys(bytes)
+                    .map_err(|e| CacheError::decode(&column, index_key, e.to_string()))?,
+                None => Vec::new(),
+            };
+            update(&mut keys);
+
+            let bytes = index::encode_keys(&keys)
+                .map_err(|e| CacheError::encode::<Vec<Vec<u8>>>(e.to_string()))?;
+            let unchanged = |current: Option<&[u8]>| Ok(current == stored.as_deref());
+
+            match self.storage().try_commit(
+                &column,
+                &[TransactionCondition {
+                    key: index_key,
+                    holds: &unchanged,
+                }],
+                &[TransactionWrite {
+                    key: index_key,
+                    value: Some(&bytes),
+                }],
+            ) {
+                Ok(true) => return Ok(()),
+                Ok(false) => {}
+                // a storage that cannot commit fails the same way writing over the entry
+                Err(CacheError::Engine(_)) => {
+                    return self.storage().try_insert(&column, index_key, &bytes);
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    /// Decode the bytes returned by storage for key,
+    /// returning the value and the codec that decoded it.
+    ///
+    /// With `whole` the value must take up every byte, as it always must once legacy codecs are set
+    fn decode_value<V: Decode<()>>(
+        &self,
+        c: &dyn ColumnDefinition,
+        key: &[u8],
+        bytes: &[u8],
+        whole: bool,
+    ) -> Result<(V, Codec), CacheError> {
+        let marked = self.marked_codec(bytes);
+        let payload = &*self.payload(c, key, bytes)?;
+
+        if (self.legacy_codecs.is_empty() || marked.is_some()) && !whole {
+            let codec = marked.unwrap_or(self.codec);
+
+            return codec
+                .decode(payload, self.decode_limit)
+                .map_err(|e| CacheError::decode(c, key, e.to_string()))
+                .map(|v| (v.0, codec));
+        }
+
+        let codecs = match marked {
+            Some(codec) => vec![codec],
+            None => std::iter::once(self.codec)
+                .chain(self.legacy_codecs.iter().copied())
+                .collect(),
+        };
+
+        let mut first_error = None;
+
+        for codec in codecs {
+            match codec.decode(payload, self.decode_limit) {
+                Ok((value, read)) if read == payload.len() => return Ok((value, codec)),
+                Ok(_) => {
+                    first_error.get_or_insert_with(|| "trailing bytes after value".to_string());
+                }
+                Err(e) => {
+                    first_error.get_or_insert_with(|| e.to_string());
+                }
+            }
+        }
+
+        Err(CacheError::decode(c, key, first_error.unwrap_or_default()))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use crate::{
+        BackendConfig, CacheError, Codec, ColumnDefinition, ColumnIndex, ColumnReport, Either,
+        Engine, FormatMismatch, InsertOutcome, key_hint, noop_engine::NoopEngine,
+    };
+
+    struct TestColumn {}
+
+    impl ColumnDefinition for TestColumn {
+        fn name(&self) -> String {
+            "test_column".to_string()
+        }
+
+        fn get_ttl_in_seconds(&self) -> i32 {
+            1
+        }
+    }
+
+    const COLUMN: TestColumn = TestColumn {};
+
+    #[test]
+    fn test_default_engine() {
+        let engine = Engine::default();
+
+        assert!(engine.try_insert(&COLUMN, &String::new(), &100i32).is_ok());
+        assert!(engine.try_get::<&str, i32>(&COLUMN, &"").is_ok());
+        assert!(engine.try_drop_column(&COLUMN).is_ok());
+    }
+
+    #[test]
+    fn test_from_url() {
+        assert!(Engine::from_url("noop://").is_ok());
+        assert!(Engine::from_url("memory://").is_ok());
+        assert!(Engine::from_url("localhost:6379").is_err());
+        assert!(Engine::from_url("memcached://localhost").is_err());
+
+        #[cfg(not(feature = "sled"))]
+        assert!(Engine::from_url("sled://./tmp/from_url").is_err());
+    }
+
+    #[test]
+    fn test_get_or_insert_with() {
+        let engine = Engine::new(NoopEngine::passthrough());
+        let mut calls = 0;
+
+        for _ in 0..2 {
+            let value = engine.try_get_or_insert_with(&COLUMN, &"key", || {
+                calls += 1;
+                100i32
+            });
+
+            assert_eq!(value.unwrap(), 100);
+        }
+
+        assert_eq!(calls, 1);
+        assert_eq!(
+            engine
+                .try_get_or_insert_with_ttl(&COLUMN, &"other", Some(1), || 5i32)
+                .unwrap(),
+            5
+        );
+    }
+
+    #[test]
+    fn test_insert_reporting() {
+        let engine = Engine::new(NoopEngine::passthrough());
+
+        assert_eq!(
+            engine.try_insert_reporting(&COLUMN, &"key", &1i32).unwrap(),
+            InsertOutcome::Created
+        );
+        assert_eq!(
+            engine.try_insert_reporting(&COLUMN, &"key", &2i32).unwrap(),
+            InsertOutcome::Overwrote
+        );
+    }
+
+    #[test]
+    fn test_preload() {
+        let engine = Engine::new(NoopEngine::passthrough());
+
+        let count = engine
+            .try_preload(&COLUMN, (0..2500u32).map(|i| (i.to_be_bytes(), i)))
+            .unwrap();
+
+        assert_eq!(count, 2500);
+        assert_eq!(
+            engine
+                .try_get::<[u8; 4], u32>(&COLUMN, &2499u32.to_be_bytes())
+                .unwrap(),
+            Some(2499)
+        );
+    }
+
+    #[test]
+    fn test_max_key_bytes() {
+        let engine = Engine::new(NoopEngine::passthrough()).with_max_key_bytes(4);
+
+        assert!(engine.try_insert(&COLUMN, &"four", &1u8).is_ok());
+        assert_eq!(engine.try_get::<_, u8>(&COLUMN, &"four").unwrap(), Some(1));
+
+        assert!(matches!(
+            engine.try_insert(&COLUMN, &"fives", &1u8),
+            Err(CacheError::Put(message)) if message == "key exceeds max length"
+        ));
+        assert!(matches!(
+            engine.try_get::<_, u8>(&COLUMN, &"fives"),
+            Err(CacheError::Get(message)) if message == "key exceeds max length"
+        ));
+        assert!(
+            engine
+                .try_get_many::<_, u8>(&COLUMN, &["four", "fives"])
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_get_required() {
+        let engine = Engine::new(NoopEngine::passthrough());
+
+        assert!(matches!(
+            engine.try_get_required::<&str, i32>(&COLUMN, &"key"),
+            Err(CacheError::NotFound { key_hint, .. }) if key_hint == "key"
+        ));
+        assert!(
+            engine
+                .try_get_required::<&str, i32>(&COLUMN, &"key")
+                .is_err_and(|e| e.is_not_found())
+        );
+
+        assert!(engine.try_insert(&COLUMN, &"key", &100i32).is_ok());
+        assert_eq!(
+            engine
+                .try_get_required::<&str, i32>(&COLUMN, &"key")
+                .unwrap(),
+            100
+        );
+    }
+
+    #[test]
+    fn test_read_only_column() {
+        struct ReadOnlyColumn;
+
+        impl ColumnDefinition for ReadOnlyColumn {
+            fn name(&self) -> String {
+                "read_only".to_string()
+            }
+
+            fn get_ttl_in_seconds(&self) -> i32 {
+                10
+            }
+
+            fn read_only(&self) -> bool {
+                true
+            }
+        }
+
+        let engine = Engine::new(NoopEngine::passthrough());
+
+        assert!(matches!(
+            engine.try_insert(&ReadOnlyColumn, &"key", &1i32),
+            Err(CacheError::Put(message)) if message == "column is read-only"
+        ));
+        assert!(engine.try_drop_column(&ReadOnlyColumn).is_err());
+        assert!(engine.try_increment(&ReadOnlyColumn, &"count", 1).is_err());
+        assert!(engine.try_increment(&ReadOnlyColumn, &"count", 0).is_err());
+        assert!(
+            engine
+                .try_get::<&str, i32>(&ReadOnlyColumn, &"key")
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_get_many() {
+        let engine = Engine::new(NoopEngine::passthrough()).with_batch_size(2);
+
+        assert_eq!(
+            engine
+                .try_preload(&COLUMN, [("a", 1i32), ("c", 3i32)])
+                .unwrap(),
+            2
+        );
+        assert_eq!(
+            engine
+                .try_get_many::<&str, i32>(&COLUMN, &["a", "b", "c"])
+                .unwrap(),
+            vec![Some(1), None, Some(3)]
+        );
+    }
+
+    #[test]
+    fn test_chunked_values() {
+        struct SmallColumn;
+
+        impl ColumnDefinition for SmallColumn {
+            fn name(&self) -> String {
+                "small".to_string()
+            }
+
+            fn get_ttl_in_seconds(&self) -> i32 {
+                10
+            }
+
+            fn max_entries(&self) -> Option<usize> {
+                Some(4)
+            }
+        }
+
+        let engine = Engine::new(NoopEngine::passthrough())
+            .with_checksums()
+            .with_chunking(64);
+        let large = vec![7u8; 1000];
+
+        assert!(engine.try_insert(&COLUMN, &"small", &1i32).is_ok());
+        assert!(engine.try_insert(&COLUMN, &"large", &large).is_ok());
+
+        assert_eq!(
+            engine.try_get::<&str, i32>(&COLUMN, &"small").unwrap(),
+            Some(1)
+        );
+        assert_eq!(
+            engine.try_get::<&str, Vec<u8>>(&COLUMN, &"large").unwrap(),
+            Some(large.clone())
+        );
+        assert_eq!(
+            engine
+                .try_get_borrowed(&COLUMN, &"large")
+                .unwrap()
+                .unwrap()
+                .decode::<&[u8]>()
+                .unwrap(),
+            large.as_slice()
+        );
+
+        // a manifest over the chunks of another value of the same length reads as a miss
+        let manifest = engine
+            .storage()
+            .try_get(&COLUMN, b"large")
+            .unwrap()
+            .unwrap();
+        assert!(
+            engine
+                .try_insert(&COLUMN, &"large", &vec![8u8; 1000])
+                .is_ok()
+        );
+        assert!(
+            engine
+                .storage()
+                .try_insert(&COLUMN, b"large", &manifest)
+                .is_ok()
+        );
+        assert!(
+            engine
+                .try_get::<&str, Vec<u8>>(&COLUMN, &"large")
+                .unwrap()
+                .is_none()
+        );
+
+        // evicting early chunks leaves the manifest pointing at missing chunks
+        assert!(engine.try_insert(&SmallColumn, &"large", &large).is_ok());
+        assert!(
+            engine
+                .try_get::<&str, Vec<u8>>(&SmallColumn, &"large")
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_value_size() {
+        let engine = Engine::new(NoopEngine::passthrough()).with_checksums();
+
+        assert!(engine.try_insert(&COLUMN, &"key", &[1u8; 10]).is_ok());
+        assert_eq!(engine.try_value_size(&COLUMN, &"key").unwrap(), Some(14));
+        assert_eq!(engine.try_value_size(&COLUMN, &"missing").unwrap(), None);
+    }
+
+    #[test]
+    fn test_typed_getters() {
+        let engine = Engine::new(NoopEngine::passthrough());
+
+        assert!(engine.try_insert(&COLUMN, &"string", &"value").is_ok());
+        assert!(engine.try_insert(&COLUMN, &"i64", &-5i64).is_ok());
+        assert!(
+            engine
+                .try_insert(&COLUMN, &"bytes", &vec![1u8, 2, 3])
+                .is_ok()
+        );
+
+        assert_eq!(
+            engine.try_get_string(&COLUMN, &"string").unwrap(),
+            Some("value".to_string())
+        );
+        assert_eq!(engine.try_get_i64(&COLUMN, &"i64").unwrap(), Some(-5));
+        assert_eq!(
+            engine.try_get_bytes(&COLUMN, &"bytes").unwrap(),
+            Some(vec![1, 2, 3])
+        );
+        assert_eq!(engine.try_get_string(&COLUMN, &"missing").unwrap(), None);
+    }
+
+    #[test]
+    fn test_swap() {
+        let engine = Engine::new(NoopEngine::passthrough()).with_chunking(64);
+
+        assert_eq!(engine.try_swap(&COLUMN, &"lease", &1i32).unwrap(), None);
+        assert_eq!(engine.try_swap(&COLUMN, &"lease", &2i32).unwrap(), Some(1));
+        assert_eq!(
+            engine.try_get::<&str, i32>(&COLUMN, &"lease").unwrap(),
+            Some(2)
+        );
+    }
+
+    #[test]
+    fn test_take() {
+        let engine = Engine::new(NoopEngine::passthrough());
+
+        assert!(engine.try_insert(&COLUMN, &"token", &1i32).is_ok());
+        assert_eq!(engine.try_take(&COLUMN, &"token").unwrap(), Some(1i32));
+        assert_eq!(engine.try_take::<_, i32>(&COLUMN, &"token").unwrap(), None);
+        assert_eq!(
+            engine.try_get::<&str, i32>(&COLUMN, &"token").unwrap(),
+            None
+        );
+
+        let inert = Engine::default();
+        assert_eq!(inert.try_take::<_, i32>(&COLUMN, &"token").unwrap(), None);
+    }
+
+    #[test]
+    fn test_describe_config() {
+        let engine = Engine::new(NoopEngine::passthrough());
+        assert_eq!(
+            engine.describe_config().to_string(),
+            "noop (passthrough=true)"
+        );
+
+        let config = Engine::new(NoopEngine::passthrough())
+            .with_key_prefix("v2")
+            .with_default_ttl(Duration::from_mins(1))
+            .describe_config();
+        assert_eq!(config.backend, "noop");
+        assert_eq!(config.get("default_ttl"), Some("60"));
+        assert!(
+            config
+                .get("key_prefix")
+                .is_some_and(|prefix| prefix.starts_with("v2"))
+        );
+        assert_eq!(config.get("missing"), None);
+
+        assert_eq!(BackendConfig::new("custom").to_string(), "custom");
+    }
+
+    #[test]
+    fn test_disabled_engine() {
+        let engine = Engine::disabled();
+
+        assert!(!engine.is_enabled());
+        assert!(Engine::default().is_enabled());
+        assert!(engine.try_insert(&COLUMN, &"key", &100i32).is_ok());
+        assert_eq!(engine.try_swap(&COLUMN, &"key", &100i32).unwrap(), None);
+        assert_eq!(engine.try_preload(&COLUMN, [("key", 1i32)]).unwrap(), 0);
+        assert_eq!(engine.try_get::<&str, i32>(&COLUMN, &"key").unwrap(), None);
+        assert_eq!(
+            engine
+                .try_get_or_insert_with(&COLUMN, &"key", || 7i32)
+                .unwrap(),
+            7
+        );
+    }
+
+    #[test]
+    fn test_discarded_writes_skip_encoding() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        static ENCODED: AtomicUsize = AtomicUsize::new(0);
+
+        struct Counted;
+
+        impl bincode::Encode for Counted {
+            fn encode<E: bincode::enc::Encoder>(
+                &self,
+                encoder: &mut E,
+            ) -> Result<(), bincode::error::EncodeError> {
+                ENCODED.fetch_add(1, Ordering::Relaxed);
+                bincode::Encode::encode(&0u8, encoder)
+            }
+        }
+
+        assert!(
+            Engine::default()
+                .try_insert(&COLUMN, &"key", &Counted)
+                .is_ok()
+        );
+        assert_eq!(ENCODED.load(Ordering::Relaxed), 0);
+
+        let engine = Engine::new(NoopEngine::passthrough());
+        assert!(engine.try_insert(&COLUMN, &"key", &Counted).is_ok());
+        assert!(ENCODED.load(Ordering::Relaxed) > 0);
+    }
+
+    #[test]
+    fn test_secondary_index() {
+        struct ByValue;
+
+        impl ColumnIndex for ByValue {
+            fn index_key(&self, _key: &[u8], value: &[u8]) -> Option<Vec<u8>> {
+                Some(value.to_vec())
+            }
+        }
+
+        struct IndexedColumn;
+
+        impl ColumnDefinition for IndexedColumn {
+            fn name(&self) -> String {
+                "indexed".to_string()
+            }
+
+            fn get_ttl_in_seconds(&self) -> i32 {
+                10
+            }
+
+            fn index(&self) -> Option<&dyn ColumnIndex> {
+                Some(&ByValue)
+            }
+        }
+
+        let engine = Engine::new(NoopEngine::passthrough()).with_checksums();
+
+        assert!(
+            engine
+                .try_insert(&IndexedColumn, &"session_1", &1u8)
+                .is_ok()
+        );
+        assert!(
+            engine
+                .try_insert(&IndexedColumn, &"session_2", &1u8)
+                .is_ok()
+        );
+        assert!(
+            engine
+                .try_insert(&IndexedColumn, &"session_3", &2u8)
+                .is_ok()
+        );
+
+        assert_eq!(
+            engine.try_get_by_index(&IndexedColumn, &[1]).unwrap(),
+            vec![b"session_1".to_vec(), b"session_2".to_vec()]
+        );
+
+        // moving a key to another index key drops it from the old one
+        assert!(
+            engine
+                .try_insert(&IndexedColumn, &"session_2", &2u8)
+                .is_ok()
+        );
+        assert_eq!(
+            engine.try_get_by_index(&IndexedColumn, &[1]).unwrap(),
+            vec![b"session_1".to_vec()]
+        );
+        assert_eq!(
+            engine.try_get_by_index(&IndexedColumn, &[2]).unwrap(),
+            vec![b"session_3".to_vec(), b"session_2".to_vec()]
+        );
+        assert!(engine.try_get_by_index(&COLUMN, &[1]).is_err());
+
+        // concurrent writes to one index key all land in it
+        std::thread::scope(|scope| {
+            for thread in 0..8 {
+                let engine = &engine;
+                scope.spawn(move || {
+                    for i in 0..25 {
+                        let key = format!("visit_{thread}_{i}");
+                        assert!(engine.try_insert(&IndexedColumn, &key, &3u8).is_ok());
+                    }
+                });
+            }
+        });
+        assert_eq!(
+            engine.try_get_by_index(&IndexedColumn, &[3]).unwrap().len(),
+            200
+        );
+        assert!(engine.try_drop_column(&IndexedColumn).is_ok());
+        assert!(
+            engine
+                .try_insert(&IndexedColumn, &"session_1", &1u8)
+                .is_ok()
+        );
+        assert!(
+            engine
+                .try_insert(&IndexedColumn, &"session_2", &2u8)
+                .is_ok()
+        );
+        assert!(
+            engine
+                .try_insert(&IndexedColumn, &"session_3", &2u8)
+                .is_ok()
+        );
+
+        // three values and two index keys
+        assert_eq!(engine.try_drop_column_dry_run(&IndexedColumn).unwrap(), 5);
+        assert!(engine.try_drop_column(&IndexedColumn).is_ok());
+        assert_eq!(engine.try_drop_column_dry_run(&IndexedColumn).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_rename() {
+        struct ByValue;
+
+        impl ColumnIndex for ByValue {
+            fn index_key(&self, _key: &[u8], value: &[u8]) -> Option<Vec<u8>> {
+                Some(value[..1].to_vec())
+            }
+        }
+
+        struct IndexedColumn;
+
+        impl ColumnDefinition for IndexedColumn {
+            fn name(&self) -> String {
+                "indexed".to_string()
+            }
+
+            fn get_ttl_in_seconds(&self) -> i32 {
+                10
+            }
+
+            fn index(&self) -> Option<&dyn ColumnIndex> {
+                Some(&ByValue)
+            }
+        }
+
+        let engine = Engine::new(NoopEngine::passthrough()).with_chunking(64);
+        let large = vec![7u8; 1000];
+
+        assert!(engine.try_insert(&IndexedColumn, &"staged", &large).is_ok());
+        assert!(engine.try_insert(&IndexedColumn, &"final", &1u8).is_ok());
+
+        assert!(
+            engine
+                .try_rename(&IndexedColumn, &"staged", &"final")
+                .unwrap()
+        );
+        assert!(
+            !engine
+                .try_rename(&IndexedColumn, &"staged", &"final")
+                .unwrap()
+        );
+        assert_eq!(
+            engine
+                .try_get::<_, Vec<u8>>(&IndexedColumn, &"final")
+                .unwrap(),
+            Some(large)
+        );
+        assert!(
+            engine
+                .try_get::<_, Vec<u8>>(&IndexedColumn, &"staged")
+                .unwrap()
+                .is_none()
+        );
+
+        // large is encoded with a length prefix of 251 then the length
+        assert_eq!(
+            engine.try_get_by_index(&IndexedColumn, &[251]).unwrap(),
+            vec![b"final".to_vec()]
+        );
+        assert!(
+            engine
+                .try_get_by_index(&IndexedColumn, &[1])
+                .unwrap()
+                .is_empty()
+        );
+
+        assert!(!Engine::default().try_rename(&COLUMN, &"a", &"b").unwrap());
+    }
+
+    #[test]
+    fn test_list_columns() {
+        let engine = Engine::new(NoopEngine::passthrough());
+
+        assert!(engine.try_list_columns().unwrap().is_empty());
+        assert!(engine.try_insert(&COLUMN, &"key", &1i32).is_ok());
+        assert_eq!(engine.try_list_columns().unwrap(), vec!["test_column"]);
+    }
+
+    #[test]
+    fn test_tagged_values() {
+        let engine = Engine::new(NoopEngine::passthrough());
+
+        assert!(engine.try_insert_tagged(&COLUMN, &"a", 1, &7i32).is_ok());
+        assert!(engine.try_insert_tagged(&COLUMN, &"b", 2, &"text").is_ok());
+
+        let (tag, stored) = engine.try_get_tagged(&COLUMN, &"a").unwrap().unwrap();
+        assert_eq!(tag, 1);
+        assert_eq!(stored.decode::<i32>().unwrap(), 7);
+
+        let (tag, stored) = engine.try_get_tagged(&COLUMN, &"b").unwrap().unwrap();
+        assert_eq!(tag, 2);
+        assert_eq!(stored.decode::<&str>().unwrap(), "text");
+
+        assert_eq!(
+            engine.try_get::<&str, (u16, i32)>(&COLUMN, &"a").unwrap(),
+            Some((1, 7))
+        );
+        assert!(engine.try_get_tagged(&COLUMN, &"c").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_key_prefix() {
+        let engine = Engine::new(NoopEngine::passthrough())
+            .with_key_prefix("v1:")
+            .with_chunking(4);
+
+        assert!(engine.try_insert(&COLUMN, &"key", &"a long value").is_ok());
+        assert_eq!(
+            engine.try_get::<&str, String>(&COLUMN, &"key").unwrap(),
+            Some("a long value".to_string())
+        );
+        assert_eq!(engine.try_increment(&COLUMN, &"counter", 2).unwrap(), 2);
+    }
+
+    #[test]
+    fn test_raw_or_decoded() {
+        let engine = Engine::new(NoopEngine::passthrough());
+
+        assert!(engine.try_insert(&COLUMN, &"key", &7u8).is_ok());
+        assert!(matches!(
+            engine.try_get_raw_or_decoded::<&str, u8>(&COLUMN, &"key"),
+            Ok(Some(Ok(7)))
+        ));
+
+        let Ok(Some(Err(failure))) = engine.try_get_raw_or_decoded::<&str, bool>(&COLUMN, &"key")
+        else {
+            panic!("expected a decode failure");
+        };
+        assert!(matches!(failure.error, CacheError::Decode { .. }));
+        assert_eq!(failure.bytes, vec![7]);
+
+        assert!(matches!(
+            engine.try_get_raw_or_decoded::<&str, u8>(&COLUMN, &"missing"),
+            Ok(None)
+        ));
+    }
+
+    #[test]
+    fn test_report() {
+        let engine = Engine::new(NoopEngine::passthrough());
+
+        assert!(engine.try_report().unwrap().is_empty());
+        assert!(engine.try_insert(&COLUMN, &"a", &1i32).is_ok());
+        assert!(engine.try_insert(&COLUMN, &"b", &2i32).is_ok());
+        assert_eq!(
+            engine.try_report().unwrap(),
+            vec![ColumnReport {
+                name: "test_column".to_string(),
+                entries: 2,
+                size_on_disk: None,
+                description: None,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_report_description() {
+        struct Described;
+
+        impl ColumnDefinition for Described {
+            fn name(&self) -> String {
+                "described".to_string()
+            }
+
+            fn get_ttl_in_seconds(&self) -> i32 {
+                60
+            }
+
+            fn description(&self) -> Option<&str> {
+                Some("profiles by user id")
+            }
+        }
+
+        let mut engine = Engine::new(NoopEngine::passthrough());
+        assert!(engine.try_insert(&Described, &"a", &1i32).is_ok());
+        assert_eq!(engine.try_report().unwrap()[0].description, None);
+
+        assert!(engine.register_column(&Described).is_ok());
+        assert_eq!(
+            engine.try_report().unwrap()[0].description.as_deref(),
+            Some("profiles by user id")
+        );
+    }
+
+    #[test]
+    fn test_validate_column() {
+        struct NamedColumn {
+            name: &'static str,
+            jitter: Option<f32>,
+        }
+
+        impl ColumnDefinition for NamedColumn {
+            fn name(&self) -> String {
+                self.name.to_string()
+            }
+
+            fn get_ttl_in_seconds(&self) -> i32 {
+                60
+            }
+
+            fn ttl_jitter(&self) -> Option<f32> {
+                self.jitter
+            }
+        }
+
+        let mut engine = Engine::default();
+        let column = |name, jitter| NamedColumn { name, jitter };
+
+        assert!(engine.validate_column(&column("users", Some(0.1))).is_ok());
+        assert!(engine.validate_column(&column("a:b", None)).is_ok());
+        assert!(engine.validate_column(&column("", None)).is_err());
+        assert!(
+            engine
+                .validate_column(&column("users#index", None))
+                .is_err()
+        );
+        assert!(
+            engine
+                .validate_column(&column("users", Some(-0.1)))
+                .is_err()
+        );
+        assert!(
+            engine
+                .validate_column(&column("users", Some(f32::NAN)))
+                .is_err()
+        );
+        assert!(matches!(
+            engine.register_column(&column("", None)),
+            Err(CacheError::Engine(_))
+        ));
+    }
+
+    #[test]
+    fn test_copy_column() {
+        struct CopyColumn;
+
+        impl ColumnDefinition for CopyColumn {
+            fn name(&self) -> String {
+                "copy_column".to_string()
+            }
+
+            fn get_ttl_in_seconds(&self) -> i32 {
+                60
+            }
+        }
+
+        let engine = Engine::new(NoopEngine::passthrough())
+            .with_key_prefix("v1:")
+            .with_chunking(4);
+
+        assert!(engine.try_insert(&COLUMN, &"short", &1u8).is_ok());
+        assert!(engine.try_insert(&COLUMN, &"long", &"a long value").is_ok());
+
+        assert!(engine.try_copy_column(&COLUMN, &CopyColumn).unwrap() > 2);
+        assert_eq!(
+            engine.try_get::<&str, u8>(&CopyColumn, &"short").unwrap(),
+            Some(1)
+        );
+        assert_eq!(
+            engine
+                .try_get::<&str, String>(&CopyColumn, &"long")
+                .unwrap(),
+            Some("a long value".to_string())
+        );
+    }
+
+    #[test]
+    fn test_get_into() {
+        let engine = Engine::new(NoopEngine::passthrough()).with_checksums();
+        let mut buf = b"stale".to_vec();
+
+        assert!(!engine.try_get_into(&COLUMN, &"key", &mut buf).unwrap());
+        assert!(buf.is_empty());
+
+        assert!(engine.try_insert(&COLUMN, &"key", &7u8).is_ok());
+        assert!(engine.try_get_into(&COLUMN, &"key", &mut buf).unwrap());
+        assert_eq!(buf, vec![7]);
+    }
+
+    #[test]
+    fn test_effective_ttl() {
+        struct JitteredColumn;
+
+        impl ColumnDefinition for JitteredColumn {
+            fn name(&self) -> String {
+                "jittered".to_string()
+            }
+
+            fn get_ttl_in_seconds(&self) -> i32 {
+                100
+            }
+
+            fn ttl_jitter(&self) -> Option<f32> {
+                Some(0.1)
+            }
+        }
+
+        struct IdleColumn(i32, Option<u32>);
+
+        impl ColumnDefinition for IdleColumn {
+            fn name(&self) -> String {
+                "idle".to_string()
+            }
+
+            fn get_ttl_in_seconds(&self) -> i32 {
+                self.0
+            }
+
+            fn idle_ttl_in_seconds(&self) -> Option<u32> {
+                self.1
+            }
+        }
+
+        let engine = Engine::new(NoopEngine::passthrough());
+
+        assert_eq!(engine.effective_ttl(&COLUMN), Some(Duration::from_secs(1)));
+        for _ in 0..100 {
+            let ttl = engine.effective_ttl(&JitteredColumn).unwrap();
+            assert!((Duration::from_secs(90)..=Duration::from_secs(110)).contains(&ttl));
+        }
+
+        assert_eq!(
+            engine.effective_ttl(&IdleColumn(100, Some(10))),
+            Some(Duration::from_secs(10))
+        );
+        assert_eq!(
+            engine.effective_ttl(&IdleColumn(5, Some(10))),
+            Some(Duration::from_secs(5))
+        );
+        assert_eq!(
+            engine.effective_ttl(&IdleColumn(0, Some(10))),
+            Some(Duration::from_secs(10))
+        );
+        assert!(engine.validate_column(&IdleColumn(100, Some(0))).is_err());
+    }
+
+    #[test]
+    fn test_format_marker() {
+        let engine = Engine::new(NoopEngine::passthrough())
+            .with_checksums()
+            .with_chunking(4)
+            .with_format_marker(FormatMismatch::Error);
+
+        assert!(engine.try_insert(&COLUMN, &"long", &"a long value").is_ok());
+        assert!(engine.try_insert(&COLUMN, &"short", &7u32).is_ok());
+        assert_eq!(
+            engine.try_get::<_, String>(&COLUMN, &"long").unwrap(),
+            Some("a long value".to_string())
+        );
+        assert_eq!(
+            engine
+                .try_get_borrowed(&COLUMN, &"short")
+                .unwrap()
+                .unwrap()
+                .decode::<u32>()
+                .unwrap(),
+            7
+        );
+
+        // a legacy codec reads values marked with it, but only with it
+        let engine = engine
+            .with_codec(Codec::Legacy)
+            .with_legacy_codec(Codec::Standard);
+        assert_eq!(
+            engine.try_get::<_, u32>(&COLUMN, &"short").unwrap(),
+            Some(7)
+        );
+
+        let engine = engine.with_codec(Codec::Legacy);
+        let engine = Engine {
+            legacy_codecs: Vec::new(),
+            ..engine
+        };
+        let Err(CacheError::Decode { source, .. }) = engine.try_get::<_, u32>(&COLUMN, &"short")
+        else {
+            panic!("value with a foreign marker was read");
+        };
+        assert!(source.contains("0xc2"));
+
+        let engine = engine.with_format_marker(FormatMismatch::Miss);
+        assert_eq!(engine.try_get::<_, u32>(&COLUMN, &"short").unwrap(), None);
+    }
+
+    #[test]
+    fn test_get_first() {
+        struct OtherColumn;
+
+        impl ColumnDefinition for OtherColumn {
+            fn name(&self) -> String {
+                "other_column".to_string()
+            }
+
+            fn get_ttl_in_seconds(&self) -> i32 {
+                1
+            }
+        }
+
+        let engine = Engine::new(NoopEngine::passthrough());
+        let columns: [&dyn ColumnDefinition; 2] = [&COLUMN, &OtherColumn];
+
+        assert_eq!(
+            engine.try_get_first::<_, String>(&columns, &"key").unwrap(),
+            None
+        );
+
+        assert!(engine.try_insert(&OtherColumn, &"key", &"b").is_ok());
+        assert_eq!(
+            engine.try_get_first::<_, String>(&columns, &"key").unwrap(),
+            Some("b".to_string())
+        );
+
+        assert!(engine.try_insert(&COLUMN, &"key", &"a").is_ok());
+        assert_eq!(
+            engine.try_get_first::<_, String>(&columns, &"key").unwrap(),
+            Some("a".to_string())
+        );
+
+        // a bad value does not fall through to the next column
+        assert!(engine.try_insert(&COLUMN, &"key", &[0xffu8; 2]).is_ok());
+        assert!(engine.try_get_first::<_, String>(&columns, &"key").is_err());
+    }
+
+    #[test]
+    fn test_get_or() {
+        let engine = Engine::new(NoopEngine::passthrough());
+
+        assert!(engine.try_insert(&COLUMN, &"old", &7u8).is_ok());
+        assert!(engine.try_insert(&COLUMN, &"new", &(7u8, 8u8)).is_ok());
+        assert!(engine.try_insert(&COLUMN, &"other", &"text").is_ok());
+
+        assert_eq!(
+            engine
+                .try_get_or::<_, (u8, u8), u8>(&COLUMN, &"new")
+                .unwrap(),
+            Some(Either::Left((7, 8)))
+        );
+        // a u8 reads the start of the tuple, but not all of it
+        assert_eq!(
+            engine
+                .try_get_or::<_, u8, (u8, u8)>(&COLUMN, &"new")
+                .unwrap(),
+            Some(Either::Right((7, 8)))
+        );
+        assert_eq!(
+            engine
+                .try_get_or::<_, (u8, u8), u8>(&COLUMN, &"old")
+                .unwrap(),
+            Some(Either::Right(7))
+        );
+        assert!(matches!(
+            engine.try_get_or::<_, (u8, u8), u8>(&COLUMN, &"other"),
+            Err(CacheError::Decode { .. })
+        ));
+        assert_eq!(
+            engine
+                .try_get_or::<_, u8, String>(&COLUMN, &"missing")
+                .unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_keyed() {
+        let engine = Engine::new(NoopEngine::passthrough());
+        let key = (7u64, "eu".to_string());
+
+        assert!(engine.try_insert_keyed(&COLUMN, &key, &3u32).is_ok());
+        assert_eq!(
+            engine.try_get_keyed::<_, u32>(&COLUMN, &key).unwrap(),
+            Some(3)
+        );
+        assert!(
+            engine
+                .try_get_keyed::<_, u32>(&COLUMN, &(7u64, "us".to_string()))
+                .unwrap()
+                .is_none()
+        );
+
+        // the encoded key is the one storage holds
+        let encoded = bincode::encode_to_vec(&key, bincode::config::standard()).unwrap();
+        assert_eq!(
+            engine.try_get::<_, u32>(&COLUMN, &encoded).unwrap(),
+            Some(3)
+        );
+    }
+
+    #[test]
+    fn test_encode_error_names_type() {
+        struct Unencodable;
+
+        impl bincode::Encode for Unencodable {
+            fn encode<E: bincode::enc::Encoder>(
+                &self,
+                _encoder: &mut E,
+            ) -> Result<(), bincode::error::EncodeError> {
+                Err(bincode::error::EncodeError::Other(
+                    "deliberately unencodable",
+                ))
+            }
+        }
+
+        let engine = Engine::new(NoopEngine::passthrough());
+
+        let Err(error) = engine.try_insert(&COLUMN, &"key", &Unencodable) else {
+            panic!("expected an encode failure");
+        };
+        assert!(matches!(
+            &error,
+            CacheError::Encode { type_name, .. } if type_name.ends_with("Unencodable")
+        ));
+        assert!(error.to_string().contains("Unencodable"));
+        assert!(error.to_string().contains("deliberately unencodable"));
+    }
+
+    #[test]
+    fn test_strict_columns() {
+        let mut engine = Engine::new(NoopEngine::passthrough()).with_strict_columns();
+
+        assert!(matches!(
+            engine.try_insert(&COLUMN, &"key", &1u8),
+            Err(CacheError::Engine(_))
+        ));
+        assert!(engine.try_get::<&str, u8>(&COLUMN, &"key").is_err());
+        assert!(engine.try_get_borrowed(&COLUMN, &"key").is_err());
+        assert!(engine.try_increment(&COLUMN, &"counter", 1).is_err());
+        assert!(engine.try_drop_column(&COLUMN).is_ok());
+
+        assert!(engine.register_column(&COLUMN).is_ok());
+        assert!(engine.try_insert(&COLUMN, &"key", &1u8).is_ok());
+        assert_eq!(
+            engine.try_get::<&str, u8>(&COLUMN, &"key").unwrap(),
+            Some(1)
+        );
+    }
+
+    #[test]
+    fn test_checksum_engine() {
+        let engine = Engine::new(NoopEngine::passthrough()).with_checksums();
+
+        assert!(engine.try_insert(&COLUMN, &"key", &100i32).is_ok());
+        assert_eq!(
+            engine.try_get::<&str, i32>(&COLUMN, &"key").unwrap(),
+            Some(100)
+        );
+    }
+
+    #[test]
+    fn test_insert_if_changed() {
+        let engine = Engine::new(NoopEngine::passthrough());
+
+        assert!(
+            engine
+                .try_insert_if_changed(&COLUMN, &"key", &1u32)
+                .unwrap()
+        );
+        assert!(
+            !engine
+                .try_insert_if_changed(&COLUMN, &"key", &1u32)
+                .unwrap()
+        );
+        assert!(
+            engine
+                .try_insert_if_changed(&COLUMN, &"key", &2u32)
+                .unwrap()
+        );
+        assert_eq!(engine.try_get::<_, u32>(&COLUMN, &"key").unwrap(), Some(2));
+
+        // a value that no longer decodes is replaced
+        assert!(engine.try_insert(&COLUMN, &"flag", &200u8).is_ok());
+        assert!(
+            engine
+                .try_insert_if_changed(&COLUMN, &"flag", &true)
+                .unwrap()
+        );
+        assert_eq!(
+            engine.try_get::<_, bool>(&COLUMN, &"flag").unwrap(),
+            Some(true)
+        );
+
+        let discarding = Engine::new(Box::new(NoopEngine::default()));
+        assert!(
+            !discarding
+                .try_insert_if_changed(&COLUMN, &"key", &1u32)
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_column_default() {
+        struct Flags;
+
+        impl ColumnDefinition for Flags {
+            fn name(&self) -> String {
+                "flags".to_string()
+            }
+
+            fn get_ttl_in_seconds(&self) -> i32 {
+                60
+            }
+
+            fn default_value_bytes(&self) -> Option<Vec<u8>> {
+                Codec::Standard.encode(&Some(true)).ok()
+            }
+        }
+
+        let engine = Engine::new(NoopEngine::passthrough());
+
+        assert_eq!(
+            engine
+                .try_get_with_column_default::<_, Option<bool>>(&Flags, &"new")
+                .unwrap(),
+            Some(Some(true))
+        );
+        assert_eq!(
+            engine.try_get::<_, Option<bool>>(&Flags, &"new").unwrap(),
+            None
+        );
+        assert_eq!(
+            engine
+                .try_get_with_column_default::<_, u32>(&COLUMN, &"new")
+                .unwrap(),
+            None
+        );
+
+        // a cached negative result is returned as stored
+        assert!(engine.try_insert(&Flags, &"off", &None::<bool>).is_ok());
+        assert_eq!(
+            engine
+                .try_get_with_column_default::<_, Option<bool>>(&Flags, &"off")
+                .unwrap(),
+            Some(None)
+        );
+
+        // neither a value nor a default that fails to decode reads as the default
+        assert!(
+            engine
+                .storage()
+                .try_insert(&Flags, b"corrupt", &[7])
+                .is_ok()
+        );
+        assert!(matches!(
+            engine.try_get_with_column_default::<_, Option<bool>>(&Flags, &"corrupt"),
+            Err(CacheError::Decode { .. })
+        ));
+        assert!(matches!(
+            engine.try_get_with_column_default::<_, [bool; 3]>(&Flags, &"new"),
+            Err(CacheError::Decode { source, .. }) if source.starts_with("column default")
+        ));
+    }
+
+    #[test]
+    fn test_decode_limit() {
+        let engine = Engine::new(NoopEngine::passthrough());
+
+        // a length prefix claiming a vec of u64::MAX / 2 strings
+        let mut crafted = vec![0xFD];
+        crafted.extend_from_slice(&(u64::MAX / 2).to_le_bytes());
+        assert!(
+            engine
+                .storage()
+                .try_insert(&COLUMN, b"key", &crafted)
+                .is_ok()
+        );
+
+        assert!(matches!(
+            engine.try_get::<_, Vec<String>>(&COLUMN, &"key"),
+            Err(CacheError::Decode { .. })
+        ));
+
+        let engine = engine.with_decode_limit(16);
+        assert!(engine.try_insert(&COLUMN, &"small", &"fits").is_ok());
+        assert!(
+            engine
+                .try_insert(&COLUMN, &"large", &"x".repeat(17))
+                .is_ok()
+        );
+        assert_eq!(
+            engine.try_get::<_, String>(&COLUMN, &"small").unwrap(),
+            Some("fits".to_string())
+        );
+        assert!(engine.try_get::<_, String>(&COLUMN, &"large").is_err());
+    }
+
+    #[test]
+    fn test_middleware_order() {
+        use crate::ValueMiddleware;
+
+        struct Tag(u8);
+
+        impl ValueMiddleware for Tag {
+            fn on_write(&self, mut bytes: Vec<u8>) -> Result<Vec<u8>, CacheError> {
+                bytes.push(self.0);
+                Ok(bytes)
+            }
+
+            fn on_read(&self, mut bytes: Vec<u8>) -> Result<Vec<u8>, CacheError> {
+                match bytes.pop() {
+                    Some(tag) if tag == self.0 => Ok(bytes),
+                    _ => Err(CacheError::Decode {
+                        column: String::new(),
+                        key_hint: String::new(),
+                        source: "wrong tag".to_string(),
+                    }),
+                }
+            }
+        }
+
+        let engine = Engine::new(NoopEngine::passthrough())
+            .with_middleware(Tag(1))
+            .with_middleware(Tag(2));
+
+        assert!(engine.try_insert(&COLUMN, &"key", &7u8).is_ok());
+        assert_eq!(
+            engine.storage().try_get(&COLUMN, b"key").unwrap(),
+            Some(vec![7, 1, 2])
+        );
+        assert_eq!(engine.try_get::<_, u8>(&COLUMN, &"key").unwrap(), Some(7));
+        assert_eq!(
+            engine
+                .try_get_borrowed(&COLUMN, &"key")
+                .unwrap()
+                .unwrap()
+                .decode::<u8>()
+                .unwrap(),
+            7
+        );
+
+        // the same middleware stacked in the other order does not read the value back
+        let swapped = Engine::new(NoopEngine::passthrough())
+            .with_middleware(Tag(2))
+            .with_middleware(Tag(1));
+        assert!(
+            swapped
+                .storage()
+                .try_insert(&COLUMN, b"key", &[7, 1, 2])
+                .is_ok()
+        );
+        assert!(matches!(
+            swapped.try_get::<_, u8>(&COLUMN, &"key"),
+            Err(CacheError::Decode { column, .. }) if column == "test_column"
+        ));
+    }
+
+    #[test]
+    #[cfg(feature = "encryption")]
+    fn test_encrypted_engine() {
+        use crate::Encryptor;
+
+        let engine = Engine::new(NoopEngine::passthrough())
+            .with_checksums()
+            .with_encryption(Encryptor::new(&[7; 32]));
+
+        assert!(engine.try_insert(&COLUMN, &"key", &"secret").is_ok());
+        assert_eq!(
+            engine.try_get::<&str, String>(&COLUMN, &"key").unwrap(),
+            Some("secret".to_string())
+        );
+        assert_eq!(
+            engine
+                .try_get_borrowed(&COLUMN, &"key")
+                .unwrap()
+                .unwrap()
+                .decode::<&str>()
+                .unwrap(),
+            "secret"
+        );
+
+        // a sealed value copied under another key does not read back there
+        let sealed = engine.storage().try_get(&COLUMN, b"key").unwrap().unwrap();
+        assert!(
+            engine
+                .storage()
+                .try_insert(&COLUMN, b"other", &sealed)
+                .is_ok()
+        );
+        assert!(matches!(
+            engine.try_get::<&str, String>(&COLUMN, &"other"),
+            Err(CacheError::Decode { .. })
+        ));
+        assert!(matches!(
+            engine.try_rename(&COLUMN, &"key", &"renamed"),
+            Err(CacheError::Engine(_))
+        ));
+    }
+
+    #[test]
+    #[cfg(feature = "gzip")]
+    fn test_compressed_engine() {
+        use crate::Compression;
+
+        let engine = Engine::new(NoopEngine::passthrough())
+            .with_checksums()
+            .with_compression(Compression::Gzip);
+        let long = "compressed ".repeat(64);
+
+        assert!(engine.try_insert(&COLUMN, &"long", &long).is_ok());
+        assert!(engine.try_insert(&COLUMN, &"short", &"tiny").is_ok());
+        assert_eq!(
+            engine.try_get::<&str, String>(&COLUMN, &"long").unwrap(),
+            Some(long.clone())
+        );
+        assert_eq!(
+            engine
+                .try_get_borrowed(&COLUMN, &"short")
+                .unwrap()
+                .unwrap()
+                .decode::<&str>()
+                .unwrap(),
+            "tiny"
+        );
+
+        // values written without a header are not read
+        let plain = Engine::new(NoopEngine::passthrough()).with_compression(Compression::Gzip);
+        assert!(
+            plain
+                .storage()
+                .try_insert(&COLUMN, b"raw", b"\x07raw")
+                .is_ok()
+        );
+        assert!(plain.try_get::<&str, String>(&COLUMN, &"raw").is_err());
+    }
+
+    #[test]
+    fn test_decode_error_context() {
+        let engine = Engine::new(NoopEngine::passthrough());
+
+        assert!(engine.try_insert(&COLUMN, &"key", &255u8).is_ok());
+
+        match engine.try_get::<&str, bool>(&COLUMN, &"key") {
+            Err(CacheError::Decode {
+                column, key_hint, ..
+            }) => {
+                assert_eq!(column, "test_column");
+                assert_eq!(key_hint, "key");
+            }
+            other => panic!("expected decode error, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_key_hint() {
+        assert_eq!(key_hint(b"user:1"), "user:1");
+        assert_eq!(key_hint(&[0, 255]), "0x00ff");
+        assert_eq!(key_hint(&[b'a'; 40]), format!("{}...", "a".repeat(32)));
+    }
+
+    #[test]
+    fn test_legacy_codec_fallback() {
+        let storage = NoopEngine::passthrough();
+        let legacy_bytes = Codec::Legacy.encode(&300u32).unwrap();
+        assert!(storage.try_insert(&COLUMN, b"key", &legacy_bytes).is_ok());
+
+        // Without the fallback the legacy bytes are misread rather than rejected
+        let engine = Engine::new(storage);
+        assert_ne!(
+            engine.try_get::<&str, u32>(&COLUMN, &"key").unwrap(),
+            Some(300)
+        );
+
+        let engine = engine
+            .with_legacy_codec(Codec::Legacy)
+            .with_legacy_rewrite();
+        assert_eq!(
+            engine.try_get::<&str, u32>(&COLUMN, &"key").unwrap(),
+            Some(300)
+        );
+        assert_eq!(
+            engine.storage().try_get(&COLUMN, b"key").unwrap(),
+            Some(Codec::Standard.encode(&300u32).unwrap())
+        );
     }
 }
@@ -0,0 +1,915 @@
+use std::{
+    collections::{BTreeMap, HashMap, hash_map::RandomState},
+    hash::BuildHasher,
+    num::NonZeroUsize,
+    sync::{Arc, Mutex, MutexGuard},
+    time::{Duration, Instant},
+};
+
+use crate::{
+    BackendConfig, CacheError, CacheStorage, ColumnDefinition, ColumnReport, EvictReason,
+    EvictionListener, RawEntry, TimedEntry, TransactionCondition, TransactionWrite, TtlEnforcement,
+    eviction::Evictions,
+};
+
+/// Shards per thread the machine can run in parallel, when the shard count is not set
+const SHARDS_PER_THREAD: usize = 4;
+
+/// A value held by a [`MemoryEngine`]
+#[derive(Debug)]
+struct Entry {
+    /// When the value was written, used to evict the oldest entries
+    written: Instant,
+    /// When the TTL of the value is up, [`None`] if it never expires
+    expires_at: Option<Instant>,
+    /// Idle TTL of the column the value was written to
+    idle: Option<Duration>,
+    /// When the value was last read or written, for its idle TTL
+    last_read: Instant,
+    /// The stored bytes
+    value: Vec<u8>,
+}
+
+impl Entry {
+    /// A value written now to the column with `ttl`, after jitter
+    fn new(c: &dyn ColumnDefinition, ttl: i32, value: Vec<u8>) -> Entry {
+        let now = Instant::now();
+
+        Entry {
+            written: now,
+            expires_at: crate::ttl::expiry(c, ttl).map(|ttl| now + Duration::from_secs(ttl)),
+            idle: c
+                .idle_ttl_in_seconds()
+                .map(|idle| Duration::from_secs(u64::from(idle))),
+            last_read: now,
+            value,
+        }
+    }
+
+    /// When the value expires, whichever of its TTL and idle TTL is up first
+    fn deadline(&self) -> Option<Instant> {
+        let idle = self.idle.map(|idle| self.last_read + idle);
+
+        match (self.expires_at, idle) {
+            (Some(expires_at), Some(idle)) => Some(expires_at.min(idle)),
+            (deadline, None) | (None, deadline) => deadline,
+        }
+    }
+
+    fn is_expired(&self, now: Instant) -> bool {
+        self.deadline().is_some_and(|deadline| deadline <= now)
+    }
+}
+
+/// Values of the keys hashed to one shard, keyed by column name then key
+type Shard = HashMap<String, HashMap<Vec<u8>, Entry>>;
+
+/// Keys leaving the cache, reported to the listener once the shards are unlocked
+type Evicted = Vec<(Vec<u8>, EvictReason)>;
+
+///
+/// In-memory storage split into shards, each behind its own lock
+///
+/// Keys are spread over the shards by a hash of their column and key, so
+/// operations on keys in different shards never wait on each other, while a single
+/// lock would have every thread queue behind it. The `concurrent` benchmark compares
+/// a single shard against the default of four shards per thread the machine runs
+/// in parallel, see [`MemoryEngineBuilder::shards`].
+///
+/// Values live as long as the engine and are lost with it. Expired values are never
+/// returned and are removed when next read or by [`crate::Engine::try_maintenance`].
+/// Columns with [`ColumnDefinition::max_entries`] lock every shard to find their
+/// oldest values whenever an insert adds a key, so they contend as a single lock would.
+///
+/// ```
+/// use omega_cache::{Engine, memory_engine::MemoryEngine};
+///
+/// let engine = Engine::new(MemoryEngine::builder().shards(64).build());
+/// ```
+///
+#[derive(Debug)]
+pub struct MemoryEngine {
+    shards: Box<[Mutex<Shard>]>,
+    hasher: RandomState,
+    evictions: Evictions,
+}
+
+///
+/// Builder for [`MemoryEngine`]
+///
+#[derive(Debug, Clone, Default)]
+pub struct MemoryEngineBuilder {
+    shards: Option<NonZeroUsize>,
+}
+
+impl MemoryEngineBuilder {
+    /// Number of shards keys are spread over, each with its own lock.
+    ///
+    /// Defaults to four per thread the machine can run in parallel, so threads
+    /// rarely wait on each other. More shards cost a little memory each,
+    /// and a single shard behaves as one lock over every value.
+    #[must_use]
+    pub fn shards(mut self, shards: usize) -> MemoryEngineBuilder {
+        self.shards = NonZeroUsize::new(shards);
+        self
+    }
+
+    /// Build the engine
+    #[must_use]
+    pub fn build(self) -> Box<dyn CacheStorage + Send + Sync> {
+        Box::new(self.open())
+    }
+
+    /// Build the engine as a [`MemoryEngine`] rather than boxed storage
+    #[must_use]
+    pub fn open(self) -> MemoryEngine {
+        let shards = self.shards.map_or_else(
+            || {
+                std::thread::available_parallelism().map_or(1, NonZeroUsize::get)
+                    * SHARDS_PER_THREAD
+            },
+            NonZeroUsize::get,
+        );
+
+        MemoryEngine {
+            shards: (0..shards).map(|_| Mutex::default()).collect(),
+            hasher: RandomState::new(),
+            evictions: Evictions::default(),
+        }
+    }
+}
+
+impl MemoryEngine {
+    ///
+    /// Builder for an in-memory engine
+    ///
+    /// ```
+    /// use omega_cache::{Engine, memory_engine::MemoryEngine};
+    ///
+    /// // a single lock over every value
+    /// let engine = Engine::new(MemoryEngine::builder().shards(1).build());
+    /// ```
+    ///
+    #[must_use]
+    pub fn builder() -> MemoryEngineBuilder {
+        MemoryEngineBuilder::default()
+    }
+
+    /// Number of shards keys are spread over
+    #[must_use]
+    pub fn shard_count(&self) -> usize {
+        self.shards.len()
+    }
+
+    /// Index of the shard holding key in the column
+    fn shard_of(&self, column: &str, key: &[u8]) -> usize {
+        let hash = self.hasher.hash_one((column, key));
+
+        // the remainder is below the shard count, so fits in usize
+        #[allow(clippy::cast_possible_truncation)]
+        let index = (hash % self.shards.len() as u64) as usize;
+
+        index
+    }
+
+    fn lock(
+        &self,
+        index: usize,
+        error: fn(String) -> CacheError,
+    ) -> Result<MutexGuard<'_, Shard>, CacheError> {
+        self.shards[index].lock().map_err(|e| error(e.to_string()))
+    }
+
+    /// Lock each of the shards once, in index order so that threads locking
+    /// several shards never wait on each other in a cycle
+    fn lock_many(
+        &self,
+        indices: impl IntoIterator<Item = usize>,
+        error: fn(String) -> CacheError,
+    ) -> Result<BTreeMap<usize, MutexGuard<'_, Shard>>, CacheError> {
+        let mut indices = indices.into_iter().collect::<Vec<_>>();
+        indices.sort_unstable();
+        indices.dedup();
+
+        indices
+            .into_iter()
+            .map(|index| Ok((index, self.lock(index, error)?)))
+            .collect()
+    }
+
+    /// Notify the listener of the keys leaving the column
+    fn notify(&self, c: &dyn ColumnDefinition, evicted: Evicted) {
+        if evicted.is_empty() {
+            return;
+        }
+
+        let name = c.name();
+        for (key, reason) in evicted {
+            self.evictions.evicted(&name, &key, reason);
+        }
+    }
+
+    /// Write the value with `ttl`, returning the live value it replaced
+    fn write(
+        &self,
+        c: &dyn ColumnDefinition,
+        key: &[u8],
+        value: &[u8],
+        ttl: i32,
+    ) -> Result<Option<Vec<u8>>, CacheError> {
+        let name = c.name();
+        let mut shard = self.lock(self.shard_of(&name, key), CacheError::Put)?;
+
+        let old = shard
+            .entry(name)
+            .or_default()
+            .insert(key.to_vec(), Entry::new(c, ttl, value.to_vec()));
+        drop(shard);
+
+        let mut evicted = Vec::new();
+        let replaced = match old {
+            Some(old) if old.is_expired(Instant::now()) => {
+                evicted.push((key.to_vec(), EvictReason::Expired));
+                None
+            }
+            Some(old) => {
+                evicted.push((key.to_vec(), EvictReason::Replaced));
+                Some(old.value)
+            }
+            None => None,
+        };
+
+        // only a write adding a key can take the column over its bound
+        if replaced.is_none() {
+            self.limit_entries(c, &mut evicted)?;
+        }
+        self.notify(c, evicted);
+
+        Ok(replaced)
+    }
+
+    /// Remove the oldest values of the column until it holds at most its
+    /// [`ColumnDefinition::max_entries`], expired values first
+    fn limit_entries(
+        &self,
+        c: &dyn ColumnDefinition,
+        evicted: &mut Evicted,
+    ) -> Result<(), CacheError> {
+        let Some(max_entries) = c.max_entries() else {
+            return Ok(());
+        };
+
+        let name = c.name();
+        let now = Instant::now();
+        let mut shards = self.lock_many(0..self.shards.len(), CacheError::Put)?;
+
+        let mut count = 0;
+        for shard in shards.values_mut() {
+            if let Some(column) = shard.get_mut(&name) {
+                column.retain(|key, entry| {
+                    let expired = entry.is_expired(now);
+                    if expired {
+                        evicted.push((key.clone(), EvictReason::Expired));
+                    }
+
+                    !expired
+                });
+                count += column.len();
+            }
+        }
+
+        while count > max_entries {
+            let Some((index, oldest)) = shards
+                .iter()
+                .filter_map(|(index, shard)| Some((index, shard.get(&name)?)))
+                .flat_map(|(index, column)| {
+                    column
+                        .iter()
+                        .map(move |(key, entry)| (*index, key, entry.written))
+                })
+                .min_by_key(|(_, _, written)| *written)
+                .map(|(index, key, _)| (index, key.clone()))
+            else {
+                break;
+            };
+
+            if let Some(column) = shards
+                .get_mut(&index)
+                .and_then(|shard| shard.get_mut(&name))
+            {
+                column.remove(&oldest);
+            }
+            evicted.push((oldest, EvictReason::CapacityEvicted));
+            count -= 1;
+        }
+
+        Ok(())
+    }
+}
+
+impl CacheStorage for MemoryEngine {
+    /// Keys are spread over the default number of shards,
+    /// as values are bounded by column rather than by engine
+    fn build(_path: String, _capacity: Option<u64>) -> Box<dyn CacheStorage + Send + Sync> {
+        MemoryEngine::builder().build()
+    }
+
+    fn ttl_enforcement(&self) -> TtlEnforcement {
+        TtlEnforcement::LazyOnRead
+    }
+
+    fn describe_config(&self) -> BackendConfig {
+        BackendConfig::new("memory").with("shards", self.shards.len())
+    }
+
+    fn try_insert(
+        &self,
+        c: &dyn ColumnDefinition,
+        key: &[u8],
+        value: &[u8],
+    ) -> Result<(), CacheError> {
+        self.write(c, key, value, c.get_ttl_in_seconds())
+            .map(|_| ())
+    }
+
+    fn try_insert_with_ttl(
+        &self,
+        c: &dyn ColumnDefinition,
+        key: &[u8],
+        value: &[u8],
+        ttl_in_seconds: i32,
+    ) -> Result<(), CacheError> {
+        self.write(c, key, value, ttl_in_seconds).map(|_| ())
+    }
+
+    fn try_swap(
+        &self,
+        c: &dyn ColumnDefinition,
+        key: &[u8],
+        value: &[u8],
+    ) -> Result<Option<Vec<u8>>, CacheError> {
+        self.write(c, key, value, c.get_ttl_in_seconds())
+    }
+
+    /// Locks the shards of both keys, the value keeps its TTL
+    fn try_rename(
+        &self,
+        c: &dyn ColumnDefinition,
+        from: &[u8],
+        to: &[u8],
+    ) -> Result<bool, CacheError> {
+        let name = c.name();
+        let (from_shard, to_shard) = (self.shard_of(&name, from), self.shard_of(&name, to));
+        let mut shards = self.lock_many([from_shard, to_shard], CacheError::Put)?;
+
+        let Some(entry) = shards
+            .get_mut(&from_shard)
+            .and_then(|shard| shard.get_mut(&name))
+            .and_then(|column| column.remove(from))
+        else {
+            return Ok(false);
+        };
+
+        let now = Instant::now();
+        if entry.is_expired(now) {
+            drop(shards);
+            self.notify(c, vec![(from.to_vec(), EvictReason::Expired)]);
+
+            return Ok(false);
+        }
+
+        let replaced = shards
+            .get_mut(&to_shard)
+            .map(|shard| shard.entry(name).or_default())
+            .and_then(|column| column.insert(to.to_vec(), entry));
+        drop(shards);
+
+        if let Some(replaced) = replaced {
+            let reason = if replaced.is_expired(now) {
+                EvictReason::Expired
+            } else {
+                EvictReason::Replaced
+            };
+            self.notify(c, vec![(to.to_vec(), reason)]);
+        }
+
+        Ok(true)
+    }
+
+    /// Takes under the lock of the key's shard
+    fn try_take(
+        &self,
+        c: &dyn ColumnDefinition,
+        key: &[u8],
+    ) -> Result<Option<Vec<u8>>, CacheError> {
+        let name = c.name();
+        let mut shard = self.lock(self.shard_of(&name, key), CacheError::Put)?;
+        let taken = shard.get_mut(&name).and_then(|column| column.remove(key));
+        drop(shard);
+
+        let Some(taken) = taken else {
+            return Ok(None);
+        };
+
+        if taken.is_expired(Instant::now()) {
+            self.notify(c, vec![(key.to_vec(), EvictReason::Expired)]);
+
+            return Ok(None);
+        }
+        self.notify(c, vec![(key.to_vec(), EvictReason::Removed)]);
+
+        Ok(Some(taken.value))
+    }
+
+    /// Commits with the shards of every key checked or written locked
+    fn try_commit(
+        &self,
+        c: &dyn ColumnDefinition,
+        conditions: &[TransactionCondition],
+        writes: &[TransactionWrite],
+    ) -> Result<bool, CacheError> {
+        let name = c.name();
+        let keys = conditions
+            .iter()
+            .map(|condition| condition.key)
+            .chain(writes.iter().map(|write| write.key));
+        let mut shards =
+            self.lock_many(keys.map(|key| self.shard_of(&name, key)), CacheError::Put)?;
+
+        let now = Instant::now();
+        for condition in conditions {
+            let stored = shards
+                .get(&self.shard_of(&name, condition.key))
+                .and_then(|shard| shard.get(&name))
+                .and_then(|column| column.get(condition.key))
+                .filter(|entry| !entry.is_expired(now))
+                .map(|entry| entry.value.as_slice());
+            if !(condition.holds)(stored)? {
+                return Ok(false);
+            }
+        }
+
+        let mut evicted = Vec::new();
+        let mut added = false;
+        for write in writes {
+            let Some(column) = shards
+                .get_mut(&self.shard_of(&name, write.key))
+                .map(|shard| shard.entry(name.clone()).or_default())
+            else {
+                continue;
+            };
+
+            let old = match write.value {
+                Some(value) => column.insert(
+                    write.key.to_vec(),
+                    Entry::new(c, c.get_ttl_in_seconds(), value.to_vec()),
+                ),
+                None => column.remove(write.key),
+            };
+
+            match old {
+                Some(old) if old.is_expired(now) => {
+                    evicted.push((write.key.to_vec(), EvictReason::Expired));
+                    added |= write.value.is_some();
+                }
+                Some(_) => {
+                    let reason = match write.value {
+                        Some(_) => EvictReason::Replaced,
+                        None => EvictReason::Removed,
+                    };
+                    evicted.push((write.key.to_vec(), reason));
+                }
+                None => added |= write.value.is_some(),
+            }
+        }
+        drop(shards);
+
+        if added {
+            self.limit_entries(c, &mut evicted)?;
+        }
+        self.notify(c, evicted);
+
+        Ok(true)
+    }
+
+    /// Restarts the TTL of the live values at keys from now
+    fn try_touch_many(
+        &self,
+        c: &dyn ColumnDefinition,
+        keys: &[&[u8]],
+        ttl_in_seconds: i32,
+    ) -> Result<usize, CacheError> {
+        let name = c.name();
+        let mut touched = 0;
+        let mut evicted = Vec::new();
+
+        for key in keys {
+            let mut shard = self.lock(self.shard_of(&name, key), CacheError::Put)?;
+            let Some(column) = shard.get_mut(&name) else {
+                continue;
+            };
+
+            let now = Instant::now();
+            match column.get_mut(*key) {
+                Some(entry) if entry.is_expired(now) => {
+                    column.remove(*key);
+                    evicted.push((key.to_vec(), EvictReason::Expired));
+                }
+                Some(entry) => {
+                    entry.expires_at = crate::ttl::expiry(c, ttl_in_seconds)
+                        .map(|ttl| now + Duration::from_secs(ttl));
+                    entry.last_read = now;
+                    touched += 1;
+                }
+                None => {}
+            }
+        }
+
+        self.notify(c, evicted);
+
+        Ok(touched)
+    }
+
+    fn try_get(&self, c: &dyn ColumnDefinition, key: &[u8]) -> Result<Option<Vec<u8>>, CacheError> {
+        let name = c.name();
+        let mut shard = self.lock(self.shard_of(&name, key), CacheError::Get)?;
+        let Some(column) = shard.get_mut(&name) else {
+            return Ok(None);
+        };
+
+        let now = Instant::now();
+        match column.get_mut(key) {
+            Some(entry) if entry.is_expired(now) => {
+                column.remove(key);
+                drop(shard);
+                self.notify(c, vec![(key.to_vec(), EvictReason::Expired)]);
+
+                Ok(None)
+            }
+            Some(entry) => {
+                entry.last_read = now;
+
+                Ok(Some(entry.value.clone()))
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn try_drop_column(&self, c: &dyn ColumnDefinition) -> Result<(), CacheError> {
+        let name = c.name();
+        for index in 0..self.shards.len() {
+            self.lock(index, CacheError::Engine)?.remove(&name);
+        }
+
+        Ok(())
+    }
+
+    fn try_entries(&self, c: &dyn ColumnDefinition) -> Result<Vec<RawEntry>, CacheError> {
+        Ok(self
+            .try_timed_entries(c)?
+            .into_iter()
+            .map(|(key, value, _)| (key, value))
+            .collect())
+    }
+
+    fn try_timed_entries(&self, c: &dyn ColumnDefinition) -> Result<Vec<TimedEntry>, CacheError> {
+        let name = c.name();
+        let now = Instant::now();
+        let mut entries = Vec::new();
+
+        for index in 0..self.shards.len() {
+            let shard = self.lock(index, CacheError::Get)?;
+            let Some(column) = shard.get(&name) else {
+                continue;
+            };
+
+            entries.extend(
+                column
+                    .iter()
+                    .filter(|(_, entry)| !entry.is_expired(now))
+                    .map(|(key, entry)| {
+                        let left = entry.deadline().map(|deadline| deadline - now);
+
+                        (key.clone(), entry.value.clone(), left)
+                    }),
+            );
+        }
+
+        Ok(entries)
+    }
+
+    fn try_entry_ages(&self, c: &dyn ColumnDefinition) -> Result<Vec<Duration>, CacheError> {
+        let name = c.name();
+        let now = Instant::now();
+        let mut ages = Vec::new();
+
+        for index in 0..self.shards.len() {
+            let shard = self.lock(index, CacheError::Get)?;
+            if let Some(column) = shard.get(&name) {
+                ages.extend(
+                    column
+                        .values()
+                        .filter(|entry| !entry.is_expired(now))
+                        .map(|entry| now - entry.written),
+                );
+            }
+        }
+
+        Ok(ages)
+    }
+
+    fn try_count_column(&self, c: &dyn ColumnDefinition) -> Result<usize, CacheError> {
+        let name = c.name();
+        let now = Instant::now();
+        let mut count = 0;
+
+        for index in 0..self.shards.len() {
+            let shard = self.lock(index, CacheError::Engine)?;
+            if let Some(column) = shard.get(&name) {
+                count += column
+                    .values()
+                    .filter(|entry| !entry.is_expired(now))
+                    .count();
+            }
+        }
+
+        Ok(count)
+    }
+
+    fn try_list_columns(&self) -> Result<Vec<String>, CacheError> {
+        Ok(self
+            .try_report()?
+            .into_iter()
+            .map(|report| report.name)
+            .collect())
+    }
+
+    /// Counts the live values of each column holding any
+    fn try_report(&self) -> Result<Vec<ColumnReport>, CacheError> {
+        let now = Instant::now();
+        let mut entries = BTreeMap::<String, usize>::new();
+
+        for index in 0..self.shards.len() {
+            let shard = self.lock(index, CacheError::Engine)?;
+            for (name, column) in shard.iter() {
+                let live = column
+                    .values()
+                    .filter(|entry| !entry.is_expired(now))
+                    .count();
+                if live > 0 {
+                    *entries.entry(name.clone()).or_default() += live;
+                }
+            }
+        }
+
+        Ok(entries
+            .into_iter()
+            .map(|(name, entries)| ColumnReport {
+                name,
+                entries,
+                size_on_disk: None,
+                description: None,
+            })
+            .collect())
+    }
+
+    /// Removes expired values from every shard, one shard at a time
+    fn try_maintenance(&self) -> Result<(), CacheError> {
+        for index in 0..self.shards.len() {
+            let mut shard = self.lock(index, CacheError::Engine)?;
+            let now = Instant::now();
+
+            let mut expired = Vec::new();
+            for (name, column) in shard.iter_mut() {
+                column.retain(|key, entry| {
+                    let live = !entry.is_expired(now);
+                    if !live {
+                        expired.push((name.clone(), key.clone()));
+                    }
+
+                    live
+                });
+            }
+            shard.retain(|_, column| !column.is_empty());
+            drop(shard);
+
+            for (name, key) in expired {
+                self.evictions.evicted(&name, &key, EvictReason::Expired);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Counters keep their TTL, new counters take the column TTL
+    fn try_increment(
+        &self,
+        c: &dyn ColumnDefinition,
+        key: &[u8],
+        delta: i64,
+    ) -> Result<i64, CacheError> {
+        let name = c.name();
+        let mut shard = self.lock(self.shard_of(&name, key), CacheError::Put)?;
+        let column = shard.entry(name).or_default();
+
+        let now = Instant::now();
+        let mut evicted = Vec::new();
+        if column.get(key).is_some_and(|entry| entry.is_expired(now)) {
+            column.remove(key);
+            evicted.push((key.to_vec(), EvictReason::Expired));
+        }
+
+        let current = match column.get(key) {
+            Some(entry) => {
+                bincode::decode_from_slice::<i64, _>(&entry.value, bincode::config::standard())
+                    .map_err(|e| CacheError::decode(c, key, e.to_string()))?
+                    .0
+            }
+            None => 0,
+        };
+
+        let value = current
+            .checked_add(delta)
+            .ok_or_else(|| CacheError::Put("counter overflow".to_string()))?;
+
+        let bytes = bincode::encode_to_vec(value, bincode::config::standard())
+            .map_err(|e| CacheError::encode::<i64>(e.to_string()))?;
+
+        let added = if let Some(entry) = column.get_mut(key) {
+            entry.value = bytes;
+            false
+        } else {
+            column.insert(key.to_vec(), Entry::new(c, c.get_ttl_in_seconds(), bytes));
+            true
+        };
+        drop(shard);
+
+        if added {
+            self.limit_entries(c, &mut evicted)?;
+        }
+        self.notify(c, evicted);
+
+        Ok(value)
+    }
+
+    fn set_eviction_listener(
+        &mut self,
+        listener: Arc<dyn EvictionListener>,
+    ) -> Result<(), CacheError> {
+        self.evictions.set(listener);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use crate::{CacheStorage, ColumnDefinition, Engine};
+
+    use super::MemoryEngine;
+
+    struct TestColumn {}
+
+    impl ColumnDefinition for TestColumn {
+        fn name(&self) -> String {
+            "test_column".to_string()
+        }
+
+        fn get_ttl_in_seconds(&self) -> i32 {
+            1
+        }
+    }
+
+    const COLUMN: TestColumn = TestColumn {};
+
+    #[test]
+    fn test_memory() {
+        let memory = MemoryEngine::builder().shards(8).build();
+
+        assert!(memory.try_insert(&COLUMN, b"key", b"value").is_ok());
+        assert_eq!(
+            memory.try_get(&COLUMN, b"key").unwrap(),
+            Some(b"value".to_vec())
+        );
+        assert_eq!(memory.try_count_column(&COLUMN).unwrap(), 1);
+        assert_eq!(memory.try_list_columns().unwrap(), ["test_column"]);
+
+        assert!(memory.try_drop_column(&COLUMN).is_ok());
+        assert!(memory.try_get(&COLUMN, b"key").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_memory_expiry() {
+        let memory = MemoryEngine::builder().build();
+
+        assert!(memory.try_insert(&COLUMN, b"short", b"value").is_ok());
+        assert!(
+            memory
+                .try_insert_with_ttl(&COLUMN, b"forever", b"value", 0)
+                .is_ok()
+        );
+        std::thread::sleep(Duration::from_millis(1100));
+
+        assert!(memory.try_get(&COLUMN, b"short").unwrap().is_none());
+        assert!(memory.try_get(&COLUMN, b"forever").unwrap().is_some());
+        assert_eq!(memory.try_timed_entries(&COLUMN).unwrap()[0].2, None);
+    }
+
+    #[test]
+    fn test_memory_max_entries_across_shards() {
+        struct BoundedColumn {}
+
+        impl ColumnDefinition for BoundedColumn {
+            fn name(&self) -> String {
+                "bounded_column".to_string()
+            }
+
+            fn get_ttl_in_seconds(&self) -> i32 {
+                60
+            }
+
+            fn max_entries(&self) -> Option<usize> {
+                Some(2)
+            }
+        }
+
+        let memory = MemoryEngine::builder().shards(16).build();
+
+        for key in ["a", "b", "c", "d"] {
+            assert!(
+                memory
+                    .try_insert(&BoundedColumn {}, key.as_bytes(), b"")
+                    .is_ok()
+            );
+        }
+
+        assert_eq!(memory.try_count_column(&BoundedColumn {}).unwrap(), 2);
+        assert!(memory.try_get(&BoundedColumn {}, b"b").unwrap().is_none());
+        assert!(memory.try_get(&BoundedColumn {}, b"d").unwrap().is_some());
+    }
+
+    #[test]
+    fn test_memory_rename_and_commit_across_shards() {
+        let engine = Engine::new(MemoryEngine::builder().shards(16).build());
+
+        for i in 0..32u32 {
+            assert!(engine.try_insert(&COLUMN, &i.to_be_bytes(), &i).is_ok());
+        }
+        for i in 0..32u32 {
+            assert!(
+                engine
+                    .try_rename(&COLUMN, &i.to_be_bytes(), &(i + 100).to_be_bytes())
+                    .unwrap()
+            );
+        }
+        assert_eq!(
+            engine
+                .try_get::<_, u32>(&COLUMN, &131u32.to_be_bytes())
+                .unwrap(),
+            Some(31)
+        );
+
+        let committed = engine
+            .transaction(&COLUMN)
+            .check_eq(&100u32.to_be_bytes(), 0u32)
+            .check_missing(&0u32.to_be_bytes())
+            .insert(&0u32.to_be_bytes(), &1u32)
+            .remove(&131u32.to_be_bytes())
+            .try_commit()
+            .unwrap();
+        assert!(committed);
+        assert_eq!(
+            engine
+                .try_take::<_, u32>(&COLUMN, &0u32.to_be_bytes())
+                .unwrap(),
+            Some(1)
+        );
+        assert_eq!(
+            engine
+                .try_get::<_, u32>(&COLUMN, &131u32.to_be_bytes())
+                .unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_memory_concurrent_increments() {
+        let memory = MemoryEngine::builder().shards(4).open();
+
+        std::thread::scope(|scope| {
+            for _ in 0..8 {
+                scope.spawn(|| {
+                    for i in 0..100u8 {
+                        memory.try_increment(&COLUMN, &[i % 10], 1).unwrap();
+                    }
+                });
+            }
+        });
+
+        for i in 0..10u8 {
+            assert_eq!(memory.try_increment(&COLUMN, &[i], 0).unwrap(), 80);
+        }
+    }
+}
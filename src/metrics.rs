@@ -0,0 +1,567 @@
+//!
+//! Hit, miss and error counts and operation latencies per column, kept by the engine
+//! for apps without a metrics stack, see [`crate::Engine::with_metrics`].
+//!
+//! ```
+//! use omega_cache::{ColumnDefinition, Engine, noop_engine::NoopEngine};
+//!
+//! struct Users;
+//!
+//! impl ColumnDefinition for Users {
+//!     fn name(&self) -> String {
+//!         "users".to_string()
+//!     }
+//!
+//!     fn get_ttl_in_seconds(&self) -> i32 {
+//!         60
+//!     }
+//! }
+//!
+//! let engine = Engine::new(NoopEngine::passthrough()).with_metrics();
+//! engine.try_get::<_, u32>(&Users, &"ada").unwrap();
+//!
+//! let metrics = engine.metrics_snapshot();
+//! let users = metrics.column("users").unwrap();
+//! assert_eq!((users.hits, users.misses), (0, 1));
+//! println!("p99 {:?}", users.latency_percentile(99.0));
+//! ```
+//!
+//! Counters are atomics behind a read lock only taken for writing the first time a column is seen.
+//!
+
+use std::{
+    collections::HashMap,
+    sync::{
+        Arc, PoisonError, RwLock,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::{Duration, Instant},
+};
+
+use crate::noop_engine::NoopEngine;
+use crate::{
+    BackendConfig, CacheError, CacheStorage, ColumnDefinition, ColumnReport, EvictionListener,
+    InsertOutcome, RawEntry, SledLayout, SlowOperationListener, StoredValue, TimedEntry,
+    TransactionCondition, TransactionWrite, TtlEnforcement,
+};
+
+/// Number of latency buckets, bucket `i` holds operations that took under `2^i` microseconds
+/// and the last one everything slower
+const LATENCY_BUCKETS: usize = 32;
+
+/// Latency bucket of an operation
+fn bucket(elapsed: Duration) -> usize {
+    let micros = u64::try_from(elapsed.as_micros()).unwrap_or(u64::MAX);
+
+    ((u64::BITS - micros.leading_zeros()) as usize).min(LATENCY_BUCKETS - 1)
+}
+
+/// Live counters of a column
+#[derive(Debug, Default)]
+struct Counters {
+    /// [`ColumnDefinition::description`] of the column when first seen
+    description: Option<String>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+    errors: AtomicU64,
+    latencies: [AtomicU64; LATENCY_BUCKETS],
+}
+
+/// Counters of every column seen, shared by an engine and its storage wrapper
+#[derive(Debug, Default)]
+pub(crate) struct MetricsRegistry {
+    columns: RwLock<HashMap<String, Counters>>,
+}
+
+impl MetricsRegistry {
+    /// Apply `update` to the counters of a column, adding them if it is new
+    fn update(&self, c: &dyn ColumnDefinition, update: impl FnOnce(&Counters)) {
+        let column = c.name();
+        {
+            let columns = self.columns.read().unwrap_or_else(PoisonError::into_inner);
+            if let Some(counters) = columns.get(&column) {
+                update(counters);
+                return;
+            }
+        }
+
+        let mut columns = self.columns.write().unwrap_or_else(PoisonError::into_inner);
+        update(columns.entry(column).or_insert_with(|| Counters {
+            description: c.description().map(str::to_string),
+            ..Counters::default()
+        }));
+    }
+
+    /// Count an operation on a column that started at `start`, and its hits and misses
+    fn observe(
+        &self,
+        c: &dyn ColumnDefinition,
+        start: Instant,
+        failed: bool,
+        hits: u64,
+        misses: u64,
+    ) {
+        let elapsed = start.elapsed();
+
+        self.update(c, |counters| {
+            counters.latencies[bucket(elapsed)].fetch_add(1, Ordering::Relaxed);
+            if failed {
+                counters.errors.fetch_add(1, Ordering::Relaxed);
+            }
+            if hits > 0 {
+                counters.hits.fetch_add(hits, Ordering::Relaxed);
+            }
+            if misses > 0 {
+                counters.misses.fetch_add(misses, Ordering::Relaxed);
+            }
+        });
+    }
+
+    /// Current counts of every column
+    pub(crate) fn snapshot(&self) -> CacheMetrics {
+        let columns = self.columns.read().unwrap_or_else(PoisonError::into_inner);
+
+        let mut columns = columns
+            .iter()
+            .map(|(name, counters)| ColumnMetrics {
+                name: name.clone(),
+                description: counters.description.clone(),
+                hits: counters.hits.load(Ordering::Relaxed),
+                misses: counters.misses.load(Ordering::Relaxed),
+                errors: counters.errors.load(Ordering::Relaxed),
+                latencies: counters
+                    .latencies
+                    .each_ref()
+                    .map(|count| count.load(Ordering::Relaxed)),
+            })
+            .collect::<Vec<_>>();
+        columns.sort_by(|a, b| a.name.cmp(&b.name));
+
+        CacheMetrics { columns }
+    }
+}
+
+/// Counts of every column used since [`crate::Engine::with_metrics`],
+/// returned by [`crate::Engine::metrics_snapshot`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CacheMetrics {
+    /// Columns by name
+    pub columns: Vec<ColumnMetrics>,
+}
+
+impl CacheMetrics {
+    /// Counts of the column with this name, [`None`] if it was never used
+    #[must_use]
+    pub fn column(&self, name: &str) -> Option<&ColumnMetrics> {
+        self.columns.iter().find(|column| column.name == name)
+    }
+}
+
+/// Counts of a column, as storage sees it.
+///
+/// Each storage call counts once: a batch read is one operation with a hit or miss
+/// per key, and chunked values and [`crate::ColumnIndex`] lookups count every key they read.
+/// Index columns show up under their own `#index` name.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ColumnMetrics {
+    /// Column name
+    pub name: String,
+    /// [`ColumnDefinition::description`] of the column
+    pub description: Option<String>,
+    /// Reads that found a value
+    pub hits: u64,
+    /// Reads that found nothing
+    pub misses: u64,
+    /// Operations that failed
+    pub errors: u64,
+    latencies: [u64; LATENCY_BUCKETS],
+}
+
+impl ColumnMetrics {
+    /// Number of operations made on the column, failed or not
+    #[must_use]
+    pub fn operations(&self) -> u64 {
+        self.latencies.iter().sum()
+    }
+
+    /// Time within which `percentile` percent of operations completed, [`None`] before the first.
+    ///
+    /// Latencies are kept in power of two buckets of microseconds, so this is the
+    /// upper bound of a bucket and can overstate the real figure up to twice.
+    #[must_use]
+    pub fn latency_percentile(&self, percentile: f64) -> Option<Duration> {
+        let operations = self.operations();
+        if operations == 0 {
+            return None;
+        }
+
+        #[allow(
+            clippy::cast_possible_truncation,
+            clippy::cast_precision_loss,
+            clippy::cast_sign_loss
+        )]
+        let rank =
+            ((percentile.clamp(0.0, 100.0) / 100.0 * operations as f64).ceil() as u64).max(1);
+
+        let mut seen = 0;
+        for (i, count) in self.latencies.iter().enumerate() {
+            seen += count;
+            if seen >= rank {
+                return Some(Duration::from_micros(1 << i));
+            }
+        }
+
+        None
+    }
+}
+
+/// Storage wrapper counting every operation made on a column
+pub(crate) struct Metered {
+    pub(crate) inner: Box<dyn CacheStorage + Send + Sync>,
+    pub(crate) registry: Arc<MetricsRegistry>,
+}
+
+impl Metered {
+    /// Count an operation that is not a read
+    fn observe<T>(
+        &self,
+        c: &dyn ColumnDefinition,
+        start: Instant,
+        result: Result<T, CacheError>,
+    ) -> Result<T, CacheError> {
+        self.registry.observe(c, start, result.is_err(), 0, 0);
+        result
+    }
+
+    /// Count a read of a single key
+    fn observe_read<T>(
+        &self,
+        c: &dyn ColumnDefinition,
+        start: Instant,
+        result: Result<Option<T>, CacheError>,
+    ) -> Result<Option<T>, CacheError> {
+        match &result {
+            Ok(Some(_)) => self.registry.observe(c, start, false, 1, 0),
+            Ok(None) => self.registry.observe(c, start, false, 0, 1),
+            Err(_) => self.registry.observe(c, start, true, 0, 0),
+        }
+
+        result
+    }
+}
+
+impl CacheStorage for Metered {
+    /// Only built by wrapping the storage of an engine, this builds an unmetered [`NoopEngine`]
+    fn build(path: String, capacity: Option<u64>) -> Box<dyn CacheStorage + Send + Sync>
+    where
+        Self: Sized,
+    {
+        NoopEngine::build(path, capacity)
+    }
+
+    fn discards_writes(&self) -> bool {
+        self.inner.discards_writes()
+    }
+
+    fn ttl_enforcement(&self) -> TtlEnforcement {
+        self.inner.ttl_enforcement()
+    }
+
+    fn describe_config(&self) -> BackendConfig {
+        self.inner.describe_config()
+    }
+
+    fn try_insert(
+        &self,
+        c: &dyn ColumnDefinition,
+        key: &[u8],
+        value: &[u8],
+    ) -> Result<(), CacheError> {
+        let start = Instant::now();
+        self.observe(c, start, self.inner.try_insert(c, key, value))
+    }
+
+    fn try_insert_with_ttl(
+        &self,
+        c: &dyn ColumnDefinition,
+        key: &[u8],
+        value: &[u8],
+        ttl_in_seconds: i32,
+    ) -> Result<(), CacheError> {
+        let start = Instant::now();
+        self.observe(
+            c,
+            start,
+            self.inner
+                .try_insert_with_ttl(c, key, value, ttl_in_seconds),
+        )
+    }
+
+    fn try_insert_reporting(
+        &self,
+        c: &dyn ColumnDefinition,
+        key: &[u8],
+        value: &[u8],
+    ) -> Result<InsertOutcome, CacheError> {
+        let start = Instant::now();
+        self.observe(c, start, self.inner.try_insert_reporting(c, key, value))
+    }
+
+    fn try_insert_batch(
+        &self,
+        c: &dyn ColumnDefinition,
+        items: &[(Vec<u8>, Vec<u8>)],
+    ) -> Result<(), CacheError> {
+        let start = Instant::now();
+        self.observe(c, start, self.inner.try_insert_batch(c, items))
+    }
+
+    fn try_swap(
+        &self,
+        c: &dyn ColumnDefinition,
+        key: &[u8],
+        value: &[u8],
+    ) -> Result<Option<Vec<u8>>, CacheError> {
+        let start = Instant::now();
+        self.observe(c, start, self.inner.try_swap(c, key, value))
+    }
+
+    fn try_rename(
+        &self,
+        c: &dyn ColumnDefinition,
+        from: &[u8],
+        to: &[u8],
+    ) -> Result<bool, CacheError> {
+        let start = Instant::now();
+        self.observe(c, start, self.inner.try_rename(c, from, to))
+    }
+
+    fn try_take(
+        &self,
+        c: &dyn ColumnDefinition,
+        key: &[u8],
+    ) -> Result<Option<Vec<u8>>, CacheError> {
+        let start = Instant::now();
+        self.observe(c, start, self.inner.try_take(c, key))
+    }
+
+    fn try_commit(
+        &self,
+        c: &dyn ColumnDefinition,
+        conditions: &[TransactionCondition],
+        writes: &[TransactionWrite],
+    ) -> Result<bool, CacheError> {
+        let start = Instant::now();
+        self.observe(c, start, self.inner.try_commit(c, conditions, writes))
+    }
+
+    fn try_touch_many(
+        &self,
+        c: &dyn ColumnDefinition,
+        keys: &[&[u8]],
+        ttl_in_seconds: i32,
+    ) -> Result<usize, CacheError> {
+        let start = Instant::now();
+        self.observe(c, start, self.inner.try_touch_many(c, keys, ttl_in_seconds))
+    }
+
+    fn try_get(&self, c: &dyn ColumnDefinition, key: &[u8]) -> Result<Option<Vec<u8>>, CacheError> {
+        let start = Instant::now();
+        self.observe_read(c, start, self.inner.try_get(c, key))
+    }
+
+    fn try_get_many(
+        &self,
+        c: &dyn ColumnDefinition,
+        keys: &[&[u8]],
+    ) -> Result<Vec<Option<Vec<u8>>>, CacheError> {
+        let start = Instant::now();
+        let result = self.inner.try_get_many(c, keys);
+
+        match &result {
+            Ok(values) => {
+                let hits = values.iter().filter(|value| value.is_some()).count() as u64;
+                self.registry
+                    .observe(c, start, false, hits, values.len() as u64 - hits);
+            }
+            Err(_) => self.registry.observe(c, start, true, 0, 0),
+        }
+
+        result
+    }
+
+    fn try_value_size(
+        &self,
+        c: &dyn ColumnDefinition,
+        key: &[u8],
+    ) -> Result<Option<usize>, CacheError> {
+        let start = Instant::now();
+        self.observe(c, start, self.inner.try_value_size(c, key))
+    }
+
+    fn try_get_stored(
+        &self,
+        c: &dyn ColumnDefinition,
+        key: &[u8],
+    ) -> Result<Option<StoredValue>, CacheError> {
+        let start = Instant::now();
+        self.observe_read(c, start, self.inner.try_get_stored(c, key))
+    }
+
+    fn try_get_allowing_stale(
+        &self,
+        c: &dyn ColumnDefinition,
+        key: &[u8],
+    ) -> Result<Option<(Vec<u8>, bool)>, CacheError> {
+        let start = Instant::now();
+        self.observe_read(c, start, self.inner.try_get_allowing_stale(c, key))
+    }
+
+    fn try_drop_column(&self, c: &dyn ColumnDefinition) -> Result<(), CacheError> {
+        let start = Instant::now();
+        self.observe(c, start, self.inner.try_drop_column(c))
+    }
+
+    fn try_entries(&self, c: &dyn ColumnDefinition) -> Result<Vec<RawEntry>, CacheError> {
+        let start = Instant::now();
+        self.observe(c, start, self.inner.try_entries(c))
+    }
+
+    fn try_timed_entries(&self, c: &dyn ColumnDefinition) -> Result<Vec<TimedEntry>, CacheError> {
+        let start = Instant::now();
+        self.observe(c, start, self.inner.try_timed_entries(c))
+    }
+
+    fn try_count_column(&self, c: &dyn ColumnDefinition) -> Result<usize, CacheError> {
+        let start = Instant::now();
+        self.observe(c, start, self.inner.try_count_column(c))
+    }
+
+    fn try_entry_ages(&self, c: &dyn ColumnDefinition) -> Result<Vec<Duration>, CacheError> {
+        let start = Instant::now();
+        self.observe(c, start, self.inner.try_entry_ages(c))
+    }
+
+    fn validate_column(&self, c: &dyn ColumnDefinition) -> Result<(), CacheError> {
+        self.inner.validate_column(c)
+    }
+
+    fn try_column_layout(&self, c: &dyn ColumnDefinition) -> Result<SledLayout, CacheError> {
+        self.inner.try_column_layout(c)
+    }
+
+    fn try_list_columns(&self) -> Result<Vec<String>, CacheError> {
+        self.inner.try_list_columns()
+    }
+
+    fn try_report(&self) -> Result<Vec<ColumnReport>, CacheError> {
+        self.inner.try_report()
+    }
+
+    fn try_maintenance(&self) -> Result<(), CacheError> {
+        self.inner.try_maintenance()
+    }
+
+    fn try_increment(
+        &self,
+        c: &dyn ColumnDefinition,
+        key: &[u8],
+        delta: i64,
+    ) -> Result<i64, CacheError> {
+        let start = Instant::now();
+        self.observe(c, start, self.inner.try_increment(c, key, delta))
+    }
+
+    fn set_eviction_listener(
+        &mut self,
+        listener: Arc<dyn EvictionListener>,
+    ) -> Result<(), CacheError> {
+        self.inner.set_eviction_listener(listener)
+    }
+
+    fn set_slow_operation_listener(
+        &mut self,
+        threshold: Duration,
+        listener: SlowOperationListener,
+    ) -> Result<(), CacheError> {
+        self.inner.set_slow_operation_listener(threshold, listener)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use super::{ColumnMetrics, LATENCY_BUCKETS, bucket};
+    use crate::{ColumnDefinition, Engine, noop_engine::NoopEngine};
+
+    #[test]
+    fn test_latency_buckets() {
+        assert_eq!(bucket(Duration::ZERO), 0);
+        assert_eq!(bucket(Duration::from_micros(1)), 1);
+        assert_eq!(bucket(Duration::from_micros(3)), 2);
+        assert_eq!(bucket(Duration::from_micros(4)), 3);
+        assert_eq!(bucket(Duration::from_hours(24)), LATENCY_BUCKETS - 1);
+    }
+
+    #[test]
+    fn test_latency_percentile() {
+        let mut metrics = ColumnMetrics {
+            name: "column".to_string(),
+            description: None,
+            hits: 0,
+            misses: 0,
+            errors: 0,
+            latencies: [0; LATENCY_BUCKETS],
+        };
+        assert_eq!(metrics.latency_percentile(50.0), None);
+
+        metrics.latencies[2] = 90;
+        metrics.latencies[10] = 10;
+        assert_eq!(metrics.operations(), 100);
+        assert_eq!(
+            metrics.latency_percentile(50.0),
+            Some(Duration::from_micros(4))
+        );
+        assert_eq!(
+            metrics.latency_percentile(90.0),
+            Some(Duration::from_micros(4))
+        );
+        assert_eq!(
+            metrics.latency_percentile(99.0),
+            Some(Duration::from_micros(1024))
+        );
+        assert_eq!(
+            metrics.latency_percentile(0.0),
+            Some(Duration::from_micros(4))
+        );
+    }
+
+    #[test]
+    fn test_column_description() {
+        struct Described;
+
+        impl ColumnDefinition for Described {
+            fn name(&self) -> String {
+                "described".to_string()
+            }
+
+            fn get_ttl_in_seconds(&self) -> i32 {
+                60
+            }
+
+            fn description(&self) -> Option<&str> {
+                Some("profiles by user id")
+            }
+        }
+
+        let engine = Engine::new(NoopEngine::passthrough()).with_metrics();
+        assert!(engine.try_get::<_, u32>(&Described, &"ada").is_ok());
+
+        let metrics = engine.metrics_snapshot();
+        assert_eq!(
+            metrics.column("described").unwrap().description.as_deref(),
+            Some("profiles by user id")
+        );
+    }
+}
@@ -0,0 +1,149 @@
+use crate::{CacheError, checksum};
+
+///
+/// A transformation of value bytes, stacked on an engine with [`crate::Engine::with_middleware`]
+///
+/// Writes pass the encoded value through [`ValueMiddleware::on_write`] of each middleware
+/// in the order they were added, and reads undo them by passing the stored bytes through
+/// [`ValueMiddleware::on_read`] in reverse order, so each middleware reads back exactly
+/// the bytes it wrote. [`Checksum`], [`crate::Compression`] and [`crate::Encryptor`]
+/// are middleware, to be combined and ordered freely with your own.
+///
+/// Failures should be [`CacheError::Encode`] from writes and [`CacheError::Decode`] from
+/// reads, whose type name, column and key the engine fills in, leaving only `source` to set.
+///
+/// ```
+/// use omega_cache::{CacheError, ValueMiddleware};
+///
+/// /// Versions values so a change of layout can be told apart on read
+/// struct Versioned(u8);
+///
+/// impl ValueMiddleware for Versioned {
+///     fn on_write(&self, mut bytes: Vec<u8>) -> Result<Vec<u8>, CacheError> {
+///         bytes.insert(0, self.0);
+///         Ok(bytes)
+///     }
+///
+///     fn on_read(&self, mut bytes: Vec<u8>) -> Result<Vec<u8>, CacheError> {
+///         if bytes.first() != Some(&self.0) {
+///             return Err(CacheError::Decode {
+///                 column: String::new(),
+///                 key_hint: String::new(),
+///                 source: "unknown version".to_string(),
+///             });
+///         }
+///
+///         bytes.remove(0);
+///         Ok(bytes)
+///     }
+/// }
+/// ```
+///
+pub trait ValueMiddleware: Send + Sync {
+    /// Transform bytes on their way to storage
+    ///
+    /// # Errors
+    /// Returns [`CacheError::Encode`] if the bytes cannot be transformed
+    fn on_write(&self, bytes: Vec<u8>) -> Result<Vec<u8>, CacheError>;
+
+    /// Undo [`ValueMiddleware::on_write`] on bytes read from storage
+    ///
+    /// # Errors
+    /// Returns [`CacheError::Decode`] if the bytes were not written by this middleware
+    /// or fail to verify
+    fn on_read(&self, bytes: Vec<u8>) -> Result<Vec<u8>, CacheError>;
+}
+
+/// Build a [`CacheError::Encode`] for the engine to name the value type of
+#[cfg(any(feature = "gzip", feature = "encryption"))]
+fn write_error(source: String) -> CacheError {
+    CacheError::Encode {
+        type_name: String::new(),
+        source,
+    }
+}
+
+/// Build a [`CacheError::Decode`] for the engine to name the column and key of
+fn read_error(source: &str) -> CacheError {
+    CacheError::Decode {
+        column: String::new(),
+        key_hint: String::new(),
+        source: source.to_string(),
+    }
+}
+
+///
+/// Middleware storing a CRC32 checksum alongside each value and verifying it on read,
+/// as [`crate::Engine::with_checksums`] does at a fixed place in the pipeline
+///
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Checksum;
+
+impl ValueMiddleware for Checksum {
+    fn on_write(&self, bytes: Vec<u8>) -> Result<Vec<u8>, CacheError> {
+        Ok(checksum::seal(&bytes))
+    }
+
+    fn on_read(&self, bytes: Vec<u8>) -> Result<Vec<u8>, CacheError> {
+        checksum::unseal(&bytes)
+            .map(<[u8]>::to_vec)
+            .ok_or_else(|| read_error("checksum mismatch"))
+    }
+}
+
+#[cfg(feature = "gzip")]
+impl ValueMiddleware for crate::Compression {
+    fn on_write(&self, bytes: Vec<u8>) -> Result<Vec<u8>, CacheError> {
+        self.compress(&bytes)
+            .map_err(|e| write_error(e.to_string()))
+    }
+
+    fn on_read(&self, bytes: Vec<u8>) -> Result<Vec<u8>, CacheError> {
+        crate::compression::decompress(bytes.into())
+            .map(std::borrow::Cow::into_owned)
+            .ok_or_else(|| read_error("decompression failed"))
+    }
+}
+
+/// Middleware does not see the column and key of the value, so values are not bound
+/// to them as with [`crate::Engine::with_encryption`]
+#[cfg(feature = "encryption")]
+impl ValueMiddleware for crate::Encryptor {
+    fn on_write(&self, bytes: Vec<u8>) -> Result<Vec<u8>, CacheError> {
+        self.seal("", &[], &bytes)
+            .map_err(|e| write_error(e.to_string()))
+    }
+
+    fn on_read(&self, bytes: Vec<u8>) -> Result<Vec<u8>, CacheError> {
+        self.open("", &[], &bytes)
+            .ok_or_else(|| read_error("decryption failed"))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Checksum, ValueMiddleware};
+    use crate::CacheError;
+
+    #[test]
+    fn test_checksum_middleware() {
+        let mut sealed = Checksum.on_write(b"some value".to_vec()).unwrap();
+        assert_eq!(Checksum.on_read(sealed.clone()).unwrap(), b"some value");
+
+        sealed[5] ^= 0x01;
+        assert!(matches!(
+            Checksum.on_read(sealed),
+            Err(CacheError::Decode { .. })
+        ));
+    }
+
+    #[test]
+    #[cfg(feature = "gzip")]
+    fn test_compression_middleware() {
+        let value = b"some value ".repeat(32);
+        let compressed = crate::Compression::Gzip.on_write(value.clone()).unwrap();
+
+        assert!(compressed.len() < value.len());
+        assert_eq!(crate::Compression::Gzip.on_read(compressed).unwrap(), value);
+    }
+}
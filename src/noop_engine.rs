@@ -1,34 +1,465 @@
-use crate::{CacheError, CacheStorage, ColumnDefinition};
+use std::{
+    collections::HashMap,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicU64, Ordering},
+    },
+};
+
+use crate::{
+    BackendConfig, CacheError, CacheStorage, ColumnDefinition, ColumnReport, EvictReason,
+    EvictionListener, RawEntry, TimedEntry, TransactionCondition, TransactionWrite,
+    eviction::Evictions,
+};
+
+/// A value held by a passthrough [`NoopEngine`]
+#[derive(Debug)]
+struct Entry {
+    /// Insertion sequence number, used to evict the oldest entries
+    inserted: u64,
+    /// The stored bytes
+    value: Vec<u8>,
+}
+
+/// Values held by a passthrough [`NoopEngine`], keyed by column name then key
+type PassthroughStore = Mutex<HashMap<String, HashMap<Vec<u8>, Entry>>>;
+
+/// Remove the oldest entries from the column until it holds at most `max_entries`,
+/// returning the keys removed
+fn evict_oldest(column: &mut HashMap<Vec<u8>, Entry>, max_entries: usize) -> Vec<Vec<u8>> {
+    let mut removed = Vec::new();
+
+    while column.len() > max_entries {
+        let Some(oldest) = column
+            .iter()
+            .min_by_key(|(_, entry)| entry.inserted)
+            .map(|(key, _)| key.clone())
+        else {
+            break;
+        };
+
+        column.remove(&oldest);
+        removed.push(oldest);
+    }
+
+    removed
+}
 
 ///
 /// Noop engine for testing
 ///
+/// By default every write is discarded and every read is a miss.
+/// A passthrough engine built with [`NoopEngine::passthrough`] instead keeps
+/// writes in a process-local [`HashMap`] so read-through logic can be tested
+/// without a real backend. Passthrough values never expire.
+///
 #[derive(Default, Debug)]
-pub struct NoopEngine {}
+pub struct NoopEngine {
+    passthrough: Option<PassthroughStore>,
+    sequence: AtomicU64,
+    evictions: Evictions,
+}
+
+impl NoopEngine {
+    /// A Noop engine that discards everything, usable in statics
+    pub(crate) const fn inert() -> NoopEngine {
+        NoopEngine {
+            passthrough: None,
+            sequence: AtomicU64::new(0),
+            evictions: Evictions::none(),
+        }
+    }
+
+    ///
+    /// Build a Noop engine whose writes are visible to subsequent reads
+    /// within the same process
+    ///
+    /// ```
+    /// use omega_cache::{Engine, noop_engine::NoopEngine};
+    ///
+    /// let engine = Engine::new(NoopEngine::passthrough());
+    /// ```
+    ///
+    #[must_use]
+    pub fn passthrough() -> Box<dyn CacheStorage + Send + Sync> {
+        Box::new(NoopEngine {
+            passthrough: Some(Mutex::default()),
+            sequence: AtomicU64::default(),
+            evictions: Evictions::default(),
+        })
+    }
+}
 
 impl CacheStorage for NoopEngine {
     fn build(_path: String, _capacity: Option<u64>) -> Box<dyn CacheStorage + Send + Sync> {
-        Box::new(NoopEngine {})
+        Box::new(NoopEngine::default())
+    }
+
+    /// Only an engine without passthrough discards writes
+    fn discards_writes(&self) -> bool {
+        self.passthrough.is_none()
+    }
+
+    fn describe_config(&self) -> BackendConfig {
+        BackendConfig::new("noop").with("passthrough", self.passthrough.is_some())
     }
 
     fn try_insert(
         &self,
-        _c: &dyn ColumnDefinition,
-        _key: &[u8],
-        _value: &[u8],
+        c: &dyn ColumnDefinition,
+        key: &[u8],
+        value: &[u8],
     ) -> Result<(), CacheError> {
-        Ok(())
+        self.try_swap(c, key, value).map(|_| ())
+    }
+
+    fn try_swap(
+        &self,
+        c: &dyn ColumnDefinition,
+        key: &[u8],
+        value: &[u8],
+    ) -> Result<Option<Vec<u8>>, CacheError> {
+        let Some(store) = &self.passthrough else {
+            return Ok(None);
+        };
+
+        let mut store = store.lock().map_err(|e| CacheError::Put(e.to_string()))?;
+        let column = store.entry(c.name()).or_default();
+
+        let old = column.insert(
+            key.to_vec(),
+            Entry {
+                inserted: self.sequence.fetch_add(1, Ordering::Relaxed),
+                value: value.to_vec(),
+            },
+        );
+
+        let evicted = match c.max_entries() {
+            Some(max_entries) => evict_oldest(column, max_entries),
+            None => Vec::new(),
+        };
+        drop(store);
+
+        // listeners run without the store locked so they may read it
+        let name = c.name();
+        if old.is_some() {
+            self.evictions.evicted(&name, key, EvictReason::Replaced);
+        }
+        for evicted in evicted {
+            self.evictions
+                .evicted(&name, &evicted, EvictReason::CapacityEvicted);
+        }
+
+        Ok(old.map(|entry| entry.value))
+    }
+
+    fn try_rename(
+        &self,
+        c: &dyn ColumnDefinition,
+        from: &[u8],
+        to: &[u8],
+    ) -> Result<bool, CacheError> {
+        let Some(store) = &self.passthrough else {
+            return Ok(false);
+        };
+
+        let mut store = store.lock().map_err(|e| CacheError::Put(e.to_string()))?;
+        let Some(column) = store.get_mut(&c.name()) else {
+            return Ok(false);
+        };
+
+        let Some(entry) = column.remove(from) else {
+            return Ok(false);
+        };
+        let replaced = column.insert(to.to_vec(), entry).is_some();
+        drop(store);
+
+        if replaced {
+            self.evictions.evicted(&c.name(), to, EvictReason::Replaced);
+        }
+
+        Ok(true)
     }
 
-    fn try_get(
+    /// Takes under the passthrough store's lock, an inert engine has nothing to take
+    fn try_take(
         &self,
-        _c: &dyn ColumnDefinition,
-        _key: &[u8],
+        c: &dyn ColumnDefinition,
+        key: &[u8],
     ) -> Result<Option<Vec<u8>>, CacheError> {
-        Ok(None)
+        let Some(store) = &self.passthrough else {
+            return Ok(None);
+        };
+
+        let mut store = store.lock().map_err(|e| CacheError::Put(e.to_string()))?;
+        let taken = store
+            .get_mut(&c.name())
+            .and_then(|column| column.remove(key))
+            .map(|entry| entry.value);
+        drop(store);
+
+        if taken.is_some() {
+            self.evictions.evicted(&c.name(), key, EvictReason::Removed);
+        }
+
+        Ok(taken)
+    }
+
+    /// Passthrough values never expire, so this only counts those at keys
+    fn try_touch_many(
+        &self,
+        c: &dyn ColumnDefinition,
+        keys: &[&[u8]],
+        _ttl_in_seconds: i32,
+    ) -> Result<usize, CacheError> {
+        let Some(store) = &self.passthrough else {
+            return Ok(0);
+        };
+
+        let store = store.lock().map_err(|e| CacheError::Put(e.to_string()))?;
+        let Some(column) = store.get(&c.name()) else {
+            return Ok(0);
+        };
+
+        Ok(keys.iter().filter(|key| column.contains_key(**key)).count())
+    }
+
+    /// Commits under the passthrough store's lock, an inert engine checks
+    /// the conditions against missing values and writes nothing
+    fn try_commit(
+        &self,
+        c: &dyn ColumnDefinition,
+        conditions: &[TransactionCondition],
+        writes: &[TransactionWrite],
+    ) -> Result<bool, CacheError> {
+        let Some(store) = &self.passthrough else {
+            for condition in conditions {
+                if !(condition.holds)(None)? {
+                    return Ok(false);
+                }
+            }
+
+            return Ok(true);
+        };
+
+        let mut store = store.lock().map_err(|e| CacheError::Put(e.to_string()))?;
+        let column = store.entry(c.name()).or_default();
+
+        for condition in conditions {
+            let stored = column
+                .get(condition.key)
+                .map(|entry| entry.value.as_slice());
+            if !(condition.holds)(stored)? {
+                return Ok(false);
+            }
+        }
+
+        let mut evicted = Vec::new();
+        for write in writes {
+            let old = match write.value {
+                Some(value) => column.insert(
+                    write.key.to_vec(),
+                    Entry {
+                        inserted: self.sequence.fetch_add(1, Ordering::Relaxed),
+                        value: value.to_vec(),
+                    },
+                ),
+                None => column.remove(write.key),
+            };
+
+            if old.is_some() {
+                let reason = match write.value {
+                    Some(_) => EvictReason::Replaced,
+                    None => EvictReason::Removed,
+                };
+                evicted.push((write.key.to_vec(), reason));
+            }
+        }
+
+        if let Some(max_entries) = c.max_entries() {
+            evicted.extend(
+                evict_oldest(column, max_entries)
+                    .into_iter()
+                    .map(|key| (key, EvictReason::CapacityEvicted)),
+            );
+        }
+        drop(store);
+
+        let name = c.name();
+        for (key, reason) in evicted {
+            self.evictions.evicted(&name, &key, reason);
+        }
+
+        Ok(true)
     }
 
-    fn try_drop_column(&self, _c: &dyn ColumnDefinition) -> Result<(), CacheError> {
+    fn try_insert_with_ttl(
+        &self,
+        c: &dyn ColumnDefinition,
+        key: &[u8],
+        value: &[u8],
+        _ttl_in_seconds: i32,
+    ) -> Result<(), CacheError> {
+        self.try_insert(c, key, value)
+    }
+
+    fn try_get(&self, c: &dyn ColumnDefinition, key: &[u8]) -> Result<Option<Vec<u8>>, CacheError> {
+        match &self.passthrough {
+            Some(store) => Ok(store
+                .lock()
+                .map_err(|e| CacheError::Get(e.to_string()))?
+                .get(&c.name())
+                .and_then(|column| column.get(key))
+                .map(|entry| entry.value.clone())),
+            None => Ok(None),
+        }
+    }
+
+    fn try_drop_column(&self, c: &dyn ColumnDefinition) -> Result<(), CacheError> {
+        if let Some(store) = &self.passthrough {
+            store
+                .lock()
+                .map_err(|e| CacheError::Engine(e.to_string()))?
+                .remove(&c.name());
+        }
+
+        Ok(())
+    }
+
+    fn try_entries(&self, c: &dyn ColumnDefinition) -> Result<Vec<RawEntry>, CacheError> {
+        match &self.passthrough {
+            Some(store) => Ok(store
+                .lock()
+                .map_err(|e| CacheError::Get(e.to_string()))?
+                .get(&c.name())
+                .map(|column| {
+                    column
+                        .iter()
+                        .map(|(key, entry)| (key.clone(), entry.value.clone()))
+                        .collect()
+                })
+                .unwrap_or_default()),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    /// Passthrough values never expire
+    fn try_timed_entries(&self, c: &dyn ColumnDefinition) -> Result<Vec<TimedEntry>, CacheError> {
+        Ok(self
+            .try_entries(c)?
+            .into_iter()
+            .map(|(key, value)| (key, value, None))
+            .collect())
+    }
+
+    fn try_count_column(&self, c: &dyn ColumnDefinition) -> Result<usize, CacheError> {
+        match &self.passthrough {
+            Some(store) => Ok(store
+                .lock()
+                .map_err(|e| CacheError::Engine(e.to_string()))?
+                .get(&c.name())
+                .map_or(0, HashMap::len)),
+            None => Ok(0),
+        }
+    }
+
+    fn try_list_columns(&self) -> Result<Vec<String>, CacheError> {
+        match &self.passthrough {
+            Some(store) => {
+                let mut columns = store
+                    .lock()
+                    .map_err(|e| CacheError::Engine(e.to_string()))?
+                    .keys()
+                    .cloned()
+                    .collect::<Vec<_>>();
+                columns.sort();
+
+                Ok(columns)
+            }
+            None => Ok(Vec::new()),
+        }
+    }
+
+    fn try_report(&self) -> Result<Vec<ColumnReport>, CacheError> {
+        match &self.passthrough {
+            Some(store) => {
+                let mut report = store
+                    .lock()
+                    .map_err(|e| CacheError::Engine(e.to_string()))?
+                    .iter()
+                    .map(|(name, column)| ColumnReport {
+                        name: name.clone(),
+                        entries: column.len(),
+                        size_on_disk: None,
+                        description: None,
+                    })
+                    .collect::<Vec<_>>();
+                report.sort_by(|a, b| a.name.cmp(&b.name));
+
+                Ok(report)
+            }
+            None => Ok(Vec::new()),
+        }
+    }
+
+    fn try_increment(
+        &self,
+        c: &dyn ColumnDefinition,
+        key: &[u8],
+        delta: i64,
+    ) -> Result<i64, CacheError> {
+        let Some(store) = &self.passthrough else {
+            return Ok(delta);
+        };
+
+        let mut store = store.lock().map_err(|e| CacheError::Put(e.to_string()))?;
+        let column = store.entry(c.name()).or_default();
+
+        let current = match column.get(key) {
+            Some(entry) => {
+                bincode::decode_from_slice::<i64, _>(&entry.value, bincode::config::standard())
+                    .map_err(|e| CacheError::decode(c, key, e.to_string()))?
+                    .0
+            }
+            None => 0,
+        };
+
+        let value = current
+            .checked_add(delta)
+            .ok_or_else(|| CacheError::Put("counter overflow".to_string()))?;
+
+        let bytes = bincode::encode_to_vec(value, bincode::config::standard())
+            .map_err(|e| CacheError::encode::<i64>(e.to_string()))?;
+
+        column
+            .entry(key.to_vec())
+            .and_modify(|entry| entry.value.clone_from(&bytes))
+            .or_insert_with(|| Entry {
+                inserted: self.sequence.fetch_add(1, Ordering::Relaxed),
+                value: bytes.clone(),
+            });
+
+        let evicted = match c.max_entries() {
+            Some(max_entries) => evict_oldest(column, max_entries),
+            None => Vec::new(),
+        };
+        drop(store);
+
+        for evicted in evicted {
+            self.evictions
+                .evicted(&c.name(), &evicted, EvictReason::CapacityEvicted);
+        }
+
+        Ok(value)
+    }
+
+    /// Only a passthrough engine evicts, the listener of an inert one is never called
+    fn set_eviction_listener(
+        &mut self,
+        listener: Arc<dyn EvictionListener>,
+    ) -> Result<(), CacheError> {
+        self.evictions.set(listener);
+
         Ok(())
     }
 }
@@ -64,4 +495,84 @@ mod test {
         assert!(noop.try_get(&COLUMN, "".as_bytes()).is_ok());
         assert!(noop.try_drop_column(&COLUMN).is_ok());
     }
+
+    #[test]
+    fn test_noop_passthrough() {
+        let noop = NoopEngine::passthrough();
+
+        assert!(
+            noop.try_insert(&COLUMN, "key".as_bytes(), "value".as_bytes())
+                .is_ok()
+        );
+        assert_eq!(
+            noop.try_get(&COLUMN, "key".as_bytes()).unwrap(),
+            Some("value".as_bytes().to_vec())
+        );
+        assert!(noop.try_drop_column(&COLUMN).is_ok());
+        assert!(noop.try_get(&COLUMN, "key".as_bytes()).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_noop_passthrough_increment_overflow() {
+        let noop = NoopEngine::passthrough();
+
+        assert_eq!(
+            noop.try_increment(&COLUMN, "counter".as_bytes(), i64::MAX - 1)
+                .unwrap(),
+            i64::MAX - 1
+        );
+        assert!(
+            noop.try_increment(&COLUMN, "counter".as_bytes(), 2)
+                .is_err()
+        );
+        assert_eq!(
+            noop.try_increment(&COLUMN, "counter".as_bytes(), 0)
+                .unwrap(),
+            i64::MAX - 1
+        );
+    }
+
+    #[test]
+    fn test_noop_passthrough_max_entries() {
+        struct BoundedColumn {}
+
+        impl ColumnDefinition for BoundedColumn {
+            fn name(&self) -> String {
+                "bounded_column".to_string()
+            }
+
+            fn get_ttl_in_seconds(&self) -> i32 {
+                1
+            }
+
+            fn max_entries(&self) -> Option<usize> {
+                Some(2)
+            }
+        }
+
+        let noop = NoopEngine::passthrough();
+
+        for key in ["a", "b", "c"] {
+            assert!(
+                noop.try_insert(&BoundedColumn {}, key.as_bytes(), "".as_bytes())
+                    .is_ok()
+            );
+        }
+
+        assert!(
+            noop.try_get(&BoundedColumn {}, "a".as_bytes())
+                .unwrap()
+                .is_none()
+        );
+        assert!(
+            noop.try_get(&BoundedColumn {}, "b".as_bytes())
+                .unwrap()
+                .is_some()
+        );
+        assert!(
+            noop.try_get(&BoundedColumn {}, "c".as_bytes())
+                .unwrap()
+                .is_some()
+        );
+    }
 }
@@ -0,0 +1,385 @@
+use std::{sync::Arc, time::Duration};
+
+use crate::{
+    BackendConfig, CacheError, CacheStorage, ColumnDefinition, ColumnReport, ColumnWriter,
+    EvictReason, EvictionListener, InsertOutcome, RawEntry, SledLayout, SlowOperationListener,
+    StoredValue, TimedEntry, TransactionCondition, TransactionWrite, TtlEnforcement,
+    noop_engine::NoopEngine,
+};
+
+/// Storage wrapper prepending a prefix to every key, see [`crate::Engine::with_key_prefix`]
+pub(crate) struct KeyPrefix {
+    pub(crate) inner: Box<dyn CacheStorage + Send + Sync>,
+    pub(crate) prefix: Vec<u8>,
+}
+
+impl KeyPrefix {
+    /// Key handed to the inner storage
+    fn key(&self, key: &[u8]) -> Vec<u8> {
+        [self.prefix.as_slice(), key].concat()
+    }
+}
+
+/// Writer of the inner storage with keys prefixed
+struct PrefixWriter<'a> {
+    inner: Box<dyn ColumnWriter + 'a>,
+    storage: &'a KeyPrefix,
+}
+
+impl ColumnWriter for PrefixWriter<'_> {
+    fn try_insert(&self, key: &[u8], value: &[u8]) -> Result<(), CacheError> {
+        self.inner.try_insert(&self.storage.key(key), value)
+    }
+}
+
+/// Listener of the inner storage, reporting keys with the prefix removed
+struct PrefixListener {
+    inner: Arc<dyn EvictionListener>,
+    prefix: Vec<u8>,
+}
+
+impl EvictionListener for PrefixListener {
+    fn on_evict(&self, column: &str, key: &[u8], reason: EvictReason) {
+        if let Some(key) = key.strip_prefix(self.prefix.as_slice()) {
+            self.inner.on_evict(column, key, reason);
+        }
+    }
+
+    fn on_error(&self, error: &CacheError) {
+        self.inner.on_error(error);
+    }
+}
+
+impl CacheStorage for KeyPrefix {
+    /// Only built by wrapping the storage of an engine, this builds an unprefixed [`NoopEngine`]
+    fn build(path: String, capacity: Option<u64>) -> Box<dyn CacheStorage + Send + Sync>
+    where
+        Self: Sized,
+    {
+        NoopEngine::build(path, capacity)
+    }
+
+    fn discards_writes(&self) -> bool {
+        self.inner.discards_writes()
+    }
+
+    fn ttl_enforcement(&self) -> TtlEnforcement {
+        self.inner.ttl_enforcement()
+    }
+
+    fn describe_config(&self) -> BackendConfig {
+        self.inner
+            .describe_config()
+            .with("key_prefix", String::from_utf8_lossy(&self.prefix))
+    }
+
+    fn try_insert(
+        &self,
+        c: &dyn ColumnDefinition,
+        key: &[u8],
+        value: &[u8],
+    ) -> Result<(), CacheError> {
+        self.inner.try_insert(c, &self.key(key), value)
+    }
+
+    fn try_column_writer<'a>(
+        &'a self,
+        c: &'a dyn ColumnDefinition,
+    ) -> Result<Box<dyn ColumnWriter + 'a>, CacheError> {
+        Ok(Box::new(PrefixWriter {
+            inner: self.inner.try_column_writer(c)?,
+            storage: self,
+        }))
+    }
+
+    fn try_insert_with_ttl(
+        &self,
+        c: &dyn ColumnDefinition,
+        key: &[u8],
+        value: &[u8],
+        ttl_in_seconds: i32,
+    ) -> Result<(), CacheError> {
+        self.inner
+            .try_insert_with_ttl(c, &self.key(key), value, ttl_in_seconds)
+    }
+
+    fn try_insert_reporting(
+        &self,
+        c: &dyn ColumnDefinition,
+        key: &[u8],
+        value: &[u8],
+    ) -> Result<InsertOutcome, CacheError> {
+        self.inner.try_insert_reporting(c, &self.key(key), value)
+    }
+
+    fn try_insert_batch(
+        &self,
+        c: &dyn ColumnDefinition,
+        items: &[(Vec<u8>, Vec<u8>)],
+    ) -> Result<(), CacheError> {
+        let items = items
+            .iter()
+            .map(|(key, value)| (self.key(key), value.clone()))
+            .collect::<Vec<_>>();
+
+        self.inner.try_insert_batch(c, &items)
+    }
+
+    fn try_swap(
+        &self,
+        c: &dyn ColumnDefinition,
+        key: &[u8],
+        value: &[u8],
+    ) -> Result<Option<Vec<u8>>, CacheError> {
+        self.inner.try_swap(c, &self.key(key), value)
+    }
+
+    fn try_rename(
+        &self,
+        c: &dyn ColumnDefinition,
+        from: &[u8],
+        to: &[u8],
+    ) -> Result<bool, CacheError> {
+        self.inner.try_rename(c, &self.key(from), &self.key(to))
+    }
+
+    fn try_take(
+        &self,
+        c: &dyn ColumnDefinition,
+        key: &[u8],
+    ) -> Result<Option<Vec<u8>>, CacheError> {
+        self.inner.try_take(c, &self.key(key))
+    }
+
+    fn try_commit(
+        &self,
+        c: &dyn ColumnDefinition,
+        conditions: &[TransactionCondition],
+        writes: &[TransactionWrite],
+    ) -> Result<bool, CacheError> {
+        let keys = conditions
+            .iter()
+            .map(|condition| self.key(condition.key))
+            .chain(writes.iter().map(|write| self.key(write.key)))
+            .collect::<Vec<_>>();
+        let (condition_keys, write_keys) = keys.split_at(conditions.len());
+
+        let conditions = conditions
+            .iter()
+            .zip(condition_keys)
+            .map(|(condition, key)| TransactionCondition {
+                key,
+                holds: condition.holds,
+            })
+            .collect::<Vec<_>>();
+        let writes = writes
+            .iter()
+            .zip(write_keys)
+            .map(|(write, key)| TransactionWrite {
+                key,
+                value: write.value,
+            })
+            .collect::<Vec<_>>();
+
+        self.inner.try_commit(c, &conditions, &writes)
+    }
+
+    fn try_touch_many(
+        &self,
+        c: &dyn ColumnDefinition,
+        keys: &[&[u8]],
+        ttl_in_seconds: i32,
+    ) -> Result<usize, CacheError> {
+        let keys = keys.iter().map(|key| self.key(key)).collect::<Vec<_>>();
+
+        self.inner.try_touch_many(
+            c,
+            &keys.iter().map(Vec::as_slice).collect::<Vec<_>>(),
+            ttl_in_seconds,
+        )
+    }
+
+    fn try_get(&self, c: &dyn ColumnDefinition, key: &[u8]) -> Result<Option<Vec<u8>>, CacheError> {
+        self.inner.try_get(c, &self.key(key))
+    }
+
+    fn try_get_many(
+        &self,
+        c: &dyn ColumnDefinition,
+        keys: &[&[u8]],
+    ) -> Result<Vec<Option<Vec<u8>>>, CacheError> {
+        let keys = keys.iter().map(|key| self.key(key)).collect::<Vec<_>>();
+
+        self.inner
+            .try_get_many(c, &keys.iter().map(Vec::as_slice).collect::<Vec<_>>())
+    }
+
+    fn try_value_size(
+        &self,
+        c: &dyn ColumnDefinition,
+        key: &[u8],
+    ) -> Result<Option<usize>, CacheError> {
+        self.inner.try_value_size(c, &self.key(key))
+    }
+
+    fn try_get_stored(
+        &self,
+        c: &dyn ColumnDefinition,
+        key: &[u8],
+    ) -> Result<Option<StoredValue>, CacheError> {
+        self.inner.try_get_stored(c, &self.key(key))
+    }
+
+    fn try_get_allowing_stale(
+        &self,
+        c: &dyn ColumnDefinition,
+        key: &[u8],
+    ) -> Result<Option<(Vec<u8>, bool)>, CacheError> {
+        self.inner.try_get_allowing_stale(c, &self.key(key))
+    }
+
+    /// Drops the whole column, including keys written under other prefixes
+    fn try_drop_column(&self, c: &dyn ColumnDefinition) -> Result<(), CacheError> {
+        self.inner.try_drop_column(c)
+    }
+
+    /// Only the entries written under this prefix, with the prefix removed
+    fn try_entries(&self, c: &dyn ColumnDefinition) -> Result<Vec<RawEntry>, CacheError> {
+        Ok(self
+            .inner
+            .try_entries(c)?
+            .into_iter()
+            .filter_map(|(key, value)| {
+                key.strip_prefix(self.prefix.as_slice())
+                    .map(|key| (key.to_vec(), value))
+            })
+            .collect())
+    }
+
+    fn try_timed_entries(&self, c: &dyn ColumnDefinition) -> Result<Vec<TimedEntry>, CacheError> {
+        Ok(self
+            .inner
+            .try_timed_entries(c)?
+            .into_iter()
+            .filter_map(|(key, value, ttl)| {
+                key.strip_prefix(self.prefix.as_slice())
+                    .map(|key| (key.to_vec(), value, ttl))
+            })
+            .collect())
+    }
+
+    /// Counts the whole column, including keys written under other prefixes
+    fn try_count_column(&self, c: &dyn ColumnDefinition) -> Result<usize, CacheError> {
+        self.inner.try_count_column(c)
+    }
+
+    /// Ages of the whole column, including keys written under other prefixes
+    fn try_entry_ages(&self, c: &dyn ColumnDefinition) -> Result<Vec<Duration>, CacheError> {
+        self.inner.try_entry_ages(c)
+    }
+
+    fn validate_column(&self, c: &dyn ColumnDefinition) -> Result<(), CacheError> {
+        self.inner.validate_column(c)
+    }
+
+    fn try_column_layout(&self, c: &dyn ColumnDefinition) -> Result<SledLayout, CacheError> {
+        self.inner.try_column_layout(c)
+    }
+
+    fn try_list_columns(&self) -> Result<Vec<String>, CacheError> {
+        self.inner.try_list_columns()
+    }
+
+    fn try_report(&self) -> Result<Vec<ColumnReport>, CacheError> {
+        self.inner.try_report()
+    }
+
+    fn try_maintenance(&self) -> Result<(), CacheError> {
+        self.inner.try_maintenance()
+    }
+
+    fn try_increment(
+        &self,
+        c: &dyn ColumnDefinition,
+        key: &[u8],
+        delta: i64,
+    ) -> Result<i64, CacheError> {
+        self.inner.try_increment(c, &self.key(key), delta)
+    }
+
+    /// Keys are reported with the prefix removed, keys under other prefixes are not reported
+    fn set_eviction_listener(
+        &mut self,
+        listener: Arc<dyn EvictionListener>,
+    ) -> Result<(), CacheError> {
+        self.inner.set_eviction_listener(Arc::new(PrefixListener {
+            inner: listener,
+            prefix: self.prefix.clone(),
+        }))
+    }
+
+    fn set_slow_operation_listener(
+        &mut self,
+        threshold: Duration,
+        listener: SlowOperationListener,
+    ) -> Result<(), CacheError> {
+        self.inner.set_slow_operation_listener(threshold, listener)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{CacheStorage, ColumnDefinition, noop_engine::NoopEngine};
+
+    use super::KeyPrefix;
+
+    struct TestColumn {}
+
+    impl ColumnDefinition for TestColumn {
+        fn name(&self) -> String {
+            "prefixed_column".to_string()
+        }
+
+        fn get_ttl_in_seconds(&self) -> i32 {
+            60
+        }
+    }
+
+    #[test]
+    fn test_keys_are_prefixed() {
+        let storage = KeyPrefix {
+            inner: NoopEngine::passthrough(),
+            prefix: b"v2:".to_vec(),
+        };
+
+        assert!(storage.try_insert(&TestColumn {}, b"key", b"value").is_ok());
+        assert!(
+            storage
+                .try_insert_batch(&TestColumn {}, &[(b"batch".to_vec(), b"value".to_vec())])
+                .is_ok()
+        );
+
+        assert!(storage.try_get(&TestColumn {}, b"key").unwrap().is_some());
+        assert!(
+            storage
+                .inner
+                .try_get(&TestColumn {}, b"v2:key")
+                .unwrap()
+                .is_some()
+        );
+        assert!(
+            storage
+                .inner
+                .try_get(&TestColumn {}, b"v2:batch")
+                .unwrap()
+                .is_some()
+        );
+        assert!(
+            storage
+                .inner
+                .try_get(&TestColumn {}, b"key")
+                .unwrap()
+                .is_none()
+        );
+    }
+}
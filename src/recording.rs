@@ -0,0 +1,718 @@
+//!
+//! Record cache operations to a file and replay them against another storage,
+//! for reproducing caching bugs seen in production.
+//!
+//! ```no_run
+//! use omega_cache::{Engine, noop_engine::NoopEngine, recording::{RecordingEngine, ReplayEngine}};
+//!
+//! // capture
+//! let engine = Engine::new(RecordingEngine::wrap(NoopEngine::passthrough(), "./cache.log").unwrap());
+//!
+//! // later, reproduce
+//! let replay = ReplayEngine::open("./cache.log").unwrap();
+//! let diverged = replay.replay(&*NoopEngine::passthrough()).unwrap();
+//! ```
+//!
+//! The log is a sequence of records, each a big-endian `u32` length followed by
+//! that many bytes of a bincode encoded record. Records hold the bytes handed to storage,
+//! so values are logged after encoding, checksums and encryption.
+//!
+
+use std::{
+    fmt::Debug,
+    fs::File,
+    io::Write,
+    path::Path,
+    sync::{Arc, Mutex, PoisonError},
+    time::Duration,
+};
+
+use crate::noop_engine::NoopEngine;
+use crate::{
+    BackendConfig, CacheError, CacheStorage, ColumnDefinition, ColumnReport, EvictionListener,
+    InsertOutcome, RawEntry, SledLayout, SlowOperationListener, StoredValue, TimedEntry,
+    TransactionCondition, TransactionWrite, TtlEnforcement,
+};
+
+/// Bytes of the length prefix of each record
+const LENGTH_PREFIX: usize = 4;
+
+/// An operation applied to storage
+#[derive(Debug, Clone, PartialEq, Eq, bincode::Encode, bincode::Decode)]
+enum Operation {
+    /// A value was written, with its TTL override if it had one
+    Insert { value: Vec<u8>, ttl: Option<i32> },
+    /// A value was read, `hit` if storage returned one
+    Get { hit: bool },
+    /// The column was dropped
+    Drop,
+    /// A counter was incremented
+    Increment { delta: i64 },
+    /// The value was moved to key `to`, `moved` if there was one
+    Rename { to: Vec<u8>, moved: bool },
+    /// The value was removed by a transaction
+    Remove,
+    /// The value was read and removed, `hit` if storage returned one
+    Take { hit: bool },
+    /// The TTLs of values at keys were restarted, `touched` of them live
+    Touch {
+        keys: Vec<Vec<u8>>,
+        ttl: i32,
+        touched: usize,
+    },
+}
+
+/// An operation and the column and key it applied to
+#[derive(Debug, Clone, PartialEq, Eq, bincode::Encode, bincode::Decode)]
+struct Record {
+    /// Milliseconds since the unix epoch when the operation completed
+    time: u64,
+    /// Column name
+    column: String,
+    /// Column TTL
+    ttl: i32,
+    /// Key, empty for column operations and refreshes of many keys
+    key: Vec<u8>,
+    operation: Operation,
+}
+
+/// Column rebuilt from a record, holding only the name and TTL
+struct RecordedColumn<'a> {
+    name: &'a str,
+    ttl: i32,
+}
+
+impl ColumnDefinition for RecordedColumn<'_> {
+    fn name(&self) -> String {
+        self.name.to_string()
+    }
+
+    fn get_ttl_in_seconds(&self) -> i32 {
+        self.ttl
+    }
+}
+
+///
+/// Storage wrapper logging every write, read, drop and increment to a file
+///
+/// The operation runs on the inner storage first and is only logged if it succeeds.
+/// Listing, reporting and size queries are passed through without being logged.
+///
+pub struct RecordingEngine {
+    inner: Box<dyn CacheStorage + Send + Sync>,
+    log: Mutex<File>,
+}
+
+impl Debug for RecordingEngine {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RecordingEngine")
+            .field("log", &self.log)
+            .finish_non_exhaustive()
+    }
+}
+
+impl RecordingEngine {
+    ///
+    /// Wrap inner storage, logging its operations to a new file at path
+    ///
+    /// # Errors
+    /// Returns [`CacheError::Engine`] if the log file cannot be created
+    pub fn wrap(
+        inner: Box<dyn CacheStorage + Send + Sync>,
+        path: impl AsRef<Path>,
+    ) -> Result<Box<dyn CacheStorage + Send + Sync>, CacheError> {
+        let log = File::create(path)
+            .map_err(|e| CacheError::Engine(format!("Failed to create recording: {e}")))?;
+
+        Ok(Box::new(RecordingEngine {
+            inner,
+            log: Mutex::new(log),
+        }))
+    }
+
+    /// Append an operation to the log
+    fn record(
+        &self,
+        c: &dyn ColumnDefinition,
+        key: &[u8],
+        operation: Operation,
+    ) -> Result<(), CacheError> {
+        let time = std::time::SystemTime::now()
+            .duration_since(std::time::SystemTime::UNIX_EPOCH)
+            .map_or(0, |d| u64::try_from(d.as_millis()).unwrap_or(u64::MAX));
+
+        let record = Record {
+            time,
+            column: c.name(),
+            ttl: c.get_ttl_in_seconds(),
+            key: key.to_vec(),
+            operation,
+        };
+
+        let body = bincode::encode_to_vec(record, bincode::config::standard())
+            .map_err(|e| CacheError::encode::<Record>(e.to_string()))?;
+        let len = u32::try_from(body.len())
+            .map_err(|_| CacheError::encode::<Record>("record is too large to log".to_string()))?;
+
+        // one write per record so concurrent writers never interleave
+        let frame = [len.to_be_bytes().as_slice(), &body].concat();
+
+        self.log
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .write_all(&frame)
+            .map_err(|e| CacheError::Engine(format!("Failed to write recording: {e}")))
+    }
+}
+
+impl CacheStorage for RecordingEngine {
+    /// Record operations on a passthrough [`NoopEngine`] to a log at path
+    ///
+    /// # Panics
+    /// Panics if the log file cannot be created
+    fn build(path: String, _capacity: Option<u64>) -> Box<dyn CacheStorage + Send + Sync>
+    where
+        Self: Sized,
+    {
+        match RecordingEngine::wrap(NoopEngine::passthrough(), path) {
+            Ok(storage) => storage,
+            Err(e) => panic!("{e}"),
+        }
+    }
+
+    fn discards_writes(&self) -> bool {
+        self.inner.discards_writes()
+    }
+
+    fn ttl_enforcement(&self) -> TtlEnforcement {
+        self.inner.ttl_enforcement()
+    }
+
+    fn describe_config(&self) -> BackendConfig {
+        self.inner.describe_config()
+    }
+
+    fn try_insert(
+        &self,
+        c: &dyn ColumnDefinition,
+        key: &[u8],
+        value: &[u8],
+    ) -> Result<(), CacheError> {
+        self.inner.try_insert(c, key, value)?;
+
+        self.record(
+            c,
+            key,
+            Operation::Insert {
+                value: value.to_vec(),
+                ttl: None,
+            },
+        )
+    }
+
+    fn try_insert_with_ttl(
+        &self,
+        c: &dyn ColumnDefinition,
+        key: &[u8],
+        value: &[u8],
+        ttl_in_seconds: i32,
+    ) -> Result<(), CacheError> {
+        self.inner
+            .try_insert_with_ttl(c, key, value, ttl_in_seconds)?;
+
+        self.record(
+            c,
+            key,
+            Operation::Insert {
+                value: value.to_vec(),
+                ttl: Some(ttl_in_seconds),
+            },
+        )
+    }
+
+    fn try_insert_reporting(
+        &self,
+        c: &dyn ColumnDefinition,
+        key: &[u8],
+        value: &[u8],
+    ) -> Result<InsertOutcome, CacheError> {
+        let outcome = self.inner.try_insert_reporting(c, key, value)?;
+
+        self.record(
+            c,
+            key,
+            Operation::Insert {
+                value: value.to_vec(),
+                ttl: None,
+            },
+        )?;
+
+        Ok(outcome)
+    }
+
+    fn try_insert_batch(
+        &self,
+        c: &dyn ColumnDefinition,
+        items: &[(Vec<u8>, Vec<u8>)],
+    ) -> Result<(), CacheError> {
+        self.inner.try_insert_batch(c, items)?;
+
+        for (key, value) in items {
+            self.record(
+                c,
+                key,
+                Operation::Insert {
+                    value: value.clone(),
+                    ttl: None,
+                },
+            )?;
+        }
+
+        Ok(())
+    }
+
+    fn try_swap(
+        &self,
+        c: &dyn ColumnDefinition,
+        key: &[u8],
+        value: &[u8],
+    ) -> Result<Option<Vec<u8>>, CacheError> {
+        let old = self.inner.try_swap(c, key, value)?;
+
+        self.record(
+            c,
+            key,
+            Operation::Insert {
+                value: value.to_vec(),
+                ttl: None,
+            },
+        )?;
+
+        Ok(old)
+    }
+
+    fn try_rename(
+        &self,
+        c: &dyn ColumnDefinition,
+        from: &[u8],
+        to: &[u8],
+    ) -> Result<bool, CacheError> {
+        let moved = self.inner.try_rename(c, from, to)?;
+
+        self.record(
+            c,
+            from,
+            Operation::Rename {
+                to: to.to_vec(),
+                moved,
+            },
+        )?;
+
+        Ok(moved)
+    }
+
+    fn try_take(
+        &self,
+        c: &dyn ColumnDefinition,
+        key: &[u8],
+    ) -> Result<Option<Vec<u8>>, CacheError> {
+        let value = self.inner.try_take(c, key)?;
+
+        self.record(
+            c,
+            key,
+            Operation::Take {
+                hit: value.is_some(),
+            },
+        )?;
+
+        Ok(value)
+    }
+
+    /// Logs the writes of committed transactions, each on its own
+    fn try_commit(
+        &self,
+        c: &dyn ColumnDefinition,
+        conditions: &[TransactionCondition],
+        writes: &[TransactionWrite],
+    ) -> Result<bool, CacheError> {
+        let committed = self.inner.try_commit(c, conditions, writes)?;
+
+        if committed {
+            for write in writes {
+                let operation = match write.value {
+                    Some(value) => Operation::Insert {
+                        value: value.to_vec(),
+                        ttl: None,
+                    },
+                    None => Operation::Remove,
+                };
+
+                self.record(c, write.key, operation)?;
+            }
+        }
+
+        Ok(committed)
+    }
+
+    fn try_touch_many(
+        &self,
+        c: &dyn ColumnDefinition,
+        keys: &[&[u8]],
+        ttl_in_seconds: i32,
+    ) -> Result<usize, CacheError> {
+        let touched = self.inner.try_touch_many(c, keys, ttl_in_seconds)?;
+
+        self.record(
+            c,
+            &[],
+            Operation::Touch {
+                keys: keys.iter().map(|key| key.to_vec()).collect(),
+                ttl: ttl_in_seconds,
+                touched,
+            },
+        )?;
+
+        Ok(touched)
+    }
+
+    fn try_get(&self, c: &dyn ColumnDefinition, key: &[u8]) -> Result<Option<Vec<u8>>, CacheError> {
+        let value = self.inner.try_get(c, key)?;
+
+        self.record(
+            c,
+            key,
+            Operation::Get {
+                hit: value.is_some(),
+            },
+        )?;
+
+        Ok(value)
+    }
+
+    fn try_get_many(
+        &self,
+        c: &dyn ColumnDefinition,
+        keys: &[&[u8]],
+    ) -> Result<Vec<Option<Vec<u8>>>, CacheError> {
+        let values = self.inner.try_get_many(c, keys)?;
+
+        for (key, value) in keys.iter().zip(&values) {
+            self.record(
+                c,
+                key,
+                Operation::Get {
+                    hit: value.is_some(),
+                },
+            )?;
+        }
+
+        Ok(values)
+    }
+
+    fn try_value_size(
+        &self,
+        c: &dyn ColumnDefinition,
+        key: &[u8],
+    ) -> Result<Option<usize>, CacheError> {
+        self.inner.try_value_size(c, key)
+    }
+
+    fn try_get_stored(
+        &self,
+        c: &dyn ColumnDefinition,
+        key: &[u8],
+    ) -> Result<Option<StoredValue>, CacheError> {
+        let value = self.inner.try_get_stored(c, key)?;
+
+        self.record(
+            c,
+            key,
+            Operation::Get {
+                hit: value.is_some(),
+            },
+        )?;
+
+        Ok(value)
+    }
+
+    fn try_get_allowing_stale(
+        &self,
+        c: &dyn ColumnDefinition,
+        key: &[u8],
+    ) -> Result<Option<(Vec<u8>, bool)>, CacheError> {
+        let value = self.inner.try_get_allowing_stale(c, key)?;
+
+        self.record(
+            c,
+            key,
+            Operation::Get {
+                hit: value.is_some(),
+            },
+        )?;
+
+        Ok(value)
+    }
+
+    fn try_drop_column(&self, c: &dyn ColumnDefinition) -> Result<(), CacheError> {
+        self.inner.try_drop_column(c)?;
+
+        self.record(c, &[], Operation::Drop)
+    }
+
+    fn try_entries(&self, c: &dyn ColumnDefinition) -> Result<Vec<RawEntry>, CacheError> {
+        self.inner.try_entries(c)
+    }
+
+    fn try_timed_entries(&self, c: &dyn ColumnDefinition) -> Result<Vec<TimedEntry>, CacheError> {
+        self.inner.try_timed_entries(c)
+    }
+
+    fn try_count_column(&self, c: &dyn ColumnDefinition) -> Result<usize, CacheError> {
+        self.inner.try_count_column(c)
+    }
+
+    fn try_entry_ages(&self, c: &dyn ColumnDefinition) -> Result<Vec<Duration>, CacheError> {
+        self.inner.try_entry_ages(c)
+    }
+
+    fn validate_column(&self, c: &dyn ColumnDefinition) -> Result<(), CacheError> {
+        self.inner.validate_column(c)
+    }
+
+    fn try_column_layout(&self, c: &dyn ColumnDefinition) -> Result<SledLayout, CacheError> {
+        self.inner.try_column_layout(c)
+    }
+
+    fn try_list_columns(&self) -> Result<Vec<String>, CacheError> {
+        self.inner.try_list_columns()
+    }
+
+    fn try_report(&self) -> Result<Vec<ColumnReport>, CacheError> {
+        self.inner.try_report()
+    }
+
+    fn try_maintenance(&self) -> Result<(), CacheError> {
+        self.inner.try_maintenance()
+    }
+
+    fn try_increment(
+        &self,
+        c: &dyn ColumnDefinition,
+        key: &[u8],
+        delta: i64,
+    ) -> Result<i64, CacheError> {
+        let value = self.inner.try_increment(c, key, delta)?;
+
+        self.record(c, key, Operation::Increment { delta })?;
+
+        Ok(value)
+    }
+
+    fn set_eviction_listener(
+        &mut self,
+        listener: Arc<dyn EvictionListener>,
+    ) -> Result<(), CacheError> {
+        self.inner.set_eviction_listener(listener)
+    }
+
+    fn set_slow_operation_listener(
+        &mut self,
+        threshold: Duration,
+        listener: SlowOperationListener,
+    ) -> Result<(), CacheError> {
+        self.inner.set_slow_operation_listener(threshold, listener)
+    }
+}
+
+///
+/// Operations read back from a log written by [`RecordingEngine`]
+///
+#[derive(Debug)]
+pub struct ReplayEngine {
+    records: Vec<Record>,
+}
+
+impl ReplayEngine {
+    ///
+    /// Read the log at path.
+    ///
+    /// A record cut short at the end of the log, as left by a crash mid-write, is ignored.
+    ///
+    /// # Errors
+    /// Returns [`CacheError::Engine`] if the log cannot be read
+    /// Returns [`CacheError::Decode`] if a record cannot be decoded
+    pub fn open(path: impl AsRef<Path>) -> Result<ReplayEngine, CacheError> {
+        let bytes = std::fs::read(path)
+            .map_err(|e| CacheError::Engine(format!("Failed to read recording: {e}")))?;
+
+        let mut records = Vec::new();
+        let mut rest = bytes.as_slice();
+
+        while let Some((len, body)) = rest.split_first_chunk::<LENGTH_PREFIX>() {
+            let Some(body) = usize::try_from(u32::from_be_bytes(*len))
+                .ok()
+                .and_then(|len| body.get(..len))
+            else {
+                break;
+            };
+
+            let record = bincode::decode_from_slice::<Record, _>(body, bincode::config::standard())
+                .map_err(|e| CacheError::Decode {
+                    column: String::new(),
+                    key_hint: format!("record {}", records.len()),
+                    source: e.to_string(),
+                })?
+                .0;
+
+            records.push(record);
+            rest = &rest[LENGTH_PREFIX + body.len()..];
+        }
+
+        Ok(ReplayEngine { records })
+    }
+
+    /// Number of recorded operations
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.records.len()
+    }
+
+    /// Whether the log holds no operations
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+
+    ///
+    /// Apply the recorded operations in order to storage, returning the number of
+    /// reads whose hit or miss differed from the recording.
+    ///
+    /// Operations run back to back rather than at their recorded times,
+    /// so values that expired between operations in the recording may still be live.
+    /// Columns are rebuilt with only their name and TTL, so limits such as
+    /// [`ColumnDefinition::max_entries`] are not applied.
+    ///
+    /// # Errors
+    /// Returns the first error from storage
+    pub fn replay(&self, storage: &dyn CacheStorage) -> Result<usize, CacheError> {
+        let mut diverged = 0;
+
+        for record in &self.records {
+            let c = RecordedColumn {
+                name: &record.column,
+                ttl: record.ttl,
+            };
+
+            match &record.operation {
+                Operation::Insert { value, ttl: None } => {
+                    storage.try_insert(&c, &record.key, value)?;
+                }
+                Operation::Insert {
+                    value,
+                    ttl: Some(ttl),
+                } => storage.try_insert_with_ttl(&c, &record.key, value, *ttl)?,
+                Operation::Get { hit } => {
+                    if storage.try_get(&c, &record.key)?.is_some() != *hit {
+                        diverged += 1;
+                    }
+                }
+                Operation::Drop => storage.try_drop_column(&c)?,
+                Operation::Increment { delta } => {
+                    storage.try_increment(&c, &record.key, *delta)?;
+                }
+                Operation::Rename { to, moved } => {
+                    if storage.try_rename(&c, &record.key, to)? != *moved {
+                        diverged += 1;
+                    }
+                }
+                Operation::Remove => {
+                    let write = TransactionWrite {
+                        key: &record.key,
+                        value: None,
+                    };
+                    storage.try_commit(&c, &[], &[write])?;
+                }
+                Operation::Take { hit } => {
+                    if storage.try_take(&c, &record.key)?.is_some() != *hit {
+                        diverged += 1;
+                    }
+                }
+                Operation::Touch { keys, ttl, touched } => {
+                    let keys = keys.iter().map(Vec::as_slice).collect::<Vec<_>>();
+                    if storage.try_touch_many(&c, &keys, *ttl)? != *touched {
+                        diverged += 1;
+                    }
+                }
+            }
+        }
+
+        Ok(diverged)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{Engine, noop_engine::NoopEngine};
+
+    use super::{RecordingEngine, ReplayEngine};
+
+    struct TestColumn {}
+
+    impl crate::ColumnDefinition for TestColumn {
+        fn name(&self) -> String {
+            "recorded_column".to_string()
+        }
+
+        fn get_ttl_in_seconds(&self) -> i32 {
+            60
+        }
+    }
+
+    #[test]
+    fn test_record_and_replay() {
+        std::fs::create_dir_all("./tmp").unwrap();
+        let path = "./tmp/recording_test.log";
+
+        let engine = Engine::new(RecordingEngine::wrap(NoopEngine::passthrough(), path).unwrap());
+        assert!(engine.try_insert(&TestColumn {}, &"a", &1i32).is_ok());
+        assert_eq!(
+            engine.try_get::<&str, i32>(&TestColumn {}, &"a").unwrap(),
+            Some(1)
+        );
+        assert!(
+            engine
+                .try_get::<&str, i32>(&TestColumn {}, &"b")
+                .unwrap()
+                .is_none()
+        );
+        assert_eq!(engine.try_increment(&TestColumn {}, &"n", 2).unwrap(), 2);
+        assert!(engine.try_drop_column(&TestColumn {}).is_ok());
+        drop(engine);
+
+        let replay = ReplayEngine::open(path).unwrap();
+        assert_eq!(replay.len(), 5);
+
+        let fresh = NoopEngine::passthrough();
+        assert_eq!(replay.replay(&*fresh).unwrap(), 0);
+
+        // a storage discarding writes misses the recorded hit
+        assert_eq!(replay.replay(&NoopEngine::default()).unwrap(), 1);
+    }
+
+    #[test]
+    fn test_replay_ignores_truncated_record() {
+        std::fs::create_dir_all("./tmp").unwrap();
+        let path = "./tmp/recording_truncated_test.log";
+
+        let engine = Engine::new(RecordingEngine::wrap(NoopEngine::passthrough(), path).unwrap());
+        assert!(engine.try_insert(&TestColumn {}, &"a", &1i32).is_ok());
+        assert!(engine.try_insert(&TestColumn {}, &"b", &2i32).is_ok());
+        drop(engine);
+
+        let bytes = std::fs::read(path).unwrap();
+        std::fs::write(path, &bytes[..bytes.len() - 1]).unwrap();
+
+        assert_eq!(ReplayEngine::open(path).unwrap().len(), 1);
+    }
+}
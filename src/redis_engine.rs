@@ -1,6 +1,368 @@
-use redis::{Commands, SetExpiry, SetOptions};
+#[cfg(feature = "redis-notifications")]
+mod notifications;
 
-use crate::{CacheError, CacheStorage};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    sync::atomic::{AtomicUsize, Ordering},
+    time::Duration,
+};
+
+use redis::{Commands, IntoConnectionInfo, Script, SetExpiry, SetOptions};
+
+use crate::timing::{OperationKind, SlowOperations, Timer};
+use crate::{
+    BackendConfig, CacheError, CacheStorage, ColumnReport, ColumnWriter, InsertOutcome, RawEntry,
+    SlowOperationListener, TimedEntry, TransactionCondition, TransactionWrite,
+};
+
+/// Increment a counter and apply the TTL only when the increment created it,
+/// so repeated increments do not extend the counter's lifetime.
+/// With a deadline key, also record the counter's absolute deadline `ARGV[3]` seconds out
+const INCREMENT_SCRIPT: &str = r"
+local value = redis.call('INCRBY', KEYS[1], ARGV[1])
+if tonumber(ARGV[2]) > 0 and redis.call('TTL', KEYS[1]) == -1 then
+    redis.call('EXPIRE', KEYS[1], ARGV[2])
+    if KEYS[2] then
+        local now = tonumber(redis.call('TIME')[1])
+        redis.call('SET', KEYS[2], now + ARGV[3], 'EX', ARGV[3])
+    end
+end
+return value
+";
+
+/// Rename a key only if it exists, returning whether it did,
+/// as `RENAME` fails on a missing key. The key keeps its TTL.
+/// Deadline keys given as `KEYS[3]` and `KEYS[4]` move along with it
+const RENAME_SCRIPT: &str = r"
+if redis.call('EXISTS', KEYS[1]) == 0 then
+    return 0
+end
+redis.call('RENAME', KEYS[1], KEYS[2])
+if KEYS[3] then
+    if redis.call('EXISTS', KEYS[3]) == 1 then
+        redis.call('RENAME', KEYS[3], KEYS[4])
+    else
+        redis.call('DEL', KEYS[4])
+    end
+end
+return 1
+";
+
+/// Set a value in a column with an idle TTL, optionally returning the value it replaced.
+///
+/// The value expires after the idle TTL `ARGV[3]`, or the absolute TTL `ARGV[2]` if sooner.
+/// The absolute deadline goes to the deadline key so reads never extend the value past it
+const IDLE_SET_SCRIPT: &str = r"
+local old = false
+if ARGV[4] == '1' then
+    old = redis.call('GET', KEYS[1])
+end
+local ttl, idle = tonumber(ARGV[2]), tonumber(ARGV[3])
+if ttl > 0 then
+    local now = tonumber(redis.call('TIME')[1])
+    redis.call('SET', KEYS[2], now + ttl, 'EX', ttl)
+    redis.call('SET', KEYS[1], ARGV[1], 'EX', math.min(ttl, idle))
+else
+    redis.call('DEL', KEYS[2])
+    redis.call('SET', KEYS[1], ARGV[1], 'EX', idle)
+end
+return old
+";
+
+/// Get a value in a column with an idle TTL, restarting its idle timer `ARGV[1]`
+/// without taking it past the deadline held by the deadline key
+const IDLE_GET_SCRIPT: &str = r"
+local value = redis.call('GET', KEYS[1])
+if not value then
+    return false
+end
+local expiry = tonumber(ARGV[1])
+local deadline = redis.call('GET', KEYS[2])
+if deadline then
+    expiry = math.min(expiry, tonumber(deadline) - tonumber(redis.call('TIME')[1]))
+end
+if expiry > 0 then
+    redis.call('EXPIRE', KEYS[1], expiry)
+end
+return value
+";
+
+/// Restart the TTL of a value in a column with an idle TTL, returning whether it exists.
+///
+/// As [`IDLE_SET_SCRIPT`] the value expires after the idle TTL `ARGV[2]`, or the
+/// absolute TTL `ARGV[1]` if sooner, with the new deadline going to the deadline key
+const IDLE_TOUCH_SCRIPT: &str = r"
+if redis.call('EXISTS', KEYS[1]) == 0 then
+    return 0
+end
+local ttl, idle = tonumber(ARGV[1]), tonumber(ARGV[2])
+if ttl > 0 then
+    local now = tonumber(redis.call('TIME')[1])
+    redis.call('SET', KEYS[2], now + ttl, 'EX', ttl)
+    redis.call('EXPIRE', KEYS[1], math.min(ttl, idle))
+else
+    redis.call('DEL', KEYS[2])
+    redis.call('EXPIRE', KEYS[1], idle)
+end
+return 1
+";
+
+/// Escape character used in column names and glob patterns
+const ESCAPE: u8 = b'\\';
+
+/// Suffix of the column holding the deadlines of a column with an idle TTL,
+/// reserved by [`crate::Engine::validate_column`]
+pub(crate) const DEADLINE_SUFFIX: &str = "#deadline";
+
+///
+/// Layout of the keys written to redis
+///
+/// Keys are written as `{prefix}{column}{separator}{key}`, by default
+/// with no prefix and a `:` separator, giving `column:key`. Any separator or
+/// `\` within a column name is escaped with a `\` so a column name can never
+/// run into the key, which keeps columns such as `a:b` and `a` apart.
+/// The key itself follows unescaped, so listing the entries of a column
+/// recovers keys holding separators, `\` or null bytes byte for byte.
+///
+/// ```
+/// use omega_cache::redis_engine::KeyFormat;
+///
+/// // Matches keys shaped like `my_app/column/key`
+/// let format = KeyFormat::default().prefix("my_app/").separator(b'/');
+/// ```
+///
+/// Columns over keys written by another system can be given that system's layout
+/// with [`KeyFormat::external_column`].
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyFormat {
+    prefix: Vec<u8>,
+    separator: u8,
+    /// Fixed prefix of the keys of each external column
+    external: BTreeMap<String, Vec<u8>>,
+}
+
+impl Default for KeyFormat {
+    fn default() -> Self {
+        KeyFormat {
+            prefix: Vec::new(),
+            separator: b':',
+            external: BTreeMap::new(),
+        }
+    }
+}
+
+impl KeyFormat {
+    /// Bytes written before the column name of every key
+    #[must_use]
+    pub fn prefix(mut self, prefix: impl Into<Vec<u8>>) -> KeyFormat {
+        self.prefix = prefix.into();
+        self
+    }
+
+    /// Byte written between the column name and the key
+    #[must_use]
+    pub fn separator(mut self, separator: u8) -> KeyFormat {
+        self.separator = separator;
+        self
+    }
+
+    /// Read and write the keys of the column as `{prefix}{key}`, for reading keys
+    /// populated by another system, with an empty prefix for plain `key`.
+    ///
+    /// Neither the format prefix nor the column name is added, so nothing keeps the
+    /// column apart from the rest of the database. With no prefix, or a prefix shared
+    /// with other keys, the column's scans, such as counting, listing its entries
+    /// and their ages, take in every key of the database or of the prefix,
+    /// whichever system wrote them. Listing and reporting columns attribute keys
+    /// under a non empty prefix to the column, and keys with no prefix to whatever
+    /// column they look like. Dropping a column with no prefix is refused, since
+    /// it would unlink every key, but a drop under a shared prefix unlinks the other
+    /// system's keys too. Values must be in the layout the engine reads, so
+    /// checksums, encryption, chunking and [`crate::Engine::with_key_prefix`]
+    /// are best left off for these columns.
+    ///
+    /// ```
+    /// use omega_cache::redis_engine::KeyFormat;
+    ///
+    /// // `users` reads the `user:{id}` keys of another service,
+    /// // `flags` reads plain keys
+    /// let format = KeyFormat::default()
+    ///     .prefix("my_app/")
+    ///     .external_column("users", "user:")
+    ///     .external_column("flags", "");
+    /// ```
+    #[must_use]
+    pub fn external_column(
+        mut self,
+        column: impl Into<String>,
+        prefix: impl Into<Vec<u8>>,
+    ) -> KeyFormat {
+        self.external.insert(column.into(), prefix.into());
+        self
+    }
+
+    /// Prefix and escaped column name shared by every key of the column,
+    /// or the fixed prefix of an external column
+    fn column_prefix(&self, column: &str) -> Vec<u8> {
+        if let Some(prefix) = self.external.get(column) {
+            return prefix.clone();
+        }
+
+        let mut bytes = self.prefix.clone();
+
+        for b in column.bytes() {
+            if b == self.separator || b == ESCAPE {
+                bytes.push(ESCAPE);
+            }
+            bytes.push(b);
+        }
+
+        bytes.push(self.separator);
+        bytes
+    }
+
+    /// Redis key for key in column
+    pub(crate) fn key(&self, column: &str, key: &[u8]) -> Vec<u8> {
+        [self.column_prefix(column).as_slice(), key].concat()
+    }
+
+    /// Key within column of a redis key written with this format
+    pub(crate) fn key_of<'a>(&self, column: &str, redis_key: &'a [u8]) -> Option<&'a [u8]> {
+        redis_key.strip_prefix(self.column_prefix(column).as_slice())
+    }
+
+    /// Glob pattern matching every key of column
+    pub(crate) fn pattern(&self, column: &str) -> Vec<u8> {
+        let mut pattern = glob_escape(&self.column_prefix(column));
+        pattern.push(b'*');
+        pattern
+    }
+
+    /// Glob pattern matching every key written with this format
+    pub(crate) fn any_pattern(&self) -> Vec<u8> {
+        let mut pattern = glob_escape(&self.prefix);
+        pattern.extend_from_slice(b"*");
+        pattern
+    }
+
+    /// Column name of a redis key written with this format
+    pub(crate) fn column_of(&self, redis_key: &[u8]) -> Option<String> {
+        if let Some((column, _)) = self
+            .external
+            .iter()
+            .find(|(_, prefix)| !prefix.is_empty() && redis_key.starts_with(prefix))
+        {
+            return Some(column.clone());
+        }
+
+        let rest = redis_key.strip_prefix(self.prefix.as_slice())?;
+        let mut column = Vec::new();
+        let mut bytes = rest.iter();
+
+        while let Some(b) = bytes.next() {
+            match *b {
+                ESCAPE => column.push(*bytes.next()?),
+                b if b == self.separator => return String::from_utf8(column).ok(),
+                b => column.push(b),
+            }
+        }
+
+        None
+    }
+
+    /// Column name of a redis key holding a value, [`None`] for deadline keys
+    /// and keys not written with this format
+    pub(crate) fn value_column_of(&self, redis_key: &[u8]) -> Option<String> {
+        self.column_of(redis_key)
+            .filter(|column| !column.ends_with(DEADLINE_SUFFIX))
+    }
+}
+
+/// Escape the characters redis treats as special in `SCAN MATCH` patterns
+fn glob_escape(bytes: &[u8]) -> Vec<u8> {
+    let mut escaped = Vec::with_capacity(bytes.len());
+
+    for &b in bytes {
+        if matches!(b, b'*' | b'?' | b'[' | b']' | ESCAPE) {
+            escaped.push(ESCAPE);
+        }
+        escaped.push(b);
+    }
+
+    escaped
+}
+
+/// Map a redis error to a [`CacheError`], reporting timeouts and lost connections
+/// as [`CacheError::Unavailable`] and anything else with `error`
+fn failure(e: &redis::RedisError, error: fn(String) -> CacheError) -> CacheError {
+    if e.is_timeout() {
+        CacheError::Unavailable("operation timed out".to_string())
+    } else if e.is_connection_refusal() || e.is_connection_dropped() {
+        CacheError::Unavailable(e.to_string())
+    } else {
+        error(e.to_string())
+    }
+}
+
+/// Whether redis refused a command as the key holds another type than a string,
+/// such as a hash or list written by another system
+fn is_wrong_type(e: &redis::RedisError) -> bool {
+    // errors raised inside scripts carry the code in their message
+    e.code() == Some("WRONGTYPE") || e.to_string().contains("WRONGTYPE")
+}
+
+impl From<redis::RedisError> for CacheError {
+    fn from(e: redis::RedisError) -> Self {
+        failure(&e, CacheError::Engine)
+    }
+}
+
+/// r2d2 only fails after waiting out its connection timeout
+impl From<r2d2::Error> for CacheError {
+    fn from(e: r2d2::Error) -> Self {
+        CacheError::Unavailable(e.to_string())
+    }
+}
+
+/// What a read does with a key redis holds as another type than a string,
+/// see [`RedisEngineBuilder::on_wrong_type`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum WrongType {
+    /// Fail the read with `CacheError::Get("key exists with incompatible Redis type")`
+    #[default]
+    Error,
+    /// Read the key as missing, leaving it as it is
+    Miss,
+}
+
+/// How reads pick among replicas, see [`RedisEngineBuilder::replica_routing`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ReplicaRouting {
+    /// Take each replica in turn
+    #[default]
+    RoundRobin,
+    /// Take the replica with the fewest connections in use, ties going to the first listed
+    LeastConnections,
+}
+
+/// A read replica and its own connection pool
+#[derive(Debug)]
+struct Replica {
+    pool: r2d2::Pool<redis::Client>,
+    addr: redis::ConnectionAddr,
+}
+
+/// Applies the per-operation timeout to every connection the pool opens
+#[derive(Debug)]
+struct OperationTimeout(Duration);
+
+impl r2d2::CustomizeConnection<redis::Connection, redis::RedisError> for OperationTimeout {
+    fn on_acquire(&self, conn: &mut redis::Connection) -> Result<(), redis::RedisError> {
+        conn.set_read_timeout(Some(self.0))?;
+        conn.set_write_timeout(Some(self.0))
+    }
+}
 
 ///
 /// Wrapper for [`r2d2::Pool<redis::Client>`]
@@ -8,20 +370,538 @@ use crate::{CacheError, CacheStorage};
 #[derive(Debug)]
 pub struct RedisEngine {
     inner: r2d2::Pool<redis::Client>,
+    /// Address, database and credentials connected with, see [`CacheStorage::describe_config`]
+    info: redis::ConnectionInfo,
+    /// Bound on each command, see [`RedisEngineBuilder::operation_timeout`]
+    operation_timeout: Option<Duration>,
+    key_format: KeyFormat,
+    on_wrong_type: WrongType,
+    /// Opens the dedicated connection subscribed to expired keys
+    #[cfg(feature = "redis-notifications")]
+    client: redis::Client,
+    /// Calls the eviction listener, see [`CacheStorage::set_eviction_listener`]
+    #[cfg(feature = "redis-notifications")]
+    subscriber: Option<notifications::Subscriber>,
+    /// Notified of operations over a latency threshold, see [`CacheStorage::set_slow_operation_listener`]
+    slow: SlowOperations,
+    /// Serve plain reads, see [`RedisEngineBuilder::replica`]
+    replicas: Vec<Replica>,
+    replica_routing: ReplicaRouting,
+    /// Replica the next round robin read goes to
+    next_replica: AtomicUsize,
+}
+
+impl RedisEngine {
+    ///
+    /// Start building a redis engine connecting to url
+    ///
+    /// Supported urls are `redis://[<username>][:<password>@]<host>[:<port>][/<db>]`,
+    /// `rediss://` of the same form over TLS, which needs the `redis-tls` feature,
+    /// and `redis+unix:///<path>` or `unix:///<path>` for unix sockets.
+    ///
+    /// ```no_run
+    /// use omega_cache::{Engine, redis_engine::{KeyFormat, RedisEngine}};
+    ///
+    /// let engine = Engine::new(
+    ///     RedisEngine::builder("redis://127.0.0.1/")
+    ///         .key_format(KeyFormat::default().prefix("my_app:"))
+    ///         .build(),
+    /// );
+    /// ```
+    ///
+    /// A managed redis over TLS with an ACL user:
+    ///
+    /// ```no_run
+    /// use omega_cache::{Engine, redis_engine::RedisEngine};
+    ///
+    /// let engine = Engine::new(
+    ///     RedisEngine::builder("rediss://my-cache.example.com:6380/0")
+    ///         .username("app")
+    ///         .password(std::env::var("REDIS_PASSWORD").unwrap())
+    ///         .build(),
+    /// );
+    /// ```
+    ///
+    #[must_use]
+    pub fn builder(url: impl Into<String>) -> RedisEngineBuilder {
+        RedisEngineBuilder {
+            url: url.into(),
+            username: None,
+            password: None,
+            key_format: KeyFormat::default(),
+            max_connections: None,
+            connection_timeout: None,
+            operation_timeout: None,
+            on_wrong_type: WrongType::default(),
+            replicas: Vec::new(),
+            replica_routing: ReplicaRouting::default(),
+        }
+    }
+
+    /// Take a connection from the pool, see [`checkout`]
+    fn connection(&self) -> Result<r2d2::PooledConnection<redis::Client>, CacheError> {
+        checkout(&self.inner)
+    }
+
+    /// Take a connection for a read that writes nothing, from a replica if there are any
+    fn read_connection(&self) -> Result<r2d2::PooledConnection<redis::Client>, CacheError> {
+        if self.replicas.is_empty() {
+            return self.connection();
+        }
+
+        let replica = match self.replica_routing {
+            ReplicaRouting::RoundRobin => {
+                let next = self.next_replica.fetch_add(1, Ordering::Relaxed);
+                &self.replicas[next % self.replicas.len()]
+            }
+            ReplicaRouting::LeastConnections => self
+                .replicas
+                .iter()
+                .min_by_key(|replica| {
+                    let state = replica.pool.state();
+                    state.connections - state.idle_connections
+                })
+                .unwrap_or(&self.replicas[0]),
+        };
+
+        checkout(&replica.pool)
+    }
+
+    /// Key holding the absolute deadline of the value at key, in a column with an idle TTL
+    fn deadline_key(&self, column: &str, key: &[u8]) -> Vec<u8> {
+        self.key_format
+            .key(&format!("{column}{DEADLINE_SUFFIX}"), key)
+    }
+
+    /// Map a failed read of a single key, reading keys of another redis type
+    /// as missing or failing with a clear error as [`WrongType`] says
+    fn read_failure<T>(&self, e: &redis::RedisError) -> Result<Option<T>, CacheError> {
+        if !is_wrong_type(e) {
+            return Err(failure(e, CacheError::Get));
+        }
+
+        match self.on_wrong_type {
+            WrongType::Miss => Ok(None),
+            WrongType::Error => Err(CacheError::Get(
+                "key exists with incompatible Redis type".to_string(),
+            )),
+        }
+    }
+
+    /// `SET` a value, optionally returning the value it replaced
+    fn set<T: redis::FromRedisValue>(
+        &self,
+        c: &dyn crate::ColumnDefinition,
+        key: &[u8],
+        value: &[u8],
+        ttl_in_seconds: i32,
+        get_old: bool,
+    ) -> Result<T, CacheError> {
+        match self.connection() {
+            Ok(mut conn) => {
+                let timer = Timer::start(&self.slow);
+
+                let name = c.name();
+                let k = self.key_format.key(&name, key);
+                let ttl = crate::ttl::expiry(c, ttl_in_seconds);
+
+                let set = if let Some(idle) = c.idle_ttl_in_seconds() {
+                    Script::new(IDLE_SET_SCRIPT)
+                        .key(&k)
+                        .key(self.deadline_key(&name, key))
+                        .arg(value)
+                        .arg(ttl.unwrap_or(0))
+                        .arg(idle)
+                        .arg(u8::from(get_old))
+                        .invoke::<T>(&mut *conn)
+                } else {
+                    let mut options = SetOptions::default().get(get_old);
+                    if let Some(ttl) = ttl {
+                        options = options.with_expiration(SetExpiry::EX(ttl));
+                    }
+
+                    conn.set_options::<&[u8], &[u8], T>(&k, value, options)
+                };
+
+                match set {
+                    Ok(old) => {
+                        timer.finish(OperationKind::Insert, c);
+                        Ok(old)
+                    }
+                    Err(e) => Err(failure(&e, CacheError::Put)),
+                }
+            }
+            Err(e) => Err(e),
+        }
+    }
+}
+
+/// Take a connection from `pool`, naming an exhausted pool as such
+///
+/// r2d2 fails every checkout with the same timeout, so the pool is only
+/// named exhausted when every connection it may open is open and in use,
+/// otherwise new connections could not be opened in time.
+fn checkout(
+    pool: &r2d2::Pool<redis::Client>,
+) -> Result<r2d2::PooledConnection<redis::Client>, CacheError> {
+    pool.get().map_err(|e| {
+        let state = pool.state();
+
+        if state.connections >= pool.max_size() && state.idle_connections == 0 {
+            CacheError::Unavailable(format!(
+                "redis connection pool exhausted, all {} connections in use",
+                pool.max_size()
+            ))
+        } else {
+            CacheError::from(e)
+        }
+    })
+}
+
+/// Writer holding the escaped key prefix of a column, see [`CacheStorage::try_column_writer`]
+struct PrefixWriter<'a> {
+    engine: &'a RedisEngine,
+    column: &'a dyn crate::ColumnDefinition,
+    prefix: Vec<u8>,
+}
+
+impl ColumnWriter for PrefixWriter<'_> {
+    fn try_insert(&self, key: &[u8], value: &[u8]) -> Result<(), CacheError> {
+        // deadline keys are set by script, which needs the column name
+        if self.column.idle_ttl_in_seconds().is_some() {
+            return self.engine.try_insert(self.column, key, value);
+        }
+
+        let mut conn = self.engine.connection()?;
+        let timer = Timer::start(&self.engine.slow);
+
+        let mut options = SetOptions::default();
+        if let Some(ttl) = crate::ttl::expiry(self.column, self.column.get_ttl_in_seconds()) {
+            options = options.with_expiration(SetExpiry::EX(ttl));
+        }
+
+        conn.set_options::<&[u8], &[u8], ()>(
+            &[self.prefix.as_slice(), key].concat(),
+            value,
+            options,
+        )
+        .map_err(|e| failure(&e, CacheError::Put))?;
+
+        timer.finish(OperationKind::Insert, self.column);
+
+        Ok(())
+    }
+}
+
+///
+/// Builder for [`RedisEngine`]
+///
+#[derive(Clone)]
+pub struct RedisEngineBuilder {
+    url: String,
+    username: Option<String>,
+    password: Option<String>,
+    key_format: KeyFormat,
+    max_connections: Option<u32>,
+    connection_timeout: Option<Duration>,
+    operation_timeout: Option<Duration>,
+    on_wrong_type: WrongType,
+    replicas: Vec<String>,
+    replica_routing: ReplicaRouting,
+}
+
+impl std::fmt::Debug for RedisEngineBuilder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RedisEngineBuilder")
+            .field("url", &self.url)
+            .field("username", &self.username)
+            .field("password", &self.password.as_ref().map(|_| "<redacted>"))
+            .field("key_format", &self.key_format)
+            .field("max_connections", &self.max_connections)
+            .field("connection_timeout", &self.connection_timeout)
+            .field("operation_timeout", &self.operation_timeout)
+            .field("on_wrong_type", &self.on_wrong_type)
+            .field("replicas", &self.replicas)
+            .field("replica_routing", &self.replica_routing)
+            .finish()
+    }
+}
+
+impl RedisEngineBuilder {
+    /// ACL username to authenticate as, replacing any username in the url
+    #[must_use]
+    pub fn username(mut self, username: impl Into<String>) -> RedisEngineBuilder {
+        self.username = Some(username.into());
+        self
+    }
+
+    /// Password to authenticate with, replacing any password in the url
+    #[must_use]
+    pub fn password(mut self, password: impl Into<String>) -> RedisEngineBuilder {
+        self.password = Some(password.into());
+        self
+    }
+
+    /// Layout of the keys written to redis, defaults to `column:key`
+    #[must_use]
+    pub fn key_format(mut self, key_format: KeyFormat) -> RedisEngineBuilder {
+        self.key_format = key_format;
+        self
+    }
+
+    /// Maximum number of pooled connections, defaults to 10
+    #[must_use]
+    pub fn max_connections(mut self, max_connections: u32) -> RedisEngineBuilder {
+        self.max_connections = Some(max_connections);
+        self
+    }
+
+    /// How long an operation waits for a free pooled connection before failing with
+    /// [`CacheError::Unavailable`], defaults to 30 seconds
+    #[must_use]
+    pub fn connection_timeout(mut self, timeout: Duration) -> RedisEngineBuilder {
+        self.connection_timeout = Some(timeout);
+        self
+    }
+
+    /// How long a single command may wait on redis before failing with
+    /// `CacheError::Unavailable("operation timed out")`, unbounded by default.
+    ///
+    /// Also bounds the wait for a pooled connection unless
+    /// [`RedisEngineBuilder::connection_timeout`] is set, and skips the `PING`
+    /// r2d2 sends when handing out a connection so a wedged server fails the
+    /// command itself rather than stalling the checkout.
+    #[must_use]
+    pub fn operation_timeout(mut self, timeout: Duration) -> RedisEngineBuilder {
+        self.operation_timeout = Some(timeout);
+        self
+    }
+
+    /// What reads do with keys redis holds as another type than a string, such as
+    /// a hash or list another application wrote under the same name on a shared redis.
+    ///
+    /// Defaults to [`WrongType::Error`], failing the read with a [`CacheError::Get`]
+    /// naming the problem rather than redis' own error. With [`WrongType::Miss`]
+    /// such keys read as missing, so the value is fetched from its source each time,
+    /// while writes still fail or replace the key as redis does for them.
+    /// Batch reads of columns without an idle TTL use `MGET`, which reads such keys
+    /// as missing under either policy.
+    #[must_use]
+    pub fn on_wrong_type(mut self, on_wrong_type: WrongType) -> RedisEngineBuilder {
+        self.on_wrong_type = on_wrong_type;
+        self
+    }
+
+    /// Add a read replica of the primary redis at url, accepting the same urls as
+    /// [`RedisEngine::builder`] and authenticating with the same username and password.
+    ///
+    /// Single and batch reads of columns without an idle TTL, and value sizes, go to the
+    /// replicas as [`RedisEngineBuilder::replica_routing`] picks them, while writes and every
+    /// other read go to the primary. Reads restarting an idle TTL write to redis, so they
+    /// stay on the primary.
+    ///
+    /// Replicas apply writes asynchronously, so a read from one may miss a value just
+    /// inserted, or still return one just replaced, removed or expired, for as long as the
+    /// replica lags, usually milliseconds. Only add replicas to caches that tolerate such
+    /// stale reads. Each replica has its own pool of up to [`RedisEngineBuilder::max_connections`].
+    ///
+    /// ```no_run
+    /// use omega_cache::{Engine, redis_engine::{RedisEngine, ReplicaRouting}};
+    ///
+    /// let engine = Engine::new(
+    ///     RedisEngine::builder("redis://primary.example.com/")
+    ///         .replica("redis://replica-1.example.com/")
+    ///         .replica("redis://replica-2.example.com/")
+    ///         .replica_routing(ReplicaRouting::LeastConnections)
+    ///         .build(),
+    /// );
+    /// ```
+    #[must_use]
+    pub fn replica(mut self, url: impl Into<String>) -> RedisEngineBuilder {
+        self.replicas.push(url.into());
+        self
+    }
+
+    /// Add several read replicas, see [`RedisEngineBuilder::replica`]
+    #[must_use]
+    pub fn replicas(
+        mut self,
+        urls: impl IntoIterator<Item = impl Into<String>>,
+    ) -> RedisEngineBuilder {
+        self.replicas.extend(urls.into_iter().map(Into::into));
+        self
+    }
+
+    /// How reads pick among replicas, defaults to [`ReplicaRouting::RoundRobin`]
+    #[must_use]
+    pub fn replica_routing(mut self, routing: ReplicaRouting) -> RedisEngineBuilder {
+        self.replica_routing = routing;
+        self
+    }
+
+    /// Parse url, applying the username and password set on the builder
+    fn connection_info(&self, url: &str) -> Result<redis::ConnectionInfo, CacheError> {
+        #[cfg(not(feature = "redis-tls"))]
+        if url.starts_with("rediss://") {
+            return Err(CacheError::Engine(
+                "rediss:// urls need the `redis-tls` feature of omega-cache".to_string(),
+            ));
+        }
+
+        let mut info = url
+            .into_connection_info()
+            .map_err(|e| CacheError::Engine(format!("Invalid redis url: {e}")))?;
+        if let Some(username) = &self.username {
+            info.redis.username = Some(username.clone());
+        }
+        if let Some(password) = &self.password {
+            info.redis.password = Some(password.clone());
+        }
+
+        Ok(info)
+    }
+
+    /// Start a pool of connections to client with the settings of the builder
+    fn pool(&self, client: redis::Client) -> Result<r2d2::Pool<redis::Client>, CacheError> {
+        let mut pool = r2d2::Pool::builder();
+        if let Some(max_connections) = self.max_connections {
+            pool = pool.max_size(max_connections);
+        }
+        if let Some(timeout) = self.connection_timeout.or(self.operation_timeout) {
+            pool = pool.connection_timeout(timeout);
+        }
+        if let Some(timeout) = self.operation_timeout {
+            pool = pool
+                .test_on_check_out(false)
+                .connection_customizer(Box::new(OperationTimeout(timeout)));
+        }
+
+        pool.build(client)
+            .map_err(|e| CacheError::Engine(format!("Failed to start redis pool: {e}")))
+    }
+
+    /// Connect to redis
+    ///
+    /// # Panics
+    /// Panics if the url is invalid or the connection pool cannot start
+    #[must_use]
+    pub fn build(self) -> Box<dyn CacheStorage + Send + Sync> {
+        match self.try_build() {
+            Ok(storage) => storage,
+            Err(e) => panic!("{e}"),
+        }
+    }
+
+    /// Connect to redis
+    ///
+    /// # Errors
+    /// Returns [`CacheError::Engine`] if the primary url is empty, the url of the primary
+    /// or a replica is invalid or is a `rediss://` url without the `redis-tls` feature,
+    /// or a connection pool cannot start
+    pub fn try_build(self) -> Result<Box<dyn CacheStorage + Send + Sync>, CacheError> {
+        if self.url.trim().is_empty() {
+            return Err(CacheError::Engine(
+                "a primary redis url is required".to_string(),
+            ));
+        }
+
+        let info = self.connection_info(&self.url)?;
+        let replicas = self
+            .replicas
+            .iter()
+            .map(|url| self.connection_info(url))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let open = |info: redis::ConnectionInfo| {
+            redis::Client::open(info)
+                .map_err(|e| CacheError::Engine(format!("Failed to open connection to redis: {e}")))
+        };
+
+        let client = open(info.clone())?;
+        let pool = self.pool(client.clone())?;
+
+        let replicas = replicas
+            .into_iter()
+            .map(|info| {
+                Ok(Replica {
+                    addr: info.addr.clone(),
+                    pool: self.pool(open(info)?)?,
+                })
+            })
+            .collect::<Result<Vec<_>, CacheError>>()?;
+
+        Ok(Box::new(RedisEngine {
+            inner: pool,
+            info,
+            operation_timeout: self.operation_timeout,
+            key_format: self.key_format,
+            on_wrong_type: self.on_wrong_type,
+            #[cfg(feature = "redis-notifications")]
+            client,
+            #[cfg(feature = "redis-notifications")]
+            subscriber: None,
+            slow: SlowOperations::default(),
+            replicas,
+            replica_routing: self.replica_routing,
+            next_replica: AtomicUsize::new(0),
+        }))
+    }
 }
 
 impl CacheStorage for RedisEngine {
+    /// Connect with the default key format,
+    /// use [`RedisEngine::builder`] for more options
     fn build(path: String, _capacity: Option<u64>) -> Box<dyn CacheStorage + Send + Sync>
     where
         Self: Sized,
     {
-        match redis::Client::open(path) {
-            Ok(client) => match r2d2::Pool::builder().build(client) {
-                Ok(pool) => Box::new(RedisEngine { inner: pool }),
-                Err(e) => panic!("Failed to start redis pool: {e}"),
-            },
-            Err(e) => panic!("Failed to open connection to redis: {e}"),
-        }
+        RedisEngine::builder(path).build()
+    }
+
+    fn ttl_enforcement(&self) -> crate::TtlEnforcement {
+        crate::TtlEnforcement::ServerSide
+    }
+
+    /// Lists the username connected as, never the password
+    fn describe_config(&self) -> BackendConfig {
+        let tls = matches!(self.info.addr, redis::ConnectionAddr::TcpTls { .. });
+
+        BackendConfig::new("redis")
+            .with("address", &self.info.addr)
+            .with("tls", tls)
+            .with("db", self.info.redis.db)
+            .with(
+                "username",
+                self.info.redis.username.as_deref().unwrap_or("default"),
+            )
+            .with("max_connections", self.inner.max_size())
+            .with(
+                "connection_timeout",
+                format!("{:?}", self.inner.connection_timeout()),
+            )
+            .with(
+                "operation_timeout",
+                self.operation_timeout
+                    .map_or_else(|| "unbounded".to_string(), |timeout| format!("{timeout:?}")),
+            )
+            .with(
+                "key_prefix",
+                String::from_utf8_lossy(&self.key_format.prefix),
+            )
+            .with(
+                "key_separator",
+                String::from_utf8_lossy(&[self.key_format.separator]),
+            )
+            .with("on_wrong_type", format!("{:?}", self.on_wrong_type))
+            .with(
+                "replicas",
+                self.replicas
+                    .iter()
+                    .map(|replica| replica.addr.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", "),
+            )
+            .with("replica_routing", format!("{:?}", self.replica_routing))
     }
 
     fn try_insert(
@@ -30,32 +910,330 @@ impl CacheStorage for RedisEngine {
         key: &[u8],
         value: &[u8],
     ) -> Result<(), crate::CacheError> {
-        match self.inner.get() {
-            Ok(mut conn) => {
-                let t = std::time::Instant::now();
-
-                let k = [c.name().as_bytes(), ":".as_bytes(), key].concat();
-                if let Err(e) = conn.set_options::<&[u8], &[u8], ()>(
-                    &k,
-                    value,
-                    SetOptions::default().with_expiration(SetExpiry::EX(
-                        u64::try_from(c.get_ttl_in_seconds())
-                            .map_err(|e| CacheError::Put(e.to_string()))?,
-                    )),
-                ) {
-                    Err(CacheError::Put(e.to_string()))
-                } else {
-                    if cfg!(debug_assertions) {
-                        eprintln!(
-                            "\x1b[0;34mTime taken for insert:\x1b[0m {}us",
-                            t.elapsed().as_micros()
-                        );
+        self.try_insert_with_ttl(c, key, value, c.get_ttl_in_seconds())
+    }
+
+    fn try_insert_with_ttl(
+        &self,
+        c: &dyn crate::ColumnDefinition,
+        key: &[u8],
+        value: &[u8],
+        ttl_in_seconds: i32,
+    ) -> Result<(), crate::CacheError> {
+        self.set::<()>(c, key, value, ttl_in_seconds, false)
+    }
+
+    fn try_column_writer<'a>(
+        &'a self,
+        c: &'a dyn crate::ColumnDefinition,
+    ) -> Result<Box<dyn ColumnWriter + 'a>, CacheError> {
+        Ok(Box::new(PrefixWriter {
+            engine: self,
+            column: c,
+            prefix: self.key_format.column_prefix(&c.name()),
+        }))
+    }
+
+    fn try_insert_reporting(
+        &self,
+        c: &dyn crate::ColumnDefinition,
+        key: &[u8],
+        value: &[u8],
+    ) -> Result<InsertOutcome, CacheError> {
+        match self.set::<Option<Vec<u8>>>(c, key, value, c.get_ttl_in_seconds(), true)? {
+            Some(_) => Ok(InsertOutcome::Overwrote),
+            None => Ok(InsertOutcome::Created),
+        }
+    }
+
+    fn try_insert_batch(
+        &self,
+        c: &dyn crate::ColumnDefinition,
+        items: &[(Vec<u8>, Vec<u8>)],
+    ) -> Result<(), CacheError> {
+        let mut conn = self.connection()?;
+
+        let timer = Timer::start(&self.slow);
+        let column = c.name();
+
+        let idle_set = Script::new(IDLE_SET_SCRIPT);
+        let idle = c.idle_ttl_in_seconds();
+        if idle.is_some() {
+            // pipelined scripts are only run by hash
+            idle_set
+                .prepare_invoke()
+                .load(&mut *conn)
+                .map_err(|e| failure(&e, CacheError::Put))?;
+        }
+
+        let mut pipe = redis::pipe();
+        for (key, value) in items {
+            let ttl = crate::ttl::expiry(c, c.get_ttl_in_seconds());
+
+            if let Some(idle) = idle {
+                pipe.invoke_script(
+                    idle_set
+                        .key(self.key_format.key(&column, key))
+                        .key(self.deadline_key(&column, key))
+                        .arg(value)
+                        .arg(ttl.unwrap_or(0))
+                        .arg(idle)
+                        .arg(0),
+                )
+                .ignore();
+                continue;
+            }
+
+            let mut options = SetOptions::default();
+            if let Some(ttl) = ttl {
+                options = options.with_expiration(SetExpiry::EX(ttl));
+            }
+
+            pipe.set_options(self.key_format.key(&column, key), value, options)
+                .ignore();
+        }
+
+        pipe.query::<()>(&mut *conn)
+            .map_err(|e| failure(&e, CacheError::Put))?;
+
+        timer.finish(OperationKind::BatchInsert, c);
+
+        Ok(())
+    }
+
+    fn try_swap(
+        &self,
+        c: &dyn crate::ColumnDefinition,
+        key: &[u8],
+        value: &[u8],
+    ) -> Result<Option<Vec<u8>>, CacheError> {
+        self.set::<Option<Vec<u8>>>(c, key, value, c.get_ttl_in_seconds(), true)
+    }
+
+    /// Both keys must hash to the same slot on a redis cluster
+    fn try_rename(
+        &self,
+        c: &dyn crate::ColumnDefinition,
+        from: &[u8],
+        to: &[u8],
+    ) -> Result<bool, CacheError> {
+        let mut conn = self.connection()?;
+        let name = c.name();
+
+        let script = Script::new(RENAME_SCRIPT);
+        let mut rename = script.prepare_invoke();
+        rename
+            .key(self.key_format.key(&name, from))
+            .key(self.key_format.key(&name, to));
+
+        if c.idle_ttl_in_seconds().is_some() {
+            rename
+                .key(self.deadline_key(&name, from))
+                .key(self.deadline_key(&name, to));
+        }
+
+        rename
+            .invoke::<bool>(&mut *conn)
+            .map_err(|e| failure(&e, CacheError::Put))
+    }
+
+    /// `GETDEL` takes the value atomically, needing redis 6.2 or later
+    fn try_take(
+        &self,
+        c: &dyn crate::ColumnDefinition,
+        key: &[u8],
+    ) -> Result<Option<Vec<u8>>, CacheError> {
+        let mut conn = self.connection()?;
+        let name = c.name();
+
+        let mut take = redis::pipe();
+        take.atomic()
+            .cmd("GETDEL")
+            .arg(self.key_format.key(&name, key));
+        if c.idle_ttl_in_seconds().is_some() {
+            take.cmd("DEL").arg(self.deadline_key(&name, key)).ignore();
+        }
+
+        let (value,) = take
+            .query::<(Option<Vec<u8>>,)>(&mut *conn)
+            .map_err(|e| failure(&e, CacheError::Put))?;
+
+        Ok(value)
+    }
+
+    /// `WATCH`es the keys checked and applies the writes in a `MULTI`,
+    /// checking again whenever a watched key changed before `EXEC`.
+    /// All keys must hash to the same slot on a redis cluster
+    fn try_commit(
+        &self,
+        c: &dyn crate::ColumnDefinition,
+        conditions: &[TransactionCondition],
+        writes: &[TransactionWrite],
+    ) -> Result<bool, CacheError> {
+        let mut conn = self.connection()?;
+        let timer = Timer::start(&self.slow);
+        let name = c.name();
+
+        let watched = conditions
+            .iter()
+            .map(|condition| self.key_format.key(&name, condition.key))
+            .collect::<Vec<_>>();
+
+        let idle_set = Script::new(IDLE_SET_SCRIPT);
+        let idle = c.idle_ttl_in_seconds();
+        if idle.is_some() {
+            // transactions only send the script's hash
+            idle_set
+                .prepare_invoke()
+                .load(&mut *conn)
+                .map_err(|e| failure(&e, CacheError::Put))?;
+        }
+
+        loop {
+            if !watched.is_empty() {
+                redis::cmd("WATCH")
+                    .arg(&watched)
+                    .query::<()>(&mut *conn)
+                    .map_err(|e| failure(&e, CacheError::Get))?;
+
+                let values = redis::cmd("MGET")
+                    .arg(&watched)
+                    .query::<Vec<Option<Vec<u8>>>>(&mut *conn)
+                    .map_err(|e| failure(&e, CacheError::Get))?;
+
+                for (condition, value) in conditions.iter().zip(&values) {
+                    match (condition.holds)(value.as_deref()) {
+                        Ok(true) => {}
+                        held => {
+                            redis::cmd("UNWATCH")
+                                .query::<()>(&mut *conn)
+                                .map_err(|e| failure(&e, CacheError::Get))?;
+                            return held;
+                        }
                     }
-                    Ok(())
                 }
             }
-            Err(e) => Err(CacheError::Engine(e.to_string())),
+
+            if writes.is_empty() {
+                redis::cmd("UNWATCH")
+                    .query::<()>(&mut *conn)
+                    .map_err(|e| failure(&e, CacheError::Get))?;
+                return Ok(true);
+            }
+
+            let mut pipe = redis::pipe();
+            pipe.atomic();
+            for write in writes {
+                let k = self.key_format.key(&name, write.key);
+
+                match (write.value, idle) {
+                    (Some(value), Some(idle)) => {
+                        pipe.invoke_script(
+                            idle_set
+                                .key(&k)
+                                .key(self.deadline_key(&name, write.key))
+                                .arg(value)
+                                .arg(crate::ttl::expiry(c, c.get_ttl_in_seconds()).unwrap_or(0))
+                                .arg(idle)
+                                .arg(0),
+                        )
+                        .ignore();
+                    }
+                    (Some(value), None) => {
+                        let mut options = SetOptions::default();
+                        if let Some(ttl) = crate::ttl::expiry(c, c.get_ttl_in_seconds()) {
+                            options = options.with_expiration(SetExpiry::EX(ttl));
+                        }
+
+                        pipe.set_options(&k, value, options).ignore();
+                    }
+                    (None, Some(_)) => {
+                        pipe.del(&k)
+                            .ignore()
+                            .del(self.deadline_key(&name, write.key))
+                            .ignore();
+                    }
+                    (None, None) => {
+                        pipe.del(&k).ignore();
+                    }
+                }
+            }
+
+            // `EXEC` replies nil when a watched key changed
+            let committed = pipe
+                .query::<Option<redis::Value>>(&mut *conn)
+                .map_err(|e| failure(&e, CacheError::Put))?;
+
+            if committed.is_some() {
+                timer.finish(OperationKind::Commit, c);
+                return Ok(true);
+            }
+        }
+    }
+
+    /// Pipelines `EXPIRE`, or `PERSIST` for values that should never expire,
+    /// and [`IDLE_TOUCH_SCRIPT`] in columns with an idle TTL, in one round trip
+    fn try_touch_many(
+        &self,
+        c: &dyn crate::ColumnDefinition,
+        keys: &[&[u8]],
+        ttl_in_seconds: i32,
+    ) -> Result<usize, CacheError> {
+        if keys.is_empty() {
+            return Ok(0);
+        }
+
+        let mut conn = self.connection()?;
+        let timer = Timer::start(&self.slow);
+        let name = c.name();
+
+        let script = Script::new(IDLE_TOUCH_SCRIPT);
+        let mut idle_touches = Vec::new();
+        if let Some(idle) = c.idle_ttl_in_seconds() {
+            // pipelines only send the script's hash
+            script
+                .prepare_invoke()
+                .load(&mut *conn)
+                .map_err(|e| failure(&e, CacheError::Put))?;
+
+            for key in keys {
+                let mut touch = script.prepare_invoke();
+                touch
+                    .key(self.key_format.key(&name, key))
+                    .key(self.deadline_key(&name, key))
+                    .arg(crate::ttl::expiry(c, ttl_in_seconds).unwrap_or(0))
+                    .arg(idle);
+                idle_touches.push(touch);
+            }
         }
+
+        let mut pipe = redis::pipe();
+        if idle_touches.is_empty() {
+            for key in keys {
+                let k = self.key_format.key(&name, key);
+
+                match crate::ttl::expiry(c, ttl_in_seconds) {
+                    Some(ttl) => {
+                        pipe.expire(k, i64::try_from(ttl).unwrap_or(i64::MAX));
+                    }
+                    // `PERSIST` only reports keys that had a TTL
+                    None => {
+                        pipe.exists(&k).persist(&k).ignore();
+                    }
+                }
+            }
+        } else {
+            for touch in &idle_touches {
+                pipe.invoke_script(touch);
+            }
+        }
+
+        let touched = pipe
+            .query::<Vec<usize>>(&mut *conn)
+            .map_err(|e| failure(&e, CacheError::Put))?;
+        timer.finish(OperationKind::TouchMany, c);
+
+        Ok(touched.into_iter().sum())
     }
 
     fn try_get(
@@ -63,49 +1241,520 @@ impl CacheStorage for RedisEngine {
         c: &dyn crate::ColumnDefinition,
         key: &[u8],
     ) -> Result<Option<Vec<u8>>, crate::CacheError> {
-        match self.inner.get() {
+        let conn = if c.idle_ttl_in_seconds().is_some() {
+            self.connection()
+        } else {
+            self.read_connection()
+        };
+
+        match conn {
             Ok(mut conn) => {
-                let t = std::time::Instant::now();
-                let k = [c.name().as_bytes(), ":".as_bytes(), key].concat();
+                let timer = Timer::start(&self.slow);
+                let name = c.name();
+                let k = self.key_format.key(&name, key);
+
+                let get = if let Some(idle) = c.idle_ttl_in_seconds() {
+                    Script::new(IDLE_GET_SCRIPT)
+                        .key(&k)
+                        .key(self.deadline_key(&name, key))
+                        .arg(idle)
+                        .invoke::<Option<Vec<u8>>>(&mut *conn)
+                        .map(Option::unwrap_or_default)
+                } else {
+                    conn.get::<&[u8], Vec<u8>>(&k)
+                };
 
-                match conn.get::<&[u8], Vec<u8>>(&k) {
+                match get {
                     Ok(bytes) => {
+                        timer.finish(OperationKind::Get, c);
+
                         if bytes.is_empty() {
                             return Ok(None);
                         }
-                        if cfg!(debug_assertions) {
-                            eprintln!(
-                                "\x1b[0;34mTime taken for get:\x1b[0m {}us",
-                                t.elapsed().as_micros()
-                            );
-                        }
 
                         Ok(Some(bytes))
                     }
-                    Err(e) => Err(CacheError::Get(e.to_string())),
+                    Err(e) => self.read_failure(&e),
                 }
             }
-            Err(e) => Err(CacheError::Engine(e.to_string())),
+            Err(e) => Err(e),
         }
     }
 
-    fn try_drop_column(&self, c: &dyn crate::ColumnDefinition) -> Result<(), crate::CacheError> {
-        let mut conn = match self.inner.get() {
-            Ok(conn) => conn,
-            Err(e) => return Err(CacheError::Engine(e.to_string())),
+    fn try_get_many(
+        &self,
+        c: &dyn crate::ColumnDefinition,
+        keys: &[&[u8]],
+    ) -> Result<Vec<Option<Vec<u8>>>, CacheError> {
+        let mut conn = if c.idle_ttl_in_seconds().is_some() {
+            self.connection()?
+        } else {
+            self.read_connection()?
         };
 
-        let items = match conn.scan_match::<&[u8], Vec<u8>>(format!("{}:*", c.name()).as_ref()) {
-            Ok(items) => items.collect::<Vec<Vec<u8>>>(),
-            Err(e) => return Err(CacheError::Engine(e.to_string())),
+        let timer = Timer::start(&self.slow);
+        let column = c.name();
+
+        let values = if let Some(idle) = c.idle_ttl_in_seconds() {
+            // every read restarts its own idle timer, which MGET cannot do
+            let idle_get = Script::new(IDLE_GET_SCRIPT);
+            idle_get
+                .prepare_invoke()
+                .load(&mut *conn)
+                .map_err(|e| failure(&e, CacheError::Get))?;
+
+            let mut pipe = redis::pipe();
+            for key in keys {
+                pipe.invoke_script(
+                    idle_get
+                        .key(self.key_format.key(&column, key))
+                        .key(self.deadline_key(&column, key))
+                        .arg(idle),
+                );
+            }
+
+            pipe.query::<Vec<Option<Vec<u8>>>>(&mut *conn)
+        } else {
+            let keys = keys
+                .iter()
+                .map(|key| self.key_format.key(&column, key))
+                .collect::<Vec<_>>();
+
+            // MGET reads keys of another type as missing rather than failing
+            redis::cmd("MGET")
+                .arg(&keys)
+                .query::<Vec<Option<Vec<u8>>>>(&mut *conn)
         };
 
-        for i in items {
-            if let Err(e) = conn.unlink::<&[u8], ()>(&i) {
-                return Err(CacheError::Engine(e.to_string()));
+        let values = match values {
+            Ok(values) => values,
+            // one key of another type fails the whole pipeline, so read them in turn
+            Err(e) if is_wrong_type(&e) => {
+                drop(conn);
+                return keys.iter().map(|key| self.try_get(c, key)).collect();
             }
+            Err(e) => return Err(failure(&e, CacheError::Get)),
+        };
+
+        timer.finish(OperationKind::BatchGet, c);
+
+        Ok(values
+            .into_iter()
+            .map(|value| value.filter(|bytes| !bytes.is_empty()))
+            .collect())
+    }
+
+    fn try_value_size(
+        &self,
+        c: &dyn crate::ColumnDefinition,
+        key: &[u8],
+    ) -> Result<Option<usize>, CacheError> {
+        let mut conn = self.read_connection()?;
+        let k = self.key_format.key(&c.name(), key);
+
+        match conn.strlen::<&[u8], usize>(&k) {
+            Ok(0) => Ok(None),
+            Ok(size) => Ok(Some(size)),
+            Err(e) => self.read_failure(&e),
+        }
+    }
+
+    /// Also drops the deadline keys of a column with an idle TTL
+    fn try_drop_column(&self, c: &dyn crate::ColumnDefinition) -> Result<(), crate::CacheError> {
+        let name = c.name();
+        if self.key_format.column_prefix(&name).is_empty() {
+            return Err(CacheError::Engine(format!(
+                "column `{name}` has no key prefix, dropping it would unlink every key"
+            )));
         }
 
+        let mut conn = self.connection()?;
+
+        let mut columns = vec![name.clone()];
+        if c.idle_ttl_in_seconds().is_some() {
+            columns.push(format!("{name}{DEADLINE_SUFFIX}"));
+        }
+
+        for column in columns {
+            let items = conn
+                .scan_match::<&[u8], Vec<u8>>(&self.key_format.pattern(&column))?
+                .collect::<Vec<Vec<u8>>>();
+
+            for i in items {
+                conn.unlink::<&[u8], ()>(&i)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn try_entries(&self, c: &dyn crate::ColumnDefinition) -> Result<Vec<RawEntry>, CacheError> {
+        let mut conn = self.connection()?;
+
+        let timer = Timer::start(&self.slow);
+        let column = c.name();
+        let keys = conn
+            .scan_match::<&[u8], Vec<u8>>(&self.key_format.pattern(&column))
+            .map_err(|e| failure(&e, CacheError::Get))?
+            .collect::<Vec<Vec<u8>>>();
+
+        let mut entries = Vec::with_capacity(keys.len());
+        for batch in keys.chunks(crate::Engine::DEFAULT_BATCH_SIZE) {
+            let values = redis::cmd("MGET")
+                .arg(batch)
+                .query::<Vec<Option<Vec<u8>>>>(&mut *conn)
+                .map_err(|e| failure(&e, CacheError::Get))?;
+
+            for (redis_key, value) in batch.iter().zip(values) {
+                // keys may expire between the scan and the read
+                if let (Some(key), Some(value)) = (
+                    self.key_format.key_of(&column, redis_key),
+                    value.filter(|bytes| !bytes.is_empty()),
+                ) {
+                    entries.push((key.to_vec(), value));
+                }
+            }
+        }
+        timer.finish(OperationKind::Entries, c);
+
+        Ok(entries)
+    }
+
+    fn try_timed_entries(
+        &self,
+        c: &dyn crate::ColumnDefinition,
+    ) -> Result<Vec<TimedEntry>, CacheError> {
+        let mut conn = self.connection()?;
+
+        let column = c.name();
+        let keys = conn
+            .scan_match::<&[u8], Vec<u8>>(&self.key_format.pattern(&column))
+            .map_err(|e| failure(&e, CacheError::Get))?
+            .collect::<Vec<Vec<u8>>>();
+
+        let mut entries = Vec::with_capacity(keys.len());
+        for batch in keys.chunks(crate::Engine::DEFAULT_BATCH_SIZE) {
+            let mut pipe = redis::pipe();
+            for key in batch {
+                pipe.get(key).ttl(key);
+            }
+
+            let read = pipe
+                .query::<Vec<(Option<Vec<u8>>, i64)>>(&mut *conn)
+                .map_err(|e| failure(&e, CacheError::Get))?;
+
+            for (redis_key, (value, ttl)) in batch.iter().zip(read) {
+                // keys may expire between the scan and the read, -1 is no expiry
+                if let (Some(key), Some(value)) = (
+                    self.key_format.key_of(&column, redis_key),
+                    value.filter(|bytes| !bytes.is_empty()),
+                ) {
+                    let left = u64::try_from(ttl).ok().map(Duration::from_secs);
+                    entries.push((key.to_vec(), value, left));
+                }
+            }
+        }
+
+        Ok(entries)
+    }
+
+    /// Estimated from the time left to live of each key and the column TTL,
+    /// keys without an expiry are left out. With an idle TTL, estimated from
+    /// the idle TTL instead, so ages count from the last read
+    fn try_entry_ages(&self, c: &dyn crate::ColumnDefinition) -> Result<Vec<Duration>, CacheError> {
+        let ttl = match c.idle_ttl_in_seconds() {
+            Some(idle) => u64::from(idle),
+            None => match u64::try_from(c.get_ttl_in_seconds()) {
+                Ok(ttl) => ttl,
+                Err(_) => return Ok(Vec::new()),
+            },
+        };
+
+        let mut conn = self.connection()?;
+        let keys = conn
+            .scan_match::<&[u8], Vec<u8>>(&self.key_format.pattern(&c.name()))
+            .map_err(|e| failure(&e, CacheError::Get))?
+            .collect::<Vec<Vec<u8>>>();
+
+        let mut ages = Vec::with_capacity(keys.len());
+        for batch in keys.chunks(crate::Engine::DEFAULT_BATCH_SIZE) {
+            let mut pipe = redis::pipe();
+            for key in batch {
+                pipe.ttl(key);
+            }
+
+            let remaining = pipe
+                .query::<Vec<i64>>(&mut *conn)
+                .map_err(|e| failure(&e, CacheError::Get))?;
+
+            // -1 for keys without an expiry, -2 for keys that expired since the scan
+            ages.extend(
+                remaining
+                    .into_iter()
+                    .filter_map(|remaining| u64::try_from(remaining).ok())
+                    .map(|remaining| Duration::from_secs(ttl.saturating_sub(remaining))),
+            );
+        }
+
+        Ok(ages)
+    }
+
+    fn try_count_column(&self, c: &dyn crate::ColumnDefinition) -> Result<usize, CacheError> {
+        let mut conn = self.connection()?;
+
+        Ok(conn
+            .scan_match::<&[u8], Vec<u8>>(&self.key_format.pattern(&c.name()))?
+            .count())
+    }
+
+    /// Deadline keys of columns with an idle TTL are not columns of their own
+    fn try_list_columns(&self) -> Result<Vec<String>, CacheError> {
+        let mut conn = self.connection()?;
+
+        let keys = conn
+            .scan_match::<&[u8], Vec<u8>>(&self.key_format.any_pattern())?
+            .collect::<Vec<Vec<u8>>>();
+
+        Ok(keys
+            .iter()
+            .filter_map(|k| self.key_format.value_column_of(k))
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect())
+    }
+
+    /// Deadline keys of columns with an idle TTL are neither reported nor counted
+    fn try_report(&self) -> Result<Vec<ColumnReport>, CacheError> {
+        let mut conn = self.connection()?;
+
+        let keys = conn
+            .scan_match::<&[u8], Vec<u8>>(&self.key_format.any_pattern())?
+            .collect::<Vec<Vec<u8>>>();
+
+        let mut entries = BTreeMap::<String, usize>::new();
+        for column in keys
+            .iter()
+            .filter_map(|k| self.key_format.value_column_of(k))
+        {
+            *entries.entry(column).or_default() += 1;
+        }
+
+        Ok(entries
+            .into_iter()
+            .map(|(name, entries)| ColumnReport {
+                name,
+                entries,
+                size_on_disk: None,
+                description: None,
+            })
+            .collect())
+    }
+
+    /// Subscribes to the database's `__keyevent@<db>__:expired` channel on a connection
+    /// of its own and reports the engine's keys expiring, replacing any earlier
+    /// subscription. Keys removed or replaced by the engine are not reported.
+    ///
+    /// Redis only publishes these events with `notify-keyspace-events` including `Ex`,
+    /// as set with `CONFIG SET notify-keyspace-events Ex` or in `redis.conf`, which is
+    /// checked here, calling [`crate::EvictionListener::on_error`] if it is unset or cannot
+    /// be read. Events only reach subscribers connected at the time, so keys expiring after
+    /// the subscriber's connection drops are missed, which is also reported to
+    /// [`crate::EvictionListener::on_error`], and every engine subscribed reports them.
+    /// With an empty [`KeyFormat::prefix`] any key shaped like `column:key` is reported.
+    #[cfg(feature = "redis-notifications")]
+    fn set_eviction_listener(
+        &mut self,
+        listener: std::sync::Arc<dyn crate::EvictionListener>,
+    ) -> Result<(), CacheError> {
+        // stop the earlier subscriber before starting its replacement
+        self.subscriber = None;
+        self.subscriber = Some(notifications::Subscriber::start(
+            &self.client,
+            self.key_format.clone(),
+            listener,
+        )?);
+
+        Ok(())
+    }
+
+    fn set_slow_operation_listener(
+        &mut self,
+        threshold: Duration,
+        listener: SlowOperationListener,
+    ) -> Result<(), CacheError> {
+        self.slow.set(threshold, listener);
+
         Ok(())
     }
+
+    fn try_increment(
+        &self,
+        c: &dyn crate::ColumnDefinition,
+        key: &[u8],
+        delta: i64,
+    ) -> Result<i64, crate::CacheError> {
+        let mut conn = self.connection()?;
+
+        let name = c.name();
+        let ttl = crate::ttl::expiry(c, c.get_ttl_in_seconds());
+        let idle = c.idle_ttl_in_seconds().map(u64::from);
+
+        let script = Script::new(INCREMENT_SCRIPT);
+        let mut increment = script.prepare_invoke();
+        increment.key(self.key_format.key(&name, key)).arg(delta);
+
+        match (ttl, idle) {
+            (Some(ttl), Some(idle)) => {
+                increment
+                    .key(self.deadline_key(&name, key))
+                    .arg(ttl.min(idle))
+                    .arg(ttl);
+            }
+            (ttl, idle) => {
+                increment.arg(ttl.or(idle).unwrap_or(0));
+            }
+        }
+
+        match increment.invoke::<i64>(&mut *conn) {
+            Ok(value) => Ok(value),
+            Err(e) if e.to_string().contains("overflow") => {
+                Err(CacheError::Put("counter overflow".to_string()))
+            }
+            Err(e) => Err(failure(&e, CacheError::Put)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{KeyFormat, RedisEngine};
+
+    #[test]
+    fn test_default_key_format() {
+        let format = KeyFormat::default();
+
+        assert_eq!(format.key("column", b"key"), b"column:key");
+        assert_eq!(format.pattern("column"), b"column:*");
+        assert_eq!(format.column_of(b"column:key"), Some("column".to_string()));
+    }
+
+    #[test]
+    fn test_deadline_keys_have_no_value_column() {
+        let format = KeyFormat::default();
+
+        assert_eq!(
+            format.value_column_of(b"column:key"),
+            Some("column".to_string())
+        );
+        assert_eq!(
+            format.column_of(b"column#deadline:key"),
+            Some("column#deadline".to_string())
+        );
+        assert_eq!(format.value_column_of(b"column#deadline:key"), None);
+    }
+
+    #[test]
+    fn test_key_format_columns_do_not_collide() {
+        let format = KeyFormat::default();
+
+        assert_ne!(format.key("a:b", b"c"), format.key("a", b"b:c"));
+        assert_eq!(
+            format.column_of(&format.key("a:b", b"c")),
+            Some("a:b".to_string())
+        );
+        assert_eq!(format.pattern("a:b*"), b"a\\\\:b\\*:*");
+    }
+
+    #[test]
+    fn test_binary_keys_round_trip() {
+        let keys: [&[u8]; 5] = [b"a:b", b":", b"\\:\\", b"\0nul\0", b"a\0:b\\\0"];
+
+        for format in [
+            KeyFormat::default(),
+            KeyFormat::default().prefix("app\0").separator(0),
+        ] {
+            for column in ["column", "a:b", "a\0b\\"] {
+                for key in keys {
+                    let redis_key = format.key(column, key);
+
+                    assert_eq!(format.key_of(column, &redis_key), Some(key));
+                    assert_eq!(format.column_of(&redis_key), Some(column.to_string()));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_custom_key_format() {
+        let format = KeyFormat::default().prefix("app/").separator(b'/');
+
+        assert_eq!(format.key("column", b"key"), b"app/column/key");
+        assert_eq!(format.pattern("column"), b"app/column/*");
+        assert_eq!(
+            format.column_of(b"app/column/key"),
+            Some("column".to_string())
+        );
+        assert_eq!(format.column_of(b"other/column/key"), None);
+    }
+
+    #[test]
+    fn test_external_columns() {
+        let format = KeyFormat::default()
+            .prefix("app/")
+            .external_column("users", "user:")
+            .external_column("flags", "");
+
+        assert_eq!(format.key("users", b"1"), b"user:1");
+        assert_eq!(format.key_of("users", b"user:1"), Some(&b"1"[..]));
+        assert_eq!(format.pattern("users"), b"user:*");
+        assert_eq!(format.column_of(b"user:1"), Some("users".to_string()));
+
+        assert_eq!(format.key("flags", b"dark_mode"), b"dark_mode");
+        assert_eq!(format.pattern("flags"), b"*");
+
+        assert_eq!(format.key("column", b"key"), b"app/column:key");
+        assert_eq!(
+            format.column_of(b"app/column:key"),
+            Some("column".to_string())
+        );
+    }
+
+    #[test]
+    #[cfg(not(feature = "redis-tls"))]
+    fn test_tls_needs_feature() {
+        let Err(e) = RedisEngine::builder("rediss://127.0.0.1/").try_build() else {
+            panic!("rediss:// built without the redis-tls feature");
+        };
+
+        assert!(e.to_string().contains("redis-tls"));
+    }
+
+    #[test]
+    fn test_invalid_url() {
+        assert!(RedisEngine::builder("not a url").try_build().is_err());
+    }
+
+    #[test]
+    fn test_primary_required() {
+        let Err(e) = RedisEngine::builder("")
+            .replica("redis://127.0.0.1/")
+            .try_build()
+        else {
+            panic!("built without a primary");
+        };
+
+        assert!(e.to_string().contains("primary"));
+    }
+
+    #[test]
+    fn test_invalid_replica_url() {
+        let Err(e) = RedisEngine::builder("redis://127.0.0.1/")
+            .replicas(["redis://127.0.0.1:6380/", "not a url"])
+            .try_build()
+        else {
+            panic!("built with an invalid replica url");
+        };
+
+        assert!(e.to_string().contains("Invalid redis url"));
+    }
 }
@@ -0,0 +1,179 @@
+use std::{
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+        mpsc,
+    },
+    time::Duration,
+};
+
+use super::KeyFormat;
+use crate::{CacheError, EvictReason, EvictionListener};
+
+/// How often the subscriber wakes to check whether it was stopped
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Whether a `notify-keyspace-events` setting publishes expired keyevents,
+/// which needs `E` and either `x` or the `A` alias that includes it
+fn publishes_expired(setting: &str) -> bool {
+    setting.contains('E') && (setting.contains('x') || setting.contains('A'))
+}
+
+/// Why the listener may not be called given the server's `notify-keyspace-events` setting,
+/// also when it cannot be read, as managed servers often refuse `CONFIG`
+fn config_error(setting: Result<Vec<String>, redis::RedisError>) -> Option<CacheError> {
+    match setting {
+        Ok(setting) if setting.get(1).is_some_and(|s| publishes_expired(s)) => None,
+        Ok(_) => Some(CacheError::Engine(
+            "redis does not publish expired keyevents, so the eviction listener \
+             will not be called; set `notify-keyspace-events` to include `Ex`"
+                .to_string(),
+        )),
+        Err(e) => Some(CacheError::Engine(format!(
+            "could not check that redis publishes expired keyevents, \
+             the eviction listener needs `notify-keyspace-events` to include `Ex`: {e}"
+        ))),
+    }
+}
+
+/// Report to the listener if the server will not publish expired keyevents
+fn check_config(conn: &mut redis::Connection, listener: &dyn EvictionListener) {
+    let setting = redis::cmd("CONFIG")
+        .arg("GET")
+        .arg("notify-keyspace-events")
+        .query::<Vec<String>>(conn);
+
+    if let Some(error) = config_error(setting) {
+        listener.on_error(&error);
+    }
+}
+
+/// Column and key of an expired redis key, [`None`] for keys the engine did not write
+fn expired_key(key_format: &KeyFormat, redis_key: &[u8]) -> Option<(String, Vec<u8>)> {
+    // deadline keys of idle TTL columns expire alongside their values
+    let column = key_format.value_column_of(redis_key)?;
+    let key = key_format.key_of(&column, redis_key)?.to_vec();
+
+    Some((column, key))
+}
+
+/// Thread subscribed to the expired keyevents of the engine's database,
+/// stopped when dropped
+#[derive(Debug)]
+pub(super) struct Subscriber {
+    stopped: Arc<AtomicBool>,
+}
+
+impl Subscriber {
+    /// Subscribe on a connection of its own and call `listener` for every expired key
+    /// of the engine, reporting to it if the server is not configured to publish them
+    /// and if the subscription drops
+    pub(super) fn start(
+        client: &redis::Client,
+        key_format: KeyFormat,
+        listener: Arc<dyn EvictionListener>,
+    ) -> Result<Subscriber, CacheError> {
+        let mut conn = client.get_connection()?;
+        check_config(&mut conn, &*listener);
+
+        let channel = format!(
+            "__keyevent@{}__:expired",
+            client.get_connection_info().redis.db
+        );
+        let stopped = Arc::new(AtomicBool::new(false));
+        let (subscribed, ready) = mpsc::channel();
+
+        let stop = stopped.clone();
+        std::thread::Builder::new()
+            .name("omega-cache-expired".to_string())
+            .spawn(move || {
+                // the subscription ends when the pubsub is dropped, so it lives on this thread
+                let mut pubsub = conn.as_pubsub();
+                let subscribe = pubsub
+                    .subscribe(&channel)
+                    .and_then(|()| pubsub.set_read_timeout(Some(POLL_INTERVAL)));
+                let failed = subscribe.is_err();
+                let _ = subscribed.send(subscribe);
+                if failed {
+                    return;
+                }
+
+                while !stop.load(Ordering::Relaxed) {
+                    match pubsub.get_message() {
+                        Ok(message) => {
+                            if let Some((column, key)) =
+                                expired_key(&key_format, message.get_payload_bytes())
+                            {
+                                listener.on_evict(&column, &key, EvictReason::Expired);
+                            }
+                        }
+                        Err(e) if e.is_timeout() => {}
+                        Err(e) => {
+                            listener.on_error(&CacheError::Unavailable(format!(
+                                "stopped listening for expired keys: {e}"
+                            )));
+                            return;
+                        }
+                    }
+                }
+            })
+            .map_err(|e| CacheError::Engine(format!("Failed to start subscriber: {e}")))?;
+
+        ready
+            .recv()
+            .map_err(|e| CacheError::Engine(format!("Failed to subscribe: {e}")))??;
+
+        Ok(Subscriber { stopped })
+    }
+}
+
+impl Drop for Subscriber {
+    fn drop(&mut self) {
+        self.stopped.store(true, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::CacheError;
+
+    use super::{KeyFormat, config_error, expired_key, publishes_expired};
+
+    #[test]
+    fn test_publishes_expired() {
+        assert!(publishes_expired("Ex"));
+        assert!(publishes_expired("AKE"));
+        assert!(!publishes_expired(""));
+        assert!(!publishes_expired("Kx"));
+    }
+
+    #[test]
+    fn test_config_error() {
+        let setting = |s: &str| Ok(vec!["notify-keyspace-events".to_string(), s.to_string()]);
+
+        assert!(config_error(setting("Ex")).is_none());
+        assert!(matches!(
+            config_error(setting("")),
+            Some(CacheError::Engine(_))
+        ));
+        assert!(matches!(
+            config_error(Err(redis::RedisError::from((
+                redis::ErrorKind::ResponseError,
+                "unknown command"
+            )))),
+            Some(CacheError::Engine(_))
+        ));
+    }
+
+    #[test]
+    fn test_expired_key() {
+        let format = KeyFormat::default().prefix("app/");
+
+        assert_eq!(
+            expired_key(&format, b"app/column:key"),
+            Some(("column".to_string(), b"key".to_vec()))
+        );
+        assert_eq!(expired_key(&format, b"app/column#deadline:key"), None);
+        assert_eq!(expired_key(&format, b"other/column:key"), None);
+    }
+}
@@ -1,16 +1,268 @@
-use crate::{CacheError, CacheStorage, ColumnDefinition};
+mod item;
+mod layout;
+mod locks;
+mod lru;
+mod order;
+mod retry;
 
-/// A cache item.
+use crate::timing::{OperationKind, SlowOperations, Timer};
+use crate::{
+    BackendConfig, CacheError, CacheStorage, ColumnDefinition, ColumnReport, ColumnWriter,
+    EvictReason, EvictionListener, InsertOutcome, RawEntry, SledLayout, SlowOperationListener,
+    StoredValue, TimedEntry, TransactionCondition, TransactionWrite, TtlEnforcement,
+    eviction::Evictions,
+};
+use sled::transaction::{ConflictableTransactionError, TransactionError};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::Duration;
+
+pub use item::ItemFormat;
+use item::{Item, ItemHeader};
+use layout::{ColumnTree, SHARED_TREE, split_shared_key};
+use locks::{ColumnGuard, ColumnLocks};
+use lru::Lru;
+use order::InsertionOrder;
+use retry::Retry;
+
+/// Default smallest batch of keys read in parallel, see [`SledEngineBuilder::parallel_get_threshold`].
+///
+/// In the `get_many` benchmark a read from sled's page cache takes under a microsecond
+/// while handing a batch to the thread pool costs several, so parallel reads only
+/// pay off from a few dozen keys with two or more threads. Run
+/// `cargo bench --features rayon -- get_many` on the target hardware to tune it.
+#[cfg(feature = "rayon")]
+#[cfg_attr(docsrs, doc(cfg(feature = "rayon")))]
+pub const PARALLEL_GET_THRESHOLD: usize = 64;
+
+/// Name of the tree sled always creates, which never holds a column
+const DEFAULT_TREE: &[u8] = b"__sled__default";
+
+/// Name of the tree holding the TTL of each column and the time it last changed,
+/// see [`ColumnDefinition::expire_on_ttl_change`]
+const TTL_EPOCH_TREE: &[u8] = b"__omega_cache__ttl_epochs";
+
+/// Whether the tree holds a single column rather than sled or engine bookkeeping
+/// or the columns sharing [`SHARED_TREE`]
+fn is_column_tree(name: &[u8]) -> bool {
+    name != DEFAULT_TREE && name != TTL_EPOCH_TREE && name != SHARED_TREE
+}
+
+/// What expires the items of a column besides their own TTL
+#[derive(Debug, Clone, Copy)]
+struct Expiry {
+    /// Items written before this time are expired, see [`SledEngine::ttl_epoch`]
+    epoch: u64,
+    /// Items not read for longer than this are expired,
+    /// see [`ColumnDefinition::idle_ttl_in_seconds`]
+    idle: Option<u64>,
+    /// Seconds items may be dated ahead of now, see [`SledEngineBuilder::clock_skew_tolerance`]
+    skew: u64,
+}
+
+/// Whether an item written or last read at `time` with `ttl` is expired at `now`,
+/// either by outliving its TTL, by going unread for longer than the column's idle TTL,
+/// by being written before the column's TTL epoch or by being dated further ahead
+/// of `now` than the clock skew tolerance
+fn expired(time: u64, ttl: Option<u64>, now: u64, expiry: Expiry) -> bool {
+    // items dated ahead of now within the tolerance read as fresh
+    let age = now.saturating_sub(time);
+
+    time > now.saturating_add(expiry.skew)
+        || time < expiry.epoch
+        || ttl.is_some_and(|ttl| age > ttl)
+        || expiry.idle.is_some_and(|idle| age > idle)
+}
+
+/// The encoded item with `header`, rewritten in the same format as read at `now`
+/// to restart its idle timer.
+///
+/// The TTL shrinks by the time since it was written or last read, so the item still
+/// expires when it first would have. [`None`] if the column has no idle TTL or the
+/// item was already read this second
+fn touched(
+    bytes: &[u8],
+    header: &ItemHeader,
+    now: u64,
+    expiry: Expiry,
+) -> Result<Option<Vec<u8>>, CacheError> {
+    let time = header.time;
+    if expiry.idle.is_none() || time >= now {
+        return Ok(None);
+    }
+
+    let item = Item {
+        time: now,
+        ttl: header.ttl.map(|ttl| (time + ttl).saturating_sub(now)),
+        data: &bytes[header.data.clone()],
+    };
+
+    item.encode(Item::format_of(bytes))
+        .map(Some)
+        .map_err(|e| CacheError::encode::<Item<&[u8]>>(e.to_string()))
+}
+
+/// Replace the item at key with its touched version, unless it changed since it was read
+fn touch(
+    tree: &ColumnTree,
+    key: &[u8],
+    read: &sled::IVec,
+    touched: Vec<u8>,
+) -> Result<(), CacheError> {
+    // losing to a concurrent write is fine, the newer item has a fresh timer
+    let _ = tree
+        .compare_and_swap(key, Some(read), Some(touched))
+        .map_err(|e| CacheError::Get(e.to_string()))?;
+
+    Ok(())
+}
+
+impl<T: bincode::Encode> Item<T> {
+    /// Whether the item is expired at `now` for a column with `expiry`
+    fn is_expired(&self, now: u64, expiry: Expiry) -> bool {
+        expired(self.time, self.ttl, now, expiry)
+    }
+}
+
+/// A failing disk makes the cache unavailable, anything else is an engine error
+impl From<sled::Error> for CacheError {
+    fn from(e: sled::Error) -> Self {
+        match e {
+            sled::Error::Io(e) => CacheError::Unavailable(e.to_string()),
+            e => CacheError::Engine(e.to_string()),
+        }
+    }
+}
+
+/// Seconds since the unix epoch
+fn now_in_seconds() -> Result<u64, std::time::SystemTimeError> {
+    Ok(std::time::SystemTime::now()
+        .duration_since(std::time::SystemTime::UNIX_EPOCH)?
+        .as_secs())
+}
+
+/// Add `delta` to the counter held in the encoded `old` item, returning the item to write.
+///
+/// Missing or expired counters restart at zero with a fresh timestamp and `ttl`,
+/// live counters keep their original timestamp and TTL so they are not extended.
+fn increment_item(
+    c: &dyn ColumnDefinition,
+    key: &[u8],
+    old: Option<&[u8]>,
+    delta: i64,
+    now: u64,
+    ttl: Option<u64>,
+    expiry: Expiry,
+) -> Result<Item<Vec<u8>>, CacheError> {
+    let (time, ttl, current) = match old {
+        Some(bytes) => {
+            let item =
+                Item::decode(bytes).map_err(|e| CacheError::decode(c, key, e.to_string()))?;
+
+            if item.is_expired(now, expiry) {
+                (now, ttl, 0)
+            } else {
+                let current =
+                    bincode::decode_from_slice::<i64, _>(&item.data, bincode::config::standard())
+                        .map_err(|e| CacheError::decode(c, key, e.to_string()))?
+                        .0;
+
+                (item.time, item.ttl, current)
+            }
+        }
+        None => (now, ttl, 0),
+    };
+
+    let value = current
+        .checked_add(delta)
+        .ok_or_else(|| CacheError::Put("counter overflow".to_string()))?;
+
+    let data = bincode::encode_to_vec(value, bincode::config::standard())
+        .map_err(|e| CacheError::encode::<i64>(e.to_string()))?;
+
+    Ok(Item { time, ttl, data })
+}
+
+/// Whether the encoded item is within its TTL at `now`
+fn is_live(bytes: &[u8], now: u64, expiry: Expiry) -> bool {
+    Item::decode(bytes).is_ok_and(|item| !item.is_expired(now, expiry))
+}
+
+/// Data of the live item at key, removing the item if it is expired at `now`
+/// and restarting its idle timer if not.
+///
+/// Removed items are reported to the eviction listener of `engine` as expired from `column`,
+/// reads and removals failing with transient errors are retried as set by its retries
+fn read_item(
+    engine: &SledEngine,
+    tree: &ColumnTree,
+    key: &[u8],
+    now: u64,
+    expiry: Expiry,
+    column: &str,
+) -> Result<Option<Vec<u8>>, CacheError> {
+    let retry = engine.retry;
+    let Some(bytes) = retry
+        .run(|| tree.get(key))
+        .map_err(|e| CacheError::Get(e.to_string()))?
+    else {
+        return Ok(None);
+    };
+
+    let header = ItemHeader::decode(&bytes).map_err(|e| CacheError::Get(e.to_string()))?;
+
+    if expired(header.time, header.ttl, now, expiry) {
+        retry
+            .run(|| tree.remove(key))
+            .map_err(|e| CacheError::Get(e.to_string()))?;
+        engine.evicted(column, key, EvictReason::Expired);
+
+        return Ok(None);
+    }
+
+    if let Some(touched) = touched(&bytes, &header, now, expiry)? {
+        touch(tree, key, &bytes, touched)?;
+    }
+
+    Ok(Some(bytes[header.data].to_vec()))
+}
+
+/// Keys of the tree oldest first, to load its [`InsertionOrder`].
+///
+/// Items are ordered by their insert time, ties fall back to key order.
+/// Items that cannot be decoded are treated as the oldest.
+fn insertion_order(tree: &ColumnTree) -> Result<Vec<Vec<u8>>, CacheError> {
+    let mut entries = tree
+        .iter()
+        .map(|entry| {
+            entry.map(|(key, bytes)| {
+                let time = Item::decode(&bytes).map_or(0, |item| item.time);
+
+                (time, key.to_vec())
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    // stable, so keys written in the same second stay in key order
+    entries.sort_by_key(|(time, _)| *time);
+
+    Ok(entries.into_iter().map(|(_, key)| key).collect())
+}
+
+///
+/// When writes reach disk, see [`SledEngineBuilder::durability`]
 ///
-/// Holds the timestamp of the item and data.
-/// Timestamp is used to check that the item is within it's TTL
-/// based on the [`ColumnDefinition`] used when inserting
-#[derive(Debug, Clone, bincode::Encode, bincode::Decode)]
-struct Item<T: bincode::Encode> {
-    /// Time in seconds this item was added to the cache
-    time: u64,
-    /// The data held by this item
-    data: T,
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Durability {
+    /// Writes return once sled has buffered them, and reach disk with sled's own
+    /// flush every 500 milliseconds, on [`SledEngine::try_flush`] or when the database
+    /// is dropped. A crash loses the writes made since the last flush.
+    #[default]
+    Buffered,
+    /// Every write flushes the database before returning, so it survives a crash,
+    /// at the cost of waiting on the disk each time
+    Flushed,
 }
 
 ///
@@ -19,123 +271,1768 @@ struct Item<T: bincode::Encode> {
 #[derive(Debug)]
 pub struct SledEngine {
     inner: sled::Db,
+    /// Where the database is stored, as opened
+    path: String,
+    /// Size in bytes of sled's page cache
+    cache_capacity: u64,
+    /// zstd level pages are compressed at, [`None`] if uncompressed
+    compression_factor: Option<i32>,
+    /// Held for reading by accesses to a column and for writing by its drop,
+    /// so a drop never interleaves with an access to the dropped tree
+    locks: ColumnLocks,
+    /// Number of column drops so far, so [`TreeWriter`]s notice their tree may be gone
+    drops: AtomicU64,
+    /// TTL and TTL epoch of the columns seen so far, see [`SledEngine::ttl_epoch`]
+    ttl_epochs: Mutex<HashMap<String, (i32, u64)>>,
+    /// Smallest batch read in parallel
+    #[cfg(feature = "rayon")]
+    parallel_get_threshold: usize,
+    /// Access order of every item, when the total stored bytes are bounded
+    lru: Option<Lru>,
+    /// Write order of the items of columns with a [`ColumnDefinition::max_entries`]
+    order: InsertionOrder,
+    /// Seconds items may be dated ahead of now, see [`SledEngineBuilder::clock_skew_tolerance`]
+    clock_skew_tolerance: u64,
+    /// Layout of the items written, see [`SledEngineBuilder::item_format`]
+    item_format: ItemFormat,
+    /// When writes reach disk, see [`SledEngineBuilder::durability`]
+    durability: Durability,
+    /// Retries of reads and writes failing with transient errors, see [`SledEngineBuilder::retries`]
+    retry: Retry,
+    /// Notified of items leaving the cache, see [`CacheStorage::set_eviction_listener`]
+    evictions: Evictions,
+    /// Notified of operations over a latency threshold, see [`CacheStorage::set_slow_operation_listener`]
+    slow: SlowOperations,
 }
 
-impl CacheStorage for SledEngine {
-    fn build(path: String, capacity: Option<u64>) -> Box<dyn CacheStorage + Send + Sync> {
-        match sled::Config::default()
-            .mode(sled::Mode::HighThroughput)
-            .path(path)
-            .cache_capacity(capacity.unwrap_or(1024 * 1024 * 1024))
-            .use_compression(true)
-            .compression_factor(5)
-            .open()
-        {
-            Ok(db) => Box::new(SledEngine { inner: db }),
-            Err(e) => panic!("Failed to open cache: {e}"),
+impl SledEngine {
+    ///
+    /// Start building a sled engine stored at path
+    ///
+    /// ```no_run
+    /// use omega_cache::{Engine, sled_engine::SledEngine};
+    ///
+    /// let engine = Engine::new(
+    ///     SledEngine::builder("./cache")
+    ///         .capacity(64 * 1024 * 1024)
+    ///         .compression(3)
+    ///         .build(),
+    /// );
+    /// ```
+    ///
+    #[must_use]
+    pub fn builder(path: impl Into<String>) -> SledEngineBuilder {
+        SledEngineBuilder {
+            path: path.into(),
+            capacity: None,
+            compression_factor: None,
+            #[cfg(feature = "rayon")]
+            parallel_get_threshold: PARALLEL_GET_THRESHOLD,
+            max_total_bytes: None,
+            clock_skew_tolerance: Duration::ZERO,
+            item_format: ItemFormat::default(),
+            durability: Durability::default(),
+            retry: Retry::default(),
         }
     }
 
-    fn try_insert(
+    /// Open the tree of a column, holding off drops until the guard is released
+    fn open_column(
+        &self,
+        c: &dyn ColumnDefinition,
+    ) -> Result<(ColumnGuard<'_>, ColumnTree), CacheError> {
+        let guard = self.locks.read(&c.name());
+
+        let tree = match c.storage_layout() {
+            SledLayout::DedicatedTree => ColumnTree::dedicated(self.inner.open_tree(c.name())?),
+            SledLayout::SharedPrefixed => {
+                ColumnTree::shared(self.inner.open_tree(SHARED_TREE)?, &c.name())
+            }
+        };
+
+        Ok((guard, tree))
+    }
+
+    /// The tree of a column if it was ever written, checked without creating it
+    fn existing_column(&self, c: &dyn ColumnDefinition) -> Result<Option<ColumnTree>, CacheError> {
+        let name = c.name();
+
+        Ok(match c.storage_layout() {
+            SledLayout::DedicatedTree if self.has_tree(&name) => {
+                Some(ColumnTree::dedicated(self.inner.open_tree(name)?))
+            }
+            SledLayout::SharedPrefixed if self.has_shared_tree() => Some(ColumnTree::shared(
+                self.inner.open_tree(SHARED_TREE)?,
+                &name,
+            )),
+            _ => None,
+        })
+    }
+
+    /// Whether any column was written with [`SledLayout::SharedPrefixed`]
+    fn has_shared_tree(&self) -> bool {
+        self.inner
+            .tree_names()
+            .iter()
+            .any(|tree| tree == SHARED_TREE)
+    }
+
+    /// Whether a tree of this name exists, checked without creating it
+    fn has_tree(&self, name: &str) -> bool {
+        self.inner
+            .tree_names()
+            .iter()
+            .any(|tree| tree == name.as_bytes())
+    }
+
+    /// Read the value at key even if it expired, along with whether it did,
+    /// for inspecting a database without changing it.
+    ///
+    /// Unlike [`CacheStorage::try_get`] an expired item is left in place, and a column that
+    /// was never written is not created, though the TTL of an
+    /// [`ColumnDefinition::expire_on_ttl_change`] column is still recorded. It does not count as a read for
+    /// [`SledEngineBuilder::max_total_bytes`]. The value is as the engine stored it,
+    /// so it is still checksummed, encrypted or chunked if the engine did any of that.
+    ///
+    /// ```no_run
+    /// use omega_cache::{ColumnDefinition, sled_engine::SledEngine};
+    ///
+    /// struct Sessions;
+    ///
+    /// impl ColumnDefinition for Sessions {
+    ///     fn name(&self) -> String {
+    ///         "sessions".to_string()
+    ///     }
+    ///
+    ///     fn get_ttl_in_seconds(&self) -> i32 {
+    ///         60
+    ///     }
+    /// }
+    ///
+    /// let sled = SledEngine::builder("./cache").open().unwrap();
+    ///
+    /// if let Some((bytes, expired)) = sled.try_peek(&Sessions, b"ada").unwrap() {
+    ///     println!("{} bytes, expired: {expired}", bytes.len());
+    /// }
+    /// ```
+    ///
+    /// # Errors
+    /// Returns [`CacheError::Get`] if the item cannot be read or decoded
+    pub fn try_peek(
         &self,
         c: &dyn ColumnDefinition,
         key: &[u8],
-        value: &[u8],
-    ) -> Result<(), crate::CacheError> {
-        let t = std::time::Instant::now();
+    ) -> Result<Option<(Vec<u8>, bool)>, CacheError> {
+        let expiry = self.expiry(c)?;
+        let now = now_in_seconds().map_err(|e| CacheError::Get(e.to_string()))?;
 
-        let item = Item {
-            time: std::time::SystemTime::now()
-                .duration_since(std::time::SystemTime::UNIX_EPOCH)
-                .map_err(|e| CacheError::Put(e.to_string()))?
-                .as_secs(),
-            data: value,
+        let _guard = self.locks.read(&c.name());
+
+        let Some(tree) = self.existing_column(c)? else {
+            return Ok(None);
+        };
+
+        let Some(bytes) = tree.get(key)? else {
+            return Ok(None);
+        };
+
+        let item = Item::decode(&bytes).map_err(|e| CacheError::Get(e.to_string()))?;
+        let expired = item.is_expired(now, expiry);
+
+        Ok(Some((item.data, expired)))
+    }
+
+    /// Remove every item that outlived its TTL and flush the database,
+    /// so sled can reuse the space they held.
+    ///
+    /// Items are otherwise only removed once read after expiring, so a database
+    /// whose keys are written once and never read again keeps growing. Only the
+    /// TTL stored with each item is checked, as the column definitions are not
+    /// known here: items expired by an idle TTL or a TTL change stay until read.
+    /// Removed items are reported to the eviction listener as expired.
+    ///
+    /// This walks every tree and rewrites the pages it removes from, so it is
+    /// expensive on large databases and should run off the hot path, for example
+    /// on a timer or after dropping a large column.
+    ///
+    /// ```no_run
+    /// use omega_cache::sled_engine::SledEngine;
+    ///
+    /// let sled = SledEngine::builder("./cache").open().unwrap();
+    ///
+    /// sled.try_compact().unwrap();
+    /// ```
+    ///
+    /// # Errors
+    /// Returns [`CacheError::Put`] if an item cannot be removed or the database cannot be flushed
+    pub fn try_compact(&self) -> Result<(), CacheError> {
+        let now = now_in_seconds().map_err(|e| CacheError::Put(e.to_string()))?;
+        let expiry = Expiry {
+            epoch: 0,
+            idle: None,
+            skew: self.clock_skew_tolerance,
         };
 
-        match bincode::encode_to_vec(item, bincode::config::standard()) {
-            Ok(bytes) => match self
-                .inner
-                .open_tree(c.name())
-                .map_err(|e| CacheError::Engine(e.to_string()))?
-                .insert(key, bytes)
+        for name in self.inner.tree_names() {
+            if name == DEFAULT_TREE || name == TTL_EPOCH_TREE {
+                continue;
+            }
+
+            // the shared tree is never dropped, a dedicated one may be
+            // dropped with its column meanwhile and is then left out
+            let dedicated =
+                (name != SHARED_TREE).then(|| String::from_utf8_lossy(&name).into_owned());
+            let _guard = dedicated.as_deref().map(|column| self.locks.read(column));
+            if dedicated
+                .as_deref()
+                .is_some_and(|column| !self.has_tree(column))
             {
-                Ok(_) => {
-                    if cfg!(debug_assertions) {
-                        eprintln!(
-                            "\x1b[0;34mTime taken for insert:\x1b[0m {}us",
-                            t.elapsed().as_micros()
-                        );
-                    }
+                continue;
+            }
+
+            let tree = self.inner.open_tree(&name)?;
+            for entry in &tree {
+                let (stored_key, bytes) = entry?;
 
-                    Ok(())
+                let Ok(header) = ItemHeader::decode(&bytes) else {
+                    continue;
+                };
+
+                if !expired(header.time, header.ttl, now, expiry) {
+                    continue;
                 }
-                Err(e) => Err(CacheError::Put(e.to_string())),
-            },
-            Err(e) => Err(CacheError::Encode(e.to_string())),
+
+                // an item rewritten since it was read is left alone
+                if tree
+                    .compare_and_swap(&stored_key, Some(&bytes), None::<&[u8]>)?
+                    .is_err()
+                {
+                    continue;
+                }
+
+                let (column, key) = if name == SHARED_TREE {
+                    match split_shared_key(&stored_key) {
+                        Some(split) => split,
+                        None => continue,
+                    }
+                } else {
+                    (String::from_utf8_lossy(&name).into_owned(), &*stored_key)
+                };
+
+                self.untrack(&column, key);
+                self.evicted(&column, key, EvictReason::Expired);
+            }
         }
+
+        self.try_flush()
     }
 
-    fn try_get(
+    /// Write every buffered write to disk, returning once it is there.
+    ///
+    /// With [`Durability::Buffered`] writes otherwise reach disk on sled's own
+    /// flush every 500 milliseconds, so call this after a batch of writes that
+    /// must survive a crash, such as the end of an ingestion run.
+    ///
+    /// # Errors
+    /// Returns [`CacheError::Put`] if the database cannot be flushed
+    pub fn try_flush(&self) -> Result<(), CacheError> {
+        self.inner
+            .flush()
+            .map_err(|e| CacheError::Put(e.to_string()))?;
+
+        Ok(())
+    }
+
+    /// Flush a write before returning with [`Durability::Flushed`]
+    fn persist(&self) -> Result<(), CacheError> {
+        match self.durability {
+            Durability::Buffered => Ok(()),
+            Durability::Flushed => self.try_flush(),
+        }
+    }
+
+    /// Time the column's TTL last changed, items written before it are expired.
+    ///
+    /// Zero unless the column sets [`ColumnDefinition::expire_on_ttl_change`].
+    /// Epochs are in whole seconds, so items written in the same second as the change survive it.
+    /// The TTL is recorded the first time the column is seen, so a change is
+    /// only noticed once the option has been enabled on an earlier run.
+    fn ttl_epoch(&self, c: &dyn ColumnDefinition) -> Result<u64, CacheError> {
+        if !c.expire_on_ttl_change() {
+            return Ok(0);
+        }
+
+        let name = c.name();
+        let ttl = c.get_ttl_in_seconds();
+        let mut epochs = self
+            .ttl_epochs
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+
+        match epochs.get(&name) {
+            Some((known, epoch)) if *known == ttl => return Ok(*epoch),
+            _ => {}
+        }
+
+        let tree = self.inner.open_tree(TTL_EPOCH_TREE)?;
+
+        let recorded = tree
+            .get(&name)?
+            .and_then(|bytes| {
+                bincode::decode_from_slice::<(i32, u64), _>(&bytes, bincode::config::standard())
+                    .ok()
+            })
+            .map(|recorded| recorded.0);
+
+        let epoch = match recorded {
+            Some((recorded_ttl, epoch)) if recorded_ttl == ttl => epoch,
+            Some(_) => now_in_seconds().map_err(|e| CacheError::Engine(e.to_string()))?,
+            None => 0,
+        };
+
+        if recorded != Some((ttl, epoch)) {
+            let bytes = bincode::encode_to_vec((ttl, epoch), bincode::config::standard())
+                .map_err(|e| CacheError::encode::<(i32, u64)>(e.to_string()))?;
+
+            tree.insert(&name, bytes)?;
+        }
+
+        epochs.insert(name, (ttl, epoch));
+
+        Ok(epoch)
+    }
+
+    /// Items and stored bytes of each column in the shared tree, walking all of it
+    fn shared_columns(&self) -> Result<BTreeMap<String, (usize, u64)>, CacheError> {
+        let mut columns = BTreeMap::<String, (usize, u64)>::new();
+        if !self.has_shared_tree() {
+            return Ok(columns);
+        }
+
+        for entry in &self.inner.open_tree(SHARED_TREE)? {
+            let (key, value) = entry?;
+            let Some((name, key)) = split_shared_key(&key) else {
+                continue;
+            };
+
+            let column = columns.entry(name).or_default();
+            column.0 += 1;
+            column.1 += (key.len() + value.len()) as u64;
+        }
+
+        Ok(columns)
+    }
+
+    /// What expires the items of the column besides their own TTL
+    fn expiry(&self, c: &dyn ColumnDefinition) -> Result<Expiry, CacheError> {
+        Ok(Expiry {
+            epoch: self.ttl_epoch(c)?,
+            idle: c.idle_ttl_in_seconds().map(u64::from),
+            skew: self.clock_skew_tolerance,
+        })
+    }
+
+    /// Track every stored item in insert order, oldest first, and evict down to the bound
+    fn load_access_order(&self, lru: &Lru) -> Result<(), CacheError> {
+        let mut items = Vec::new();
+        for name in self.inner.tree_names() {
+            if !is_column_tree(&name) {
+                continue;
+            }
+
+            let column = String::from_utf8_lossy(&name).into_owned();
+            for entry in &self.inner.open_tree(&name)? {
+                let (key, bytes) = entry?;
+                let time = ItemHeader::decode(&bytes).map_or(0, |header| header.time);
+
+                let size = key.len() + bytes.len();
+                items.push((time, column.clone(), key, size));
+            }
+        }
+
+        if self.has_shared_tree() {
+            for entry in &self.inner.open_tree(SHARED_TREE)? {
+                let (key, bytes) = entry?;
+                let Some((column, column_key)) = split_shared_key(&key) else {
+                    continue;
+                };
+
+                let time = ItemHeader::decode(&bytes).map_or(0, |header| header.time);
+
+                let size = column_key.len() + bytes.len();
+                items.push((time, column, sled::IVec::from(column_key), size));
+            }
+        }
+
+        items.sort_by_key(|(time, ..)| *time);
+        for (_, column, key, bytes) in items {
+            lru.write(&column, &key, bytes);
+        }
+
+        self.evict_least_recently_used()
+    }
+
+    /// Record a read of key in the column, for [`SledEngineBuilder::max_total_bytes`]
+    fn track_read(&self, c: &dyn ColumnDefinition, key: &[u8], hit: bool) {
+        if let Some(lru) = &self.lru {
+            lru.read(&c.name(), key, hit);
+        }
+        if !hit {
+            self.order.remove(&c.name(), key);
+        }
+    }
+
+    /// Record reads of many keys in the column, see [`SledEngine::track_read`]
+    fn track_reads(&self, c: &dyn ColumnDefinition, keys: &[&[u8]], values: &[Option<Vec<u8>>]) {
+        for (key, value) in keys.iter().zip(values) {
+            self.track_read(c, key, value.is_some());
+        }
+    }
+
+    /// Record a write of `bytes` stored bytes at key in the column,
+    /// for [`SledEngineBuilder::max_total_bytes`]
+    fn track_write(&self, c: &dyn ColumnDefinition, key: &[u8], bytes: usize) {
+        if let Some(lru) = &self.lru {
+            lru.write(&c.name(), key, key.len() + bytes);
+        }
+    }
+
+    /// Notify the eviction listener of key leaving the column,
+    /// once this thread holds no column lock
+    fn evicted(&self, column: &str, key: &[u8], reason: EvictReason) {
+        if let Some(notification) = self.evictions.later(column, key, reason) {
+            self.locks.notify(notification);
+        }
+    }
+
+    /// Finish timing an operation on the column, notifying the slow operation
+    /// listener once this thread holds no column lock
+    fn finish(&self, timer: Timer<'_>, kind: OperationKind, c: &dyn ColumnDefinition) {
+        if let Some(notification) = timer.finish_later(kind, c) {
+            self.locks.notify(notification);
+        }
+    }
+
+    /// Stop tracking an item removed from the column
+    fn untrack(&self, column: &str, key: &[u8]) {
+        if let Some(lru) = &self.lru {
+            lru.remove(column, key);
+        }
+        self.order.remove(column, key);
+    }
+
+    /// Record the writes of keys to the column and evict its oldest items
+    /// beyond [`ColumnDefinition::max_entries`], never the newest written
+    fn limit_entries(
         &self,
         c: &dyn ColumnDefinition,
-        key: &[u8],
-    ) -> Result<Option<Vec<u8>>, crate::CacheError> {
-        let t = std::time::Instant::now();
+        tree: &ColumnTree,
+        keys: &[&[u8]],
+    ) -> Result<(), CacheError> {
+        let name = c.name();
+        let Some(max_entries) = c.max_entries() else {
+            return Ok(());
+        };
 
-        match self
-            .inner
-            .open_tree(c.name())
-            .map_err(|e| CacheError::Engine(e.to_string()))?
-            .get(key)
+        for key in self
+            .order
+            .evictions(&name, keys, max_entries, || insertion_order(tree))?
         {
-            Ok(Some(bytes)) => {
-                match bincode::decode_from_slice::<Item<Vec<u8>>, _>(
-                    &bytes,
-                    bincode::config::standard(),
-                ) {
-                    Ok(value) => {
-                        if cfg!(debug_assertions) {
-                            eprintln!(
-                                "\x1b[0;34mTime taken for get:\x1b[0m {}us",
-                                t.elapsed().as_micros()
-                            );
-                        }
+            // a key removed without being tracked is already gone
+            if tree.remove(&key)?.is_none() {
+                continue;
+            }
+
+            if let Some(lru) = &self.lru {
+                lru.remove(&name, &key);
+            }
+            self.evicted(&name, &key, EvictReason::CapacityEvicted);
+        }
+
+        Ok(())
+    }
+
+    /// Remove the least recently used items until the total is within
+    /// [`SledEngineBuilder::max_total_bytes`].
+    ///
+    /// Evicted items stop being tracked before they are removed, so an item
+    /// rewritten in between is removed too and counted until it is next read.
+    /// Each item is removed holding off drops of its own column, so this is called
+    /// holding no column lock, as a thread taking a lock it already holds could deadlock.
+    fn evict_least_recently_used(&self) -> Result<(), CacheError> {
+        let Some(lru) = &self.lru else {
+            return Ok(());
+        };
+
+        let mut evicted = false;
+
+        // items are only tracked by column name, so evict from whichever layout holds them
+        for (column, key) in lru.evictions() {
+            let _guard = self.locks.read(&column);
+            let mut removed = false;
+
+            if self.has_tree(&column) {
+                removed |= self.inner.open_tree(&column)?.remove(&key)?.is_some();
+            }
+
+            if self.has_shared_tree() {
+                removed |= ColumnTree::shared(self.inner.open_tree(SHARED_TREE)?, &column)
+                    .remove(&key)?
+                    .is_some();
+            }
+
+            self.order.remove(&column, &key);
+            if removed {
+                self.evicted(&column, &key, EvictReason::CapacityEvicted);
+            }
+            evicted |= removed;
+        }
+
+        if evicted {
+            self.persist()?;
+        }
+
+        Ok(())
+    }
+
+    /// Report the item a write replaced, as expired if it had
+    fn replaced(&self, c: &dyn ColumnDefinition, key: &[u8], old: &[u8]) -> Result<(), CacheError> {
+        if !self.evictions.is_listening() {
+            return Ok(());
+        }
+
+        let now = now_in_seconds().map_err(|e| CacheError::Put(e.to_string()))?;
+        let reason = if is_live(old, now, self.expiry(c)?) {
+            EvictReason::Replaced
+        } else {
+            EvictReason::Expired
+        };
+        self.evicted(&c.name(), key, reason);
+
+        Ok(())
+    }
+
+    /// Insert an item, returning the item it replaced
+    fn insert_item(
+        &self,
+        c: &dyn ColumnDefinition,
+        key: &[u8],
+        value: &[u8],
+        ttl_in_seconds: i32,
+    ) -> Result<Option<sled::IVec>, CacheError> {
+        self.ttl_epoch(c)?;
+
+        let (guard, tree) = self.open_column(c)?;
+        let old = self.write_item(c, &tree, key, value, ttl_in_seconds)?;
+        drop(guard);
+
+        self.evict_least_recently_used()?;
+
+        Ok(old)
+    }
+
+    /// Insert into the opened tree of the column, returning the bytes of the item replaced.
+    ///
+    /// Callers evict for [`SledEngineBuilder::max_total_bytes`] once they release the column
+    fn write_item(
+        &self,
+        c: &dyn ColumnDefinition,
+        tree: &ColumnTree,
+        key: &[u8],
+        value: &[u8],
+        ttl_in_seconds: i32,
+    ) -> Result<Option<sled::IVec>, CacheError> {
+        let timer = Timer::start(&self.slow);
+
+        let item = Item {
+            time: std::time::SystemTime::now()
+                .duration_since(std::time::SystemTime::UNIX_EPOCH)
+                .map_err(|e| CacheError::Put(e.to_string()))?
+                .as_secs(),
+            ttl: crate::ttl::expiry(c, ttl_in_seconds),
+            data: value,
+        };
 
-                        if (std::time::SystemTime::now()
-                            .duration_since(std::time::UNIX_EPOCH)
-                            .map_err(|e| CacheError::Get(e.to_string()))?
-                            .as_secs()
-                            - value.0.time)
-                            > u64::try_from(c.get_ttl_in_seconds())
-                                .map_err(|e| CacheError::Get(e.to_string()))?
-                        {
-                            self.inner
-                                .open_tree(c.name())
-                                .map_err(|e| CacheError::Engine(e.to_string()))?
-                                .remove(key)
-                                .expect("Failed to remove outdated cache item");
-
-                            return Ok(None);
+        match item.encode(self.item_format) {
+            Ok(bytes) => {
+                let len = bytes.len();
+
+                match self.retry.run(|| tree.insert(key, bytes.as_slice())) {
+                    Ok(old) => {
+                        if let Some(old) = &old {
+                            self.replaced(c, key, old)?;
                         }
+                        self.track_write(c, key, len);
+                        self.limit_entries(c, tree, &[key])?;
+                        self.persist()?;
+
+                        self.finish(timer, OperationKind::Insert, c);
 
-                        Ok(Some(value.0.data))
+                        Ok(old)
                     }
-                    Err(e) => Err(CacheError::Get(e.to_string())),
+                    Err(e) => Err(CacheError::Put(e.to_string())),
                 }
             }
-            Ok(None) => Ok(None),
-            Err(e) => Err(CacheError::Get(e.to_string())),
+            Err(e) => Err(CacheError::encode::<Item<&[u8]>>(e.to_string())),
         }
     }
+}
 
-    fn try_drop_column(&self, c: &dyn ColumnDefinition) -> Result<(), CacheError> {
-        if let Err(e) = self.inner.drop_tree(c.name()) {
-            return Err(CacheError::Engine(e.to_string()));
-        }
+/// What [`CacheStorage::try_rename`] found at the key renamed
+enum Renamed {
+    Missing,
+    /// Dropped rather than moved
+    Expired,
+    Moved {
+        size: usize,
+        /// Item previously at the new key
+        replaced: Option<sled::IVec>,
+    },
+}
 
-        match self.inner.open_tree(c.name()) {
-            Ok(_) => Ok(()),
-            Err(e) => Err(CacheError::Engine(e.to_string())),
+/// Writer holding the opened tree of a column, see [`CacheStorage::try_column_writer`]
+struct TreeWriter<'a> {
+    engine: &'a SledEngine,
+    column: &'a dyn ColumnDefinition,
+    tree: ColumnTree,
+    /// [`SledEngine::drops`] when the tree was opened
+    drops: u64,
+}
+
+impl ColumnWriter for TreeWriter<'_> {
+    fn try_insert(&self, key: &[u8], value: &[u8]) -> Result<(), CacheError> {
+        let guard = self.engine.locks.read(&self.column.name());
+
+        // a write to a dropped tree would be lost, so reopen it through a plain insert
+        if self.engine.drops.load(Ordering::Relaxed) != self.drops {
+            drop(guard);
+            return self.engine.try_insert(self.column, key, value);
         }
+
+        self.engine.write_item(
+            self.column,
+            &self.tree,
+            key,
+            value,
+            self.column.get_ttl_in_seconds(),
+        )?;
+        drop(guard);
+
+        self.engine.evict_least_recently_used()
+    }
+}
+
+///
+/// Builder for [`SledEngine`]
+///
+#[derive(Debug, Clone)]
+pub struct SledEngineBuilder {
+    path: String,
+    capacity: Option<u64>,
+    compression_factor: Option<i32>,
+    #[cfg(feature = "rayon")]
+    parallel_get_threshold: usize,
+    max_total_bytes: Option<u64>,
+    clock_skew_tolerance: Duration,
+    item_format: ItemFormat,
+    durability: Durability,
+    retry: Retry,
+}
+
+impl SledEngineBuilder {
+    /// Size in bytes of sled's page cache, defaults to 1GiB.
+    ///
+    /// This bounds memory use, not the number of items,
+    /// see [`ColumnDefinition::max_entries`] for that.
+    #[must_use]
+    pub fn capacity(mut self, capacity: u64) -> SledEngineBuilder {
+        self.capacity = Some(capacity);
+        self
+    }
+
+    /// Compress stored pages with zstd at the given level (1 to 22).
+    ///
+    /// Off by default as compression costs CPU on every write and read
+    /// and makes small values such as integers larger rather than smaller.
+    /// Worth enabling for large, repetitive values such as text or JSON.
+    #[must_use]
+    pub fn compression(mut self, factor: i32) -> SledEngineBuilder {
+        self.compression_factor = Some(factor);
+        self
+    }
+
+    /// Smallest batch of keys read in parallel by `try_get_many`,
+    /// defaults to [`PARALLEL_GET_THRESHOLD`].
+    ///
+    /// Batches are capped by [`crate::Engine::with_batch_size`], so raise that too
+    /// for batches beyond [`crate::Engine::DEFAULT_BATCH_SIZE`] keys.
+    /// `usize::MAX` always reads sequentially.
+    #[cfg(feature = "rayon")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "rayon")))]
+    #[must_use]
+    pub fn parallel_get_threshold(mut self, threshold: usize) -> SledEngineBuilder {
+        self.parallel_get_threshold = threshold;
+        self
+    }
+
+    /// Bound the bytes stored across every column, evicting the least recently
+    /// read or written items after a write takes the total over `max_total_bytes`.
+    ///
+    /// Stored bytes are each item's key and encoded value, as in `size_on_disk` of
+    /// [`crate::Engine::try_report`], rather than sled's own `size_on_disk`. sled
+    /// never shrinks its files and reuses the space freed by evictions instead, so
+    /// the files level off at a multiple of this bound rather than at it.
+    ///
+    /// Access order is kept in memory: every item costs a copy of its key and column
+    /// name plus around 100 bytes, each read and write takes a lock shared by all
+    /// columns, and opening the database walks every column to rebuild the order
+    /// from insert times. Unbounded by default.
+    #[must_use]
+    pub fn max_total_bytes(mut self, max_total_bytes: u64) -> SledEngineBuilder {
+        self.max_total_bytes = Some(max_total_bytes);
+        self
+    }
+
+    /// How far ahead of this process's clock an item may be dated and still be read,
+    /// for databases written by several hosts, such as sled files on shared or
+    /// replicated storage, whose clocks are not perfectly in sync.
+    ///
+    /// An item dated ahead of now within the tolerance reads as fresh and still expires
+    /// by its own date, so on this host it may outlive its TTL by up to the tolerance.
+    /// One dated further ahead is expired, as its age cannot be told. Item times are whole seconds, so the
+    /// tolerance is rounded down to whole seconds. Defaults to zero: any item dated
+    /// ahead of the clock, including after the clock is stepped back, is expired.
+    #[must_use]
+    pub fn clock_skew_tolerance(mut self, tolerance: Duration) -> SledEngineBuilder {
+        self.clock_skew_tolerance = tolerance;
+        self
+    }
+
+    /// Layout of the items written, [`ItemFormat::Bincode`] by default.
+    ///
+    /// Items of both formats are always read, so an existing database can be opened
+    /// with either: its items are read as they were written and move to this format
+    /// when next written, expiring as they always would. Older versions of this crate
+    /// read [`ItemFormat::Bincode`] alone and fail to decode [`ItemFormat::Compact`] items.
+    ///
+    /// ```no_run
+    /// use omega_cache::{Engine, sled_engine::{ItemFormat, SledEngine}};
+    ///
+    /// let engine = Engine::new(
+    ///     SledEngine::builder("./cache")
+    ///         .item_format(ItemFormat::Compact)
+    ///         .build(),
+    /// );
+    /// ```
+    #[must_use]
+    pub fn item_format(mut self, item_format: ItemFormat) -> SledEngineBuilder {
+        self.item_format = item_format;
+        self
+    }
+
+    /// When writes reach disk, [`Durability::Buffered`] by default.
+    ///
+    /// [`Durability::Flushed`] waits on the disk in every write, inserts, removals,
+    /// counter increments and drops alike, so ingestion that can afford to lose its
+    /// latest writes on a crash should keep the default and call
+    /// [`SledEngine::try_flush`] at the points it needs on disk. The `durability`
+    /// benchmark compares insert throughput of the two.
+    ///
+    /// ```no_run
+    /// use omega_cache::{Engine, sled_engine::{Durability, SledEngine}};
+    ///
+    /// let engine = Engine::new(
+    ///     SledEngine::builder("./cache")
+    ///         .durability(Durability::Flushed)
+    ///         .build(),
+    /// );
+    /// ```
+    #[must_use]
+    pub fn durability(mut self, durability: Durability) -> SledEngineBuilder {
+        self.durability = durability;
+        self
+    }
+
+    /// Retry the reads and writes of single items failing with IO errors, such as
+    /// on a busy disk, up to `retries` times, waiting `backoff` before the first retry
+    /// and doubling the wait before each one after. No retries by default.
+    ///
+    /// Only IO errors are retried, as they may go away on their own. Corruption and
+    /// other errors of the database itself fail at once, as do errors of batches,
+    /// scans and transactions. An operation that keeps failing holds its caller
+    /// for the sum of the waits before returning the last error.
+    ///
+    /// ```no_run
+    /// use std::time::Duration;
+    ///
+    /// use omega_cache::{Engine, sled_engine::SledEngine};
+    ///
+    /// let engine = Engine::new(
+    ///     SledEngine::builder("./cache")
+    ///         .retries(3, Duration::from_millis(5))
+    ///         .build(),
+    /// );
+    /// ```
+    #[must_use]
+    pub fn retries(mut self, retries: u32, backoff: Duration) -> SledEngineBuilder {
+        self.retry = Retry { retries, backoff };
+        self
+    }
+
+    /// Open the sled database.
+    ///
+    /// sled cannot toggle compression on an existing database, so when
+    /// compression was not requested a database created with it enabled
+    /// (the default before compression became opt-in) is reopened with it.
+    ///
+    /// # Panics
+    /// Panics if the database cannot be opened
+    #[must_use]
+    pub fn build(self) -> Box<dyn CacheStorage + Send + Sync> {
+        match self.try_build() {
+            Ok(storage) => storage,
+            Err(e) => panic!("{e}"),
+        }
+    }
+
+    /// Open the sled database, see [`SledEngineBuilder::build`]
+    ///
+    /// # Errors
+    /// Returns [`CacheError::Engine`] if the database cannot be opened
+    pub fn try_build(self) -> Result<Box<dyn CacheStorage + Send + Sync>, CacheError> {
+        Ok(Box::new(self.open()?))
+    }
+
+    /// Open the sled database as a [`SledEngine`] rather than boxed storage,
+    /// for calling its own methods such as [`SledEngine::try_peek`]
+    ///
+    /// # Errors
+    /// Returns [`CacheError::Engine`] if the database cannot be opened
+    pub fn open(self) -> Result<SledEngine, CacheError> {
+        let cache_capacity = self.capacity.unwrap_or(1024 * 1024 * 1024);
+        let config = sled::Config::default()
+            .mode(sled::Mode::HighThroughput)
+            .path(&self.path)
+            .cache_capacity(cache_capacity);
+
+        let (opened, compression_factor) = match self.compression_factor {
+            Some(factor) => (
+                config
+                    .use_compression(true)
+                    .compression_factor(factor)
+                    .open(),
+                Some(factor),
+            ),
+            None => match config.open() {
+                Err(sled::Error::Unsupported(message)) if message.contains("use_compression") => (
+                    config.use_compression(true).compression_factor(5).open(),
+                    Some(5),
+                ),
+                opened => (opened, None),
+            },
+        };
+
+        let engine = match opened {
+            Ok(db) => SledEngine {
+                inner: db,
+                path: self.path,
+                cache_capacity,
+                compression_factor,
+                locks: ColumnLocks::default(),
+                drops: AtomicU64::new(0),
+                ttl_epochs: Mutex::default(),
+                #[cfg(feature = "rayon")]
+                parallel_get_threshold: self.parallel_get_threshold,
+                lru: self.max_total_bytes.map(Lru::new),
+                order: InsertionOrder::default(),
+                clock_skew_tolerance: self.clock_skew_tolerance.as_secs(),
+                item_format: self.item_format,
+                durability: self.durability,
+                retry: self.retry,
+                evictions: Evictions::none(),
+                slow: SlowOperations::default(),
+            },
+            Err(e) => return Err(CacheError::Engine(format!("Failed to open cache: {e}"))),
+        };
+
+        if let Some(lru) = &engine.lru {
+            engine.load_access_order(lru)?;
+        }
+
+        Ok(engine)
+    }
+}
+
+impl CacheStorage for SledEngine {
+    /// Open a sled database without compression,
+    /// use [`SledEngine::builder`] for more options
+    fn build(path: String, capacity: Option<u64>) -> Box<dyn CacheStorage + Send + Sync> {
+        let builder = SledEngine::builder(path);
+
+        match capacity {
+            Some(capacity) => builder.capacity(capacity).build(),
+            None => builder.build(),
+        }
+    }
+
+    fn ttl_enforcement(&self) -> TtlEnforcement {
+        TtlEnforcement::LazyOnRead
+    }
+
+    /// Compression is as the database was opened, so `zstd level 5` for a database
+    /// created with compression and reopened without asking for it
+    fn describe_config(&self) -> BackendConfig {
+        let config = BackendConfig::new("sled")
+            .with("path", &self.path)
+            .with("mode", "high_throughput")
+            .with("cache_capacity", self.cache_capacity)
+            .with(
+                "compression",
+                self.compression_factor.map_or_else(
+                    || "off".to_string(),
+                    |factor| format!("zstd level {factor}"),
+                ),
+            )
+            .with(
+                "max_total_bytes",
+                self.lru.as_ref().map_or_else(
+                    || "unbounded".to_string(),
+                    |lru| lru.max_bytes().to_string(),
+                ),
+            )
+            .with(
+                "clock_skew_tolerance",
+                format!("{}s", self.clock_skew_tolerance),
+            )
+            .with("item_format", format!("{:?}", self.item_format))
+            .with("durability", format!("{:?}", self.durability))
+            .with(
+                "retries",
+                format!("{} after {:?}", self.retry.retries, self.retry.backoff),
+            );
+
+        #[cfg(feature = "rayon")]
+        let config = config.with("parallel_get_threshold", self.parallel_get_threshold);
+
+        config
+    }
+
+    fn try_insert(
+        &self,
+        c: &dyn ColumnDefinition,
+        key: &[u8],
+        value: &[u8],
+    ) -> Result<(), crate::CacheError> {
+        self.try_insert_with_ttl(c, key, value, c.get_ttl_in_seconds())
+    }
+
+    fn try_insert_with_ttl(
+        &self,
+        c: &dyn ColumnDefinition,
+        key: &[u8],
+        value: &[u8],
+        ttl_in_seconds: i32,
+    ) -> Result<(), CacheError> {
+        self.insert_item(c, key, value, ttl_in_seconds).map(|_| ())
+    }
+
+    /// Opens the column's tree once, a drop of any column makes
+    /// the writer fall back to reopening it on every insert
+    fn try_column_writer<'a>(
+        &'a self,
+        c: &'a dyn ColumnDefinition,
+    ) -> Result<Box<dyn ColumnWriter + 'a>, CacheError> {
+        self.ttl_epoch(c)?;
+
+        let (_guard, tree) = self.open_column(c)?;
+
+        Ok(Box::new(TreeWriter {
+            engine: self,
+            column: c,
+            tree,
+            drops: self.drops.load(Ordering::Relaxed),
+        }))
+    }
+
+    fn try_insert_reporting(
+        &self,
+        c: &dyn ColumnDefinition,
+        key: &[u8],
+        value: &[u8],
+    ) -> Result<InsertOutcome, CacheError> {
+        let now = now_in_seconds().map_err(|e| CacheError::Put(e.to_string()))?;
+        let expiry = self.expiry(c)?;
+
+        match self.insert_item(c, key, value, c.get_ttl_in_seconds())? {
+            Some(old) if is_live(&old, now, expiry) => Ok(InsertOutcome::Overwrote),
+            _ => Ok(InsertOutcome::Created),
+        }
+    }
+
+    fn try_swap(
+        &self,
+        c: &dyn ColumnDefinition,
+        key: &[u8],
+        value: &[u8],
+    ) -> Result<Option<Vec<u8>>, CacheError> {
+        let now = now_in_seconds().map_err(|e| CacheError::Put(e.to_string()))?;
+        let expiry = self.expiry(c)?;
+
+        let Some(old) = self.insert_item(c, key, value, c.get_ttl_in_seconds())? else {
+            return Ok(None);
+        };
+
+        let item = Item::decode(&old).map_err(|e| CacheError::decode(c, key, e.to_string()))?;
+
+        if item.is_expired(now, expiry) {
+            return Ok(None);
+        }
+
+        Ok(Some(item.data))
+    }
+
+    /// The item keeps its write time and TTL, so it expires when it would have at `from`
+    fn try_rename(
+        &self,
+        c: &dyn ColumnDefinition,
+        from: &[u8],
+        to: &[u8],
+    ) -> Result<bool, CacheError> {
+        let now = now_in_seconds().map_err(|e| CacheError::Put(e.to_string()))?;
+        let expiry = self.expiry(c)?;
+
+        let (_guard, tree) = self.open_column(c)?;
+
+        let (from_key, to_key) = (tree.key(from), tree.key(to));
+        let moved = tree
+            .tree()
+            .transaction(|tx| {
+                let Some(bytes) = tx.remove(from_key.as_ref())? else {
+                    return Ok(Renamed::Missing);
+                };
+
+                // Expired items are dropped rather than moved
+                let live = ItemHeader::decode(&bytes)
+                    .is_ok_and(|header| !expired(header.time, header.ttl, now, expiry));
+                if !live {
+                    return Ok(Renamed::Expired);
+                }
+
+                let size = bytes.len();
+                let replaced = tx.insert(to_key.as_ref(), bytes)?;
+
+                Ok(Renamed::Moved { size, replaced })
+            })
+            .map_err(|e: TransactionError| CacheError::Put(e.to_string()))?;
+        self.persist()?;
+
+        self.untrack(&c.name(), from);
+
+        match moved {
+            Renamed::Missing => Ok(false),
+            Renamed::Expired => {
+                self.evicted(&c.name(), from, EvictReason::Expired);
+
+                Ok(false)
+            }
+            Renamed::Moved { size, replaced } => {
+                if let Some(replaced) = replaced {
+                    self.replaced(c, to, &replaced)?;
+                }
+                self.track_write(c, to, size);
+                self.order.write(&c.name(), &[to]);
+
+                Ok(true)
+            }
+        }
+    }
+
+    /// Rewrites the items as written now with the new TTL, in one transaction
+    fn try_touch_many(
+        &self,
+        c: &dyn ColumnDefinition,
+        keys: &[&[u8]],
+        ttl_in_seconds: i32,
+    ) -> Result<usize, CacheError> {
+        let now = now_in_seconds().map_err(|e| CacheError::Put(e.to_string()))?;
+        let expiry = self.expiry(c)?;
+
+        let (_guard, tree) = self.open_column(c)?;
+
+        // jittered once so a retried transaction writes the same TTLs
+        let rewrites = keys
+            .iter()
+            .map(|key| (tree.key(key), crate::ttl::expiry(c, ttl_in_seconds)))
+            .collect::<Vec<_>>();
+
+        tree.tree()
+            .transaction(|tx| {
+                let mut touched = 0;
+
+                for (key, ttl) in &rewrites {
+                    let Some(bytes) = tx.get(key.as_ref())? else {
+                        continue;
+                    };
+
+                    let Ok(item) = Item::decode(&bytes) else {
+                        continue;
+                    };
+                    if item.is_expired(now, expiry) {
+                        continue;
+                    }
+
+                    let item = Item {
+                        time: now,
+                        ttl: *ttl,
+                        data: item.data,
+                    };
+                    let bytes = item.encode(self.item_format).map_err(|e| {
+                        ConflictableTransactionError::Abort(CacheError::encode::<Item<Vec<u8>>>(
+                            e.to_string(),
+                        ))
+                    })?;
+
+                    tx.insert(key.as_ref(), bytes)?;
+                    touched += 1;
+                }
+
+                Ok(touched)
+            })
+            .map_err(|e| match e {
+                TransactionError::Abort(e) => e,
+                TransactionError::Storage(e) => CacheError::Put(e.to_string()),
+            })
+            .and_then(|touched| self.persist().map(|()| touched))
+    }
+
+    fn try_get(
+        &self,
+        c: &dyn ColumnDefinition,
+        key: &[u8],
+    ) -> Result<Option<Vec<u8>>, crate::CacheError> {
+        let timer = Timer::start(&self.slow);
+        let expiry = self.expiry(c)?;
+        let now = now_in_seconds().map_err(|e| CacheError::Get(e.to_string()))?;
+
+        let (_guard, tree) = self.open_column(c)?;
+
+        let data = read_item(self, &tree, key, now, expiry, &c.name())?;
+        self.track_read(c, key, data.is_some());
+        self.finish(timer, OperationKind::Get, c);
+
+        Ok(data)
+    }
+
+    /// With the `rayon` feature, batches of at least
+    /// [`SledEngineBuilder::parallel_get_threshold`] keys are read in parallel
+    /// on rayon's global thread pool, unless it only has one thread
+    fn try_get_many(
+        &self,
+        c: &dyn ColumnDefinition,
+        keys: &[&[u8]],
+    ) -> Result<Vec<Option<Vec<u8>>>, CacheError> {
+        let timer = Timer::start(&self.slow);
+        let expiry = self.expiry(c)?;
+        let now = now_in_seconds().map_err(|e| CacheError::Get(e.to_string()))?;
+
+        let (_guard, tree) = self.open_column(c)?;
+        let name = c.name();
+
+        #[cfg(feature = "rayon")]
+        if keys.len() >= self.parallel_get_threshold && rayon::current_num_threads() > 1 {
+            use rayon::prelude::*;
+
+            // notifications wait for this thread to release the column
+            let values = keys
+                .par_iter()
+                .map(|key| {
+                    ColumnLocks::deferring(|| read_item(self, &tree, key, now, expiry, &name))
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            self.track_reads(c, keys, &values);
+            self.finish(timer, OperationKind::BatchGet, c);
+
+            return Ok(values);
+        }
+
+        let values = keys
+            .iter()
+            .map(|key| read_item(self, &tree, key, now, expiry, &name))
+            .collect::<Result<Vec<_>, _>>()?;
+        self.track_reads(c, keys, &values);
+        self.finish(timer, OperationKind::BatchGet, c);
+
+        Ok(values)
+    }
+
+    fn try_insert_batch(
+        &self,
+        c: &dyn ColumnDefinition,
+        items: &[(Vec<u8>, Vec<u8>)],
+    ) -> Result<(), CacheError> {
+        let timer = Timer::start(&self.slow);
+        let time = now_in_seconds().map_err(|e| CacheError::Put(e.to_string()))?;
+        self.ttl_epoch(c)?;
+
+        let mut batch = Vec::with_capacity(items.len());
+        let mut sizes = Vec::with_capacity(items.len());
+        for (key, value) in items {
+            let item = Item {
+                time,
+                ttl: crate::ttl::expiry(c, c.get_ttl_in_seconds()),
+                data: value,
+            };
+
+            let bytes = item
+                .encode(self.item_format)
+                .map_err(|e| CacheError::encode::<Item<&Vec<u8>>>(e.to_string()))?;
+            sizes.push(bytes.len());
+            batch.push((key.as_slice(), bytes));
+        }
+
+        let (guard, tree) = self.open_column(c)?;
+
+        tree.apply_batch(batch)
+            .map_err(|e| CacheError::Put(e.to_string()))?;
+
+        for ((key, _), len) in items.iter().zip(sizes) {
+            self.track_write(c, key, len);
+        }
+        let keys = items
+            .iter()
+            .map(|(key, _)| key.as_slice())
+            .collect::<Vec<_>>();
+        self.limit_entries(c, &tree, &keys)?;
+        drop(guard);
+        self.evict_least_recently_used()?;
+        self.persist()?;
+
+        self.finish(timer, OperationKind::BatchInsert, c);
+
+        Ok(())
+    }
+
+    fn try_value_size(
+        &self,
+        c: &dyn ColumnDefinition,
+        key: &[u8],
+    ) -> Result<Option<usize>, CacheError> {
+        let (_guard, tree) = self.open_column(c)?;
+
+        tree.get(key)
+            .map(|bytes| bytes.map(|bytes| bytes.len()))
+            .map_err(|e| CacheError::Get(e.to_string()))
+    }
+
+    fn try_get_stored(
+        &self,
+        c: &dyn ColumnDefinition,
+        key: &[u8],
+    ) -> Result<Option<StoredValue>, CacheError> {
+        let expiry = self.expiry(c)?;
+        let (_guard, tree) = self.open_column(c)?;
+
+        let Some(bytes) = tree.get(key).map_err(|e| CacheError::Get(e.to_string()))? else {
+            self.track_read(c, key, false);
+            return Ok(None);
+        };
+
+        let header = ItemHeader::decode(&bytes).map_err(|e| CacheError::Get(e.to_string()))?;
+
+        let now = now_in_seconds().map_err(|e| CacheError::Get(e.to_string()))?;
+
+        if expired(header.time, header.ttl, now, expiry) {
+            tree.remove(key)
+                .map_err(|e| CacheError::Get(e.to_string()))?;
+            self.evicted(&c.name(), key, EvictReason::Expired);
+            self.track_read(c, key, false);
+
+            return Ok(None);
+        }
+
+        if let Some(touched) = touched(&bytes, &header, now, expiry)? {
+            touch(&tree, key, &bytes, touched)?;
+        }
+
+        self.track_read(c, key, true);
+
+        let ItemHeader { data, .. } = header;
+        Ok(Some(StoredValue::new(bytes).slice(data.start, data.end)))
+    }
+
+    /// Reads as [`SledEngine::try_peek`], so live values are not refreshed either
+    fn try_get_allowing_stale(
+        &self,
+        c: &dyn ColumnDefinition,
+        key: &[u8],
+    ) -> Result<Option<(Vec<u8>, bool)>, CacheError> {
+        self.try_peek(c, key)
+    }
+
+    /// Drops wait for in flight accesses to the column, and the few columns sharing
+    /// its lock, and hold off new ones,
+    /// so a write to the column either lands before the drop and is removed,
+    /// or after it and is kept. Dropping a missing column does nothing.
+    fn try_drop_column(&self, c: &dyn ColumnDefinition) -> Result<(), CacheError> {
+        let _guard = self.locks.write(&c.name());
+
+        // a missing tree is not an error, and the tree is left dropped
+        // rather than reopened so dropping never creates an empty one
+        match c.storage_layout() {
+            SledLayout::DedicatedTree => {
+                self.inner.drop_tree(c.name())?;
+            }
+            SledLayout::SharedPrefixed => {
+                if let Some(tree) = self.existing_column(c)? {
+                    tree.clear()?;
+                }
+            }
+        }
+        self.drops.fetch_add(1, Ordering::Relaxed);
+
+        if let Some(lru) = &self.lru {
+            lru.drop_column(&c.name());
+        }
+        self.order.drop_column(&c.name());
+
+        self.persist()
+    }
+
+    fn try_entries(&self, c: &dyn ColumnDefinition) -> Result<Vec<RawEntry>, CacheError> {
+        let timer = Timer::start(&self.slow);
+        let expiry = self.expiry(c)?;
+        let now = now_in_seconds().map_err(|e| CacheError::Get(e.to_string()))?;
+
+        let (_guard, tree) = self.open_column(c)?;
+
+        let mut entries = Vec::new();
+        for entry in tree.iter() {
+            let (key, bytes) = entry.map_err(|e| CacheError::Get(e.to_string()))?;
+
+            let item =
+                Item::decode(&bytes).map_err(|e| CacheError::decode(c, &key, e.to_string()))?;
+
+            if !item.is_expired(now, expiry) {
+                entries.push((key.to_vec(), item.data));
+            }
+        }
+        self.finish(timer, OperationKind::Entries, c);
+
+        Ok(entries)
+    }
+
+    fn try_timed_entries(&self, c: &dyn ColumnDefinition) -> Result<Vec<TimedEntry>, CacheError> {
+        let expiry = self.expiry(c)?;
+        let now = now_in_seconds().map_err(|e| CacheError::Get(e.to_string()))?;
+
+        let (_guard, tree) = self.open_column(c)?;
+
+        let mut entries = Vec::new();
+        for entry in tree.iter() {
+            let (key, bytes) = entry.map_err(|e| CacheError::Get(e.to_string()))?;
+
+            let item =
+                Item::decode(&bytes).map_err(|e| CacheError::decode(c, &key, e.to_string()))?;
+
+            if item.is_expired(now, expiry) {
+                continue;
+            }
+
+            // the item expires at the earlier of its TTL and its idle TTL
+            let ttl = match (item.ttl, expiry.idle) {
+                (Some(ttl), Some(idle)) => Some(ttl.min(idle)),
+                (ttl, idle) => ttl.or(idle),
+            };
+            let left = ttl.map(|ttl| Duration::from_secs((item.time + ttl).saturating_sub(now)));
+
+            entries.push((key.to_vec(), item.data, left));
+        }
+
+        Ok(entries)
+    }
+
+    fn try_entry_ages(&self, c: &dyn ColumnDefinition) -> Result<Vec<Duration>, CacheError> {
+        let expiry = self.expiry(c)?;
+        let now = now_in_seconds().map_err(|e| CacheError::Get(e.to_string()))?;
+
+        let (_guard, tree) = self.open_column(c)?;
+
+        let mut ages = Vec::new();
+        for entry in tree.iter() {
+            let (key, bytes) = entry.map_err(|e| CacheError::Get(e.to_string()))?;
+
+            let header = ItemHeader::decode(&bytes)
+                .map_err(|e| CacheError::decode(c, &key, e.to_string()))?;
+
+            if !expired(header.time, header.ttl, now, expiry) {
+                ages.push(Duration::from_secs(now.saturating_sub(header.time)));
+            }
+        }
+
+        Ok(ages)
+    }
+
+    fn try_count_column(&self, c: &dyn ColumnDefinition) -> Result<usize, CacheError> {
+        let _guard = self.locks.read(&c.name());
+
+        Ok(self.existing_column(c)?.map_or(0, |tree| tree.len()))
+    }
+
+    /// Columns cannot take the name of sled's default tree or the TTL epoch tree
+    fn validate_column(&self, c: &dyn ColumnDefinition) -> Result<(), CacheError> {
+        let name = c.name();
+
+        if !is_column_tree(name.as_bytes()) {
+            return Err(CacheError::Engine(format!(
+                "column `{name}` is reserved by the sled engine"
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// The layout of the tree holding items of the column, or the column's own if neither does
+    fn try_column_layout(&self, c: &dyn ColumnDefinition) -> Result<SledLayout, CacheError> {
+        let name = c.name();
+        let _guard = self.locks.read(&name);
+
+        let dedicated = self.has_tree(&name) && !self.inner.open_tree(&name)?.is_empty();
+        let shared = self.has_shared_tree()
+            && ColumnTree::shared(self.inner.open_tree(SHARED_TREE)?, &name)
+                .iter()
+                .next()
+                .is_some();
+
+        match (dedicated, shared) {
+            (true, true) => Err(CacheError::Engine(format!(
+                "column `{name}` has items both in a tree of its own and in the shared tree"
+            ))),
+            (true, false) => Ok(SledLayout::DedicatedTree),
+            (false, true) => Ok(SledLayout::SharedPrefixed),
+            (false, false) => Ok(c.storage_layout()),
+        }
+    }
+
+    /// Shared columns are found by walking the whole shared tree
+    fn try_list_columns(&self) -> Result<Vec<String>, CacheError> {
+        let mut columns = self
+            .inner
+            .tree_names()
+            .into_iter()
+            .filter(|name| is_column_tree(name))
+            .map(|name| String::from_utf8_lossy(&name).into_owned())
+            .collect::<Vec<_>>();
+        columns.extend(self.shared_columns()?.into_keys());
+        columns.sort();
+        columns.dedup();
+
+        Ok(columns)
+    }
+
+    fn try_maintenance(&self) -> Result<(), CacheError> {
+        self.try_compact()
+    }
+
+    /// Each tree is walked holding off drops of its column only,
+    /// so a column dropped meanwhile is left out
+    fn try_report(&self) -> Result<Vec<ColumnReport>, CacheError> {
+        let mut report = Vec::new();
+        for name in self.inner.tree_names() {
+            if !is_column_tree(&name) {
+                continue;
+            }
+
+            let column = String::from_utf8_lossy(&name).into_owned();
+            let _guard = self.locks.read(&column);
+            if !self.has_tree(&column) {
+                continue;
+            }
+
+            let tree = self.inner.open_tree(&name)?;
+
+            let (mut entries, mut bytes) = (0, 0);
+            for item in &tree {
+                let (key, value) = item?;
+                entries += 1;
+                bytes += (key.len() + value.len()) as u64;
+            }
+
+            report.push(ColumnReport {
+                name: column,
+                entries,
+                size_on_disk: Some(bytes),
+                description: None,
+            });
+        }
+
+        for (name, (entries, bytes)) in self.shared_columns()? {
+            report.push(ColumnReport {
+                name,
+                entries,
+                size_on_disk: Some(bytes),
+                description: None,
+            });
+        }
+        report.sort_by(|a, b| a.name.cmp(&b.name));
+
+        Ok(report)
+    }
+
+    /// A single tree remove, so no other read or write sees the value once it is taken
+    fn try_take(
+        &self,
+        c: &dyn ColumnDefinition,
+        key: &[u8],
+    ) -> Result<Option<Vec<u8>>, CacheError> {
+        let now = now_in_seconds().map_err(|e| CacheError::Put(e.to_string()))?;
+        let expiry = self.expiry(c)?;
+        let (_guard, tree) = self.open_column(c)?;
+
+        let Some(bytes) = tree
+            .remove(key)
+            .map_err(|e| CacheError::Put(e.to_string()))?
+        else {
+            return Ok(None);
+        };
+        self.persist()?;
+
+        let name = c.name();
+        self.untrack(&name, key);
+
+        let header =
+            ItemHeader::decode(&bytes).map_err(|e| CacheError::decode(c, key, e.to_string()))?;
+        if expired(header.time, header.ttl, now, expiry) {
+            self.evicted(&name, key, EvictReason::Expired);
+
+            return Ok(None);
+        }
+        self.evicted(&name, key, EvictReason::Removed);
+
+        Ok(Some(bytes[header.data].to_vec()))
+    }
+
+    /// Commits in one transaction on the column's tree, inserts take the column TTL
+    fn try_commit(
+        &self,
+        c: &dyn ColumnDefinition,
+        conditions: &[TransactionCondition],
+        writes: &[TransactionWrite],
+    ) -> Result<bool, CacheError> {
+        let now = now_in_seconds().map_err(|e| CacheError::Put(e.to_string()))?;
+        let expiry = self.expiry(c)?;
+        self.ttl_epoch(c)?;
+
+        let (guard, tree) = self.open_column(c)?;
+
+        let condition_keys = conditions
+            .iter()
+            .map(|condition| tree.key(condition.key))
+            .collect::<Vec<_>>();
+        let items = writes
+            .iter()
+            .map(|write| {
+                let item = write
+                    .value
+                    .map(|data| {
+                        let item = Item {
+                            time: now,
+                            ttl: crate::ttl::expiry(c, c.get_ttl_in_seconds()),
+                            data,
+                        };
+
+                        item.encode(self.item_format)
+                            .map_err(|e| CacheError::encode::<Item<&[u8]>>(e.to_string()))
+                    })
+                    .transpose()?;
+
+                Ok((tree.key(write.key), item))
+            })
+            .collect::<Result<Vec<_>, CacheError>>()?;
+
+        let committed = tree
+            .tree()
+            .transaction(|tx| {
+                for (condition, key) in conditions.iter().zip(&condition_keys) {
+                    let live = match tx.get(key.as_ref())? {
+                        Some(bytes) => {
+                            let item = Item::decode(&bytes).map_err(|e| {
+                                ConflictableTransactionError::Abort(CacheError::decode(
+                                    c,
+                                    condition.key,
+                                    e.to_string(),
+                                ))
+                            })?;
+
+                            (!item.is_expired(now, expiry)).then_some(item.data)
+                        }
+                        None => None,
+                    };
+
+                    if !(condition.holds)(live.as_deref())
+                        .map_err(ConflictableTransactionError::Abort)?
+                    {
+                        return Ok(None);
+                    }
+                }
+
+                let mut replaced = Vec::with_capacity(items.len());
+                for (key, item) in &items {
+                    replaced.push(match item {
+                        Some(bytes) => tx.insert(key.as_ref(), bytes.as_slice())?,
+                        None => tx.remove(key.as_ref())?,
+                    });
+                }
+
+                Ok(Some(replaced))
+            })
+            .map_err(|e| match e {
+                TransactionError::Abort(e) => e,
+                TransactionError::Storage(e) => CacheError::Put(e.to_string()),
+            })?;
+
+        let Some(replaced) = committed else {
+            return Ok(false);
+        };
+        self.persist()?;
+
+        let name = c.name();
+        for ((write, (_, item)), old) in writes.iter().zip(&items).zip(replaced) {
+            match (item, old) {
+                (Some(bytes), old) => {
+                    if let Some(old) = old {
+                        self.replaced(c, write.key, &old)?;
+                    }
+                    self.track_write(c, write.key, bytes.len());
+                }
+                (None, old) => {
+                    self.untrack(&name, write.key);
+                    if old.is_some_and(|old| is_live(&old, now, expiry)) {
+                        self.evicted(&name, write.key, EvictReason::Removed);
+                    }
+                }
+            }
+        }
+        let written = writes
+            .iter()
+            .zip(&items)
+            .filter(|(_, (_, item))| item.is_some())
+            .map(|(write, _)| write.key)
+            .collect::<Vec<_>>();
+        self.limit_entries(c, &tree, &written)?;
+        drop(guard);
+        self.evict_least_recently_used()?;
+
+        Ok(true)
+    }
+
+    fn set_eviction_listener(
+        &mut self,
+        listener: Arc<dyn EvictionListener>,
+    ) -> Result<(), CacheError> {
+        self.evictions.set(listener);
+
+        Ok(())
+    }
+
+    fn set_slow_operation_listener(
+        &mut self,
+        threshold: Duration,
+        listener: SlowOperationListener,
+    ) -> Result<(), CacheError> {
+        self.slow.set(threshold, listener);
+
+        Ok(())
+    }
+
+    fn try_increment(
+        &self,
+        c: &dyn ColumnDefinition,
+        key: &[u8],
+        delta: i64,
+    ) -> Result<i64, CacheError> {
+        let now = now_in_seconds().map_err(|e| CacheError::Put(e.to_string()))?;
+        let ttl = crate::ttl::expiry(c, c.get_ttl_in_seconds());
+        let expiry = self.expiry(c)?;
+        let mut failure = None;
+
+        let (guard, tree) = self.open_column(c)?;
+
+        let updated = tree
+            .update_and_fetch(key, |old| {
+                failure = None;
+
+                let item = increment_item(c, key, old, delta, now, ttl, expiry).and_then(|item| {
+                    item.encode(self.item_format)
+                        .map_err(|e| CacheError::encode::<Item<Vec<u8>>>(e.to_string()))
+                });
+
+                match item {
+                    Ok(bytes) => Some(bytes),
+                    Err(e) => {
+                        // Leave the stored counter untouched on overflow
+                        failure = Some(e);
+                        old.map(<[u8]>::to_vec)
+                    }
+                }
+            })
+            .map_err(|e| CacheError::Put(e.to_string()))?;
+
+        if let Some(e) = failure {
+            return Err(e);
+        }
+
+        let Some(bytes) = updated else {
+            return Err(CacheError::Put(
+                "counter vanished during increment".to_string(),
+            ));
+        };
+
+        self.track_write(c, key, bytes.len());
+        self.limit_entries(c, &tree, &[key])?;
+        drop(guard);
+        self.evict_least_recently_used()?;
+        self.persist()?;
+
+        let item = Item::decode(&bytes).map_err(|e| CacheError::decode(c, key, e.to_string()))?;
+
+        bincode::decode_from_slice::<i64, _>(&item.data, bincode::config::standard())
+            .map_err(|e| CacheError::decode(c, key, e.to_string()))
+            .map(|v| v.0)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Expiry, expired};
+
+    const NOW: u64 = 1_000;
+
+    fn expiry(skew: u64) -> Expiry {
+        Expiry {
+            epoch: 0,
+            idle: None,
+            skew,
+        }
+    }
+
+    #[test]
+    fn test_expired_behind_now() {
+        assert!(!expired(NOW - 10, Some(10), NOW, expiry(0)));
+        assert!(expired(NOW - 11, Some(10), NOW, expiry(0)));
+        assert!(expired(NOW - 11, Some(10), NOW, expiry(5)));
+        assert!(!expired(NOW - 11, None, NOW, expiry(0)));
+    }
+
+    #[test]
+    fn test_expired_ahead_of_now() {
+        assert!(!expired(NOW, Some(10), NOW, expiry(0)));
+        assert!(expired(NOW + 1, Some(10), NOW, expiry(0)));
+        assert!(expired(NOW + 1, None, NOW, expiry(0)));
+
+        assert!(!expired(NOW + 2, Some(10), NOW, expiry(2)));
+        assert!(expired(NOW + 3, Some(10), NOW, expiry(2)));
+        // within the tolerance the item expires by its own date
+        assert!(!expired(NOW + 2, Some(10), NOW + 10, expiry(2)));
+        assert!(expired(NOW + 2, Some(10), NOW + 13, expiry(2)));
     }
 }
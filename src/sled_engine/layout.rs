@@ -0,0 +1,155 @@
+use std::borrow::Cow;
+
+use sled::{CompareAndSwapError, IVec};
+
+/// Name of the tree holding every [`crate::SledLayout::SharedPrefixed`] column
+pub(super) const SHARED_TREE: &[u8] = b"__omega_cache__shared";
+
+/// Prefix of a column's keys in the shared tree: the length of its name
+/// as a big endian `u32` then the name, so no column's prefix starts another's
+fn shared_prefix(name: &str) -> Vec<u8> {
+    let len = u32::try_from(name.len()).unwrap_or(u32::MAX);
+
+    [len.to_be_bytes().as_slice(), name.as_bytes()].concat()
+}
+
+/// Column name and key of a key in the shared tree
+pub(super) fn split_shared_key(key: &[u8]) -> Option<(String, &[u8])> {
+    let (len, rest) = key.split_first_chunk::<4>()?;
+    let len = usize::try_from(u32::from_be_bytes(*len)).ok()?;
+
+    let (name, key) = rest.split_at_checked(len)?;
+
+    Some((String::from_utf8_lossy(name).into_owned(), key))
+}
+
+/// The items of one column: a tree of its own, or its range of the shared tree.
+///
+/// Takes and returns keys as the column sees them, adding and removing the prefix
+/// of a shared column.
+#[derive(Clone)]
+pub(super) struct ColumnTree {
+    tree: sled::Tree,
+    /// Empty for a dedicated tree
+    prefix: Vec<u8>,
+}
+
+impl ColumnTree {
+    /// The column's own tree
+    pub(super) fn dedicated(tree: sled::Tree) -> ColumnTree {
+        ColumnTree {
+            tree,
+            prefix: Vec::new(),
+        }
+    }
+
+    /// The range of column `name` in the shared tree
+    pub(super) fn shared(tree: sled::Tree, name: &str) -> ColumnTree {
+        ColumnTree {
+            tree,
+            prefix: shared_prefix(name),
+        }
+    }
+
+    /// Key of the item in the underlying tree
+    pub(super) fn key<'a>(&self, key: &'a [u8]) -> Cow<'a, [u8]> {
+        if self.prefix.is_empty() {
+            Cow::Borrowed(key)
+        } else {
+            Cow::Owned([self.prefix.as_slice(), key].concat())
+        }
+    }
+
+    /// The underlying tree, whose keys include the prefix, see [`ColumnTree::key`]
+    pub(super) fn tree(&self) -> &sled::Tree {
+        &self.tree
+    }
+
+    pub(super) fn get(&self, key: &[u8]) -> sled::Result<Option<IVec>> {
+        self.tree.get(self.key(key))
+    }
+
+    pub(super) fn insert(&self, key: &[u8], value: impl Into<IVec>) -> sled::Result<Option<IVec>> {
+        self.tree.insert(self.key(key), value)
+    }
+
+    pub(super) fn remove(&self, key: &[u8]) -> sled::Result<Option<IVec>> {
+        self.tree.remove(self.key(key))
+    }
+
+    pub(super) fn compare_and_swap(
+        &self,
+        key: &[u8],
+        old: Option<&IVec>,
+        new: Option<Vec<u8>>,
+    ) -> sled::Result<Result<(), CompareAndSwapError>> {
+        self.tree.compare_and_swap(self.key(key), old, new)
+    }
+
+    pub(super) fn update_and_fetch(
+        &self,
+        key: &[u8],
+        f: impl FnMut(Option<&[u8]>) -> Option<Vec<u8>>,
+    ) -> sled::Result<Option<IVec>> {
+        self.tree.update_and_fetch(self.key(key), f)
+    }
+
+    /// Insert every item in one atomic batch
+    pub(super) fn apply_batch(&self, items: Vec<(&[u8], Vec<u8>)>) -> sled::Result<()> {
+        let mut batch = sled::Batch::default();
+        for (key, value) in items {
+            batch.insert(self.key(key).as_ref(), value);
+        }
+
+        self.tree.apply_batch(batch)
+    }
+
+    /// Every item of the column, in key order
+    pub(super) fn iter(&self) -> impl Iterator<Item = sled::Result<(IVec, IVec)>> + '_ {
+        self.tree.scan_prefix(&self.prefix).map(|entry| {
+            entry.map(|(key, value)| match self.prefix.len() {
+                0 => (key, value),
+                len => (IVec::from(&key[len..]), value),
+            })
+        })
+    }
+
+    /// Number of items, walking the range of a shared column
+    pub(super) fn len(&self) -> usize {
+        if self.prefix.is_empty() {
+            self.tree.len()
+        } else {
+            self.tree.scan_prefix(&self.prefix).count()
+        }
+    }
+
+    /// Remove every item of a shared column, key by key
+    pub(super) fn clear(&self) -> sled::Result<()> {
+        let mut batch = sled::Batch::default();
+        for key in self.tree.scan_prefix(&self.prefix).keys() {
+            batch.remove(key?);
+        }
+
+        self.tree.apply_batch(batch)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{shared_prefix, split_shared_key};
+
+    #[test]
+    fn test_shared_keys_split() {
+        let key = [shared_prefix("users").as_slice(), b"ada"].concat();
+        assert_eq!(
+            split_shared_key(&key),
+            Some(("users".to_string(), b"ada".as_slice()))
+        );
+
+        // a name is never read as the start of a longer one
+        assert!(!shared_prefix("user").starts_with(&shared_prefix("users")));
+        assert!(!key.starts_with(&shared_prefix("user")));
+
+        assert_eq!(split_shared_key(&[0, 0, 0, 9, b'a']), None);
+    }
+}
@@ -0,0 +1,141 @@
+use std::cell::Cell;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::sync::{Mutex, PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+/// Number of locks the columns are spread over by name
+const STRIPES: usize = 64;
+
+thread_local! {
+    /// Column locks held by this thread, listeners are only called once none are
+    static HELD: Cell<usize> = const { Cell::new(0) };
+}
+
+/// Call to a listener, made once the locks held by the operation are released
+pub(super) type Notification = Box<dyn FnOnce() + Send>;
+
+/// Locks held for reading by accesses to a column and for writing by its drop,
+/// so a drop never interleaves with an access to the dropped tree.
+///
+/// Columns are spread over a fixed set of locks by name, so dropping a column only
+/// holds off the columns sharing its lock. Listeners notified while a lock is held
+/// are called once the thread releases its last lock, so a listener may use the
+/// engine, even to drop the column it was notified about.
+pub(super) struct ColumnLocks {
+    stripes: [RwLock<()>; STRIPES],
+    pending: Mutex<Vec<Notification>>,
+}
+
+impl std::fmt::Debug for ColumnLocks {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ColumnLocks")
+            .field("stripes", &STRIPES)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Default for ColumnLocks {
+    fn default() -> ColumnLocks {
+        ColumnLocks {
+            stripes: std::array::from_fn(|_| RwLock::new(())),
+            pending: Mutex::default(),
+        }
+    }
+}
+
+impl ColumnLocks {
+    fn stripe(&self, column: &str) -> &RwLock<()> {
+        let mut hasher = DefaultHasher::new();
+        column.hash(&mut hasher);
+
+        #[allow(clippy::cast_possible_truncation)]
+        &self.stripes[hasher.finish() as usize % STRIPES]
+    }
+
+    /// Hold off drops of the column until the guard is released
+    pub(super) fn read(&self, column: &str) -> ColumnGuard<'_> {
+        let lock = self
+            .stripe(column)
+            .read()
+            .unwrap_or_else(PoisonError::into_inner);
+
+        ColumnGuard::new(self, Some(lock), None)
+    }
+
+    /// Wait for accesses to the column to finish and hold off new ones until the guard is released
+    pub(super) fn write(&self, column: &str) -> ColumnGuard<'_> {
+        let lock = self
+            .stripe(column)
+            .write()
+            .unwrap_or_else(PoisonError::into_inner);
+
+        ColumnGuard::new(self, None, Some(lock))
+    }
+
+    /// Call a listener now, or once this thread releases its last lock
+    pub(super) fn notify(&self, notification: Notification) {
+        if HELD.get() == 0 {
+            notification();
+        } else {
+            self.pending
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .push(notification);
+        }
+    }
+
+    /// Defer the notifications of `f` as if this thread held a lock,
+    /// for work done on other threads on behalf of one that does
+    pub(super) fn deferring<R>(f: impl FnOnce() -> R) -> R {
+        HELD.set(HELD.get() + 1);
+        let result = f();
+        HELD.set(HELD.get() - 1);
+
+        result
+    }
+
+    /// Call every pending listener in order, without holding the queue as they may notify more
+    fn deliver(&self) {
+        loop {
+            let pending =
+                std::mem::take(&mut *self.pending.lock().unwrap_or_else(PoisonError::into_inner));
+            if pending.is_empty() {
+                return;
+            }
+
+            for notification in pending {
+                notification();
+            }
+        }
+    }
+}
+
+/// A held column lock, calling the listeners notified while it was held once released
+pub(super) struct ColumnGuard<'a> {
+    locks: &'a ColumnLocks,
+    read: Option<RwLockReadGuard<'a, ()>>,
+    write: Option<RwLockWriteGuard<'a, ()>>,
+}
+
+impl<'a> ColumnGuard<'a> {
+    fn new(
+        locks: &'a ColumnLocks,
+        read: Option<RwLockReadGuard<'a, ()>>,
+        write: Option<RwLockWriteGuard<'a, ()>>,
+    ) -> ColumnGuard<'a> {
+        HELD.set(HELD.get() + 1);
+
+        ColumnGuard { locks, read, write }
+    }
+}
+
+impl Drop for ColumnGuard<'_> {
+    fn drop(&mut self) {
+        self.read.take();
+        self.write.take();
+        HELD.set(HELD.get() - 1);
+
+        if HELD.get() == 0 {
+            self.locks.deliver();
+        }
+    }
+}
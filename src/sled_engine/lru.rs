@@ -0,0 +1,155 @@
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+/// Column name and key of a tracked item
+type ItemId = (String, Vec<u8>);
+
+/// Access order and size of every item, see [`super::SledEngineBuilder::max_total_bytes`]
+#[derive(Debug, Default)]
+struct State {
+    /// Sequence number given to the next access
+    next: u64,
+    /// Sum of the stored bytes of every tracked item
+    total: u64,
+    /// Tracked items by last access, oldest first
+    order: BTreeMap<u64, ItemId>,
+    /// Last access and stored bytes of each tracked item
+    items: HashMap<ItemId, (u64, u64)>,
+}
+
+impl State {
+    /// Stop tracking an item
+    fn forget(&mut self, id: &ItemId) {
+        if let Some((access, bytes)) = self.items.remove(id) {
+            self.order.remove(&access);
+            self.total -= bytes;
+        }
+    }
+
+    /// Mark an item as the most recently used, with its stored bytes
+    fn touch(&mut self, id: ItemId, bytes: u64) {
+        self.forget(&id);
+
+        let access = self.next;
+        self.next += 1;
+        self.total += bytes;
+        self.order.insert(access, id.clone());
+        self.items.insert(id, (access, bytes));
+    }
+}
+
+/// Least recently used tracking of the items held by every column,
+/// bounding the total bytes they store
+#[derive(Debug)]
+pub(super) struct Lru {
+    max_bytes: u64,
+    state: Mutex<State>,
+}
+
+impl Lru {
+    pub(super) fn new(max_bytes: u64) -> Lru {
+        Lru {
+            max_bytes,
+            state: Mutex::default(),
+        }
+    }
+
+    /// Bound on the bytes stored across every column
+    pub(super) fn max_bytes(&self) -> u64 {
+        self.max_bytes
+    }
+
+    fn state(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Record a write of an item taking `bytes` of storage
+    pub(super) fn write(&self, column: &str, key: &[u8], bytes: usize) {
+        self.state()
+            .touch((column.to_string(), key.to_vec()), bytes as u64);
+    }
+
+    /// Record a read of an item, a miss means the item is gone
+    pub(super) fn read(&self, column: &str, key: &[u8], hit: bool) {
+        let id = (column.to_string(), key.to_vec());
+        let mut state = self.state();
+
+        match state.items.get(&id) {
+            Some((_, bytes)) if hit => {
+                let bytes = *bytes;
+                state.touch(id, bytes);
+            }
+            Some(_) => state.forget(&id),
+            None => {}
+        }
+    }
+
+    /// Stop tracking an item removed from storage
+    pub(super) fn remove(&self, column: &str, key: &[u8]) {
+        self.state().forget(&(column.to_string(), key.to_vec()));
+    }
+
+    /// Stop tracking every item of a dropped column
+    pub(super) fn drop_column(&self, column: &str) {
+        let mut state = self.state();
+        let dropped = state
+            .items
+            .keys()
+            .filter(|(name, _)| name == column)
+            .cloned()
+            .collect::<Vec<_>>();
+
+        for id in &dropped {
+            state.forget(id);
+        }
+    }
+
+    /// Least recently used items to remove to bring the total under the limit,
+    /// which are no longer tracked
+    pub(super) fn evictions(&self) -> Vec<ItemId> {
+        let mut state = self.state();
+        let mut evicted = Vec::new();
+
+        while state.total > self.max_bytes {
+            let Some((_, id)) = state.order.first_key_value() else {
+                break;
+            };
+
+            let id = id.clone();
+            state.forget(&id);
+            evicted.push(id);
+        }
+
+        evicted
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Lru;
+
+    #[test]
+    fn test_evicts_least_recently_used() {
+        let lru = Lru::new(30);
+
+        lru.write("a", b"1", 10);
+        lru.write("a", b"2", 10);
+        lru.write("b", b"3", 10);
+        assert!(lru.evictions().is_empty());
+
+        // reading the oldest item makes the next oldest the first to go
+        lru.read("a", b"1", true);
+        lru.write("b", b"4", 10);
+        assert_eq!(lru.evictions(), vec![("a".to_string(), b"2".to_vec())]);
+
+        // rewriting an item replaces its size rather than adding to it
+        lru.write("b", b"4", 5);
+        assert!(lru.evictions().is_empty());
+
+        lru.drop_column("a");
+        lru.write("c", b"5", 15);
+        assert!(lru.evictions().is_empty());
+        lru.write("c", b"6", 10);
+        assert_eq!(lru.evictions(), vec![("b".to_string(), b"3".to_vec())]);
+    }
+}
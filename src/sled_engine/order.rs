@@ -0,0 +1,116 @@
+use std::collections::hash_map::Entry;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+/// Write order of the items of one column, see [`crate::ColumnDefinition::max_entries`]
+#[derive(Debug, Default)]
+struct Column {
+    /// Sequence number given to the next write
+    next: u64,
+    /// Tracked keys by last write, oldest first
+    order: BTreeMap<u64, Vec<u8>>,
+    /// Last write of each tracked key
+    keys: HashMap<Vec<u8>, u64>,
+}
+
+impl Column {
+    /// Stop tracking a key
+    fn forget(&mut self, key: &[u8]) {
+        if let Some(write) = self.keys.remove(key) {
+            self.order.remove(&write);
+        }
+    }
+
+    /// Mark a key as the most recently written
+    fn touch(&mut self, key: &[u8]) {
+        self.forget(key);
+
+        let write = self.next;
+        self.next += 1;
+        self.order.insert(write, key.to_vec());
+        self.keys.insert(key.to_vec(), write);
+    }
+}
+
+/// Write order of the items of every column bounded by
+/// [`crate::ColumnDefinition::max_entries`], so evicting the oldest
+/// neither walks nor sorts the column.
+///
+/// A column is loaded from its tree on its first bounded write,
+/// until then its writes and removals are not tracked.
+#[derive(Debug, Default)]
+pub(super) struct InsertionOrder {
+    columns: Mutex<HashMap<String, Column>>,
+}
+
+impl InsertionOrder {
+    fn columns(&self) -> MutexGuard<'_, HashMap<String, Column>> {
+        self.columns.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Record writes of keys to a loaded column, in order
+    pub(super) fn write(&self, column: &str, keys: &[&[u8]]) {
+        if let Some(tracked) = self.columns().get_mut(column) {
+            for key in keys {
+                tracked.touch(key);
+            }
+        }
+    }
+
+    /// Stop tracking a key removed from storage
+    pub(super) fn remove(&self, column: &str, key: &[u8]) {
+        if let Some(tracked) = self.columns().get_mut(column) {
+            tracked.forget(key);
+        }
+    }
+
+    /// Stop tracking a dropped column, reloading it on its next bounded write
+    pub(super) fn drop_column(&self, column: &str) {
+        self.columns().remove(column);
+    }
+
+    /// Record writes of keys to a column as by [`InsertionOrder::write`] and return
+    /// the oldest keys to remove to bring it down to `max_entries`, which are no longer
+    /// tracked.
+    ///
+    /// An unloaded column is first loaded with the keys given by `load`, oldest first.
+    /// The keys written are the newest, so they are only evicted by writing more
+    /// than `max_entries` of them at once.
+    pub(super) fn evictions<E>(
+        &self,
+        column: &str,
+        keys: &[&[u8]],
+        max_entries: usize,
+        load: impl FnOnce() -> Result<Vec<Vec<u8>>, E>,
+    ) -> Result<Vec<Vec<u8>>, E> {
+        let mut columns = self.columns();
+
+        let tracked = match columns.entry(column.to_string()) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => {
+                let mut loaded = Column::default();
+                for key in load()? {
+                    loaded.touch(&key);
+                }
+
+                entry.insert(loaded)
+            }
+        };
+
+        for key in keys {
+            tracked.touch(key);
+        }
+
+        let mut evicted = Vec::new();
+        while tracked.keys.len() > max_entries {
+            let Some((_, key)) = tracked.order.pop_first() else {
+                break;
+            };
+
+            tracked.keys.remove(&key);
+            evicted.push(key);
+        }
+
+        Ok(evicted)
+    }
+}
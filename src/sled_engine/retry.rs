@@ -0,0 +1,105 @@
+use std::time::Duration;
+
+/// Retries of tree operations failing with transient errors, see [`super::SledEngineBuilder::retries`]
+#[derive(Debug, Clone, Copy, Default)]
+pub(super) struct Retry {
+    /// Further attempts after the first fails
+    pub(super) retries: u32,
+    /// Wait before the first retry, doubled before each one after
+    pub(super) backoff: Duration,
+}
+
+/// Whether the error may go away on its own, such as an interrupted or timed out
+/// read of a busy disk. Corruption and other errors of the database itself never do
+fn is_transient(e: &sled::Error) -> bool {
+    matches!(e, sled::Error::Io(_))
+}
+
+impl Retry {
+    /// Run `op` until it succeeds, fails with an error that is not transient,
+    /// or has failed every retry, returning its last result
+    pub(super) fn run<T>(&self, mut op: impl FnMut() -> sled::Result<T>) -> sled::Result<T> {
+        let mut backoff = self.backoff;
+
+        for _ in 0..self.retries {
+            match op() {
+                Err(e) if is_transient(&e) => {
+                    std::thread::sleep(backoff);
+                    backoff = backoff.saturating_mul(2);
+                }
+                result => return result,
+            }
+        }
+
+        op()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{cell::Cell, io, time::Duration};
+
+    use super::Retry;
+
+    const RETRY: Retry = Retry {
+        retries: 2,
+        backoff: Duration::from_millis(1),
+    };
+
+    /// A tree operation failing with `error` for the first `failures` attempts
+    fn failing(
+        failures: u32,
+        error: impl Fn() -> sled::Error,
+    ) -> (Cell<u32>, impl Fn(&Cell<u32>) -> sled::Result<&'static str>) {
+        let op = move |attempts: &Cell<u32>| {
+            attempts.set(attempts.get() + 1);
+
+            if attempts.get() <= failures {
+                return Err(error());
+            }
+
+            Ok("value")
+        };
+
+        (Cell::new(0), op)
+    }
+
+    fn io_error() -> sled::Error {
+        sled::Error::Io(io::Error::new(io::ErrorKind::Interrupted, "disk busy"))
+    }
+
+    #[test]
+    fn test_retries_transient_errors() {
+        let (attempts, op) = failing(1, io_error);
+
+        assert_eq!(RETRY.run(|| op(&attempts)).unwrap(), "value");
+        assert_eq!(attempts.get(), 2);
+    }
+
+    #[test]
+    fn test_gives_up_after_retries() {
+        let (attempts, op) = failing(3, io_error);
+
+        assert!(matches!(
+            RETRY.run(|| op(&attempts)),
+            Err(sled::Error::Io(_))
+        ));
+        assert_eq!(attempts.get(), 3);
+
+        let (attempts, op) = failing(1, io_error);
+
+        assert!(Retry::default().run(|| op(&attempts)).is_err());
+        assert_eq!(attempts.get(), 1);
+    }
+
+    #[test]
+    fn test_does_not_retry_corruption() {
+        let (attempts, op) = failing(1, || sled::Error::Corruption { at: None, bt: () });
+
+        assert!(matches!(
+            RETRY.run(|| op(&attempts)),
+            Err(sled::Error::Corruption { .. })
+        ));
+        assert_eq!(attempts.get(), 1);
+    }
+}
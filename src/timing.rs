@@ -0,0 +1,255 @@
+//!
+//! Timing of storage operations, printed to stderr in debug builds
+//! and reported to the slow operation listener of the storage.
+//!
+//! Printing is compiled out without the `timing` feature, and switchable at runtime
+//! with [`set_timing_enabled`] so the clock is not read when nobody is looking.
+//!
+
+use std::{
+    fmt::Display,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::Duration,
+};
+
+static ENABLED: AtomicBool = AtomicBool::new(true);
+
+/// Turn printing of operation timings on or off for the whole process.
+///
+/// Timings are only ever printed in debug builds with the `timing` feature,
+/// this has no effect otherwise. Slow operation listeners are called either way,
+/// see [`crate::Engine::on_slow_operation`].
+pub fn set_timing_enabled(enabled: bool) {
+    ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+/// Whether timings are compiled in and switched on
+#[cfg(any(feature = "sled", feature = "redis"))]
+fn enabled() -> bool {
+    cfg!(all(feature = "timing", debug_assertions)) && ENABLED.load(Ordering::Relaxed)
+}
+
+///
+/// Kind of a timed storage operation, see [`SlowOperation`]
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum OperationKind {
+    /// Insert of a single value
+    Insert,
+    /// Insert of a batch of values
+    BatchInsert,
+    /// Read of a single value
+    Get,
+    /// Read of a batch of values
+    BatchGet,
+    /// Read of every value of a column
+    Entries,
+    /// Refresh of the TTLs of a batch of keys
+    TouchMany,
+    /// Conditional commit of a transaction
+    Commit,
+}
+
+impl Display for OperationKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            OperationKind::Insert => "insert",
+            OperationKind::BatchInsert => "batch insert",
+            OperationKind::Get => "get",
+            OperationKind::BatchGet => "batch get",
+            OperationKind::Entries => "entries",
+            OperationKind::TouchMany => "touch many",
+            OperationKind::Commit => "commit",
+        })
+    }
+}
+
+///
+/// A storage operation that took at least the threshold set with
+/// [`crate::Engine::on_slow_operation`]
+///
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct SlowOperation<'a> {
+    /// Name of the column operated on, as the storage sees it
+    pub column: &'a str,
+    /// [`crate::ColumnDefinition::description`] of the column
+    pub description: Option<&'a str>,
+    /// What the operation was
+    pub kind: OperationKind,
+    /// Time the operation took
+    pub elapsed: Duration,
+}
+
+/// Called with each slow operation, see [`crate::Engine::on_slow_operation`]
+pub type SlowOperationListener = Arc<dyn Fn(&SlowOperation) + Send + Sync>;
+
+/// The slow operation listener a storage reports to and its threshold, if any
+#[cfg(any(feature = "sled", feature = "redis"))]
+#[derive(Clone, Default)]
+pub(crate) struct SlowOperations {
+    listener: Option<(Duration, SlowOperationListener)>,
+}
+
+#[cfg(any(feature = "sled", feature = "redis"))]
+impl std::fmt::Debug for SlowOperations {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SlowOperations")
+            .field(
+                "threshold",
+                &self.listener.as_ref().map(|(threshold, _)| threshold),
+            )
+            .finish()
+    }
+}
+
+#[cfg(any(feature = "sled", feature = "redis"))]
+impl SlowOperations {
+    pub(crate) fn set(&mut self, threshold: Duration, listener: SlowOperationListener) {
+        self.listener = Some((threshold, listener));
+    }
+}
+
+/// Times a single operation, reading the clock only when timings are printed
+/// or a slow operation listener is set
+#[cfg(any(feature = "sled", feature = "redis"))]
+pub(crate) struct Timer<'a> {
+    start: Option<std::time::Instant>,
+    print: bool,
+    slow: &'a SlowOperations,
+}
+
+#[cfg(any(feature = "sled", feature = "redis"))]
+impl Timer<'_> {
+    pub(crate) fn start(slow: &SlowOperations) -> Timer<'_> {
+        let print = enabled();
+
+        Timer {
+            start: (print || slow.listener.is_some()).then(std::time::Instant::now),
+            print,
+            slow,
+        }
+    }
+
+    /// Print the time taken since [`Timer::start`] by an operation on column `c`
+    /// and report it to the listener if it took at least the threshold
+    pub(crate) fn finish(self, kind: OperationKind, c: &dyn crate::ColumnDefinition) {
+        if let Some(report) = self.measure(kind, c) {
+            report();
+        }
+    }
+
+    /// As [`Timer::finish`], returning the call to the listener rather than making it,
+    /// for storages to make once the locks held by the operation are released
+    #[cfg(feature = "sled")]
+    pub(crate) fn finish_later(
+        self,
+        kind: OperationKind,
+        c: &dyn crate::ColumnDefinition,
+    ) -> Option<Box<dyn FnOnce() + Send>> {
+        self.measure(kind, c)
+    }
+
+    /// Print the time taken and return the call to the listener if it took at least the threshold
+    fn measure(
+        self,
+        kind: OperationKind,
+        c: &dyn crate::ColumnDefinition,
+    ) -> Option<Box<dyn FnOnce() + Send>> {
+        let start = self.start?;
+
+        let elapsed = start.elapsed();
+        let column = c.name();
+        let description = c.description();
+
+        if self.print {
+            let described = description.map_or_else(String::new, |d| format!(" ({d})"));
+            eprintln!(
+                "\x1b[0;34mTime taken for {kind} on {column}{described}:\x1b[0m {}us",
+                elapsed.as_micros()
+            );
+        }
+
+        let (threshold, listener) = self.slow.listener.as_ref()?;
+        if elapsed < *threshold {
+            return None;
+        }
+
+        let listener = listener.clone();
+        let description = description.map(str::to_string);
+        Some(Box::new(move || {
+            listener(&SlowOperation {
+                column: &column,
+                description: description.as_deref(),
+                kind,
+                elapsed,
+            });
+        }))
+    }
+}
+
+#[cfg(all(test, any(feature = "sled", feature = "redis")))]
+mod test {
+    use std::{
+        sync::{Arc, Mutex},
+        time::Duration,
+    };
+
+    use super::{OperationKind, SlowOperations, Timer};
+    use crate::ColumnDefinition;
+
+    struct Column(&'static str, Option<&'static str>);
+
+    impl ColumnDefinition for Column {
+        fn name(&self) -> String {
+            self.0.to_string()
+        }
+
+        fn get_ttl_in_seconds(&self) -> i32 {
+            60
+        }
+
+        fn description(&self) -> Option<&str> {
+            self.1
+        }
+    }
+
+    #[test]
+    fn test_slow_operations() {
+        let reported = Arc::new(Mutex::new(Vec::new()));
+        let mut slow = SlowOperations::default();
+
+        Timer::start(&slow).finish(OperationKind::Get, &Column("unobserved", None));
+
+        let listener = reported.clone();
+        slow.set(
+            Duration::from_millis(5),
+            Arc::new(move |op| {
+                listener.lock().unwrap().push((
+                    op.column.to_string(),
+                    op.description.map(str::to_string),
+                    op.kind,
+                ));
+            }),
+        );
+
+        Timer::start(&slow).finish(OperationKind::Get, &Column("fast", None));
+
+        let timer = Timer::start(&slow);
+        std::thread::sleep(Duration::from_millis(5));
+        timer.finish(OperationKind::Insert, &Column("slow", Some("slow writes")));
+
+        assert_eq!(
+            *reported.lock().unwrap(),
+            [(
+                "slow".to_string(),
+                Some("slow writes".to_string()),
+                OperationKind::Insert
+            )]
+        );
+    }
+}
@@ -0,0 +1,237 @@
+use bincode::{Decode, Encode};
+
+use crate::{CacheError, ColumnDefinition, Engine, check_writable};
+
+/// Check of the live value at a key, [`None`] if there is none,
+/// see [`TransactionCondition`]
+pub type ConditionCheck<'a> = dyn Fn(Option<&[u8]>) -> Result<bool, CacheError> + 'a;
+
+/// Condition a transaction commits on, checked by storage against the live value
+/// at key as stored, see [`crate::CacheStorage::try_commit`]
+pub struct TransactionCondition<'a> {
+    /// Key of the value checked
+    pub key: &'a [u8],
+    /// Whether the value at key lets the transaction commit
+    pub holds: &'a ConditionCheck<'a>,
+}
+
+impl std::fmt::Debug for TransactionCondition<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TransactionCondition")
+            .field("key", &self.key)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Write applied by a committed transaction, see [`crate::CacheStorage::try_commit`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TransactionWrite<'a> {
+    /// Key written
+    pub key: &'a [u8],
+    /// Value inserted with the column TTL, [`None`] to remove the key
+    pub value: Option<&'a [u8]>,
+}
+
+/// A step queued on a [`Transaction`]
+enum Step<'a> {
+    Condition {
+        key: Vec<u8>,
+        holds: Box<ConditionCheck<'a>>,
+    },
+    Write {
+        key: Vec<u8>,
+        value: Option<Vec<u8>>,
+    },
+}
+
+///
+/// Conditional writes to one column committed together, from [`Engine::transaction`]
+///
+/// Conditions are checked against the values as they were before the transaction,
+/// and only if every one of them holds are the writes applied, in the order queued,
+/// with nothing else reading or writing the keys in between. Committing returns
+/// whether the conditions held, so whether anything was written.
+///
+/// ```
+/// use omega_cache::{ColumnDefinition, Engine, noop_engine::NoopEngine};
+///
+/// struct Jobs;
+///
+/// impl ColumnDefinition for Jobs {
+///     fn name(&self) -> String {
+///         "jobs".to_string()
+///     }
+///
+///     fn get_ttl_in_seconds(&self) -> i32 {
+///         60
+///     }
+/// }
+///
+/// let engine = Engine::new(NoopEngine::passthrough());
+/// engine.try_insert(&Jobs, &"state", &"queued").unwrap();
+/// engine.try_insert(&Jobs, &"lease", &1u64).unwrap();
+///
+/// // claim the job only if it is still queued
+/// let claimed = engine
+///     .transaction(&Jobs)
+///     .check_eq(&"state", "queued".to_string())
+///     .insert(&"state", &"running")
+///     .remove(&"lease")
+///     .try_commit()
+///     .unwrap();
+///
+/// assert!(claimed);
+/// assert_eq!(engine.try_get::<_, String>(&Jobs, &"state").unwrap().as_deref(), Some("running"));
+/// assert_eq!(engine.try_get::<_, u64>(&Jobs, &"lease").unwrap(), None);
+/// ```
+///
+pub struct Transaction<'a> {
+    engine: &'a Engine,
+    column: &'a dyn ColumnDefinition,
+    steps: Vec<Step<'a>>,
+    /// First error queuing a step, returned on commit
+    error: Option<CacheError>,
+}
+
+impl std::fmt::Debug for Transaction<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Transaction")
+            .field("column", &self.column.name())
+            .field("steps", &self.steps.len())
+            .finish_non_exhaustive()
+    }
+}
+
+impl<'a> Transaction<'a> {
+    pub(crate) fn new(engine: &'a Engine, column: &'a dyn ColumnDefinition) -> Transaction<'a> {
+        // values split into chunks are written in several steps
+        let error = engine.chunk_size.map(|_| {
+            CacheError::Engine("transactions are not supported with chunking".to_string())
+        });
+
+        Transaction {
+            engine,
+            column,
+            steps: Vec::new(),
+            error,
+        }
+    }
+
+    /// Commit only if the value at key decodes as `V` and equals `expected`
+    #[must_use]
+    pub fn check_eq<K, V>(mut self, key: &K, expected: V) -> Transaction<'a>
+    where
+        K: AsRef<[u8]> + ?Sized,
+        V: Decode<()> + PartialEq + 'a,
+    {
+        let (engine, column) = (self.engine, self.column);
+        let key = key.as_ref().to_vec();
+        let decode_key = key.clone();
+
+        self.steps.push(Step::Condition {
+            key,
+            holds: Box::new(move |stored| {
+                let Some(stored) = stored else {
+                    return Ok(false);
+                };
+
+                match engine.load_value(column, &decode_key, stored)? {
+                    Some(value) => Ok(engine
+                        .decode_value::<V>(column, &decode_key, &value, false)?
+                        .0
+                        == expected),
+                    None => Ok(false),
+                }
+            }),
+        });
+
+        self
+    }
+
+    /// Commit only if there is no live value at key
+    #[must_use]
+    pub fn check_missing<K: AsRef<[u8]> + ?Sized>(mut self, key: &K) -> Transaction<'a> {
+        self.steps.push(Step::Condition {
+            key: key.as_ref().to_vec(),
+            holds: Box::new(|stored| Ok(stored.is_none())),
+        });
+
+        self
+    }
+
+    /// Insert a value with the column TTL on commit
+    #[must_use]
+    pub fn insert<K: AsRef<[u8]> + ?Sized, V: Encode>(
+        mut self,
+        key: &K,
+        value: &V,
+    ) -> Transaction<'a> {
+        let key = key.as_ref().to_vec();
+
+        match self.engine.store_value(self.column, &key, value, None) {
+            Ok(value) => self.steps.push(Step::Write {
+                key,
+                value: Some(value),
+            }),
+            Err(e) => {
+                self.error.get_or_insert(e);
+            }
+        }
+
+        self
+    }
+
+    /// Remove the value at key on commit
+    #[must_use]
+    pub fn remove<K: AsRef<[u8]> + ?Sized>(mut self, key: &K) -> Transaction<'a> {
+        self.steps.push(Step::Write {
+            key: key.as_ref().to_vec(),
+            value: None,
+        });
+
+        self
+    }
+
+    /// Check the conditions and, if they all hold, apply the writes,
+    /// returning whether they did
+    ///
+    /// # Errors
+    /// Returns [`CacheError::Put`] if the commit fails or the column is read only
+    /// Returns [`CacheError::Encode`] if a value inserted could not be encoded
+    /// Returns [`CacheError::Decode`] if a value checked could not be decoded
+    /// Returns [`CacheError::Engine`] if the storage has no transactions,
+    /// or the engine splits values with [`Engine::with_chunking`]
+    pub fn try_commit(self) -> Result<bool, CacheError> {
+        if let Some(e) = self.error {
+            return Err(e);
+        }
+
+        check_writable(self.column)?;
+        self.engine.check_column(self.column)?;
+
+        let mut conditions = Vec::new();
+        let mut writes = Vec::new();
+        for step in &self.steps {
+            match step {
+                Step::Condition { key, holds } => {
+                    self.engine.check_key(key, CacheError::Get)?;
+                    conditions.push(TransactionCondition {
+                        key,
+                        holds: &**holds,
+                    });
+                }
+                Step::Write { key, value } => {
+                    self.engine.check_key(key, CacheError::Put)?;
+                    writes.push(TransactionWrite {
+                        key,
+                        value: value.as_deref(),
+                    });
+                }
+            }
+        }
+
+        self.engine
+            .storage()
+            .try_commit(self.column, &conditions, &writes)
+    }
+}
@@ -0,0 +1,92 @@
+use std::hash::{BuildHasher, Hasher};
+
+use crate::ColumnDefinition;
+
+/// Random value in the range `[0, 1)`.
+///
+/// Each [`std::collections::hash_map::RandomState`] is seeded differently,
+/// which is plenty for spreading expiry times without pulling in an RNG.
+fn random_unit() -> f64 {
+    let bits = std::collections::hash_map::RandomState::new()
+        .build_hasher()
+        .finish();
+
+    f64::from(u32::try_from(bits >> 32).unwrap_or(0)) / (f64::from(u32::MAX) + 1.0)
+}
+
+/// TTL in seconds for a fresh insert into the column with `ttl`.
+///
+/// Applies the column's [`ColumnDefinition::ttl_jitter`] so keys inserted
+/// together do not all expire together. Jitter is randomised per call,
+/// so per key, and never takes the TTL below one second.
+pub(crate) fn jittered(c: &dyn ColumnDefinition, ttl: i32) -> i32 {
+    match c.ttl_jitter() {
+        Some(jitter) if jitter > 0.0 && ttl > 0 => {
+            let spread = f64::from(ttl) * f64::from(jitter.min(1.0));
+            let offset = spread * (2.0 * random_unit() - 1.0);
+
+            // Bounded by twice the column TTL, saturates rather than wraps if that overflows
+            #[allow(clippy::cast_possible_truncation)]
+            let jittered = (f64::from(ttl) + offset).round() as i32;
+
+            jittered.max(1)
+        }
+        _ => ttl,
+    }
+}
+
+/// Seconds until an item inserted into the column with `ttl` expires,
+/// after jitter, or [`None`] if the TTL is zero or less and it never expires
+pub(crate) fn expiry(c: &dyn ColumnDefinition, ttl: i32) -> Option<u64> {
+    u64::try_from(jittered(c, ttl)).ok().filter(|ttl| *ttl > 0)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::ColumnDefinition;
+
+    use super::{expiry, jittered};
+
+    struct JitteredColumn {
+        jitter: Option<f32>,
+    }
+
+    impl ColumnDefinition for JitteredColumn {
+        fn name(&self) -> String {
+            "jittered_column".to_string()
+        }
+
+        fn get_ttl_in_seconds(&self) -> i32 {
+            100
+        }
+
+        fn ttl_jitter(&self) -> Option<f32> {
+            self.jitter
+        }
+    }
+
+    #[test]
+    fn test_no_jitter() {
+        assert_eq!(jittered(&JitteredColumn { jitter: None }, 100), 100);
+    }
+
+    #[test]
+    fn test_jitter_within_spread() {
+        let column = JitteredColumn { jitter: Some(0.1) };
+        let ttls = (0..1000)
+            .map(|_| jittered(&column, 100))
+            .collect::<Vec<_>>();
+
+        assert!(ttls.iter().all(|ttl| (90..=110).contains(ttl)));
+        assert!(ttls.iter().any(|ttl| *ttl != ttls[0]));
+    }
+
+    #[test]
+    fn test_no_expiry_sentinel() {
+        let column = JitteredColumn { jitter: Some(0.1) };
+
+        assert_eq!(expiry(&column, 0), None);
+        assert_eq!(expiry(&column, -1), None);
+        assert!(expiry(&column, 100).is_some());
+    }
+}
@@ -1,5 +1,7 @@
 #[cfg(feature = "redis")]
-use omega_cache::{CacheStorage, ColumnDefinition, Engine, redis_engine::RedisEngine};
+use omega_cache::{
+    CacheError, CacheStorage, ColumnDefinition, Engine, InsertOutcome, redis_engine::RedisEngine,
+};
 
 #[test]
 #[cfg(feature = "redis")]
@@ -105,3 +107,488 @@ fn test_redis_drop() {
         Err(e) => panic!("{e}"),
     }
 }
+
+#[test]
+#[cfg(feature = "redis")]
+fn test_redis_increment_overflow() {
+    struct Column {}
+    impl ColumnDefinition for Column {
+        fn name(&self) -> String {
+            "test_column".to_string()
+        }
+
+        fn get_ttl_in_seconds(&self) -> i32 {
+            10
+        }
+    }
+
+    let c = Column {};
+    let k = "test_counter";
+    let redis = Engine::new(RedisEngine::build("redis://127.0.0.1/".to_string(), None));
+    assert!(redis.try_drop_column(&c).is_ok());
+
+    assert_eq!(
+        redis.try_increment(&c, &k, i64::MAX - 1).unwrap(),
+        i64::MAX - 1
+    );
+    assert!(redis.try_increment(&c, &k, 2).is_err());
+    assert_eq!(redis.try_increment(&c, &k, 0).unwrap(), i64::MAX - 1);
+}
+
+#[test]
+#[cfg(feature = "redis")]
+fn test_redis_insert_reporting() {
+    struct Column {}
+    impl ColumnDefinition for Column {
+        fn name(&self) -> String {
+            "test_column".to_string()
+        }
+
+        fn get_ttl_in_seconds(&self) -> i32 {
+            10
+        }
+    }
+
+    let c = Column {};
+    let k = "reporting_key";
+    let redis = Engine::new(RedisEngine::build("redis://127.0.0.1/".to_string(), None));
+    assert!(redis.try_drop_column(&c).is_ok());
+
+    assert_eq!(
+        redis.try_insert_reporting(&c, &k, &1i32).unwrap(),
+        InsertOutcome::Created
+    );
+    assert_eq!(
+        redis.try_insert_reporting(&c, &k, &2i32).unwrap(),
+        InsertOutcome::Overwrote
+    );
+}
+
+#[test]
+#[cfg(feature = "redis")]
+fn test_redis_list_columns() {
+    struct Column {}
+    impl ColumnDefinition for Column {
+        fn name(&self) -> String {
+            "listed_column".to_string()
+        }
+
+        fn get_ttl_in_seconds(&self) -> i32 {
+            10
+        }
+    }
+
+    let c = Column {};
+    let redis = Engine::new(RedisEngine::build("redis://127.0.0.1/".to_string(), None));
+    assert!(redis.try_insert(&c, &"key", &1i32).is_ok());
+
+    let columns = redis.try_list_columns().unwrap();
+    assert!(columns.contains(&"listed_column".to_string()));
+}
+
+#[test]
+#[cfg(feature = "redis")]
+fn test_redis_pool_exhausted() {
+    struct Column {}
+    impl ColumnDefinition for Column {
+        fn name(&self) -> String {
+            "pool_column".to_string()
+        }
+
+        fn get_ttl_in_seconds(&self) -> i32 {
+            10
+        }
+    }
+
+    let redis = Engine::new(
+        RedisEngine::builder("redis://127.0.0.1/")
+            .max_connections(1)
+            .connection_timeout(std::time::Duration::from_millis(200))
+            .build(),
+    );
+
+    // pausing redis holds the pool's only connection in the first insert
+    let mut pauser = redis::Client::open("redis://127.0.0.1/")
+        .unwrap()
+        .get_connection()
+        .unwrap();
+    redis::cmd("CLIENT")
+        .arg("PAUSE")
+        .arg(1000)
+        .query::<()>(&mut pauser)
+        .unwrap();
+
+    std::thread::scope(|s| {
+        let holder = s.spawn(|| redis.try_insert(&Column {}, &"held", &1u8));
+        std::thread::sleep(std::time::Duration::from_millis(100));
+
+        assert!(matches!(
+            redis.try_insert(&Column {}, &"waiting", &2u8),
+            Err(CacheError::Unavailable(message)) if message.contains("exhausted")
+        ));
+        assert!(holder.join().unwrap().is_ok());
+    });
+}
+
+#[test]
+#[cfg(feature = "redis")]
+fn test_redis_get_many() {
+    struct Column {}
+    impl ColumnDefinition for Column {
+        fn name(&self) -> String {
+            "batch_column".to_string()
+        }
+
+        fn get_ttl_in_seconds(&self) -> i32 {
+            10
+        }
+    }
+
+    let c = Column {};
+    let redis =
+        Engine::new(RedisEngine::build("redis://127.0.0.1/".to_string(), None)).with_batch_size(2);
+    assert!(redis.try_drop_column(&c).is_ok());
+    assert_eq!(
+        redis.try_preload(&c, [("a", 1i32), ("c", 3i32)]).unwrap(),
+        2
+    );
+
+    assert_eq!(
+        redis
+            .try_get_many::<&str, i32>(&c, &["a", "b", "c"])
+            .unwrap(),
+        vec![Some(1), None, Some(3)]
+    );
+}
+
+#[test]
+#[cfg(feature = "redis")]
+fn test_redis_operation_timeout() {
+    use std::{
+        io::{Read, Write},
+        net::{TcpListener, TcpStream},
+        sync::{
+            Arc,
+            atomic::{AtomicBool, Ordering},
+        },
+        time::Duration,
+    };
+
+    struct Column {}
+    impl ColumnDefinition for Column {
+        fn name(&self) -> String {
+            "timeout_column".to_string()
+        }
+
+        fn get_ttl_in_seconds(&self) -> i32 {
+            10
+        }
+    }
+
+    // Forward to redis, holding back replies while `delay` is set
+    let delay = Arc::new(AtomicBool::new(false));
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let proxy_delay = Arc::clone(&delay);
+    std::thread::spawn(move || {
+        for client in listener.incoming() {
+            let mut client = client.unwrap();
+            let mut server = TcpStream::connect("127.0.0.1:6379").unwrap();
+            let (mut client_reader, mut server_writer) =
+                (client.try_clone().unwrap(), server.try_clone().unwrap());
+            std::thread::spawn(move || std::io::copy(&mut client_reader, &mut server_writer));
+
+            let delay = Arc::clone(&proxy_delay);
+            std::thread::spawn(move || {
+                let mut buffer = [0u8; 4096];
+                while let Ok(n @ 1..) = server.read(&mut buffer) {
+                    if delay.load(Ordering::Relaxed) {
+                        std::thread::sleep(Duration::from_secs(1));
+                    }
+                    if client.write_all(&buffer[..n]).is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+    });
+
+    let redis = Engine::new(
+        RedisEngine::builder(format!("redis://127.0.0.1:{port}/"))
+            .max_connections(1)
+            .operation_timeout(Duration::from_millis(100))
+            .build(),
+    );
+    assert!(redis.try_insert(&Column {}, &"key", &1i32).is_ok());
+
+    delay.store(true, Ordering::Relaxed);
+    assert!(matches!(
+        redis.try_get::<&str, i32>(&Column {}, &"key"),
+        Err(CacheError::Unavailable(message)) if message == "operation timed out"
+    ));
+}
+
+#[test]
+#[cfg(feature = "redis-notifications")]
+fn test_redis_expired_notifications() {
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    use omega_cache::{EvictReason, EvictionListener, redis_engine::KeyFormat};
+
+    struct Column {}
+    impl ColumnDefinition for Column {
+        fn name(&self) -> String {
+            "expired_notifications_column".to_string()
+        }
+
+        fn get_ttl_in_seconds(&self) -> i32 {
+            1
+        }
+    }
+
+    #[derive(Default)]
+    struct Evicted(Mutex<Vec<(String, Vec<u8>, EvictReason)>>);
+    impl EvictionListener for Evicted {
+        fn on_evict(&self, column: &str, key: &[u8], reason: EvictReason) {
+            self.0
+                .lock()
+                .unwrap()
+                .push((column.to_string(), key.to_vec(), reason));
+        }
+    }
+
+    let mut conn = redis::Client::open("redis://127.0.0.1/")
+        .unwrap()
+        .get_connection()
+        .unwrap();
+    redis::cmd("CONFIG")
+        .arg("SET")
+        .arg("notify-keyspace-events")
+        .arg("Ex")
+        .exec(&mut conn)
+        .unwrap();
+
+    let mut redis = Engine::new(
+        RedisEngine::builder("redis://127.0.0.1/")
+            .key_format(KeyFormat::default().prefix("notifications_test/"))
+            .build(),
+    );
+    let evicted = Arc::new(Evicted::default());
+    assert!(redis.set_eviction_listener(evicted.clone()).is_ok());
+
+    assert!(redis.try_insert(&Column {}, &"key", &1u8).is_ok());
+    std::thread::sleep(Duration::from_millis(2500));
+
+    assert_eq!(
+        *evicted.0.lock().unwrap(),
+        vec![(
+            "expired_notifications_column".to_string(),
+            b"key".to_vec(),
+            EvictReason::Expired
+        )]
+    );
+}
+
+#[test]
+#[cfg(feature = "redis")]
+fn test_redis_touch_many() {
+    use std::time::Duration;
+
+    struct Column {}
+    impl ColumnDefinition for Column {
+        fn name(&self) -> String {
+            "touch_many_column".to_string()
+        }
+
+        fn get_ttl_in_seconds(&self) -> i32 {
+            1
+        }
+    }
+
+    let redis = Engine::new(RedisEngine::build("redis://127.0.0.1/".to_string(), None));
+    assert!(redis.try_drop_column(&Column {}).is_ok());
+
+    for key in ["a", "b", "c"] {
+        assert!(redis.try_insert(&Column {}, &key, &1u8).is_ok());
+    }
+
+    assert_eq!(
+        redis
+            .try_touch_many(&Column {}, &["a", "b", "missing"], Some(60))
+            .unwrap(),
+        2
+    );
+    assert_eq!(
+        redis.try_touch_many(&Column {}, &["b"], Some(0)).unwrap(),
+        1
+    );
+
+    std::thread::sleep(Duration::from_millis(2100));
+    assert_eq!(redis.try_get::<_, u8>(&Column {}, &"a").unwrap(), Some(1));
+    assert_eq!(redis.try_get::<_, u8>(&Column {}, &"b").unwrap(), Some(1));
+    assert_eq!(redis.try_get::<_, u8>(&Column {}, &"c").unwrap(), None);
+}
+
+#[test]
+#[cfg(feature = "redis")]
+fn test_redis_transaction() {
+    struct Column {}
+    impl ColumnDefinition for Column {
+        fn name(&self) -> String {
+            "transaction_column".to_string()
+        }
+
+        fn get_ttl_in_seconds(&self) -> i32 {
+            60
+        }
+    }
+
+    let redis = Engine::new(RedisEngine::build("redis://127.0.0.1/".to_string(), None));
+    assert!(redis.try_drop_column(&Column {}).is_ok());
+
+    assert!(redis.try_insert(&Column {}, &"from", &10u64).is_ok());
+    assert!(redis.try_insert(&Column {}, &"to", &0u64).is_ok());
+
+    let moved = redis
+        .transaction(&Column {})
+        .check_eq(&"from", 10u64)
+        .check_missing(&"lock")
+        .insert(&"from", &5u64)
+        .insert(&"to", &5u64)
+        .try_commit()
+        .unwrap();
+    assert!(moved);
+    assert_eq!(
+        redis.try_get::<_, u64>(&Column {}, &"from").unwrap(),
+        Some(5)
+    );
+    assert_eq!(redis.try_get::<_, u64>(&Column {}, &"to").unwrap(), Some(5));
+
+    let moved = redis
+        .transaction(&Column {})
+        .check_eq(&"from", 10u64)
+        .remove(&"to")
+        .try_commit()
+        .unwrap();
+    assert!(!moved);
+    assert_eq!(redis.try_get::<_, u64>(&Column {}, &"to").unwrap(), Some(5));
+}
+
+#[test]
+#[cfg(feature = "redis")]
+fn test_redis_take() {
+    struct Column {}
+    impl ColumnDefinition for Column {
+        fn name(&self) -> String {
+            "take_column".to_string()
+        }
+
+        fn get_ttl_in_seconds(&self) -> i32 {
+            60
+        }
+    }
+
+    let redis = Engine::new(RedisEngine::build("redis://127.0.0.1/".to_string(), None));
+    assert!(redis.try_drop_column(&Column {}).is_ok());
+
+    assert!(redis.try_insert(&Column {}, &"token", &1i32).is_ok());
+    assert_eq!(redis.try_take(&Column {}, &"token").unwrap(), Some(1i32));
+    assert_eq!(
+        redis.try_take::<_, i32>(&Column {}, &"token").unwrap(),
+        None
+    );
+}
+
+#[test]
+#[cfg(feature = "redis")]
+fn test_redis_wrong_type() {
+    use omega_cache::redis_engine::WrongType;
+
+    struct Column {}
+    impl ColumnDefinition for Column {
+        fn name(&self) -> String {
+            "wrong_type_column".to_string()
+        }
+
+        fn get_ttl_in_seconds(&self) -> i32 {
+            60
+        }
+    }
+
+    let mut conn = redis::Client::open("redis://127.0.0.1/")
+        .unwrap()
+        .get_connection()
+        .unwrap();
+    redis::cmd("HSET")
+        .arg("wrong_type_column:shared")
+        .arg("field")
+        .arg("value")
+        .exec(&mut conn)
+        .unwrap();
+
+    let redis = Engine::new(RedisEngine::builder("redis://127.0.0.1/").build());
+    assert!(matches!(
+        redis.try_get::<_, String>(&Column {}, &"shared"),
+        Err(CacheError::Get(message)) if message == "key exists with incompatible Redis type"
+    ));
+
+    let redis = Engine::new(
+        RedisEngine::builder("redis://127.0.0.1/")
+            .on_wrong_type(WrongType::Miss)
+            .build(),
+    );
+    assert_eq!(
+        redis.try_get::<_, String>(&Column {}, &"shared").unwrap(),
+        None
+    );
+    assert!(redis.try_drop_column(&Column {}).is_ok());
+}
+
+#[test]
+#[cfg(feature = "redis")]
+fn test_redis_binary_keys_round_trip() {
+    struct Column(&'static str);
+    impl ColumnDefinition for Column {
+        fn name(&self) -> String {
+            self.0.to_string()
+        }
+
+        fn get_ttl_in_seconds(&self) -> i32 {
+            10
+        }
+    }
+
+    // glob characters and the separator in the name, and a column whose keys
+    // run into the other's, so the scan must match the escaped name exactly
+    let c = Column("binary*[keys]");
+    let neighbour = Column("binary*[keys]:a");
+
+    let redis = RedisEngine::build("redis://127.0.0.1/".to_string(), None);
+    assert!(redis.try_drop_column(&c).is_ok());
+    assert!(redis.try_drop_column(&neighbour).is_ok());
+
+    let keys: [&[u8]; 6] = [b"a:b", b":", b"\\:\\", b"\0nul\0", b"a\0:b\\\0", b"*?["];
+    for (i, key) in keys.iter().enumerate() {
+        assert!(
+            redis
+                .try_insert(&c, key, &[u8::try_from(i).unwrap()])
+                .is_ok()
+        );
+    }
+    assert!(redis.try_insert(&neighbour, b"b", b"other").is_ok());
+
+    let mut entries = redis.try_entries(&c).unwrap();
+    entries.sort();
+
+    let mut expected = keys
+        .iter()
+        .enumerate()
+        .map(|(i, key)| (key.to_vec(), vec![u8::try_from(i).unwrap()]))
+        .collect::<Vec<_>>();
+    expected.sort();
+    assert_eq!(entries, expected);
+
+    assert!(redis.try_drop_column(&c).is_ok());
+    assert!(redis.try_drop_column(&neighbour).is_ok());
+}
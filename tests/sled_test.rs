@@ -1,5 +1,5 @@
 #[cfg(feature = "sled")]
-use omega_cache::{CacheStorage, ColumnDefinition, sled_engine::SledEngine};
+use omega_cache::{CacheStorage, ColumnDefinition, InsertOutcome, sled_engine::SledEngine};
 
 #[test]
 #[cfg(feature = "sled")]
@@ -113,3 +113,2038 @@ fn test_sled_drop() {
         Err(e) => panic!("{e}"),
     }
 }
+
+#[test]
+#[cfg(feature = "sled")]
+fn test_sled_checksums() {
+    #[derive(bincode::Encode, bincode::Decode, PartialEq, Eq, Debug)]
+    struct Data {
+        name: String,
+    }
+
+    struct Column {}
+    impl ColumnDefinition for Column {
+        fn name(&self) -> String {
+            "test_column".to_string()
+        }
+
+        fn get_ttl_in_seconds(&self) -> i32 {
+            10
+        }
+    }
+
+    let c = Column {};
+    let d = Data {
+        name: "test_data".to_string(),
+    };
+    let k = "test_key";
+    let sled = omega_cache::Engine::new(SledEngine::build(
+        "./tmp/sled_test_checksums".to_string(),
+        None,
+    ))
+    .with_checksums();
+    assert!(sled.try_insert(&c, &k, &d).is_ok());
+
+    match sled.try_get(&c, &k) {
+        Ok(data) => {
+            assert!(data.is_some());
+            assert_eq!(d, data.unwrap());
+        }
+        Err(e) => panic!("{e}"),
+    }
+}
+
+#[test]
+#[cfg(feature = "sled")]
+fn test_sled_increment_overflow() {
+    struct Column {}
+    impl ColumnDefinition for Column {
+        fn name(&self) -> String {
+            "test_column".to_string()
+        }
+
+        fn get_ttl_in_seconds(&self) -> i32 {
+            10
+        }
+    }
+
+    let c = Column {};
+    let k = "test_counter";
+    let sled = omega_cache::Engine::new(SledEngine::build(
+        "./tmp/sled_test_increment".to_string(),
+        None,
+    ));
+    assert!(sled.try_drop_column(&c).is_ok());
+
+    assert_eq!(
+        sled.try_increment(&c, &k, i64::MAX - 1).unwrap(),
+        i64::MAX - 1
+    );
+    assert!(sled.try_increment(&c, &k, 2).is_err());
+    assert_eq!(
+        sled.try_get::<&str, i64>(&c, &k).unwrap(),
+        Some(i64::MAX - 1)
+    );
+}
+
+#[test]
+#[cfg(feature = "sled")]
+fn test_sled_max_entries() {
+    struct Column {}
+    impl ColumnDefinition for Column {
+        fn name(&self) -> String {
+            "bounded_column".to_string()
+        }
+
+        fn get_ttl_in_seconds(&self) -> i32 {
+            10
+        }
+
+        fn max_entries(&self) -> Option<usize> {
+            Some(2)
+        }
+    }
+
+    let c = Column {};
+    let sled = omega_cache::Engine::new(SledEngine::build(
+        "./tmp/sled_test_max_entries".to_string(),
+        None,
+    ));
+    assert!(sled.try_drop_column(&c).is_ok());
+
+    for k in ["a", "b", "c"] {
+        assert!(sled.try_insert(&c, &k, &k.to_string()).is_ok());
+    }
+
+    assert!(sled.try_get::<&str, String>(&c, &"a").unwrap().is_none());
+    assert!(sled.try_get::<&str, String>(&c, &"b").unwrap().is_some());
+    assert!(sled.try_get::<&str, String>(&c, &"c").unwrap().is_some());
+}
+
+#[test]
+#[cfg(feature = "sled")]
+fn test_sled_max_entries_keeps_newest() {
+    struct Column {}
+    impl ColumnDefinition for Column {
+        fn name(&self) -> String {
+            "bounded_column".to_string()
+        }
+
+        fn get_ttl_in_seconds(&self) -> i32 {
+            10
+        }
+
+        fn max_entries(&self) -> Option<usize> {
+            Some(2)
+        }
+    }
+
+    let c = Column {};
+    let sled = omega_cache::Engine::new(SledEngine::build(
+        "./tmp/sled_test_max_entries_keeps_newest".to_string(),
+        None,
+    ));
+    assert!(sled.try_drop_column(&c).is_ok());
+
+    // written within the same second in reverse key order
+    for k in ["c", "b", "a"] {
+        assert!(sled.try_insert(&c, &k, &k.to_string()).is_ok());
+    }
+
+    assert!(sled.try_get::<&str, String>(&c, &"c").unwrap().is_none());
+    assert!(sled.try_get::<&str, String>(&c, &"b").unwrap().is_some());
+    assert!(sled.try_get::<&str, String>(&c, &"a").unwrap().is_some());
+
+    // a removed item frees its place without evicting another
+    assert!(sled.try_take::<&str, String>(&c, &"b").unwrap().is_some());
+    assert!(sled.try_insert(&c, &"d", &"d".to_string()).is_ok());
+    assert!(sled.try_get::<&str, String>(&c, &"a").unwrap().is_some());
+    assert!(sled.try_get::<&str, String>(&c, &"d").unwrap().is_some());
+}
+
+#[test]
+#[cfg(feature = "sled")]
+fn test_sled_compression() {
+    struct Column {}
+    impl ColumnDefinition for Column {
+        fn name(&self) -> String {
+            "test_column".to_string()
+        }
+
+        fn get_ttl_in_seconds(&self) -> i32 {
+            10
+        }
+    }
+
+    let c = Column {};
+    let d = "some repetitive value ".repeat(100);
+    let k = "test_key";
+    let sled = omega_cache::Engine::new(
+        SledEngine::builder("./tmp/sled_test_compression")
+            .capacity(1024 * 1024)
+            .compression(5)
+            .build(),
+    );
+    assert!(sled.try_insert(&c, &k, &d).is_ok());
+    assert_eq!(sled.try_get::<&str, String>(&c, &k).unwrap(), Some(d));
+}
+
+#[test]
+#[cfg(feature = "sled")]
+fn test_sled_get_or_insert_with_ttl() {
+    struct Column {}
+    impl ColumnDefinition for Column {
+        fn name(&self) -> String {
+            "test_column".to_string()
+        }
+
+        fn get_ttl_in_seconds(&self) -> i32 {
+            10
+        }
+    }
+
+    let c = Column {};
+    let k = "negative_key";
+    let sled = omega_cache::Engine::new(SledEngine::build(
+        "./tmp/sled_test_get_or_insert_with_ttl".to_string(),
+        None,
+    ));
+    assert!(sled.try_drop_column(&c).is_ok());
+
+    let value = sled.try_get_or_insert_with_ttl(&c, &k, Some(1), || "not found".to_string());
+    assert_eq!(value.unwrap(), "not found");
+    assert!(sled.try_get::<&str, String>(&c, &k).unwrap().is_some());
+
+    std::thread::sleep(std::time::Duration::from_secs(2));
+
+    assert!(sled.try_get::<&str, String>(&c, &k).unwrap().is_none());
+}
+
+#[test]
+#[cfg(feature = "sled")]
+fn test_sled_insert_reporting() {
+    struct Column {}
+    impl ColumnDefinition for Column {
+        fn name(&self) -> String {
+            "test_column".to_string()
+        }
+
+        fn get_ttl_in_seconds(&self) -> i32 {
+            10
+        }
+    }
+
+    let c = Column {};
+    let k = "reporting_key";
+    let sled = omega_cache::Engine::new(SledEngine::build(
+        "./tmp/sled_test_insert_reporting".to_string(),
+        None,
+    ));
+    assert!(sled.try_drop_column(&c).is_ok());
+
+    assert_eq!(
+        sled.try_insert_reporting(&c, &k, &1i32).unwrap(),
+        InsertOutcome::Created
+    );
+    assert_eq!(
+        sled.try_insert_reporting(&c, &k, &2i32).unwrap(),
+        InsertOutcome::Overwrote
+    );
+}
+
+#[test]
+#[cfg(feature = "sled")]
+fn test_sled_list_columns() {
+    struct Column {}
+    impl ColumnDefinition for Column {
+        fn name(&self) -> String {
+            "listed_column".to_string()
+        }
+
+        fn get_ttl_in_seconds(&self) -> i32 {
+            10
+        }
+    }
+
+    let c = Column {};
+    let sled = omega_cache::Engine::new(SledEngine::build(
+        "./tmp/sled_test_list_columns".to_string(),
+        None,
+    ));
+    assert!(sled.try_insert(&c, &"key", &1i32).is_ok());
+
+    let columns = sled.try_list_columns().unwrap();
+    assert!(columns.contains(&"listed_column".to_string()));
+    assert!(!columns.contains(&"__sled__default".to_string()));
+}
+
+#[test]
+#[cfg(feature = "sled")]
+fn test_sled_insert_without_expiry() {
+    struct Column {}
+    impl ColumnDefinition for Column {
+        fn name(&self) -> String {
+            "test_column".to_string()
+        }
+
+        fn get_ttl_in_seconds(&self) -> i32 {
+            1
+        }
+    }
+
+    let c = Column {};
+    let sled = omega_cache::Engine::new(SledEngine::build(
+        "./tmp/sled_test_without_expiry".to_string(),
+        None,
+    ));
+    assert!(
+        sled.try_insert_with_ttl(&c, &"forever", &1i32, Some(0))
+            .is_ok()
+    );
+    assert!(
+        sled.try_insert_with_ttl(&c, &"default", &1i32, None)
+            .is_ok()
+    );
+
+    std::thread::sleep(std::time::Duration::from_secs(2));
+
+    assert!(sled.try_get::<&str, i32>(&c, &"forever").unwrap().is_some());
+    assert!(sled.try_get::<&str, i32>(&c, &"default").unwrap().is_none());
+}
+
+#[test]
+#[cfg(feature = "sled")]
+fn test_sled_get_borrowed() {
+    struct Column {}
+    impl ColumnDefinition for Column {
+        fn name(&self) -> String {
+            "test_column".to_string()
+        }
+
+        fn get_ttl_in_seconds(&self) -> i32 {
+            10
+        }
+    }
+
+    let c = Column {};
+    let sled = omega_cache::Engine::new(SledEngine::build(
+        "./tmp/sled_test_get_borrowed".to_string(),
+        None,
+    ))
+    .with_checksums();
+    assert!(sled.try_insert(&c, &"key", &"borrowed value").is_ok());
+
+    let stored = sled.try_get_borrowed(&c, &"key").unwrap().unwrap();
+    assert_eq!(stored.decode::<&str>().unwrap(), "borrowed value");
+    assert!(stored.decode::<bool>().is_err());
+
+    assert!(sled.try_get_borrowed(&c, &"missing").unwrap().is_none());
+}
+
+#[test]
+#[cfg(feature = "sled")]
+fn test_sled_preload() {
+    struct Column {}
+    impl ColumnDefinition for Column {
+        fn name(&self) -> String {
+            "preloaded_column".to_string()
+        }
+
+        fn get_ttl_in_seconds(&self) -> i32 {
+            10
+        }
+    }
+
+    let c = Column {};
+    let sled = omega_cache::Engine::new(SledEngine::build(
+        "./tmp/sled_test_preload".to_string(),
+        None,
+    ));
+    assert!(sled.try_drop_column(&c).is_ok());
+
+    let count = sled
+        .try_preload(&c, (0..2500u32).map(|i| (format!("key_{i}"), i)))
+        .unwrap();
+    assert_eq!(count, 2500);
+
+    assert_eq!(
+        sled.try_get::<&str, u32>(&c, &"key_2499").unwrap(),
+        Some(2499)
+    );
+}
+
+#[test]
+#[cfg(feature = "sled")]
+fn test_sled_value_size() {
+    struct Column {}
+    impl ColumnDefinition for Column {
+        fn name(&self) -> String {
+            "test_column".to_string()
+        }
+
+        fn get_ttl_in_seconds(&self) -> i32 {
+            10
+        }
+    }
+
+    let c = Column {};
+    let sled = omega_cache::Engine::new(SledEngine::build(
+        "./tmp/sled_test_value_size".to_string(),
+        None,
+    ));
+    assert!(sled.try_insert(&c, &"key", &vec![1u8; 1000]).is_ok());
+
+    let size = sled.try_value_size(&c, &"key").unwrap().unwrap();
+    assert!(size > 1000);
+    assert!(sled.try_value_size(&c, &"missing").unwrap().is_none());
+}
+
+#[test]
+#[cfg(feature = "sled")]
+fn test_sled_concurrent_drop() {
+    struct Column {}
+    impl ColumnDefinition for Column {
+        fn name(&self) -> String {
+            "dropped_column".to_string()
+        }
+
+        fn get_ttl_in_seconds(&self) -> i32 {
+            10
+        }
+    }
+
+    let c = Column {};
+    let sled = omega_cache::Engine::new(SledEngine::build(
+        "./tmp/sled_test_concurrent_drop".to_string(),
+        None,
+    ));
+
+    std::thread::scope(|s| {
+        let writer = s.spawn(|| {
+            for i in 0..500u32 {
+                assert!(sled.try_insert(&c, &i.to_be_bytes(), &i).is_ok());
+                // a write either survives intact or is dropped, never half visible
+                if let Some(value) = sled.try_get::<[u8; 4], u32>(&c, &i.to_be_bytes()).unwrap() {
+                    assert_eq!(value, i);
+                }
+            }
+        });
+
+        for _ in 0..50 {
+            assert!(sled.try_drop_column(&c).is_ok());
+        }
+
+        writer.join().unwrap();
+    });
+
+    // the column is usable once the drops have finished
+    assert!(sled.try_insert(&c, &"after", &1u32).is_ok());
+    assert_eq!(sled.try_get::<&str, u32>(&c, &"after").unwrap(), Some(1));
+}
+
+#[test]
+#[cfg(feature = "sled")]
+fn test_sled_swap() {
+    struct Column {}
+    impl ColumnDefinition for Column {
+        fn name(&self) -> String {
+            "test_column".to_string()
+        }
+
+        fn get_ttl_in_seconds(&self) -> i32 {
+            1
+        }
+    }
+
+    let c = Column {};
+    let sled =
+        omega_cache::Engine::new(SledEngine::build("./tmp/sled_test_swap".to_string(), None));
+    assert!(sled.try_drop_column(&c).is_ok());
+
+    assert_eq!(sled.try_swap(&c, &"lease", &1i32).unwrap(), None);
+    assert_eq!(sled.try_swap(&c, &"lease", &2i32).unwrap(), Some(1));
+
+    std::thread::sleep(std::time::Duration::from_secs(2));
+
+    // an expired value is not handed back
+    assert_eq!(sled.try_swap(&c, &"lease", &3i32).unwrap(), None);
+}
+
+#[test]
+#[cfg(feature = "sled")]
+fn test_sled_drop_is_idempotent() {
+    struct Column {}
+    impl ColumnDefinition for Column {
+        fn name(&self) -> String {
+            "never_used_column".to_string()
+        }
+
+        fn get_ttl_in_seconds(&self) -> i32 {
+            10
+        }
+    }
+
+    let c = Column {};
+    let sled = omega_cache::Engine::new(SledEngine::build(
+        "./tmp/sled_test_drop_idempotent".to_string(),
+        None,
+    ));
+
+    assert!(sled.try_drop_column(&c).is_ok());
+    assert!(sled.try_drop_column(&c).is_ok());
+    assert!(
+        !sled
+            .try_list_columns()
+            .unwrap()
+            .contains(&"never_used_column".to_string())
+    );
+
+    assert!(sled.try_insert(&c, &"key", &1i32).is_ok());
+    assert!(sled.try_drop_column(&c).is_ok());
+    assert!(sled.try_drop_column(&c).is_ok());
+    assert!(
+        !sled
+            .try_list_columns()
+            .unwrap()
+            .contains(&"never_used_column".to_string())
+    );
+    assert!(sled.try_get::<&str, i32>(&c, &"key").unwrap().is_none());
+}
+
+#[test]
+#[cfg(feature = "sled")]
+fn test_sled_report() {
+    struct Column {}
+    impl ColumnDefinition for Column {
+        fn name(&self) -> String {
+            "report_column".to_string()
+        }
+
+        fn get_ttl_in_seconds(&self) -> i32 {
+            10
+        }
+    }
+
+    let c = Column {};
+    let sled = omega_cache::Engine::new(SledEngine::build(
+        "./tmp/sled_test_report".to_string(),
+        None,
+    ));
+    assert!(sled.try_drop_column(&c).is_ok());
+    assert!(sled.try_insert(&c, &"a", &1i32).is_ok());
+    assert!(sled.try_insert(&c, &"b", &2i32).is_ok());
+
+    let report = sled.try_report().unwrap();
+    let column = report
+        .iter()
+        .find(|column| column.name == "report_column")
+        .unwrap();
+
+    assert_eq!(column.entries, 2);
+    assert!(column.size_on_disk.is_some_and(|bytes| bytes > 0));
+}
+
+#[test]
+#[cfg(feature = "sled")]
+fn test_sled_drop_column_dry_run() {
+    struct Column {}
+    impl ColumnDefinition for Column {
+        fn name(&self) -> String {
+            "dry_run_column".to_string()
+        }
+
+        fn get_ttl_in_seconds(&self) -> i32 {
+            10
+        }
+    }
+
+    let c = Column {};
+    let sled = omega_cache::Engine::new(SledEngine::build(
+        "./tmp/sled_test_drop_dry_run".to_string(),
+        None,
+    ));
+    assert!(sled.try_drop_column(&c).is_ok());
+    assert_eq!(sled.try_drop_column_dry_run(&c).unwrap(), 0);
+    assert!(!sled.try_list_columns().unwrap().contains(&c.name()));
+
+    assert!(sled.try_insert(&c, &"a", &1i32).is_ok());
+    assert!(sled.try_insert(&c, &"b", &2i32).is_ok());
+    assert_eq!(sled.try_drop_column_dry_run(&c).unwrap(), 2);
+    assert_eq!(sled.try_get::<_, i32>(&c, &"a").unwrap(), Some(1));
+}
+
+#[test]
+#[cfg(feature = "sled")]
+fn test_sled_ttl_change_between_opens() {
+    struct Column {
+        name: &'static str,
+        ttl: i32,
+        expire_on_ttl_change: bool,
+    }
+    impl ColumnDefinition for Column {
+        fn name(&self) -> String {
+            self.name.to_string()
+        }
+
+        fn get_ttl_in_seconds(&self) -> i32 {
+            self.ttl
+        }
+
+        fn expire_on_ttl_change(&self) -> bool {
+            self.expire_on_ttl_change
+        }
+    }
+
+    let columns = |ttl| {
+        [
+            Column {
+                name: "ttl_epoch_column",
+                ttl,
+                expire_on_ttl_change: true,
+            },
+            Column {
+                name: "ttl_kept_column",
+                ttl,
+                expire_on_ttl_change: false,
+            },
+        ]
+    };
+    let open = || {
+        omega_cache::Engine::new(SledEngine::build(
+            "./tmp/sled_test_ttl_change".to_string(),
+            None,
+        ))
+    };
+
+    let [expiring, kept] = columns(100);
+    let sled = open();
+    assert!(sled.try_drop_column(&expiring).is_ok());
+    assert!(sled.try_drop_column(&kept).is_ok());
+    assert!(sled.try_insert(&expiring, &"key", &1i32).is_ok());
+    assert!(sled.try_insert(&kept, &"key", &1i32).is_ok());
+    drop(sled);
+
+    // items written in the same second as the change are kept
+    std::thread::sleep(std::time::Duration::from_millis(1100));
+
+    let [expiring, kept] = columns(50);
+    let sled = open();
+    assert!(
+        sled.try_get::<&str, i32>(&expiring, &"key")
+            .unwrap()
+            .is_none()
+    );
+    assert_eq!(sled.try_get::<&str, i32>(&kept, &"key").unwrap(), Some(1));
+
+    assert!(sled.try_insert(&expiring, &"key", &2i32).is_ok());
+    assert_eq!(
+        sled.try_get::<&str, i32>(&expiring, &"key").unwrap(),
+        Some(2)
+    );
+}
+
+#[test]
+#[cfg(feature = "sled")]
+fn test_sled_key_prefix_between_versions() {
+    struct Column {}
+    impl ColumnDefinition for Column {
+        fn name(&self) -> String {
+            "versioned_column".to_string()
+        }
+
+        fn get_ttl_in_seconds(&self) -> i32 {
+            10
+        }
+    }
+
+    let open = |version: &str| {
+        omega_cache::Engine::new(SledEngine::build(
+            "./tmp/sled_test_key_prefix".to_string(),
+            None,
+        ))
+        .with_key_prefix(format!("{version}:"))
+    };
+
+    let sled = open("v1");
+    assert!(sled.try_drop_column(&Column {}).is_ok());
+    assert!(sled.try_insert(&Column {}, &"key", &1i32).is_ok());
+    drop(sled);
+
+    let sled = open("v2");
+    assert!(
+        sled.try_get::<&str, i32>(&Column {}, &"key")
+            .unwrap()
+            .is_none()
+    );
+    assert!(sled.try_insert(&Column {}, &"key", &2i32).is_ok());
+    drop(sled);
+
+    let sled = open("v1");
+    assert_eq!(
+        sled.try_get::<&str, i32>(&Column {}, &"key").unwrap(),
+        Some(1)
+    );
+}
+
+#[test]
+#[cfg(feature = "sled")]
+fn test_sled_get_many() {
+    struct Column {}
+    impl ColumnDefinition for Column {
+        fn name(&self) -> String {
+            "get_many_column".to_string()
+        }
+
+        fn get_ttl_in_seconds(&self) -> i32 {
+            10
+        }
+    }
+
+    let builder = SledEngine::builder("./tmp/sled_test_get_many");
+    #[cfg(feature = "rayon")]
+    let builder = builder.parallel_get_threshold(0);
+    let sled = omega_cache::Engine::new(builder.build());
+
+    let keys = (0..100).map(|i| i.to_string()).collect::<Vec<_>>();
+    assert!(sled.try_drop_column(&Column {}).is_ok());
+    for key in keys.iter().step_by(2) {
+        assert!(sled.try_insert(&Column {}, key, key).is_ok());
+    }
+
+    let values = sled.try_get_many::<_, String>(&Column {}, &keys).unwrap();
+    for (i, value) in values.into_iter().enumerate() {
+        assert_eq!(value, (i % 2 == 0).then(|| i.to_string()));
+    }
+}
+
+#[test]
+#[cfg(feature = "sled")]
+fn test_sled_reserved_column() {
+    struct Column {}
+    impl ColumnDefinition for Column {
+        fn name(&self) -> String {
+            "__sled__default".to_string()
+        }
+
+        fn get_ttl_in_seconds(&self) -> i32 {
+            10
+        }
+    }
+
+    let sled = omega_cache::Engine::new(SledEngine::build(
+        "./tmp/sled_test_reserved_column".to_string(),
+        None,
+    ));
+
+    assert!(matches!(
+        sled.validate_column(&Column {}),
+        Err(omega_cache::CacheError::Engine(_))
+    ));
+}
+
+#[test]
+#[cfg(feature = "sled")]
+fn test_sled_column_alias() {
+    struct Column {
+        name: &'static str,
+        aliases: &'static [&'static str],
+    }
+    impl ColumnDefinition for Column {
+        fn name(&self) -> String {
+            self.name.to_string()
+        }
+
+        fn get_ttl_in_seconds(&self) -> i32 {
+            10
+        }
+
+        fn aliases(&self) -> &[&str] {
+            self.aliases
+        }
+    }
+
+    let old = Column {
+        name: "alias_old_column",
+        aliases: &[],
+    };
+    let renamed = Column {
+        name: "alias_new_column",
+        aliases: &["alias_old_column"],
+    };
+    let sled = omega_cache::Engine::new(SledEngine::build(
+        "./tmp/sled_test_column_alias".to_string(),
+        None,
+    ));
+    assert!(sled.try_drop_column(&renamed).is_ok());
+
+    assert!(sled.try_insert(&old, &"key", &"old value").is_ok());
+    assert_eq!(
+        sled.try_get::<_, String>(&renamed, &"key").unwrap(),
+        Some("old value".to_string())
+    );
+
+    assert!(sled.try_insert(&renamed, &"key", &"new value").is_ok());
+    assert_eq!(
+        sled.try_get::<_, String>(&renamed, &"key").unwrap(),
+        Some("new value".to_string())
+    );
+
+    assert!(sled.try_drop_column(&renamed).is_ok());
+    assert!(sled.try_get::<_, String>(&old, &"key").unwrap().is_none());
+}
+
+#[test]
+#[cfg(feature = "sled")]
+fn test_sled_copy_column() {
+    struct Column {
+        name: &'static str,
+        ttl: i32,
+    }
+    impl ColumnDefinition for Column {
+        fn name(&self) -> String {
+            self.name.to_string()
+        }
+
+        fn get_ttl_in_seconds(&self) -> i32 {
+            self.ttl
+        }
+    }
+
+    let (from, to) = (
+        Column {
+            name: "copy_from_column",
+            ttl: 1,
+        },
+        Column {
+            name: "copy_to_column",
+            ttl: 60,
+        },
+    );
+    let sled = omega_cache::Engine::new(SledEngine::build(
+        "./tmp/sled_test_copy_column".to_string(),
+        None,
+    ));
+    assert!(sled.try_drop_column(&from).is_ok());
+    assert!(sled.try_drop_column(&to).is_ok());
+
+    assert!(sled.try_insert(&from, &"live", &1i32).is_ok());
+    assert!(
+        sled.try_insert_with_ttl(&from, &"forever", &2i32, Some(0))
+            .is_ok()
+    );
+    assert_eq!(sled.try_copy_column(&from, &to).unwrap(), 2);
+
+    // copies take the TTL of the destination column
+    std::thread::sleep(std::time::Duration::from_millis(2100));
+    assert!(sled.try_get::<_, i32>(&from, &"live").unwrap().is_none());
+    assert_eq!(sled.try_get::<_, i32>(&to, &"live").unwrap(), Some(1));
+    assert_eq!(sled.try_get::<_, i32>(&to, &"forever").unwrap(), Some(2));
+}
+
+#[test]
+#[cfg(feature = "sled")]
+fn test_sled_strict_columns() {
+    struct Column {}
+    impl ColumnDefinition for Column {
+        fn name(&self) -> String {
+            "strict_typo_column".to_string()
+        }
+
+        fn get_ttl_in_seconds(&self) -> i32 {
+            10
+        }
+    }
+
+    let sled = omega_cache::Engine::new(SledEngine::build(
+        "./tmp/sled_test_strict_columns".to_string(),
+        None,
+    ))
+    .with_strict_columns();
+    assert!(sled.try_drop_column(&Column {}).is_ok());
+
+    assert!(sled.try_get::<_, i32>(&Column {}, &"key").is_err());
+    assert!(
+        !sled
+            .try_list_columns()
+            .unwrap()
+            .contains(&"strict_typo_column".to_string())
+    );
+}
+
+#[test]
+#[cfg(feature = "sled")]
+fn test_sled_max_total_bytes() {
+    struct Column {
+        name: &'static str,
+    }
+    impl ColumnDefinition for Column {
+        fn name(&self) -> String {
+            self.name.to_string()
+        }
+
+        fn get_ttl_in_seconds(&self) -> i32 {
+            60
+        }
+    }
+
+    let (first, second) = (
+        Column {
+            name: "bounded_first_column",
+        },
+        Column {
+            name: "bounded_second_column",
+        },
+    );
+    let path = "./tmp/sled_test_max_total_bytes";
+    let _ = std::fs::remove_dir_all(path);
+    let sled = omega_cache::Engine::new(SledEngine::builder(path).max_total_bytes(4096).build());
+
+    let value = vec![7u8; 1000];
+    for key in ["a", "b", "c"] {
+        assert!(sled.try_insert(&first, &key, &value).is_ok());
+    }
+    // reading `a` leaves `b` as the least recently used
+    assert!(sled.try_get::<_, Vec<u8>>(&first, &"a").unwrap().is_some());
+    assert!(sled.try_insert(&second, &"d", &value).is_ok());
+    assert!(sled.try_insert(&second, &"e", &value).is_ok());
+
+    assert!(sled.try_get::<_, Vec<u8>>(&first, &"a").unwrap().is_some());
+    assert!(sled.try_get::<_, Vec<u8>>(&first, &"b").unwrap().is_none());
+    assert!(sled.try_get::<_, Vec<u8>>(&second, &"e").unwrap().is_some());
+
+    let stored = sled
+        .try_report()
+        .unwrap()
+        .iter()
+        .filter_map(|column| column.size_on_disk)
+        .sum::<u64>();
+    assert!(stored <= 4096);
+}
+
+#[test]
+#[cfg(feature = "sled")]
+fn test_sled_age_distribution() {
+    use std::time::Duration;
+
+    struct Column {}
+    impl ColumnDefinition for Column {
+        fn name(&self) -> String {
+            "age_distribution_column".to_string()
+        }
+
+        fn get_ttl_in_seconds(&self) -> i32 {
+            60
+        }
+    }
+
+    let sled = omega_cache::Engine::new(SledEngine::build(
+        "./tmp/sled_test_age_distribution".to_string(),
+        None,
+    ));
+    assert!(sled.try_drop_column(&Column {}).is_ok());
+
+    assert!(sled.try_insert(&Column {}, &"old", &1i32).is_ok());
+    std::thread::sleep(Duration::from_millis(2100));
+    assert!(sled.try_insert(&Column {}, &"new", &2i32).is_ok());
+    assert!(sled.try_insert(&Column {}, &"newer", &3i32).is_ok());
+
+    assert_eq!(
+        sled.try_age_distribution(&Column {}, &[Duration::from_secs(1)])
+            .unwrap(),
+        vec![2, 1]
+    );
+    assert_eq!(sled.try_age_distribution(&Column {}, &[]).unwrap(), vec![3]);
+    assert!(matches!(
+        sled.try_age_distribution(
+            &Column {},
+            &[Duration::from_mins(1), Duration::from_secs(1)]
+        ),
+        Err(omega_cache::CacheError::Engine(_))
+    ));
+}
+
+#[test]
+#[cfg(feature = "sled")]
+fn test_sled_rename() {
+    struct Column {}
+    impl ColumnDefinition for Column {
+        fn name(&self) -> String {
+            "rename_column".to_string()
+        }
+
+        fn get_ttl_in_seconds(&self) -> i32 {
+            1
+        }
+    }
+
+    let sled = omega_cache::Engine::new(SledEngine::build(
+        "./tmp/sled_test_rename".to_string(),
+        None,
+    ));
+    assert!(sled.try_drop_column(&Column {}).is_ok());
+
+    assert!(sled.try_insert(&Column {}, &"staged", &1i32).is_ok());
+    assert!(sled.try_insert(&Column {}, &"final", &2i32).is_ok());
+    assert!(sled.try_rename(&Column {}, &"staged", &"final").unwrap());
+    assert!(!sled.try_rename(&Column {}, &"staged", &"final").unwrap());
+    assert_eq!(
+        sled.try_get::<_, i32>(&Column {}, &"final").unwrap(),
+        Some(1)
+    );
+    assert!(
+        sled.try_get::<_, i32>(&Column {}, &"staged")
+            .unwrap()
+            .is_none()
+    );
+
+    // expired values are dropped rather than moved
+    assert!(sled.try_insert(&Column {}, &"staged", &3i32).is_ok());
+    std::thread::sleep(std::time::Duration::from_millis(2100));
+    assert!(!sled.try_rename(&Column {}, &"staged", &"final").unwrap());
+    assert!(
+        sled.try_get::<_, i32>(&Column {}, &"final")
+            .unwrap()
+            .is_none()
+    );
+}
+
+#[test]
+#[cfg(feature = "sled")]
+fn test_sled_take() {
+    struct Column {}
+    impl ColumnDefinition for Column {
+        fn name(&self) -> String {
+            "take_column".to_string()
+        }
+
+        fn get_ttl_in_seconds(&self) -> i32 {
+            1
+        }
+    }
+
+    let sled =
+        omega_cache::Engine::new(SledEngine::build("./tmp/sled_test_take".to_string(), None));
+    assert!(sled.try_drop_column(&Column {}).is_ok());
+
+    assert!(sled.try_insert(&Column {}, &"token", &1i32).is_ok());
+    assert_eq!(sled.try_take(&Column {}, &"token").unwrap(), Some(1i32));
+    assert!(
+        sled.try_take::<_, i32>(&Column {}, &"token")
+            .unwrap()
+            .is_none()
+    );
+    assert!(
+        sled.try_get::<_, i32>(&Column {}, &"token")
+            .unwrap()
+            .is_none()
+    );
+
+    // expired values are removed without being returned
+    assert!(sled.try_insert(&Column {}, &"token", &2i32).is_ok());
+    std::thread::sleep(std::time::Duration::from_millis(2100));
+    assert!(
+        sled.try_take::<_, i32>(&Column {}, &"token")
+            .unwrap()
+            .is_none()
+    );
+}
+
+#[test]
+#[cfg(feature = "sled")]
+fn test_sled_peek() {
+    struct Column {}
+    impl ColumnDefinition for Column {
+        fn name(&self) -> String {
+            "peek_column".to_string()
+        }
+
+        fn get_ttl_in_seconds(&self) -> i32 {
+            1
+        }
+    }
+
+    let sled = SledEngine::builder("./tmp/sled_test_peek").open().unwrap();
+    assert!(sled.try_drop_column(&Column {}).is_ok());
+
+    assert!(sled.try_peek(&Column {}, b"key").unwrap().is_none());
+    assert!(sled.try_insert(&Column {}, b"key", b"value").is_ok());
+    assert_eq!(
+        sled.try_peek(&Column {}, b"key").unwrap(),
+        Some((b"value".to_vec(), false))
+    );
+
+    // expired items are reported and left in place
+    std::thread::sleep(std::time::Duration::from_millis(2100));
+    assert_eq!(
+        sled.try_peek(&Column {}, b"key").unwrap(),
+        Some((b"value".to_vec(), true))
+    );
+    assert_eq!(
+        sled.try_peek(&Column {}, b"key").unwrap(),
+        Some((b"value".to_vec(), true))
+    );
+    assert!(sled.try_get(&Column {}, b"key").unwrap().is_none());
+    assert!(sled.try_peek(&Column {}, b"key").unwrap().is_none());
+}
+
+#[test]
+#[cfg(feature = "sled")]
+fn test_sled_idle_ttl() {
+    struct Column {}
+    impl ColumnDefinition for Column {
+        fn name(&self) -> String {
+            "idle_column".to_string()
+        }
+
+        fn get_ttl_in_seconds(&self) -> i32 {
+            5
+        }
+
+        fn idle_ttl_in_seconds(&self) -> Option<u32> {
+            Some(2)
+        }
+    }
+
+    let sled = SledEngine::build("./tmp/sled_test_idle_ttl".to_string(), None);
+    assert!(sled.try_drop_column(&Column {}).is_ok());
+
+    assert!(sled.try_insert(&Column {}, b"read", b"value").is_ok());
+    assert!(sled.try_insert(&Column {}, b"unread", b"value").is_ok());
+
+    // reads keep an item alive past the idle TTL
+    std::thread::sleep(std::time::Duration::from_millis(1600));
+    assert!(sled.try_get(&Column {}, b"read").unwrap().is_some());
+    std::thread::sleep(std::time::Duration::from_millis(1600));
+    assert!(sled.try_get(&Column {}, b"read").unwrap().is_some());
+    assert!(sled.try_get(&Column {}, b"unread").unwrap().is_none());
+
+    // but not past the absolute TTL
+    std::thread::sleep(std::time::Duration::from_millis(1600));
+    assert!(sled.try_get_stored(&Column {}, b"read").unwrap().is_some());
+    std::thread::sleep(std::time::Duration::from_millis(1600));
+    assert!(sled.try_get(&Column {}, b"read").unwrap().is_none());
+}
+
+#[test]
+#[cfg(feature = "sled")]
+fn test_sled_column_handle() {
+    use omega_cache::Engine;
+
+    struct Column {}
+    impl ColumnDefinition for Column {
+        fn name(&self) -> String {
+            "handle_column".to_string()
+        }
+
+        fn get_ttl_in_seconds(&self) -> i32 {
+            60
+        }
+    }
+
+    let engine = Engine::new(SledEngine::build(
+        "./tmp/sled_test_column_handle".to_string(),
+        None,
+    ));
+    assert!(engine.try_drop_column(&Column {}).is_ok());
+
+    let handle = engine.column_handle(&Column {}).unwrap();
+    for i in 0..100u32 {
+        assert!(handle.try_insert(&i.to_be_bytes(), &i).is_ok());
+    }
+    assert_eq!(engine.try_drop_column_dry_run(&Column {}).unwrap(), 100);
+
+    // inserts after a drop land in the recreated column
+    assert!(engine.try_drop_column(&Column {}).is_ok());
+    assert!(handle.try_insert(&"after", &7u32).is_ok());
+    assert_eq!(engine.try_get(&Column {}, &"after").unwrap(), Some(7u32));
+}
+
+#[test]
+#[cfg(feature = "sled")]
+fn test_sled_export_import() {
+    use omega_cache::Engine;
+
+    struct Column {}
+    impl ColumnDefinition for Column {
+        fn name(&self) -> String {
+            "export_column".to_string()
+        }
+
+        fn get_ttl_in_seconds(&self) -> i32 {
+            60
+        }
+    }
+
+    let from = Engine::new(SledEngine::build(
+        "./tmp/sled_test_export".to_string(),
+        None,
+    ));
+    assert!(from.try_drop_column(&Column {}).is_ok());
+    assert!(from.try_insert(&Column {}, &"expiring", &1u32).is_ok());
+    assert!(
+        from.try_insert_with_ttl(&Column {}, &"forever", &2u32, Some(0))
+            .is_ok()
+    );
+
+    let mut export = Vec::new();
+    assert_eq!(from.try_export_column(&Column {}, &mut export).unwrap(), 2);
+
+    let storage = SledEngine::build("./tmp/sled_test_import".to_string(), None);
+    assert!(storage.try_drop_column(&Column {}).is_ok());
+    let to = Engine::new(storage);
+    assert_eq!(
+        to.try_import_column(&Column {}, &mut export.as_slice())
+            .unwrap(),
+        2
+    );
+
+    assert_eq!(to.try_get(&Column {}, &"expiring").unwrap(), Some(1u32));
+    assert_eq!(to.try_get(&Column {}, &"forever").unwrap(), Some(2u32));
+
+    let mut export = Vec::new();
+    assert_eq!(to.try_export_column(&Column {}, &mut export).unwrap(), 2);
+    assert!(
+        to.try_import_column(&Column {}, &mut &export[..export.len() - 1])
+            .is_err()
+    );
+}
+
+#[test]
+#[cfg(feature = "sled")]
+fn test_sled_snapshot_restore() {
+    use omega_cache::{Engine, memory_engine::MemoryEngine};
+
+    struct Prices {}
+    impl ColumnDefinition for Prices {
+        fn name(&self) -> String {
+            "snapshot_prices".to_string()
+        }
+
+        fn get_ttl_in_seconds(&self) -> i32 {
+            60
+        }
+    }
+
+    struct Stock {}
+    impl ColumnDefinition for Stock {
+        fn name(&self) -> String {
+            "snapshot_stock".to_string()
+        }
+
+        fn get_ttl_in_seconds(&self) -> i32 {
+            0
+        }
+    }
+
+    let path = "./tmp/sled_test_snapshot";
+    let _ = std::fs::remove_dir_all(path);
+    let from = Engine::new(SledEngine::build(path.to_string(), None));
+    assert!(from.try_insert(&Prices {}, &"apple", &3u32).is_ok());
+    assert!(from.try_insert(&Prices {}, &"pear", &4u32).is_ok());
+    assert!(from.try_insert(&Stock {}, &"apple", &120u32).is_ok());
+
+    let mut snapshot = Vec::new();
+    assert_eq!(from.try_snapshot(&mut snapshot).unwrap(), 3);
+
+    // restored into another kind of storage
+    let to = Engine::new(MemoryEngine::builder().build());
+    assert_eq!(to.try_restore(&mut snapshot.as_slice()).unwrap(), 3);
+    assert_eq!(
+        to.try_list_columns().unwrap(),
+        ["snapshot_prices", "snapshot_stock"]
+    );
+    assert_eq!(to.try_get(&Prices {}, &"pear").unwrap(), Some(4u32));
+    assert_eq!(to.try_get(&Stock {}, &"apple").unwrap(), Some(120u32));
+
+    let mut again = Vec::new();
+    assert_eq!(to.try_snapshot(&mut again).unwrap(), 3);
+    assert!(again.starts_with(b"OMEGASNP\x02"));
+
+    assert!(
+        to.try_restore(&mut &snapshot[..snapshot.len() - 1])
+            .is_err()
+    );
+    assert!(to.try_restore(&mut b"not a snapshot".as_slice()).is_err());
+}
+
+#[test]
+#[cfg(feature = "sled")]
+fn test_sled_snapshot_restore_shared_column() {
+    use omega_cache::{CacheError, Engine, SledLayout};
+
+    struct Sessions {}
+    impl ColumnDefinition for Sessions {
+        fn name(&self) -> String {
+            "snapshot_sessions".to_string()
+        }
+
+        fn get_ttl_in_seconds(&self) -> i32 {
+            3600
+        }
+
+        fn idle_ttl_in_seconds(&self) -> Option<u32> {
+            Some(60)
+        }
+
+        fn storage_layout(&self) -> SledLayout {
+            SledLayout::SharedPrefixed
+        }
+    }
+
+    /// The same column in a tree of its own
+    struct Dedicated {}
+    impl ColumnDefinition for Dedicated {
+        fn name(&self) -> String {
+            "snapshot_sessions".to_string()
+        }
+
+        fn get_ttl_in_seconds(&self) -> i32 {
+            3600
+        }
+    }
+
+    let open = |path: &str| {
+        let _ = std::fs::remove_dir_all(path);
+        let mut engine = Engine::new(SledEngine::build(path.to_string(), None));
+        assert!(engine.register_column(&Sessions {}).is_ok());
+        engine
+    };
+    // one report entry for the column, so no empty dedicated tree was opened beside it
+    let entries = |engine: &Engine| {
+        engine
+            .try_report()
+            .unwrap()
+            .into_iter()
+            .filter(|column| column.name == "snapshot_sessions")
+            .map(|column| column.entries)
+            .collect::<Vec<_>>()
+    };
+
+    let from = open("./tmp/sled_test_snapshot_shared_from");
+    assert!(from.try_insert(&Sessions {}, &"ada", &1u32).is_ok());
+    assert!(from.try_insert(&Sessions {}, &"bob", &2u32).is_ok());
+
+    let mut snapshot = Vec::new();
+    assert_eq!(from.try_snapshot(&mut snapshot).unwrap(), 2);
+    assert_eq!(entries(&from), [2]);
+
+    let to = open("./tmp/sled_test_snapshot_shared_to");
+    assert_eq!(to.try_restore(&mut snapshot.as_slice()).unwrap(), 2);
+    assert_eq!(entries(&to), [2]);
+    assert_eq!(to.try_get(&Sessions {}, &"bob").unwrap(), Some(2u32));
+
+    // the idle TTL bounds the time left rather than the hour of the column TTL
+    let mut export = Vec::new();
+    assert_eq!(to.try_export_column(&Sessions {}, &mut export).unwrap(), 2);
+    let ttl = u32::from_be_bytes(export[export.len() - 4..].try_into().unwrap());
+    assert!(ttl > 0 && ttl <= 60);
+
+    // without registering, the column is still found in the shared tree
+    drop(from);
+    let unregistered = Engine::new(SledEngine::build(
+        "./tmp/sled_test_snapshot_shared_from".to_string(),
+        None,
+    ));
+    let mut snapshot = Vec::new();
+    assert_eq!(unregistered.try_snapshot(&mut snapshot).unwrap(), 2);
+    assert_eq!(entries(&unregistered), [2]);
+
+    // values of one column in both layouts cannot be told apart
+    assert!(unregistered.try_insert(&Dedicated {}, &"cy", &3u32).is_ok());
+    assert!(matches!(
+        unregistered.try_snapshot(&mut Vec::new()),
+        Err(CacheError::Engine(_))
+    ));
+}
+
+#[test]
+#[cfg(feature = "sled")]
+fn test_sled_default_ttl() {
+    use omega_cache::Engine;
+
+    struct Column {}
+    impl ColumnDefinition for Column {
+        fn name(&self) -> String {
+            "default_ttl_column".to_string()
+        }
+
+        fn get_ttl_in_seconds(&self) -> i32 {
+            0
+        }
+    }
+
+    let engine = Engine::new(SledEngine::build(
+        "./tmp/sled_test_default_ttl".to_string(),
+        None,
+    ))
+    .with_default_ttl(std::time::Duration::from_secs(1));
+    assert!(engine.try_drop_column(&Column {}).is_ok());
+
+    assert!(engine.try_insert(&Column {}, &"default", &1u8).is_ok());
+    assert!(
+        engine
+            .try_insert_with_ttl(&Column {}, &"override", &2u8, Some(60))
+            .is_ok()
+    );
+    assert!(
+        engine
+            .column_handle(&Column {})
+            .unwrap()
+            .try_insert(&"handle", &3u8)
+            .is_ok()
+    );
+    assert_eq!(
+        engine.effective_ttl(&Column {}),
+        Some(std::time::Duration::from_secs(1))
+    );
+
+    std::thread::sleep(std::time::Duration::from_millis(2100));
+    assert_eq!(
+        engine.try_get::<_, u8>(&Column {}, &"default").unwrap(),
+        None
+    );
+    assert_eq!(
+        engine.try_get::<_, u8>(&Column {}, &"handle").unwrap(),
+        None
+    );
+    assert_eq!(
+        engine.try_get::<_, u8>(&Column {}, &"override").unwrap(),
+        Some(2)
+    );
+}
+
+#[test]
+#[cfg(feature = "sled")]
+fn test_sled_get_allowing_stale() {
+    use omega_cache::Engine;
+
+    struct Column {}
+    impl ColumnDefinition for Column {
+        fn name(&self) -> String {
+            "stale_column".to_string()
+        }
+
+        fn get_ttl_in_seconds(&self) -> i32 {
+            1
+        }
+    }
+
+    let engine = Engine::new(SledEngine::build(
+        "./tmp/sled_test_allowing_stale".to_string(),
+        None,
+    ));
+    assert!(engine.try_drop_column(&Column {}).is_ok());
+
+    assert!(engine.try_insert(&Column {}, &"key", &7u32).is_ok());
+    assert_eq!(
+        engine
+            .try_get_allowing_stale::<_, u32>(&Column {}, &"key")
+            .unwrap(),
+        Some((7, false))
+    );
+
+    std::thread::sleep(std::time::Duration::from_millis(2100));
+    assert_eq!(
+        engine
+            .try_get_allowing_stale::<_, u32>(&Column {}, &"key")
+            .unwrap(),
+        Some((7, true))
+    );
+    // the stale read leaves the value for the next one
+    assert_eq!(
+        engine
+            .try_get_allowing_stale::<_, u32>(&Column {}, &"key")
+            .unwrap(),
+        Some((7, true))
+    );
+    assert_eq!(engine.try_get::<_, u32>(&Column {}, &"key").unwrap(), None);
+    assert_eq!(
+        engine
+            .try_get_allowing_stale::<_, u32>(&Column {}, &"missing")
+            .unwrap(),
+        None
+    );
+}
+
+#[test]
+#[cfg(feature = "sled")]
+fn test_sled_shared_layout() {
+    use omega_cache::SledLayout;
+
+    struct Shared(&'static str);
+    impl ColumnDefinition for Shared {
+        fn name(&self) -> String {
+            self.0.to_string()
+        }
+
+        fn get_ttl_in_seconds(&self) -> i32 {
+            60
+        }
+
+        fn storage_layout(&self) -> SledLayout {
+            SledLayout::SharedPrefixed
+        }
+    }
+
+    let sled = SledEngine::build("./tmp/sled_test_shared_layout".to_string(), None);
+    let (users, user) = (Shared("users"), Shared("user"));
+    assert!(sled.try_drop_column(&users).is_ok());
+    assert!(sled.try_drop_column(&user).is_ok());
+
+    assert!(sled.try_insert(&users, b"ada", b"1").is_ok());
+    assert!(sled.try_insert(&users, b"grace", b"2").is_ok());
+    assert!(sled.try_insert(&user, b"ada", b"3").is_ok());
+
+    assert_eq!(sled.try_get(&users, b"ada").unwrap(), Some(b"1".to_vec()));
+    assert_eq!(sled.try_get(&user, b"ada").unwrap(), Some(b"3".to_vec()));
+    assert_eq!(sled.try_count_column(&users).unwrap(), 2);
+    assert!(sled.try_rename(&users, b"grace", b"hopper").unwrap());
+
+    let mut entries = sled.try_entries(&users).unwrap();
+    entries.sort();
+    assert_eq!(
+        entries,
+        vec![
+            (b"ada".to_vec(), b"1".to_vec()),
+            (b"hopper".to_vec(), b"2".to_vec())
+        ]
+    );
+
+    let columns = sled.try_list_columns().unwrap();
+    assert!(columns.contains(&"users".to_string()));
+    assert!(columns.contains(&"user".to_string()));
+    assert!(
+        sled.try_report()
+            .unwrap()
+            .iter()
+            .any(|report| report.name == "users" && report.entries == 2)
+    );
+
+    // dropping one shared column leaves the others in place
+    assert!(sled.try_drop_column(&users).is_ok());
+    assert_eq!(sled.try_get(&users, b"ada").unwrap(), None);
+    assert_eq!(sled.try_count_column(&users).unwrap(), 0);
+    assert_eq!(sled.try_get(&user, b"ada").unwrap(), Some(b"3".to_vec()));
+    assert!(
+        !sled
+            .try_list_columns()
+            .unwrap()
+            .contains(&"users".to_string())
+    );
+}
+
+#[test]
+#[cfg(feature = "sled")]
+fn test_sled_eviction_listener() {
+    use std::sync::{Arc, Mutex};
+
+    use omega_cache::{EvictReason, EvictionListener};
+
+    struct Column {}
+    impl ColumnDefinition for Column {
+        fn name(&self) -> String {
+            "eviction_listener_column".to_string()
+        }
+
+        fn get_ttl_in_seconds(&self) -> i32 {
+            1
+        }
+
+        fn max_entries(&self) -> Option<usize> {
+            Some(2)
+        }
+    }
+
+    #[derive(Default)]
+    struct Evicted(Mutex<Vec<(String, Vec<u8>, EvictReason)>>);
+    impl EvictionListener for Evicted {
+        fn on_evict(&self, column: &str, key: &[u8], reason: EvictReason) {
+            self.0
+                .lock()
+                .unwrap()
+                .push((column.to_string(), key.to_vec(), reason));
+        }
+    }
+
+    let mut engine = SledEngine::builder("./tmp/sled_test_eviction_listener")
+        .open()
+        .unwrap();
+    assert!(engine.try_drop_column(&Column {}).is_ok());
+
+    let evicted = Arc::new(Evicted::default());
+    assert!(engine.set_eviction_listener(evicted.clone()).is_ok());
+
+    assert!(engine.try_insert(&Column {}, b"a", b"1").is_ok());
+    assert!(engine.try_insert(&Column {}, b"a", b"2").is_ok());
+    assert!(engine.try_insert(&Column {}, b"b", b"3").is_ok());
+
+    std::thread::sleep(std::time::Duration::from_millis(1100));
+    assert!(engine.try_insert(&Column {}, b"c", b"4").is_ok());
+
+    std::thread::sleep(std::time::Duration::from_millis(2100));
+    assert_eq!(engine.try_get(&Column {}, b"c").unwrap(), None);
+
+    let column = "eviction_listener_column".to_string();
+    assert_eq!(
+        *evicted.0.lock().unwrap(),
+        vec![
+            (column.clone(), b"a".to_vec(), EvictReason::Replaced),
+            (column.clone(), b"a".to_vec(), EvictReason::CapacityEvicted),
+            (column, b"c".to_vec(), EvictReason::Expired),
+        ]
+    );
+}
+
+#[test]
+#[cfg(feature = "sled")]
+fn test_sled_eviction_listener_uses_engine() {
+    use std::sync::{Arc, OnceLock, mpsc};
+    use std::time::Duration;
+
+    use omega_cache::{EvictReason, EvictionListener};
+
+    struct Column {}
+    impl ColumnDefinition for Column {
+        fn name(&self) -> String {
+            "eviction_reentrant_column".to_string()
+        }
+
+        fn get_ttl_in_seconds(&self) -> i32 {
+            10
+        }
+
+        fn max_entries(&self) -> Option<usize> {
+            Some(1)
+        }
+    }
+
+    // drops the column of the evicted value, waiting on the lock its eviction was under
+    struct DropOnEvict(Arc<OnceLock<Arc<SledEngine>>>);
+    impl EvictionListener for DropOnEvict {
+        fn on_evict(&self, _column: &str, _key: &[u8], reason: EvictReason) {
+            if reason == EvictReason::CapacityEvicted
+                && let Some(engine) = self.0.get()
+            {
+                engine.try_drop_column(&Column {}).unwrap();
+            }
+        }
+    }
+
+    let mut engine = SledEngine::builder("./tmp/sled_test_eviction_listener_uses_engine")
+        .open()
+        .unwrap();
+    assert!(engine.try_drop_column(&Column {}).is_ok());
+
+    let shared = Arc::new(OnceLock::new());
+    assert!(
+        engine
+            .set_eviction_listener(Arc::new(DropOnEvict(shared.clone())))
+            .is_ok()
+    );
+    let engine = Arc::new(engine);
+    assert!(shared.set(engine.clone()).is_ok());
+
+    let (done, finished) = mpsc::channel();
+    let writer = engine.clone();
+    std::thread::spawn(move || {
+        writer.try_insert(&Column {}, b"a", b"1").unwrap();
+        writer.try_insert(&Column {}, b"b", b"2").unwrap();
+        done.send(()).unwrap();
+    });
+
+    assert!(finished.recv_timeout(Duration::from_secs(10)).is_ok());
+    assert_eq!(engine.try_count_column(&Column {}).unwrap(), 0);
+}
+
+#[test]
+#[cfg(feature = "sled")]
+fn test_sled_touch_many() {
+    use omega_cache::Engine;
+
+    struct Column {}
+    impl ColumnDefinition for Column {
+        fn name(&self) -> String {
+            "touch_many_column".to_string()
+        }
+
+        fn get_ttl_in_seconds(&self) -> i32 {
+            1
+        }
+    }
+
+    let engine = Engine::new(SledEngine::build(
+        "./tmp/sled_test_touch_many".to_string(),
+        None,
+    ));
+    assert!(engine.try_drop_column(&Column {}).is_ok());
+
+    for key in ["a", "b", "c"] {
+        assert!(engine.try_insert(&Column {}, &key, &1u8).is_ok());
+    }
+
+    assert_eq!(
+        engine
+            .try_touch_many(&Column {}, &["a", "b", "missing"], Some(60))
+            .unwrap(),
+        2
+    );
+
+    std::thread::sleep(std::time::Duration::from_millis(2100));
+    assert_eq!(engine.try_get::<_, u8>(&Column {}, &"a").unwrap(), Some(1));
+    assert_eq!(engine.try_get::<_, u8>(&Column {}, &"b").unwrap(), Some(1));
+    assert_eq!(engine.try_get::<_, u8>(&Column {}, &"c").unwrap(), None);
+
+    // expired values are not brought back
+    assert_eq!(
+        engine
+            .try_touch_many(&Column {}, &["a", "c"], None)
+            .unwrap(),
+        1
+    );
+}
+
+#[test]
+#[cfg(feature = "sled")]
+fn test_sled_transaction() {
+    use omega_cache::Engine;
+
+    struct Column {}
+    impl ColumnDefinition for Column {
+        fn name(&self) -> String {
+            "transaction_column".to_string()
+        }
+
+        fn get_ttl_in_seconds(&self) -> i32 {
+            60
+        }
+    }
+
+    let engine = Engine::new(SledEngine::build(
+        "./tmp/sled_test_transaction".to_string(),
+        None,
+    ));
+    assert!(engine.try_drop_column(&Column {}).is_ok());
+
+    assert!(engine.try_insert(&Column {}, &"from", &10u64).is_ok());
+    assert!(engine.try_insert(&Column {}, &"to", &0u64).is_ok());
+
+    let moved = engine
+        .transaction(&Column {})
+        .check_eq(&"from", 10u64)
+        .check_missing(&"lock")
+        .insert(&"from", &5u64)
+        .insert(&"to", &5u64)
+        .remove(&"pending")
+        .try_commit()
+        .unwrap();
+    assert!(moved);
+    assert_eq!(
+        engine.try_get::<_, u64>(&Column {}, &"from").unwrap(),
+        Some(5)
+    );
+    assert_eq!(
+        engine.try_get::<_, u64>(&Column {}, &"to").unwrap(),
+        Some(5)
+    );
+
+    // a condition that fails writes nothing
+    let moved = engine
+        .transaction(&Column {})
+        .check_eq(&"from", 10u64)
+        .insert(&"from", &0u64)
+        .insert(&"to", &10u64)
+        .try_commit()
+        .unwrap();
+    assert!(!moved);
+    assert_eq!(
+        engine.try_get::<_, u64>(&Column {}, &"from").unwrap(),
+        Some(5)
+    );
+    assert_eq!(
+        engine.try_get::<_, u64>(&Column {}, &"to").unwrap(),
+        Some(5)
+    );
+
+    let moved = engine
+        .transaction(&Column {})
+        .check_missing(&"to")
+        .remove(&"from")
+        .try_commit()
+        .unwrap();
+    assert!(!moved);
+    assert_eq!(
+        engine.try_get::<_, u64>(&Column {}, &"from").unwrap(),
+        Some(5)
+    );
+
+    // a check of the wrong type is an error
+    assert!(
+        engine
+            .transaction(&Column {})
+            .check_eq(&"from", "five".to_string())
+            .remove(&"from")
+            .try_commit()
+            .is_err()
+    );
+}
+
+#[test]
+#[cfg(feature = "sled")]
+fn test_sled_compact() {
+    use omega_cache::Engine;
+
+    struct ShortLived {}
+    impl ColumnDefinition for ShortLived {
+        fn name(&self) -> String {
+            "compact_short_lived".to_string()
+        }
+
+        fn get_ttl_in_seconds(&self) -> i32 {
+            1
+        }
+    }
+
+    struct LongLived {}
+    impl ColumnDefinition for LongLived {
+        fn name(&self) -> String {
+            "compact_long_lived".to_string()
+        }
+
+        fn get_ttl_in_seconds(&self) -> i32 {
+            60
+        }
+    }
+
+    let engine = Engine::new(SledEngine::build(
+        "./tmp/sled_test_compact".to_string(),
+        None,
+    ));
+    assert!(engine.try_drop_column(&ShortLived {}).is_ok());
+    assert!(engine.try_drop_column(&LongLived {}).is_ok());
+
+    let size = |engine: &Engine| -> u64 {
+        engine
+            .try_report()
+            .unwrap()
+            .iter()
+            .filter(|report| report.name.starts_with("compact_"))
+            .filter_map(|report| report.size_on_disk)
+            .sum()
+    };
+
+    for i in 0..100u32 {
+        assert!(
+            engine
+                .try_insert(&ShortLived {}, &i.to_be_bytes(), &[0u8; 64])
+                .is_ok()
+        );
+        assert!(
+            engine
+                .try_insert(&LongLived {}, &i.to_be_bytes(), &[1u8; 64])
+                .is_ok()
+        );
+    }
+    let written = size(&engine);
+
+    assert!(engine.try_drop_column(&LongLived {}).is_ok());
+    assert!(
+        engine
+            .try_insert(&LongLived {}, &"kept", &[1u8; 64])
+            .is_ok()
+    );
+
+    // expired items hold their space until compacted
+    std::thread::sleep(std::time::Duration::from_millis(2100));
+    let expired = size(&engine);
+    assert!(expired < written);
+
+    assert!(engine.try_maintenance().is_ok());
+    assert!(size(&engine) < expired);
+    assert_eq!(
+        engine
+            .try_get::<_, [u8; 64]>(&LongLived {}, &"kept")
+            .unwrap(),
+        Some([1u8; 64])
+    );
+}
+
+#[test]
+#[cfg(feature = "sled")]
+fn test_sled_item_format() {
+    use omega_cache::{Engine, sled_engine::ItemFormat};
+
+    struct Column {}
+    impl ColumnDefinition for Column {
+        fn name(&self) -> String {
+            "item_format_column".to_string()
+        }
+
+        fn get_ttl_in_seconds(&self) -> i32 {
+            300
+        }
+    }
+
+    let open = |format: ItemFormat| {
+        Engine::new(
+            SledEngine::builder("./tmp/sled_test_item_format")
+                .item_format(format)
+                .build(),
+        )
+    };
+
+    let sled = open(ItemFormat::Compact);
+    assert!(sled.try_drop_column(&Column {}).is_ok());
+    assert!(sled.try_insert(&Column {}, &"key", &[7u8; 8]).is_ok());
+    assert!(sled.try_increment(&Column {}, &"counter", 2).is_ok());
+
+    // marker, four byte time and two byte TTL ahead of the encoded value
+    assert_eq!(
+        sled.try_value_size(&Column {}, &"key").unwrap(),
+        Some(7 + 8)
+    );
+    assert_eq!(
+        sled.try_get::<_, [u8; 8]>(&Column {}, &"key").unwrap(),
+        Some([7u8; 8])
+    );
+    drop(sled);
+
+    // compact items are still read after switching back, and rewritten as bincode
+    let sled = open(ItemFormat::Bincode);
+    assert_eq!(
+        sled.try_get::<_, [u8; 8]>(&Column {}, &"key").unwrap(),
+        Some([7u8; 8])
+    );
+    assert_eq!(sled.try_increment(&Column {}, &"counter", 3).unwrap(), 5);
+
+    assert!(sled.try_insert(&Column {}, &"key", &[8u8; 8]).is_ok());
+    assert!(sled.try_value_size(&Column {}, &"key").unwrap() > Some(7 + 8));
+    assert_eq!(
+        sled.try_get::<_, [u8; 8]>(&Column {}, &"key").unwrap(),
+        Some([8u8; 8])
+    );
+}
+
+#[test]
+#[cfg(feature = "sled")]
+fn test_sled_ttl_enforcement() {
+    use omega_cache::{Engine, TtlEnforcement};
+
+    let engine = Engine::new(SledEngine::build(
+        "./tmp/sled_test_ttl_enforcement".to_string(),
+        None,
+    ))
+    .with_key_prefix("v1");
+
+    assert_eq!(engine.ttl_enforcement(), TtlEnforcement::LazyOnRead);
+}
+
+#[test]
+#[cfg(feature = "sled")]
+fn test_sled_durability() {
+    use omega_cache::{Engine, sled_engine::Durability};
+
+    struct Column {}
+    impl ColumnDefinition for Column {
+        fn name(&self) -> String {
+            "durability_column".to_string()
+        }
+
+        fn get_ttl_in_seconds(&self) -> i32 {
+            300
+        }
+    }
+
+    let sled = Engine::new(
+        SledEngine::builder("./tmp/sled_test_durability")
+            .durability(Durability::Flushed)
+            .build(),
+    );
+    assert!(sled.try_drop_column(&Column {}).is_ok());
+    assert!(sled.try_insert(&Column {}, &"key", &1u32).is_ok());
+    assert_eq!(sled.try_increment(&Column {}, &"counter", 2).unwrap(), 2);
+    assert_eq!(sled.try_take(&Column {}, &"key").unwrap(), Some(1u32));
+    drop(sled);
+
+    let sled = SledEngine::builder("./tmp/sled_test_durability")
+        .open()
+        .unwrap();
+    assert!(sled.try_insert(&Column {}, b"key", b"value").is_ok());
+    assert!(sled.try_flush().is_ok());
+    assert_eq!(sled.try_count_column(&Column {}).unwrap(), 2);
+}
+
+#[cfg(feature = "sled")]
+#[test]
+fn test_sled_describe_config() {
+    use omega_cache::sled_engine::Durability;
+
+    let sled = SledEngine::builder("./tmp/sled_test_describe_config")
+        .durability(Durability::Flushed)
+        .open()
+        .unwrap();
+    let config = sled.describe_config();
+
+    assert_eq!(config.backend, "sled");
+    assert_eq!(config.get("path"), Some("./tmp/sled_test_describe_config"));
+    assert_eq!(config.get("compression"), Some("off"));
+    assert_eq!(config.get("durability"), Some("Flushed"));
+    assert!(config.to_string().starts_with("sled (path="));
+}
+
+#[cfg(feature = "sled")]
+#[test]
+fn test_sled_slow_operations() {
+    use std::{
+        sync::{Arc, Mutex},
+        time::Duration,
+    };
+
+    use omega_cache::{Engine, OperationKind, memory_engine::MemoryEngine};
+
+    struct Column {}
+    impl ColumnDefinition for Column {
+        fn name(&self) -> String {
+            "slow_operations_column".to_string()
+        }
+
+        fn get_ttl_in_seconds(&self) -> i32 {
+            10
+        }
+    }
+
+    let reported = Arc::new(Mutex::new(Vec::new()));
+    let mut engine = Engine::new(SledEngine::build(
+        "./tmp/sled_test_slow_operations".to_string(),
+        None,
+    ));
+
+    let listener = reported.clone();
+    assert!(
+        engine
+            .on_slow_operation(Duration::ZERO, move |op| {
+                listener
+                    .lock()
+                    .unwrap()
+                    .push((op.column.to_string(), op.kind));
+            })
+            .is_ok()
+    );
+
+    assert!(engine.try_insert(&Column {}, &"key", &1u32).is_ok());
+    assert_eq!(
+        engine.try_get::<&str, u32>(&Column {}, &"key").unwrap(),
+        Some(1)
+    );
+
+    let column = "slow_operations_column".to_string();
+    assert_eq!(
+        *reported.lock().unwrap(),
+        vec![
+            (column.clone(), OperationKind::Insert),
+            (column, OperationKind::Get)
+        ]
+    );
+
+    let mut memory = Engine::new(MemoryEngine::builder().build());
+    assert!(memory.on_slow_operation(Duration::ZERO, |_| {}).is_err());
+}